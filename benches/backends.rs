@@ -0,0 +1,66 @@
+//! Times the choices the docs compare: the two `PrimeSieve` backends, and the 32-bit
+//! `Comb32` / `mod_exp_u32` against their `u64` counterparts.
+//!
+//! Run with `cargo bench --bench backends`. Pass `--large` to also sieve up to 10^9.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use math_comb::{modular, Comb, Comb32, PrimeSieve, SieveBackend};
+
+const MOD: u32 = 998244353;
+
+/// Returns the fastest of `runs` timings of `f`.
+fn best_of<T>(runs: usize, mut f: impl FnMut() -> T) -> Duration {
+    (0..runs).map(|_| {
+        let start = Instant::now();
+        black_box(f());
+        start.elapsed()
+    }).min().unwrap()
+}
+
+fn sieves(large: bool) {
+    let mut limits = vec![10_000_000, 100_000_000];
+    if large {
+        limits.push(1_000_000_000);
+    }
+    for n in limits {
+        for backend in [SieveBackend::Eratosthenes, SieveBackend::Segmented] {
+            let time = best_of(3, || PrimeSieve::with_backend(n, backend));
+            println!("PrimeSieve {:?} n = {}: {:?}", backend, n, time);
+        }
+    }
+}
+
+fn comb() {
+    const N: usize = 10_000_000;
+    let comb32 = Comb32::with_modulus(MOD, N);
+    let comb64 = Comb::with_modulus(MOD as u64, N);
+    let queries = |f: &dyn Fn(u64, u64) -> u64| {
+        let mut acc = 0;
+        let mut x = 12345u64;
+        for _ in 0..N {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let n = (x >> 33) % N as u64;
+            acc ^= f(n, (x >> 7) % (n + 1));
+        }
+        acc
+    };
+    let t32 = best_of(3, || queries(&|n, r| comb32.nCr(n, r) as u64));
+    let t64 = best_of(3, || queries(&|n, r| comb64.nCr(n, r)));
+    println!("nCr x {}: Comb32 {:?}, Comb<u64> {:?}", N, t32, t64);
+}
+
+fn mod_exp() {
+    const N: u32 = 1_000_000;
+    let t32 = best_of(3, || (1..=N).fold(0, |acc, b| acc ^ modular::mod_exp_u32(b, MOD as u64 - 2, MOD)));
+    let t64 = best_of(3, || (1..=N as u64).fold(0, |acc, b| acc ^ modular::mod_exp(b, MOD as u64 - 2, MOD as u64)));
+    println!("mod_exp x {}: mod_exp_u32 {:?}, mod_exp {:?}", N, t32, t64);
+}
+
+fn main() {
+    let large = std::env::args().any(|arg| arg == "--large");
+    sieves(large);
+    comb();
+    mod_exp();
+}
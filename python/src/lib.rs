@@ -0,0 +1,153 @@
+//! Python bindings for `math-comb`, built into the `math_comb` extension module with maturin.
+//!
+//! Bindings go through the fallible (`try_*`) entry points of the library where it has
+//! them. The sieves and prime counting have none, since they accept any size, so their
+//! bindings first check the size against the limits below. Invalid arguments therefore
+//! raise `ValueError` instead of aborting the interpreter with a panic or running out of
+//! memory.
+
+use math_comb::{modular, prime, Comb, MathCombError, PrimeSieve, Spf};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// The largest table built for Python: the limit of `Spf` and `primes_up_to`, and the
+/// `max_fact` of `Comb`, which keeps each table within a few GB.
+const MAX_TABLE: u64 = 1_000_000_000;
+
+/// The largest bound of `count_primes`, whose O(n^(3/4)) running time stays within seconds.
+const MAX_COUNT_PRIMES: u64 = 10_000_000_000_000;
+
+fn value_error(e: MathCombError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+// Rejects `value` above `limit` with the library's `AboveLimit` error.
+fn check_limit(value: u64, limit: u64) -> PyResult<()> {
+    if value > limit {
+        return Err(value_error(MathCombError::AboveLimit { value, limit }));
+    }
+    Ok(())
+}
+
+/// Returns the prime factors of `n` in sorted order.
+#[pyfunction]
+fn factor(n: u64) -> PyResult<Vec<u64>> {
+    prime::try_factor(n).map_err(value_error)
+}
+
+/// Returns the `(prime, exponent)` pairs of `n` sorted by prime.
+#[pyfunction]
+fn factor_map(n: u64) -> PyResult<Vec<(u64, u32)>> {
+    prime::try_factor_map(n).map_err(value_error)
+}
+
+/// Checks whether `n` is prime.
+#[pyfunction]
+fn is_prime(n: u64) -> bool {
+    prime::is_prime(n)
+}
+
+/// Counts the primes `<= n`, for `n` up to 10^13.
+#[pyfunction]
+fn count_primes(n: u64) -> PyResult<u64> {
+    check_limit(n, MAX_COUNT_PRIMES)?;
+    Ok(prime::count_primes(n))
+}
+
+/// Lists the primes `<= n`, for `n` up to 10^9.
+#[pyfunction]
+fn primes_up_to(n: u64) -> PyResult<Vec<u64>> {
+    check_limit(n, MAX_TABLE)?;
+    Ok(PrimeSieve::new(n).iter().collect())
+}
+
+/// Computes `base^exponent % modulus`.
+#[pyfunction]
+fn mod_exp(base: u64, exponent: u64, modulus: u64) -> PyResult<u64> {
+    modular::try_mod_exp(base, exponent, modulus).map_err(value_error)
+}
+
+/// Computes the inverse of `x` modulo `modulus`, which need not be prime.
+#[pyfunction]
+fn mod_inv(x: u64, modulus: u64) -> PyResult<u64> {
+    modular::try_mod_inv(x, modulus).map_err(value_error)
+}
+
+/// Computes `C(n, r)` modulo the prime `modulus`. Use `Comb` for many queries.
+#[pyfunction]
+#[pyo3(signature = (n, r, modulus = 1000000007))]
+fn ncr(n: u64, r: u64, modulus: u64) -> PyResult<u64> {
+    let comb = Comb::builder().modulus(modulus).auto_grow().try_build().map_err(value_error)?;
+    comb.try_nCr(n, r).map_err(value_error)
+}
+
+/// Factorials and inverse factorials modulo a prime, tabulated up to `max_fact` (at most
+/// 10^9).
+#[pyclass(name = "Comb", frozen)]
+struct PyComb {
+    inner: Comb,
+}
+
+#[pymethods]
+impl PyComb {
+    #[new]
+    #[pyo3(signature = (modulus, max_fact, auto_grow = false))]
+    fn new(modulus: u64, max_fact: usize, auto_grow: bool) -> PyResult<PyComb> {
+        check_limit(max_fact as u64, MAX_TABLE)?;
+        let builder = Comb::builder().modulus(modulus).max_fact(max_fact);
+        let builder = if auto_grow { builder.auto_grow() } else { builder };
+        Ok(PyComb { inner: builder.try_build().map_err(value_error)? })
+    }
+
+    fn ncr(&self, n: u64, r: u64) -> PyResult<u64> {
+        self.inner.try_nCr(n, r).map_err(value_error)
+    }
+
+    fn npr(&self, n: u64, r: u64) -> PyResult<u64> {
+        self.inner.try_nPr(n, r).map_err(value_error)
+    }
+}
+
+/// A smallest-prime-factor table for factorizing many numbers up to `limit` (at most 10^9).
+#[pyclass(name = "Spf", frozen)]
+struct PySpf {
+    inner: Spf,
+}
+
+#[pymethods]
+impl PySpf {
+    #[new]
+    fn new(limit: usize) -> PyResult<PySpf> {
+        check_limit(limit as u64, MAX_TABLE)?;
+        Ok(PySpf { inner: Spf::new(limit) })
+    }
+
+    fn factorize(&self, x: u64) -> PyResult<Vec<u64>> {
+        let factors = self.inner.try_factorize_map(x).map_err(value_error)?;
+        Ok(factors.into_iter().flat_map(|(p, e)| std::iter::repeat_n(p, e as usize)).collect())
+    }
+
+    fn factorize_map(&self, x: u64) -> PyResult<Vec<(u64, u32)>> {
+        self.inner.try_factorize_map(x).map_err(value_error)
+    }
+
+    fn divisors(&self, x: u64) -> PyResult<Vec<u64>> {
+        self.inner.try_divisors(x).map_err(value_error)
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "math_comb")]
+fn math_comb_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(factor, m)?)?;
+    m.add_function(wrap_pyfunction!(factor_map, m)?)?;
+    m.add_function(wrap_pyfunction!(is_prime, m)?)?;
+    m.add_function(wrap_pyfunction!(count_primes, m)?)?;
+    m.add_function(wrap_pyfunction!(primes_up_to, m)?)?;
+    m.add_function(wrap_pyfunction!(mod_exp, m)?)?;
+    m.add_function(wrap_pyfunction!(mod_inv, m)?)?;
+    m.add_function(wrap_pyfunction!(ncr, m)?)?;
+    m.add_class::<PyComb>()?;
+    m.add_class::<PySpf>()?;
+    Ok(())
+}
@@ -0,0 +1,1198 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use std::iter::Sum;
+
+use crate::error::MathCombError;
+use crate::{modexp, pollard, sieve, ProgressHook};
+
+/// The number of terms the sublinear sums add up between two progress reports.
+const PROGRESS_BLOCK: u64 = 1 << 16;
+
+// Sums `term(i)` over `1 <= i <= r` in blocks of `PROGRESS_BLOCK` terms, reporting `(i, r)`
+// to `hook` after each block and returning `None` as soon as it cancels.
+fn sum_with_progress<T: Sum>(r: u64, hook: &dyn ProgressHook, term: impl Fn(u64) -> T) -> Option<T> {
+    let mut blocks = Vec::new();
+    let mut lo = 1;
+    while lo <= r {
+        if hook.is_cancelled() {
+            return None;
+        }
+        let hi = r.min(lo + PROGRESS_BLOCK - 1);
+        blocks.push((lo..=hi).map(&term).sum::<T>());
+        hook.report(hi, r);
+        lo = hi + 1;
+    }
+    hook.report(r, r);
+    Some(blocks.into_iter().sum())
+}
+
+/// Counts the distinct prime factors of `n`, ω(n).
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// The number of distinct primes dividing `n` (0 for `n` = 1).
+pub fn omega(n: u64) -> u32 {
+    pollard::factor_map(n).len() as u32
+}
+
+/// Counts the prime factors of `n` with multiplicity, Ω(n).
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// The sum of the exponents in the prime factorization of `n` (0 for `n` = 1).
+pub fn big_omega(n: u64) -> u32 {
+    pollard::factor_map(n).iter().map(|&(_, e)| e).sum()
+}
+
+/// Checks whether `n` is a k-almost-prime, a number with exactly `k` prime factors
+/// counted with multiplicity (`k = 1` gives the primes, `k = 2` the semiprimes).
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+/// * `k` - The number of prime factors.
+///
+/// # Returns
+///
+/// `true` if Ω(n) = `k` (so 1 is the only 0-almost-prime, and 0 is never one).
+pub fn is_k_almost_prime(n: u64, k: u32) -> bool {
+    n != 0 && big_omega(n) == k
+}
+
+/// Computes the Liouville function λ(n) = (−1)^Ω(n), which is completely multiplicative
+/// with λ(p) = −1.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// 1 if `n` has an even number of prime factors counted with multiplicity, −1 otherwise.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn liouville(n: u64) -> i32 {
+    if big_omega(n).is_multiple_of(2) { 1 } else { -1 }
+}
+
+/// Checks whether `n` is squarefree, i.e. not divisible by the square of any prime.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+///
+/// # Returns
+///
+/// `true` if every exponent in the factorization of `n` is 1 (`1` counts as squarefree).
+pub fn is_squarefree(n: u64) -> bool {
+    pollard::factor_map(n).iter().all(|&(_, e)| e == 1)
+}
+
+/// Finds the squarefree part of `n`: `n` divided by its largest square divisor, which is
+/// the product of the primes dividing `n` an odd number of times.
+///
+/// # Arguments
+///
+/// * `n` - The number to reduce.
+///
+/// # Returns
+///
+/// The squarefree `s` with `n = s·k²` for some `k`.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn squarefree_part(n: u64) -> u64 {
+    pollard::factor_map(n).iter().filter(|&&(_, e)| e % 2 == 1).map(|&(p, _)| p).product()
+}
+
+/// Checks whether `n` is powerful, i.e. divisible by `p²` for every prime `p` dividing it.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+///
+/// # Returns
+///
+/// `true` if every exponent in the factorization of `n` is at least 2 (`1` counts as
+/// powerful).
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn is_powerful(n: u64) -> bool {
+    pollard::factor_map(n).iter().all(|&(_, e)| e >= 2)
+}
+
+/// Counts the squarefree numbers in `1..=n`.
+///
+/// Uses the inclusion–exclusion Q(n) = Σ μ(k)·⌊n / k²⌋ over `k <= √n`, so only the
+/// Möbius function up to √n has to be sieved.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Complexity
+///
+/// O(√n) time and memory.
+pub fn count_squarefree(n: u64) -> u64 {
+    // `()` never cancels.
+    count_squarefree_with_progress(n, &()).unwrap()
+}
+
+/// Counts the squarefree numbers in `1..=n` like `count_squarefree`, reporting progress to
+/// `hook` and giving up when it cancels.
+///
+/// Progress is counted in terms of the sum: once μ is sieved up to √n, `hook.report(k, √n)`
+/// is called after every 2^16 values of `k`, and with `(√n, √n)` at the end.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// The count, or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn count_squarefree_with_progress(n: u64, hook: &impl ProgressHook) -> Result<u64, MathCombError> {
+    let r = n.isqrt();
+    let mobius = sieve::MobiusSieve::new(r);
+    let count = sum_with_progress(r, hook, |k| mobius.mu(k) as i64 * (n / (k * k)) as i64);
+    count.map(|count| count as u64).ok_or(MathCombError::Cancelled)
+}
+
+/// Checks whether `n` is a Carmichael number using Korselt's criterion.
+///
+/// A composite `n` is a Carmichael number if and only if it is squarefree and
+/// `p - 1` divides `n - 1` for every prime `p` dividing `n`.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+///
+/// # Returns
+///
+/// `true` if `n` is a Carmichael number, `false` otherwise.
+pub fn is_carmichael(n: u64) -> bool {
+    if n < 3 || pollard::is_prime(n) {
+        return false;
+    }
+    let map = pollard::factor_map(n);
+    map.iter().all(|&(p, e)| e == 1 && (n - 1).is_multiple_of(p - 1))
+}
+
+/// Computes the sum of the divisors of `n`, σ(n), from its factorization.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// The sum of all positive divisors of `n`, as a `u128` since it can exceed `u64::MAX`.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn sigma(n: u64) -> u128 {
+    pollard::factor_map(n)
+        .iter()
+        .map(|&(p, e)| {
+            // 1 + p + ... + p^e
+            let p = p as u128;
+            let mut sum = 1;
+            let mut pk = 1;
+            for _ in 0..e {
+                pk *= p;
+                sum += pk;
+            }
+            sum
+        })
+        .product()
+}
+
+/// Computes Pillai's gcd-sum function P(n) = Σ_{i=1}^{n} gcd(i, n).
+///
+/// Grouping the `i` by `d = gcd(i, n)` gives `P(n) = Σ_{d|n} d·φ(n/d)`, which is
+/// multiplicative with `P(p^e) = (e + 1)·p^e − e·p^(e−1)`.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// P(n), as a `u128` since it can exceed `u64::MAX` (0 for `n` = 0).
+pub fn gcd_sum(n: u64) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+    pollard::factor_map(n)
+        .iter()
+        .map(|&(p, e)| {
+            let below = (p as u128).pow(e - 1);
+            (e as u128 + 1) * below * p as u128 - e as u128 * below
+        })
+        .product()
+}
+
+/// Counts how many applications of Euler's φ take `n` down to 1.
+///
+/// Every φ(x) with `x > 2` is even, and φ halves any power of two, so the chain has at most
+/// about `log2 n + 1` steps, each costing one factorization. `TotientSieve` tabulates the
+/// lengths for every `n` up to a bound.
+///
+/// # Arguments
+///
+/// * `n` - The start of the chain.
+///
+/// # Returns
+///
+/// The length of the chain `n, φ(n), φ(φ(n)), …, 1`, not counting `n` itself (0 for `n` = 1).
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn phi_iteration_length(n: u64) -> u32 {
+    if n == 0 {
+        panic!("n cannot be zero!");
+    }
+    let mut x = n;
+    let mut steps = 0;
+    while x != 1 {
+        x = pollard::factor_map(x).iter().map(|&(p, e)| (p - 1) * p.pow(e - 1)).product();
+        steps += 1;
+    }
+    steps
+}
+
+/// Computes Jordan's totient J_k(n) = n^k·∏_{p|n} (1 − p^(−k)), the number of `k`-tuples
+/// in `1..=n` whose gcd with `n` is 1.
+///
+/// It is multiplicative with `J_k(p^e) = p^(k(e−1))·(p^k − 1)`, so `J_1` is Euler's φ.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+/// * `k` - The length of the tuples.
+///
+/// # Returns
+///
+/// J_k(n), or `None` if it does not fit in a `u128` (`J_0` is 1 at `n = 1` and 0 elsewhere).
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn jordan_totient(n: u64, k: u32) -> Option<u128> {
+    pollard::factor_map(n).iter().try_fold(1u128, |acc, &(p, e)| {
+        let pk = (p as u128).checked_pow(k)?;
+        acc.checked_mul(pk.checked_pow(e - 1)?)?.checked_mul(pk - 1)
+    })
+}
+
+/// Computes Jordan's totient J_k(n) modulo `modulus`, for tuple lengths whose exact value
+/// overflows.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+/// * `k` - The length of the tuples.
+/// * `modulus` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `n` or `modulus` is 0.
+pub fn jordan_totient_mod(n: u64, k: u64, modulus: u64) -> u64 {
+    if modulus == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    pollard::factor_map(n).iter().fold(1 % modulus, |acc, &(p, e)| {
+        let pk = modexp::mod_exp(p, k, modulus);
+        let term = modexp::mod_exp(pk, e as u64 - 1, modulus);
+        let term = pollard::modmul(term, (pk + modulus - 1) % modulus, modulus);
+        pollard::modmul(acc, term, modulus)
+    })
+}
+
+/// Computes the divisor summatory function D(n) = Σ_{i≤n} d(i), the number of pairs
+/// `(a, b)` with `a·b <= n`.
+///
+/// By Dirichlet's hyperbola method, the pairs with `a <= √n` and those with `b <= √n` are
+/// counted by `Σ_{i≤√n} ⌊n/i⌋` each, and the square `a, b <= √n` counted twice is removed.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Complexity
+///
+/// O(√n) time and O(1) memory.
+pub fn divisor_summatory(n: u64) -> u128 {
+    // `()` never cancels.
+    divisor_summatory_with_progress(n, &()).unwrap()
+}
+
+/// Computes the divisor summatory function D(n) like `divisor_summatory`, reporting
+/// progress to `hook` and giving up when it cancels.
+///
+/// Progress is counted in terms of the sum: `hook.report(i, √n)` is called after every
+/// 2^16 values of `i`, and with `(√n, √n)` at the end.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// D(n), or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn divisor_summatory_with_progress(n: u64, hook: &impl ProgressHook) -> Result<u128, MathCombError> {
+    let r = n.isqrt();
+    let sum = sum_with_progress(r, hook, |i| (n / i) as u128).ok_or(MathCombError::Cancelled)?;
+    Ok(2 * sum - r as u128 * r as u128)
+}
+
+// Computes 1 + 2 + … + m.
+fn triangular(m: u64) -> u128 {
+    m as u128 * (m as u128 + 1) / 2
+}
+
+/// Computes Σ_{i≤n} σ(i), the sum of `a` over the pairs `(a, b)` with `a·b <= n`, with the
+/// same hyperbola split as `divisor_summatory`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Returns
+///
+/// The sum, which is about `π²n²/12` and so fits in a `u128` for every `u64` bound.
+///
+/// # Complexity
+///
+/// O(√n) time and O(1) memory.
+pub fn sigma_summatory(n: u64) -> u128 {
+    let r = n.isqrt();
+    let sum: u128 = (1..=r).map(|i| i as u128 * (n / i) as u128 + triangular(n / i)).sum();
+    sum - triangular(r) * r as u128
+}
+
+/// The largest sieve used by the sublinear summatory functions, 10^7 entries of 8 bytes or
+/// about 80 MB.
+const MAX_SUMMATORY_SIEVE: u64 = 10_000_000;
+
+/// The most values above the sieve kept by `totient_sum`, 5·10^6 `u128`s or about 80 MB.
+const MAX_SUMMATORY_LARGE: u64 = 5_000_000;
+
+/// The largest bound accepted by `totient_sum`, up to which both of its tables stay within
+/// their 80 MB caps.
+pub const MAX_TOTIENT_SUM: u64 = MAX_SUMMATORY_SIEVE * MAX_SUMMATORY_LARGE;
+
+/// Computes the totient summatory function Φ(n) = Σ_{i≤n} φ(i).
+///
+/// Since every pair `1 <= b <= a <= v` has a unique gcd `g` with `(a/g, b/g)` counted by
+/// Φ(v/g), `Σ_{g≤v} Φ(⌊v/g⌋) = v(v+1)/2`. A sieve provides Φ up to about `n^(2/3)`, and the
+/// identity gives the remaining values `Φ(⌊n/i⌋)` from the smallest upwards, grouping the
+/// `g` with equal quotients.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Returns
+///
+/// Φ(n), which is about `3n²/π²`.
+///
+/// # Panics
+///
+/// Panics if `n` is greater than `MAX_TOTIENT_SUM` (5·10^13), beyond which the tables would
+/// exceed their memory caps.
+///
+/// # Complexity
+///
+/// O(n^(2/3)) time and memory while the sieve of size L ≈ n^(2/3) stays below 10^7 entries,
+/// and O(n / √L) time with L = 10^7 beyond that, with at most 160 MB of memory in all;
+/// `n = 10^11` takes about a second.
+pub fn totient_sum(n: u64) -> u128 {
+    if n > MAX_TOTIENT_SUM {
+        panic!("n cannot be greater than {}!", MAX_TOTIENT_SUM);
+    }
+    // `()` never cancels.
+    totient_sum_by(n, &()).unwrap()
+}
+
+/// Computes the totient summatory function Φ(n) like `totient_sum`, reporting progress to
+/// `hook` and giving up when it cancels.
+///
+/// Progress is counted in the values Φ(⌊n/i⌋) beyond the sieve, which are computed from
+/// the smallest: `hook.report(done, count)` is called after each of them. Nothing is
+/// reported when the sieve covers `n` itself.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// Φ(n), `MathCombError::AboveLimit` if `n` is greater than `MAX_TOTIENT_SUM`, or
+/// `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn totient_sum_with_progress(n: u64, hook: &impl ProgressHook) -> Result<u128, MathCombError> {
+    if n > MAX_TOTIENT_SUM {
+        return Err(MathCombError::AboveLimit { value: n, limit: MAX_TOTIENT_SUM });
+    }
+    totient_sum_by(n, hook).ok_or(MathCombError::Cancelled)
+}
+
+// Computes Φ(n) for `n <= MAX_TOTIENT_SUM`, returning `None` as soon as `hook` cancels.
+fn totient_sum_by(n: u64, hook: &dyn ProgressHook) -> Option<u128> {
+    let r = iroot(n, 3);
+    // The table of large values holds n / L entries, which the bound on n keeps within its
+    // cap.
+    let limit = (r * r).min(MAX_SUMMATORY_SIEVE).max(n.isqrt()).max(n.div_ceil(MAX_SUMMATORY_LARGE));
+    let sieve = sieve::TotientSieve::new(limit);
+    if n <= limit {
+        return Some(sieve.prefix_sum(n) as u128);
+    }
+    // large[i] = Φ(n / i) for the i with n / i > limit.
+    let count = (n / (limit + 1)) as usize;
+    let mut large = vec![0u128; count + 1];
+    for i in (1..=count).rev() {
+        if hook.is_cancelled() {
+            return None;
+        }
+        let v = n / i as u64;
+        let mut sum = triangular(v);
+        let mut g = 2;
+        while g <= v {
+            let q = v / g;
+            let next = v / q + 1;
+            let phi = if q <= limit { sieve.prefix_sum(q) as u128 } else { large[i * g as usize] };
+            sum -= (next - g) as u128 * phi;
+            g = next;
+        }
+        large[i] = sum;
+        hook.report((count - i + 1) as u64, count as u64);
+    }
+    Some(large[1])
+}
+
+/// Counts the ordered pairs `(a, b)` with `1 <= a, b <= n` and gcd(a, b) = 1, which is
+/// `2Φ(n) − 1` since the pair `(1, 1)` is the only one with `a = b`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound on both coordinates.
+///
+/// # Complexity
+///
+/// That of `totient_sum`.
+///
+/// # Panics
+///
+/// Panics if `n` is greater than `MAX_TOTIENT_SUM`.
+pub fn count_coprime_pairs(n: u64) -> u128 {
+    (2 * totient_sum(n)).saturating_sub(1)
+}
+
+/// Computes the length of the Farey sequence of order `n`, the reduced fractions in
+/// `[0, 1]` with denominator at most `n`, which is `1 + Φ(n)`.
+///
+/// # Arguments
+///
+/// * `n` - The order of the sequence.
+///
+/// # Complexity
+///
+/// That of `totient_sum`.
+///
+/// # Panics
+///
+/// Panics if `n` is greater than `MAX_TOTIENT_SUM`.
+pub fn farey_length(n: u64) -> u128 {
+    1 + totient_sum(n)
+}
+
+/// The classification of a positive integer by comparing the sum of its proper divisors
+/// with itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Abundance {
+    /// The proper divisors sum to less than the number.
+    Deficient,
+    /// The proper divisors sum to exactly the number.
+    Perfect,
+    /// The proper divisors sum to more than the number.
+    Abundant,
+}
+
+/// Classifies `n` as deficient, perfect or abundant by comparing σ(n) with `2n`.
+///
+/// # Arguments
+///
+/// * `n` - The number to classify.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn classify(n: u64) -> Abundance {
+    let s = sigma(n);
+    let twice = 2 * n as u128;
+    if s < twice {
+        Abundance::Deficient
+    } else if s == twice {
+        Abundance::Perfect
+    } else {
+        Abundance::Abundant
+    }
+}
+
+/// Checks whether `a` and `b` form an amicable pair, i.e. they are distinct and each is the
+/// sum of the proper divisors of the other.
+///
+/// # Arguments
+///
+/// * `a` - The first number.
+/// * `b` - The second number.
+///
+/// # Returns
+///
+/// `true` if `a` and `b` are amicable, `false` otherwise (including when either is 0).
+pub fn are_amicable(a: u64, b: u64) -> bool {
+    if a == 0 || b == 0 || a == b {
+        return false;
+    }
+    let sum = a as u128 + b as u128;
+    sigma(a) == sum && sigma(b) == sum
+}
+
+/// An iterator over the divisors of a number in increasing order, generated lazily.
+///
+/// Each divisor `d` has a unique parent obtained by removing one factor of its largest
+/// prime, so popping the smallest divisor from a min-heap and pushing its children
+/// (`d` times its largest prime again, or times any larger prime) yields every divisor
+/// exactly once and in order, without materializing all of them.
+pub struct SortedDivisors {
+    map: Vec<(u64, u32)>,
+    // (divisor, index of its largest prime in `map`, exponent of that prime)
+    heap: BinaryHeap<Reverse<(u64, usize, u32)>>,
+}
+
+impl SortedDivisors {
+    /// Creates the iterator from the factorization of the number, given as sorted
+    /// `(prime, exponent)` pairs.
+    pub fn new(map: Vec<(u64, u32)>) -> SortedDivisors {
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((1, 0, 0)));
+        SortedDivisors { map, heap }
+    }
+}
+
+impl Iterator for SortedDivisors {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let Reverse((d, j, e)) = self.heap.pop()?;
+        if d > 1 && e < self.map[j].1 {
+            self.heap.push(Reverse((d * self.map[j].0, j, e + 1)));
+        }
+        let first = if d == 1 { 0 } else { j + 1 };
+        for m in first..self.map.len() {
+            self.heap.push(Reverse((d * self.map[m].0, m, 1)));
+        }
+        Some(d)
+    }
+}
+
+/// Finds the `k`-th smallest divisor of `n` (1-indexed).
+///
+/// Divisors are generated lazily in increasing order. Since divisors pair up as `d` and
+/// `n / d`, indices past the middle are answered from the other end, so at most half of the
+/// divisors are ever generated.
+///
+/// # Arguments
+///
+/// * `n` - The number whose divisors are considered.
+/// * `k` - The rank of the divisor, starting at 1.
+///
+/// # Returns
+///
+/// The `k`-th smallest divisor, or `None` if `n` is 0, `k` is 0 or `n` has fewer than `k`
+/// divisors.
+pub fn kth_divisor(n: u64, k: u64) -> Option<u64> {
+    if n == 0 || k == 0 {
+        return None;
+    }
+    let map = pollard::factor_map(n);
+    let count: u64 = map.iter().map(|&(_, e)| e as u64 + 1).product();
+    if k > count {
+        return None;
+    }
+    if 2 * k > count + 1 {
+        let d = SortedDivisors::new(map).nth((count - k) as usize)?;
+        return Some(n / d);
+    }
+    SortedDivisors::new(map).nth((k - 1) as usize)
+}
+
+// Counts the x in [1, n] divisible by none of `primes` by inclusion–exclusion over the
+// products of subsets of them.
+fn count_free_of(n: u64, primes: &[u64]) -> u64 {
+    fn go(n: u64, primes: &[u64]) -> i128 {
+        // Every product past the first prime exceeding n contributes nothing.
+        primes.iter().enumerate().take_while(|&(_, &p)| p <= n).fold(n as i128, |count, (i, &p)| count - go(n / p, &primes[i + 1..]))
+    }
+    go(n, primes) as u64
+}
+
+/// Counts the integers in `[1, n]` coprime to `k`, by inclusion–exclusion over the
+/// distinct prime factors of `k`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `k` - The number to be coprime to; only 1 is coprime to 0.
+///
+/// # Complexity
+///
+/// The factorization of `k` plus at most 2^ω(k) terms, skipping those whose product exceeds
+/// `n`.
+pub fn count_coprime_up_to(n: u64, k: u64) -> u64 {
+    if k == 0 {
+        return n.min(1);
+    }
+    let primes: Vec<u64> = pollard::factor_map(k).iter().map(|&(p, _)| p).collect();
+    count_free_of(n, &primes)
+}
+
+/// Finds the `idx`-th positive integer coprime to `k` (1-indexed).
+///
+/// The coprime residues repeat with period `k`, φ(k) of them per period, so the answer is a
+/// whole number of periods plus a residue found by binary search over
+/// `count_coprime_up_to` within the first period.
+///
+/// # Arguments
+///
+/// * `k` - The number to be coprime to.
+/// * `idx` - The rank, starting at 1.
+///
+/// # Returns
+///
+/// The `idx`-th integer coprime to `k`, or `None` if `idx` is 0, `k` is 0 and `idx` is
+/// not 1, or the answer does not fit in a `u64`.
+pub fn kth_coprime(k: u64, idx: u64) -> Option<u64> {
+    if idx == 0 {
+        return None;
+    }
+    if k == 0 {
+        return (idx == 1).then_some(1);
+    }
+    let map = pollard::factor_map(k);
+    let phi: u64 = map.iter().map(|&(p, e)| (p - 1) * p.pow(e - 1)).product();
+    let primes: Vec<u64> = map.iter().map(|&(p, _)| p).collect();
+    let (periods, rank) = ((idx - 1) / phi, (idx - 1) % phi + 1);
+    // The smallest x in [1, k] with rank coprime integers up to it.
+    let (mut lo, mut hi) = (1, k);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if count_free_of(mid, &primes) >= rank {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    periods.checked_mul(k)?.checked_add(lo)
+}
+
+/// Computes the integer `k`-th root of `n`, the largest `r` with `r^k <= n`.
+///
+/// # Arguments
+///
+/// * `n` - The radicand.
+/// * `k` - The degree of the root, at least 1.
+pub fn iroot(n: u64, k: u32) -> u64 {
+    if k == 1 || n < 2 {
+        return n;
+    }
+    let mut r = (n as f64).powf(1.0 / k as f64).round() as u64;
+    // The float estimate may be off by one in either direction.
+    while r > 0 && r.checked_pow(k).is_none_or(|v| v > n) {
+        r -= 1;
+    }
+    while (r + 1).checked_pow(k).is_some_and(|v| v <= n) {
+        r += 1;
+    }
+    r
+}
+
+/// Checks whether `n` is a prime power `p^k` with `k >= 1`.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// `Some((p, k))` if `n = p^k` for a prime `p`, `None` otherwise.
+pub fn as_prime_power(n: u64) -> Option<(u64, u32)> {
+    if n < 2 {
+        return None;
+    }
+    // Trying the largest exponent first guarantees the root found is the prime itself.
+    for k in (1..=63).rev() {
+        let r = iroot(n, k);
+        if r >= 2 && r.pow(k) == n && pollard::is_prime(r) {
+            return Some((r, k));
+        }
+    }
+    None
+}
+
+/// Computes the primorial n#, the product of all primes `<= n`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound on the primes.
+///
+/// # Returns
+///
+/// The primorial of `n`, or `None` if it does not fit in a `u64`.
+pub fn primorial(n: u64) -> Option<u64> {
+    let mut retval: u64 = 1;
+    let mut p = 2;
+    while p <= n {
+        if pollard::is_prime(p) {
+            retval = retval.checked_mul(p)?;
+        }
+        p += 1;
+    }
+    Some(retval)
+}
+
+/// Computes the primorial n# as a `u128`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound on the primes.
+///
+/// # Returns
+///
+/// The primorial of `n`, or `None` if it does not fit in a `u128`.
+pub fn primorial_u128(n: u64) -> Option<u128> {
+    let mut retval: u128 = 1;
+    let mut p = 2;
+    while p <= n {
+        if pollard::is_prime(p) {
+            retval = retval.checked_mul(p as u128)?;
+        }
+        p += 1;
+    }
+    Some(retval)
+}
+
+/// Computes the primorial n# modulo `modulus`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound on the primes.
+/// * `modulus` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `modulus` is 0.
+///
+/// # Complexity
+///
+/// Sieves all primes up to `n`, so this takes O(n log log n) time and O(n) memory.
+pub fn primorial_mod(n: u64, modulus: u64) -> u64 {
+    if modulus == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    sieve::primes_up_to(n as usize)
+        .into_iter()
+        .fold(1 % modulus, |acc, p| pollard::modmul(acc, p, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_omega() {
+        assert_eq!(omega(1), 0);
+        assert_eq!(omega(2), 1);
+        assert_eq!(omega(24), 2);
+        assert_eq!(omega(30), 3);
+        assert_eq!(omega(34486788), 5);
+    }
+
+    #[test]
+    fn test_big_omega() {
+        assert_eq!(big_omega(1), 0);
+        assert_eq!(big_omega(2), 1);
+        assert_eq!(big_omega(24), 4);
+        assert_eq!(big_omega(30), 3);
+        assert_eq!(big_omega(34486788), 7);
+    }
+
+    #[test]
+    fn test_is_k_almost_prime() {
+        assert!(!is_k_almost_prime(0, 0));
+        assert!(is_k_almost_prime(1, 0));
+        assert!(is_k_almost_prime(97, 1));
+        assert!(is_k_almost_prime(1000000007 * 998244353, 2));
+        assert!(!is_k_almost_prime(30, 2));
+        assert!(is_k_almost_prime(1 << 63, 63));
+    }
+
+    #[test]
+    fn test_liouville() {
+        let lambda: Vec<i32> = (1..=12).map(liouville).collect();
+        assert_eq!(lambda, vec![1, -1, -1, 1, -1, 1, -1, -1, 1, 1, -1, -1]);
+        assert_eq!(liouville(1 << 63), -1);
+        assert_eq!(liouville(1000003 * 1000003), 1);
+        // Σ_{d|n} λ(d) is 1 exactly when n is a square.
+        for n in 1..500u64 {
+            let sum: i32 = (1..=n).filter(|&d| n.is_multiple_of(d)).map(liouville).sum();
+            assert_eq!(sum, (n.isqrt().pow(2) == n) as i32);
+        }
+    }
+
+    #[test]
+    fn test_is_squarefree() {
+        assert!(is_squarefree(1));
+        assert!(is_squarefree(30));
+        assert!(!is_squarefree(12));
+        assert!(!is_squarefree(1000003 * 1000003));
+    }
+
+    #[test]
+    fn test_phi_iteration_length() {
+        assert_eq!(phi_iteration_length(1), 0);
+        assert_eq!(phi_iteration_length(2), 1);
+        // 10 -> 4 -> 2 -> 1.
+        assert_eq!(phi_iteration_length(10), 3);
+        assert_eq!(phi_iteration_length(1 << 63), 63);
+        // 3^40 -> 2·3^39 -> 2·3^38 -> ... -> 2 -> 1.
+        assert_eq!(phi_iteration_length(3u64.pow(40)), 41);
+        let sieve = sieve::TotientSieve::new(5000);
+        let lengths = sieve.phi_iteration_lengths();
+        for n in 1..=5000u64 {
+            assert_eq!(phi_iteration_length(n), lengths[n as usize]);
+        }
+    }
+
+    #[test]
+    fn test_jordan_totient() {
+        // J_k(n) counts the k-tuples in 1..=n with gcd(t_1, …, t_k, n) = 1.
+        for n in 1..40u64 {
+            let phi = (1..=n).filter(|&i| modexp::gcd(i, n) == 1).count() as u128;
+            assert_eq!(jordan_totient(n, 1), Some(phi));
+            let pairs = (1..=n)
+                .flat_map(|a| (1..=n).map(move |b| (a, b)))
+                .filter(|&(a, b)| modexp::gcd(modexp::gcd(a, b), n) == 1)
+                .count() as u128;
+            assert_eq!(jordan_totient(n, 2), Some(pairs));
+            assert_eq!(jordan_totient(n, 0), Some((n == 1) as u128));
+            for k in 0..5 {
+                let m = 1000000007;
+                assert_eq!(jordan_totient_mod(n, k, m) as u128, jordan_totient(n, k as u32).unwrap() % m as u128);
+            }
+        }
+        assert_eq!(jordan_totient(12, 3), Some(1728 * 7 / 8 * 26 / 27));
+        assert_eq!(jordan_totient(2, 127), Some((1 << 127) - 1));
+        assert_eq!(jordan_totient(2, 128), None);
+        assert_eq!(jordan_totient_mod(2, 128, 1000000007), (modexp::mod_exp(2, 128, 1000000007) + 1000000006) % 1000000007);
+        assert_eq!(jordan_totient_mod(7, 3, 1), 0);
+    }
+
+    #[test]
+    fn test_squarefree_part() {
+        assert_eq!(squarefree_part(1), 1);
+        assert_eq!(squarefree_part(12), 3);
+        assert_eq!(squarefree_part(72), 2);
+        assert_eq!(squarefree_part(1000003 * 1000003), 1);
+        for n in 1..2000u64 {
+            let s = squarefree_part(n);
+            let k = (n / s).isqrt();
+            assert!(is_squarefree(s));
+            assert_eq!(s * k * k, n);
+        }
+    }
+
+    #[test]
+    fn test_is_powerful() {
+        let powerful: Vec<u64> = (1..=50).filter(|&n| is_powerful(n)).collect();
+        assert_eq!(powerful, vec![1, 4, 8, 9, 16, 25, 27, 32, 36, 49]);
+        assert!(is_powerful(72));
+        assert!(!is_powerful(1000003 * 1000003 * 2));
+        assert!(is_powerful(1000003 * 1000003 * 8));
+    }
+
+    #[test]
+    fn test_is_carmichael() {
+        let carmichaels = [561, 1105, 1729, 2465, 2821, 6601, 8911, 10585, 15841, 29341];
+        for n in 0..30000 {
+            assert_eq!(is_carmichael(n), carmichaels.contains(&n), "n = {}", n);
+        }
+        assert!(is_carmichael(9999109081));
+    }
+
+    #[test]
+    fn test_sigma() {
+        assert_eq!(sigma(1), 1);
+        assert_eq!(sigma(12), 28);
+        assert_eq!(sigma(1000000007), 1000000008);
+        for n in 1..300u64 {
+            assert_eq!(sigma(n), (1..=n).filter(|d| n % d == 0).sum::<u64>() as u128);
+        }
+        // 18401055938125660800 is a highly abundant number with σ(n) > u64::MAX.
+        assert!(sigma(18401055938125660800) > u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(1), Abundance::Deficient);
+        assert_eq!(classify(12), Abundance::Abundant);
+        assert_eq!(classify(13), Abundance::Deficient);
+        let perfect: Vec<u64> = (1..10000).filter(|&n| classify(n) == Abundance::Perfect).collect();
+        assert_eq!(perfect, vec![6, 28, 496, 8128]);
+        assert_eq!(classify(2305843008139952128), Abundance::Perfect);
+        assert_eq!((1..100).filter(|&n| classify(n) == Abundance::Abundant).count(), 21);
+    }
+
+    #[test]
+    fn test_are_amicable() {
+        assert!(are_amicable(220, 284));
+        assert!(are_amicable(284, 220));
+        assert!(are_amicable(1184, 1210));
+        assert!(are_amicable(17296, 18416));
+        assert!(!are_amicable(6, 6));
+        assert!(!are_amicable(220, 285));
+        assert!(!are_amicable(0, 284));
+    }
+
+    #[test]
+    fn test_count_squarefree() {
+        assert_eq!(count_squarefree(0), 0);
+        assert_eq!(count_squarefree(1), 1);
+        assert_eq!(count_squarefree(10), 7);
+        let sieve = sieve::SquarefreeSieve::new(10000);
+        for n in 0..=10000u64 {
+            assert_eq!(count_squarefree(n), sieve.iter().take_while(|&x| x <= n).count() as u64);
+        }
+        assert_eq!(count_squarefree(1000000000000), 607927102274);
+    }
+
+    #[test]
+    fn test_divisor_summatory() {
+        assert_eq!(divisor_summatory(0), 0);
+        assert_eq!(sigma_summatory(0), 0);
+        assert_eq!(divisor_summatory(10), 27);
+        assert_eq!(sigma_summatory(10), 87);
+        let (mut d_sum, mut sigma_sum) = (0, 0);
+        for n in 1..=3000u64 {
+            d_sum += (1..=n).filter(|d| n % d == 0).count() as u128;
+            sigma_sum += sigma(n);
+            assert_eq!(divisor_summatory(n), d_sum, "n = {}", n);
+            assert_eq!(sigma_summatory(n), sigma_sum, "n = {}", n);
+        }
+        assert_eq!(divisor_summatory(1000000000000), 27785452449086);
+        assert_eq!(sigma_summatory(1000000), 822468118437);
+        assert_eq!(sigma_summatory(1000000000000), 822467033425357340138978);
+    }
+
+    #[test]
+    fn test_totient_sum() {
+        assert_eq!(totient_sum(0), 0);
+        assert_eq!(totient_sum(1), 1);
+        assert_eq!(totient_sum(10), 32);
+        let sieve = sieve::TotientSieve::new(100000);
+        for n in (0..=2000).chain((2001..=100000).step_by(997)) {
+            assert_eq!(totient_sum(n), sieve.prefix_sum(n) as u128, "n = {}", n);
+        }
+        assert_eq!(totient_sum(1000000), 303963552392);
+        assert_eq!(totient_sum(1000000000), 303963551173008414);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be greater than 50000000000000!")]
+    fn test_totient_sum_above_limit() {
+        totient_sum(MAX_TOTIENT_SUM + 1);
+    }
+
+    #[test]
+    fn test_sums_with_progress() {
+        use crate::CancelToken;
+        use std::sync::Mutex;
+
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        assert_eq!(divisor_summatory_with_progress(1000000000000, &hook), Ok(27785452449086));
+        // One report per block of 2^16 terms out of √n = 10^6, then the final one.
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 1000000usize.div_ceil(1 << 16) + 1);
+        assert_eq!(reports.last(), Some(&(1000000, 1000000)));
+        assert_eq!(count_squarefree_with_progress(1000000000000, &()), Ok(607927102274));
+
+        let last = Mutex::new((0, 0));
+        let hook = |done, total| *last.lock().unwrap() = (done, total);
+        assert_eq!(totient_sum_with_progress(1000000000, &hook), Ok(303963551173008414));
+        let (done, total) = last.into_inner().unwrap();
+        assert!(done == total && total > 0);
+        let above = Err(MathCombError::AboveLimit { value: MAX_TOTIENT_SUM + 1, limit: MAX_TOTIENT_SUM });
+        assert_eq!(totient_sum_with_progress(MAX_TOTIENT_SUM + 1, &()), above);
+
+        let token = CancelToken::new();
+        token.cancel();
+        assert_eq!(divisor_summatory_with_progress(1000000, &token), Err(MathCombError::Cancelled));
+        assert_eq!(count_squarefree_with_progress(1000000, &token), Err(MathCombError::Cancelled));
+        assert_eq!(totient_sum_with_progress(1000000000, &token), Err(MathCombError::Cancelled));
+        // An empty sum has nothing left to cancel.
+        assert_eq!(divisor_summatory_with_progress(0, &token), Ok(0));
+    }
+
+    #[test]
+    fn test_coprime_pairs_and_farey_length() {
+        assert_eq!(count_coprime_pairs(0), 0);
+        assert_eq!(count_coprime_pairs(1), 1);
+        for n in 1..60u64 {
+            let pairs = (1..=n).flat_map(|a| (1..=n).map(move |b| (a, b))).filter(|&(a, b)| crate::modexp::gcd(a, b) == 1).count();
+            assert_eq!(count_coprime_pairs(n), pairs as u128);
+        }
+        assert_eq!(farey_length(1), 2);
+        assert_eq!(farey_length(5), 11);
+        assert_eq!(farey_length(8), 23);
+        assert_eq!(count_coprime_pairs(1000000000), 607927102346016827);
+        assert_eq!(farey_length(1000000000), 303963551173008415);
+    }
+
+    #[test]
+    fn test_coprime_counting() {
+        for k in 0..80u64 {
+            let coprime: Vec<u64> = (1..=400).filter(|&x| crate::modexp::gcd(x, k) == 1).collect();
+            for n in 0..=400 {
+                assert_eq!(count_coprime_up_to(n, k), coprime.partition_point(|&x| x <= n) as u64, "n = {}, k = {}", n, k);
+            }
+            for (i, &x) in coprime.iter().enumerate() {
+                assert_eq!(kth_coprime(k, i as u64 + 1), Some(x), "k = {}, idx = {}", k, i + 1);
+            }
+        }
+        assert_eq!(kth_coprime(10, 0), None);
+        assert_eq!(kth_coprime(0, 2), None);
+        assert_eq!(count_coprime_up_to(u64::MAX, 1), u64::MAX);
+        // The product of the first 15 primes.
+        let k = 614889782588491410;
+        assert_eq!(count_coprime_up_to(k, k), 85287729364992000);
+        assert_eq!(kth_coprime(k, 1), Some(1));
+        assert_eq!(kth_coprime(k, 2), Some(53));
+        assert_eq!(kth_coprime(k, 85287729364992000), Some(k - 1));
+        assert_eq!(kth_coprime(k, 85287729364992001), Some(k + 1));
+        assert_eq!(kth_coprime(2, u64::MAX), None);
+        assert_eq!(kth_coprime(2, 1 << 63), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_gcd_sum() {
+        assert_eq!(gcd_sum(0), 0);
+        assert_eq!(gcd_sum(1), 1);
+        assert_eq!(gcd_sum(12), 40);
+        assert_eq!(gcd_sum(1 << 40), 42 << 39);
+        let n = 614889782588491410;
+        let expected: u128 = [2u128, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47].iter().map(|&p| 2 * p - 1).product();
+        assert_eq!(gcd_sum(n), expected);
+    }
+
+    #[test]
+    fn test_sorted_divisors() {
+        let divisors: Vec<u64> = SortedDivisors::new(pollard::factor_map(360)).collect();
+        let mut expected: Vec<u64> = (1..=360).filter(|d| 360 % d == 0).collect();
+        assert_eq!(divisors, expected);
+        let n = 963761198400;
+        let divisors: Vec<u64> = SortedDivisors::new(pollard::factor_map(n)).collect();
+        assert_eq!(divisors.len(), 6720);
+        assert!(divisors.windows(2).all(|w| w[0] < w[1]));
+        assert!(divisors.iter().all(|d| n % d == 0));
+        expected = SortedDivisors::new(vec![]).collect();
+        assert_eq!(expected, vec![1]);
+    }
+
+    #[test]
+    fn test_kth_divisor() {
+        assert_eq!(kth_divisor(0, 1), None);
+        assert_eq!(kth_divisor(12, 0), None);
+        assert_eq!(kth_divisor(1, 1), Some(1));
+        assert_eq!(kth_divisor(1, 2), None);
+        for n in 1..500u64 {
+            let divisors: Vec<u64> = (1..=n).filter(|d| n % d == 0).collect();
+            for k in 1..=divisors.len() as u64 + 1 {
+                assert_eq!(kth_divisor(n, k), divisors.get(k as usize - 1).copied());
+            }
+        }
+        assert_eq!(kth_divisor(963761198400, 6720), Some(963761198400));
+        assert_eq!(kth_divisor(1000000007, 2), Some(1000000007));
+    }
+
+    #[test]
+    fn test_iroot() {
+        assert_eq!(iroot(0, 2), 0);
+        assert_eq!(iroot(15, 2), 3);
+        assert_eq!(iroot(16, 2), 4);
+        assert_eq!(iroot(u64::MAX, 2), 4294967295);
+        assert_eq!(iroot(u64::MAX, 3), 2642245);
+        assert_eq!(iroot(1 << 63, 63), 2);
+        assert_eq!(iroot(1000000000000000000, 6), 1000);
+        assert_eq!(iroot(999999999999999999, 6), 999);
+    }
+
+    #[test]
+    fn test_as_prime_power() {
+        assert_eq!(as_prime_power(0), None);
+        assert_eq!(as_prime_power(1), None);
+        assert_eq!(as_prime_power(2), Some((2, 1)));
+        assert_eq!(as_prime_power(1 << 63), Some((2, 63)));
+        assert_eq!(as_prime_power(3486784401), Some((3, 20)));
+        assert_eq!(as_prime_power(1000000007), Some((1000000007, 1)));
+        assert_eq!(as_prime_power(1000003 * 1000003), Some((1000003, 2)));
+        assert_eq!(as_prime_power(36), None);
+        assert_eq!(as_prime_power(64 * 27), None);
+        assert_eq!(as_prime_power(1 << 62 | 1), None);
+    }
+
+    #[test]
+    fn test_primorial() {
+        assert_eq!(primorial(0), Some(1));
+        assert_eq!(primorial(1), Some(1));
+        assert_eq!(primorial(10), Some(210));
+        assert_eq!(primorial(13), Some(30030));
+        assert_eq!(primorial(52), Some(614889782588491410));
+        assert_eq!(primorial(53), None);
+        assert_eq!(primorial_u128(53), Some(614889782588491410 * 53));
+        assert_eq!(primorial_u128(1000), None);
+    }
+
+    #[test]
+    fn test_primorial_mod() {
+        assert_eq!(primorial_mod(13, 1000000007), 30030);
+        assert_eq!(primorial_mod(52, 1000000007), 614889782588491410 % 1000000007);
+        assert_eq!(primorial_mod(100, 1), 0);
+        assert_eq!(primorial_mod(100, 2), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Modulus cannot be zero.")]
+    fn test_primorial_mod_zero_modulus() {
+        primorial_mod(10, 0);
+    }
+}
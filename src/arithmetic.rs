@@ -0,0 +1,31 @@
+//! Integer arithmetic on `u64`: greatest common divisors and least common multiples, floor
+//! sums, continued fractions, and sums of two and four squares.
+//!
+//! It holds the functions of the former `Arith` facade, callable as
+//! `arithmetic::gcd(a, b)`. Width-generic gcds live in the [`int`](crate::int) module.
+
+pub use crate::contfrac::{continued_fraction, sqrt_continued_fraction};
+pub use crate::floorsum::{floor_sum, floor_sums};
+pub use crate::gcd::{binary_gcd, gcd_slice, lcm, lcm_slice};
+pub use crate::modexp::gcd;
+pub use crate::squares::{four_squares, four_squares_with_rng, two_squares};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_paths() {
+        const GCDS: (u64, u64) = (gcd(84, 36), binary_gcd(84, 36));
+        assert_eq!(GCDS, (12, 12));
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(gcd_slice(&[12, 18, 30]), 6);
+        assert_eq!(lcm_slice(&[u64::MAX, 2]), None);
+        assert_eq!(floor_sum(4, 2, 1, 0), 2);
+        assert_eq!(continued_fraction(415, 93), vec![4, 2, 6, 7]);
+        assert_eq!(sqrt_continued_fraction(7), (2, vec![1, 1, 1, 4]));
+        assert_eq!(two_squares(25), Some((4, 3)));
+        let (a, b, c, d) = four_squares(1000000007);
+        assert_eq!(a * a + b * b + c * c + d * d, 1000000007);
+    }
+}
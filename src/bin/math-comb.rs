@@ -0,0 +1,154 @@
+//! `math-comb`, a small command-line front end to the library, built with the `cli` feature.
+
+use std::env;
+use std::process::ExitCode;
+
+use math_comb::{modular, prime, Comb, MathCombError};
+
+/// The modulus of `ncr` when none is given.
+const DEFAULT_MODULUS: u64 = 1000000007;
+
+const COMMANDS: [&str; 5] = ["factor", "isprime", "ncr", "phi", "crt"];
+
+const USAGE: &str = "\
+Usage: math-comb <command> [arguments]
+
+Commands:
+  factor <n>...                  Prints the prime factors of each n
+  isprime <n>...                 Tells whether each n is prime
+  ncr <n> <r> [modulus]          Prints C(n, r) modulo a prime (1000000007 by default)
+  phi <n>...                     Prints Euler's totient of each n
+  crt <a1> <m1> [<a2> <m2>]...   Solves the system x ≡ a_i (mod m_i)";
+
+// Why a command failed: the arguments do not fit the command, or the library rejected them.
+enum CliError {
+    Usage(String),
+    Math(MathCombError),
+}
+
+impl From<MathCombError> for CliError {
+    fn from(e: MathCombError) -> CliError {
+        CliError::Math(e)
+    }
+}
+
+fn parse(arg: &str) -> Result<u64, CliError> {
+    arg.parse().map_err(|_| CliError::Usage(format!("not a non-negative integer: {}", arg)))
+}
+
+// Runs the command in `args` (without the program name), returning the lines to print.
+fn run(args: &[String]) -> Result<Vec<String>, CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage("missing command".to_string()));
+    };
+    if matches!(command.as_str(), "help" | "-h" | "--help") {
+        return Ok(vec![USAGE.to_string()]);
+    }
+    if !COMMANDS.contains(&command.as_str()) {
+        return Err(CliError::Usage(format!("unknown command: {}", command)));
+    }
+    let numbers = rest.iter().map(|arg| parse(arg)).collect::<Result<Vec<u64>, CliError>>()?;
+    if numbers.is_empty() {
+        return Err(CliError::Usage(format!("{} needs arguments", command)));
+    }
+    match command.as_str() {
+        "factor" => numbers
+            .iter()
+            .map(|&n| {
+                let factors: Vec<String> = prime::try_factor(n)?.iter().map(|p| p.to_string()).collect();
+                Ok(format!("{}: {}", n, factors.join(" ")))
+            })
+            .collect(),
+        "isprime" => Ok(numbers.iter().map(|&n| format!("{}: {}", n, prime::is_prime(n))).collect()),
+        "ncr" => {
+            let (n, r, modulus) = match numbers[..] {
+                [n, r] => (n, r, DEFAULT_MODULUS),
+                [n, r, modulus] => (n, r, modulus),
+                _ => return Err(CliError::Usage("ncr takes n, r and an optional modulus".to_string())),
+            };
+            // Factorials are multiplied on demand rather than tabulated up to n.
+            let comb = Comb::builder().modulus(modulus).auto_grow().try_build()?;
+            Ok(vec![comb.try_nCr(n, r)?.to_string()])
+        }
+        "phi" => numbers
+            .iter()
+            .map(|&n| {
+                prime::try_factor(n)?;
+                // J_1 = φ is at most n, so it always fits.
+                Ok(format!("{}: {}", n, prime::jordan_totient(n, 1).unwrap()))
+            })
+            .collect(),
+        "crt" => {
+            if numbers.len() % 2 != 0 {
+                return Err(CliError::Usage("crt takes pairs of a residue and a modulus".to_string()));
+            }
+            let congruences: Vec<(u64, u64)> = numbers.chunks(2).map(|c| (c[0], c[1])).collect();
+            if congruences.iter().any(|&(_, m)| m == 0) {
+                return Err(CliError::Math(MathCombError::ZeroModulus));
+            }
+            match modular::crt(&congruences) {
+                Some((x, m)) => Ok(vec![format!("x ≡ {} (mod {})", x, m)]),
+                None => Err(CliError::Math(MathCombError::InvalidArgument(
+                    "the congruences have no common solution below u64::MAX",
+                ))),
+            }
+        }
+        _ => unreachable!("commands are checked above"),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Math(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(line: &str) -> Result<Vec<String>, String> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        run(&args).map_err(|e| match e {
+            CliError::Usage(message) => format!("usage: {}", message),
+            CliError::Math(e) => e.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(output("factor 360 97"), Ok(vec!["360: 2 2 2 3 3 5".to_string(), "97: 97".to_string()]));
+        assert_eq!(output("factor 0"), Err("n cannot be zero".to_string()));
+        assert_eq!(output("isprime 1000000007 21"), Ok(vec!["1000000007: true".to_string(), "21: false".to_string()]));
+        assert_eq!(output("ncr 5 2"), Ok(vec!["10".to_string()]));
+        assert_eq!(output("ncr 100000 3 998244353"), Ok(vec![(166661666700000u64 % 998244353).to_string()]));
+        assert_eq!(output("ncr 2 5"), Err("n cannot be less than r".to_string()));
+        assert_eq!(output("ncr 13 2 13"), Err("13 is greater than the limit 12".to_string()));
+        assert_eq!(output("ncr 5 2 12"), Err("modulus 12 is not prime".to_string()));
+        assert_eq!(output("phi 36 1"), Ok(vec!["36: 12".to_string(), "1: 1".to_string()]));
+        assert_eq!(output("crt 2 3 3 5 2 7"), Ok(vec!["x ≡ 23 (mod 105)".to_string()]));
+        assert_eq!(output("crt 1 4 2 6"), Err("the congruences have no common solution below u64::MAX".to_string()));
+        assert_eq!(output("crt 1 0"), Err("modulus cannot be zero".to_string()));
+        assert_eq!(output("crt 1"), Err("usage: crt takes pairs of a residue and a modulus".to_string()));
+        assert_eq!(output("factor x"), Err("usage: not a non-negative integer: x".to_string()));
+        assert_eq!(output("factor"), Err("usage: factor needs arguments".to_string()));
+        assert_eq!(output("frobnicate 1"), Err("usage: unknown command: frobnicate".to_string()));
+        assert_eq!(output("frobnicate"), Err("usage: unknown command: frobnicate".to_string()));
+        assert_eq!(output(""), Err("usage: missing command".to_string()));
+        assert_eq!(output("help"), Ok(vec![USAGE.to_string()]));
+    }
+}
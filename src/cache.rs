@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::pollard;
+
+struct Entry {
+    factors: Vec<u64>,
+    prev: Option<u64>,
+    next: Option<u64>,
+}
+
+/// A memoization cache for prime factorizations.
+///
+/// Repeated factorizations of the same value are answered from a hash map after the first
+/// call. The cache is either unbounded or bounded to a fixed number of entries, in which
+/// case the least recently used entry is evicted once the capacity is exceeded.
+pub struct FactorCache {
+    map: HashMap<u64, Entry>,
+    capacity: Option<usize>,
+    // Most recently used key.
+    head: Option<u64>,
+    // Least recently used key.
+    tail: Option<u64>,
+}
+
+impl FactorCache {
+    /// Creates an unbounded `FactorCache`.
+    pub fn new() -> FactorCache {
+        FactorCache {
+            map: HashMap::new(),
+            capacity: None,
+            head: None,
+            tail: None,
+        }
+    }
+
+    /// Creates a `FactorCache` holding at most `capacity` factorizations, evicting the least
+    /// recently used entry when full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize) -> FactorCache {
+        if capacity == 0 {
+            panic!("capacity cannot be zero!");
+        }
+        FactorCache {
+            map: HashMap::with_capacity(capacity),
+            capacity: Some(capacity),
+            head: None,
+            tail: None,
+        }
+    }
+
+    /// Returns the prime factors of `n` in sorted order, computing and caching them on the
+    /// first request.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    pub fn factor(&mut self, n: u64) -> &[u64] {
+        if self.contains(n) {
+            self.detach(n);
+        } else {
+            let factors = pollard::factor(n);
+            self.map.insert(n, Entry { factors, prev: None, next: None });
+            if let Some(capacity) = self.capacity {
+                if self.map.len() > capacity {
+                    self.evict();
+                }
+            }
+        }
+        self.push_front(n);
+        &self.map[&n].factors
+    }
+
+    /// Returns `true` if the factorization of `n` is currently cached.
+    pub fn contains(&self, n: u64) -> bool {
+        self.map.contains_key(&n)
+    }
+
+    /// Returns the number of cached factorizations.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all cached factorizations.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    // Unlinks `key` from the recency list, leaving its entry in the map.
+    fn detach(&mut self, key: u64) {
+        let (prev, next) = {
+            let entry = &self.map[&key];
+            (entry.prev, entry.next)
+        };
+        match prev {
+            Some(p) => self.map.get_mut(&p).unwrap().next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(nx) => self.map.get_mut(&nx).unwrap().prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    // Links `key` in as the most recently used entry.
+    fn push_front(&mut self, key: u64) {
+        let old_head = self.head;
+        {
+            let entry = self.map.get_mut(&key).unwrap();
+            entry.prev = None;
+            entry.next = old_head;
+        }
+        if let Some(h) = old_head {
+            self.map.get_mut(&h).unwrap().prev = Some(key);
+        }
+        self.head = Some(key);
+        if self.tail.is_none() {
+            self.tail = Some(key);
+        }
+    }
+
+    // Drops the least recently used linked entry.
+    fn evict(&mut self) {
+        if let Some(t) = self.tail {
+            self.detach(t);
+            self.map.remove(&t);
+        }
+    }
+}
+
+impl Default for FactorCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A thread-safe `FactorCache` that can be shared between threads (e.g. behind an `Arc`).
+pub struct SyncFactorCache {
+    inner: Mutex<FactorCache>,
+}
+
+impl SyncFactorCache {
+    /// Creates an unbounded `SyncFactorCache`.
+    pub fn new() -> SyncFactorCache {
+        SyncFactorCache { inner: Mutex::new(FactorCache::new()) }
+    }
+
+    /// Creates a `SyncFactorCache` holding at most `capacity` factorizations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize) -> SyncFactorCache {
+        SyncFactorCache { inner: Mutex::new(FactorCache::with_capacity(capacity)) }
+    }
+
+    /// Returns the prime factors of `n` in sorted order, computing and caching them on the
+    /// first request.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    pub fn factor(&self, n: u64) -> Vec<u64> {
+        self.inner.lock().unwrap().factor(n).to_vec()
+    }
+
+    /// Returns `true` if the factorization of `n` is currently cached.
+    pub fn contains(&self, n: u64) -> bool {
+        self.inner.lock().unwrap().contains(n)
+    }
+
+    /// Returns the number of cached factorizations.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().is_empty()
+    }
+
+    /// Removes all cached factorizations.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().clear();
+    }
+}
+
+impl Default for SyncFactorCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_factor_cache() {
+        let mut cache = FactorCache::new();
+        assert!(cache.is_empty());
+        assert_eq!(cache.factor(24), &[2, 2, 2, 3]);
+        assert_eq!(cache.factor(24), &[2, 2, 2, 3]);
+        assert_eq!(cache.factor(1), &[] as &[u64]);
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(!cache.contains(24));
+    }
+
+    #[test]
+    fn test_factor_cache_lru_eviction() {
+        let mut cache = FactorCache::with_capacity(2);
+        cache.factor(12);
+        cache.factor(45);
+        cache.factor(12);
+        cache.factor(49);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(12));
+        assert!(!cache.contains(45));
+        assert!(cache.contains(49));
+        assert_eq!(cache.factor(45), &[3, 3, 5]);
+        assert!(!cache.contains(12));
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity cannot be zero!")]
+    fn test_factor_cache_zero_capacity() {
+        FactorCache::with_capacity(0);
+    }
+
+    #[test]
+    fn test_sync_factor_cache() {
+        let cache = Arc::new(SyncFactorCache::with_capacity(64));
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = Arc::clone(&cache);
+                thread::spawn(move || {
+                    for n in 2..50u64 {
+                        let factors = cache.factor(n * (t + 1));
+                        assert_eq!(factors.iter().product::<u64>(), n * (t + 1));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(cache.len() <= 64);
+    }
+}
@@ -0,0 +1,140 @@
+/// Expands `p/q` as a continued fraction `[a0; a1, a2, …]`, the sequence of quotients of
+/// the Euclidean algorithm on `p` and `q`.
+///
+/// # Arguments
+///
+/// * `p` - The numerator.
+/// * `q` - The denominator.
+///
+/// # Returns
+///
+/// The terms, whose last one is at least 2 unless the expansion is the single term `[a0]`.
+///
+/// # Panics
+///
+/// Panics if `q` is zero.
+pub fn continued_fraction(p: u64, q: u64) -> Vec<u64> {
+    if q == 0 {
+        panic!("Denominator cannot be zero!");
+    }
+    let (mut a, mut b) = (p, q);
+    let mut terms = Vec::new();
+    while b != 0 {
+        terms.push(a / b);
+        (a, b) = (b, a % b);
+    }
+    terms
+}
+
+/// Expands `√d` as the periodic continued fraction `[a0; a1, …, ak]` with the bar over
+/// `a1, …, ak`.
+///
+/// The complete quotients are `(m + √d) / t` with integers `m` and `t`, and the period ends
+/// at the first term `2·a0`.
+///
+/// # Arguments
+///
+/// * `d` - The number under the root.
+///
+/// # Returns
+///
+/// `(a0, period)`, where `period` is empty if `d` is a perfect square.
+///
+/// # Complexity
+///
+/// O(k) for a period of length k, which is O(√d log d) at worst.
+pub fn sqrt_continued_fraction(d: u64) -> (u64, Vec<u64>) {
+    let a0 = d.isqrt();
+    let mut period = Vec::new();
+    if a0 * a0 == d {
+        return (a0, period);
+    }
+    let (d, a0) = (d as u128, a0 as u128);
+    let (mut m, mut t, mut a) = (0, 1, a0);
+    while a != 2 * a0 {
+        m = t * a - m;
+        t = (d - m * m) / t;
+        a = (a0 + m) / t;
+        period.push(a as u64);
+    }
+    (a0 as u64, period)
+}
+
+/// An iterator over the convergents `p_i / q_i` of a continued fraction, given its terms.
+///
+/// The convergents follow `p_i = a_i·p_{i−1} + p_{i−2}` and likewise for `q_i`; the
+/// iterator ends with the terms or at the first convergent that overflows a `u128`.
+pub struct Convergents<I> {
+    terms: I,
+    // (p_{i−2}, q_{i−2}) and (p_{i−1}, q_{i−1}), starting from 0/1 and 1/0.
+    prev: (u128, u128),
+    cur: (u128, u128),
+}
+
+impl<I: Iterator<Item = u64>> Convergents<I> {
+    /// Creates the iterator over the convergents of `[a0; a1, …]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `terms` - The terms `a0, a1, …`, possibly infinite (e.g. a cycled period).
+    pub fn new<T: IntoIterator<IntoIter = I>>(terms: T) -> Convergents<I> {
+        Convergents { terms: terms.into_iter(), prev: (0, 1), cur: (1, 0) }
+    }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for Convergents<I> {
+    type Item = (u128, u128);
+
+    fn next(&mut self) -> Option<(u128, u128)> {
+        let a = self.terms.next()? as u128;
+        let p = a.checked_mul(self.cur.0)?.checked_add(self.prev.0)?;
+        let q = a.checked_mul(self.cur.1)?.checked_add(self.prev.1)?;
+        (self.prev, self.cur) = (self.cur, (p, q));
+        Some((p, q))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continued_fraction() {
+        assert_eq!(continued_fraction(415, 93), vec![4, 2, 6, 7]);
+        assert_eq!(continued_fraction(0, 5), vec![0]);
+        assert_eq!(continued_fraction(7, 1), vec![7]);
+        assert_eq!(continued_fraction(1, 2), vec![0, 2]);
+        assert_eq!(continued_fraction(10, 4), vec![2, 2]);
+        for p in 0..60 {
+            for q in 1..60 {
+                let g = crate::modexp::gcd(p, q).max(1);
+                let last = Convergents::new(continued_fraction(p, q)).last();
+                assert_eq!(last, Some(((p / g) as u128, (q / g) as u128)));
+            }
+        }
+        let fib: Vec<u64> = Convergents::new(std::iter::repeat(1)).map(|(p, _)| p as u64).take(10).collect();
+        assert_eq!(fib, vec![1, 2, 3, 5, 8, 13, 21, 34, 55, 89]);
+    }
+
+    #[test]
+    fn test_sqrt_continued_fraction() {
+        assert_eq!(sqrt_continued_fraction(0), (0, vec![]));
+        assert_eq!(sqrt_continued_fraction(16), (4, vec![]));
+        assert_eq!(sqrt_continued_fraction(2), (1, vec![2]));
+        assert_eq!(sqrt_continued_fraction(7), (2, vec![1, 1, 1, 4]));
+        assert_eq!(sqrt_continued_fraction(23), (4, vec![1, 3, 1, 8]));
+        assert_eq!(sqrt_continued_fraction(u64::MAX), (4294967295, vec![1, 8589934590]));
+        // Project Euler 64: 1322 of the d <= 10000 have an odd period.
+        assert_eq!((2..=10000).filter(|&d| sqrt_continued_fraction(d).1.len() % 2 == 1).count(), 1322);
+        // The period of √61 is odd, so the convergent ending its second repetition solves
+        // Pell's equation x² − 61y² = 1.
+        let (a0, period) = sqrt_continued_fraction(61);
+        let terms = std::iter::once(a0).chain(period.iter().copied().cycle());
+        let (x, y) = Convergents::new(terms).nth(2 * period.len() - 1).unwrap();
+        assert_eq!((x, y), (1766319049, 226153980));
+        assert_eq!(x * x - 61 * y * y, 1);
+        // Convergents of √2 run until a u128 overflows.
+        let terms = std::iter::once(1).chain(std::iter::repeat(2));
+        assert_eq!(Convergents::new(terms).count(), 101);
+    }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fmt;
+
+/// The reasons a fallible (`try_*`) operation can reject its arguments, for callers that
+/// cannot afford the panics of the plain variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathCombError {
+    /// The modulus is 0.
+    ZeroModulus,
+    /// The modulus has to be prime but is not.
+    ModulusNotPrime(u64),
+    /// `x` has no inverse modulo `modulus`.
+    NotInvertible { x: u64, modulus: u64 },
+    /// A query lies above the limit a table was built for.
+    AboveLimit { value: u64, limit: u64 },
+    /// An argument lies outside the domain of the operation.
+    InvalidArgument(&'static str),
+    /// A `ProgressHook` cancelled the computation before it finished.
+    Cancelled,
+}
+
+impl fmt::Display for MathCombError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MathCombError::ZeroModulus => write!(f, "modulus cannot be zero"),
+            MathCombError::ModulusNotPrime(m) => write!(f, "modulus {} is not prime", m),
+            MathCombError::NotInvertible { x, modulus } => write!(f, "{} has no inverse modulo {}", x, modulus),
+            MathCombError::AboveLimit { value, limit } => write!(f, "{} is greater than the limit {}", value, limit),
+            MathCombError::InvalidArgument(reason) => write!(f, "{}", reason),
+            MathCombError::Cancelled => write!(f, "the computation was cancelled"),
+        }
+    }
+}
+
+impl Error for MathCombError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(MathCombError::ZeroModulus.to_string(), "modulus cannot be zero");
+        assert_eq!(MathCombError::ModulusNotPrime(12).to_string(), "modulus 12 is not prime");
+        assert_eq!(MathCombError::NotInvertible { x: 8, modulus: 12 }.to_string(), "8 has no inverse modulo 12");
+        assert_eq!(MathCombError::AboveLimit { value: 11, limit: 10 }.to_string(), "11 is greater than the limit 10");
+        assert_eq!(MathCombError::InvalidArgument("n cannot be zero").to_string(), "n cannot be zero");
+        assert_eq!(MathCombError::Cancelled.to_string(), "the computation was cancelled");
+        let boxed: Box<dyn Error> = Box::new(MathCombError::ZeroModulus);
+        assert!(boxed.source().is_none());
+    }
+}
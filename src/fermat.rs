@@ -0,0 +1,57 @@
+/// Fermat's difference-of-squares factorization.
+///
+/// Searches for `a >= ⌈√n⌉` such that `a² - n = b²`, in which case `n = (a - b)(a + b)`.
+/// Each step only needs an addition and a square test, so factors close to √n are found
+/// almost immediately.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `max_steps` - The number of candidates `a` to try before giving up.
+///
+/// # Returns
+///
+/// A non-trivial factor of `n`, or `None` if none was found within `max_steps` steps
+/// (always the case for `n` prime or `n` < 4).
+pub fn fermat(n: u64, max_steps: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let n = n as u128;
+    let mut a = n.isqrt();
+    if a * a < n {
+        a += 1;
+    }
+    let mut b2 = a * a - n;
+    for _ in 0..max_steps {
+        let b = b2.isqrt();
+        if b * b == b2 {
+            let x = (a - b) as u64;
+            return if x > 1 { Some(x) } else { None };
+        }
+        // (a + 1)² - a² = 2a + 1
+        b2 += 2 * a + 1;
+        a += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fermat() {
+        assert_eq!(fermat(5959, 10), Some(59));
+        assert_eq!(fermat(1000003 * 1000033, 10), Some(1000003));
+        assert_eq!(fermat(4294967291 * 4294967279, 10), Some(4294967279));
+        assert_eq!(fermat(49, 1), Some(7));
+        assert_eq!(fermat(1000, 1), Some(2));
+        assert_eq!(fermat(3 * 1000000007, 10), None);
+        assert_eq!(fermat(1000000007, 1000), None);
+        assert_eq!(fermat(3, 10), None);
+    }
+}
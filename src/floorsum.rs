@@ -0,0 +1,229 @@
+/// Computes `Σ_{i=0}^{n−1} ⌊(a·i + b) / m⌋` in O(log m) steps.
+///
+/// The quotients `⌊a/m⌋` and `⌊b/m⌋` are peeled off in closed form, after which the sum counts
+/// the lattice points under a line with slope below 1. Counting them by columns instead of
+/// rows swaps the roles of `a` and `m`, exactly as in the Euclidean algorithm.
+///
+/// # Arguments
+///
+/// * `n` - The number of terms.
+/// * `m` - The denominator.
+/// * `a` - The slope.
+/// * `b` - The offset.
+///
+/// # Returns
+///
+/// The sum, wrapped modulo 2^128 in the rare cases where it does not fit.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+pub fn floor_sum(n: u64, m: u64, a: u64, b: u64) -> u128 {
+    if m == 0 {
+        panic!("m cannot be zero!");
+    }
+    let (mut n, mut m, mut a, mut b) = (n as u128, m as u128, a as u128, b as u128);
+    let mut sum: u128 = 0;
+    loop {
+        if a >= m {
+            sum = sum.wrapping_add((n * n.saturating_sub(1) / 2).wrapping_mul(a / m));
+            a %= m;
+        }
+        if b >= m {
+            sum = sum.wrapping_add(n * (b / m));
+            b %= m;
+        }
+        // With a, b < m, the terms are the points (i, j) with 1 ≤ j and m·j ≤ a·i + b.
+        let y_max = a * n + b;
+        if y_max < m {
+            return sum;
+        }
+        (n, b) = (y_max / m, y_max % m);
+        std::mem::swap(&mut m, &mut a);
+    }
+}
+
+// A segment of the walk along the line y = (a·x + b) / m, recording at every step right
+// the current height. All sums wrap modulo 2^128, which is exact since only ring operations
+// are used.
+#[derive(Clone, Copy)]
+struct Walk {
+    // Steps right and up.
+    x: u128,
+    y: u128,
+    // Σ y, Σ x, Σ x·y and Σ y² over the steps right, with x and y measured from the start
+    // of the segment before each step.
+    sum_y: u128,
+    sum_x: u128,
+    sum_xy: u128,
+    sum_yy: u128,
+}
+
+impl Walk {
+    const EMPTY: Walk = Walk { x: 0, y: 0, sum_y: 0, sum_x: 0, sum_xy: 0, sum_yy: 0 };
+    const UP: Walk = Walk { y: 1, ..Walk::EMPTY };
+    const RIGHT: Walk = Walk { x: 1, ..Walk::EMPTY };
+
+    // The segment `self` followed by `next`, whose steps right are shifted by (x, y).
+    fn then(self, next: Walk) -> Walk {
+        let (x, y) = (self.x, self.y);
+        Walk {
+            x: x.wrapping_add(next.x),
+            y: y.wrapping_add(next.y),
+            sum_y: self.sum_y.wrapping_add(next.sum_y).wrapping_add(y.wrapping_mul(next.x)),
+            sum_x: self.sum_x.wrapping_add(next.sum_x).wrapping_add(x.wrapping_mul(next.x)),
+            sum_xy: self
+                .sum_xy
+                .wrapping_add(next.sum_xy)
+                .wrapping_add(x.wrapping_mul(next.sum_y))
+                .wrapping_add(y.wrapping_mul(next.sum_x))
+                .wrapping_add(x.wrapping_mul(y).wrapping_mul(next.x)),
+            sum_yy: self
+                .sum_yy
+                .wrapping_add(next.sum_yy)
+                .wrapping_add(2u128.wrapping_mul(y).wrapping_mul(next.sum_y))
+                .wrapping_add(y.wrapping_mul(y).wrapping_mul(next.x)),
+        }
+    }
+
+    fn pow(self, mut k: u128) -> Walk {
+        let (mut result, mut base) = (Walk::EMPTY, self);
+        while k > 0 {
+            if k & 1 == 1 {
+                result = result.then(base);
+            }
+            base = base.then(base);
+            k >>= 1;
+        }
+        result
+    }
+}
+
+// The universal Euclidean algorithm: the walk for x = 1, …, l, where the x-th step `right`
+// comes after ⌊(p·x + r) / q⌋ steps `up` in total, for r < q. Swapping the roles of the two
+// steps reduces (p, q) exactly as the Euclidean algorithm does.
+fn universal_euclid(p: u128, q: u128, r: u128, l: u128, up: Walk, right: Walk) -> Walk {
+    if l == 0 {
+        return Walk::EMPTY;
+    }
+    if p >= q {
+        return universal_euclid(p % q, q, r, l, up, up.pow(p / q).then(right));
+    }
+    let top = (l * p + r) / q;
+    if top == 0 {
+        return right.pow(l);
+    }
+    let tail = l - (q * top - r - 1) / p;
+    let inner = universal_euclid(q, p, (q - r - 1) % p, top - 1, right, up);
+    right.pow((q - r - 1) / p).then(up).then(inner).then(right.pow(tail))
+}
+
+/// Computes `Σ ⌊(a·i + b) / m⌋`, `Σ i·⌊(a·i + b) / m⌋` and `Σ ⌊(a·i + b) / m⌋²` over
+/// `0 <= i < n` with the universal Euclidean algorithm.
+///
+/// The terms are the heights of a walk along the line, which is a word in the steps up and
+/// right. Each step is a small record of sums that compose associatively, and the word is
+/// built by the same quotient-and-swap recursion as `floor_sum`, with repeated steps
+/// raised to powers by squaring.
+///
+/// # Arguments
+///
+/// * `n` - The number of terms.
+/// * `m` - The denominator.
+/// * `a` - The slope.
+/// * `b` - The offset.
+///
+/// # Returns
+///
+/// The three sums, each wrapped modulo 2^128 if it does not fit.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+///
+/// # Complexity
+///
+/// O(log² max(a, m)).
+pub fn floor_sums(n: u64, m: u64, a: u64, b: u64) -> (u128, u128, u128) {
+    if m == 0 {
+        panic!("m cannot be zero!");
+    }
+    if n == 0 {
+        return (0, 0, 0);
+    }
+    let start = Walk::UP.pow((b / m) as u128).then(Walk::RIGHT);
+    let walk = start.then(universal_euclid(a as u128, m as u128, (b % m) as u128, n as u128 - 1, Walk::UP, Walk::RIGHT));
+    (walk.sum_y, walk.sum_xy, walk.sum_yy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor_sum_naive(n: u64, m: u64, a: u64, b: u64) -> u128 {
+        (0..n).map(|i| (a as u128 * i as u128 + b as u128) / m as u128).sum()
+    }
+
+    #[test]
+    fn test_floor_sum() {
+        assert_eq!(floor_sum(0, 5, 3, 7), 0);
+        // ⌊3/10⌋ + ⌊9/10⌋ + ⌊15/10⌋ + ⌊21/10⌋
+        assert_eq!(floor_sum(4, 10, 6, 3), 3);
+        assert_eq!(floor_sum(6, 5, 4, 3), 13);
+        assert_eq!(floor_sum(1, 1, 0, u64::MAX), u64::MAX as u128);
+        for n in 0..25 {
+            for m in 1..25 {
+                for a in 0..25 {
+                    for b in 0..25 {
+                        assert_eq!(floor_sum(n, m, a, b), floor_sum_naive(n, m, a, b), "({}, {}, {}, {})", n, m, a, b);
+                    }
+                }
+            }
+        }
+        for (n, m, a, b) in [(100000, 999983, 123456789, 987654321), (77777, 1 << 40, u64::MAX, u64::MAX), (3, u64::MAX, u64::MAX - 1, u64::MAX)] {
+            assert_eq!(floor_sum(n, m, a, b), floor_sum_naive(n, m, a, b));
+        }
+        // Σ_{i<m} ⌊a·i / m⌋ = (a − 1)(m − 1)/2 for coprime a and m, and sums near 2^128.
+        let m = (1 << 61) - 1;
+        assert_eq!(floor_sum(m, m, 12345678901, 0), (12345678901 - 1) * (m as u128 - 1) / 2);
+        let n = u64::MAX as u128;
+        assert_eq!(floor_sum(u64::MAX, u64::MAX, u64::MAX, 0), n * (n - 1) / 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "m cannot be zero!")]
+    fn test_floor_sum_zero_denominator() {
+        floor_sum(5, 0, 1, 1);
+    }
+
+    #[test]
+    fn test_floor_sums() {
+        assert_eq!(floor_sums(0, 5, 3, 7), (0, 0, 0));
+        assert_eq!(floor_sums(4, 10, 6, 3), (3, 2 + 3 * 2, 1 + 4));
+        for n in 0..20 {
+            for m in 1..20 {
+                for a in 0..20 {
+                    for b in 0..30 {
+                        let terms = (0..n).map(|i| ((a * i + b) / m) as u128);
+                        let weighted = terms.clone().zip(0..).map(|(t, i)| t * i).sum();
+                        let squares = terms.clone().map(|t| t * t).sum();
+                        assert_eq!(floor_sums(n, m, a, b), (floor_sum(n, m, a, b), weighted, squares), "({}, {}, {}, {})", n, m, a, b);
+                    }
+                }
+            }
+        }
+        for (n, m, a, b) in [(100000, 999983, 123456789, 987654321), (77777, 1 << 40, u64::MAX, u64::MAX), (3, u64::MAX, u64::MAX - 1, u64::MAX)] {
+            let terms = (0..n).map(|i| (a as u128 * i as u128 + b as u128) / m as u128);
+            let weighted = terms.clone().zip(0..).map(|(t, i)| t * i).sum();
+            let squares = terms.clone().map(|t| t * t).sum();
+            assert_eq!(floor_sums(n, m, a, b), (floor_sum(n, m, a, b), weighted, squares));
+        }
+        // The sums wrap modulo 2^128: Σ_{i<n} i² for n = 2^64 − 1.
+        let n = u64::MAX as u128;
+        let (sum, weighted, squares) = floor_sums(u64::MAX, 1, 1, 0);
+        assert_eq!(sum, n * (n - 1) / 2);
+        assert_eq!(weighted, squares);
+        // 3 divides n, so Σ i² = (n − 1)n(2n − 1)/6 is computed without overflowing first.
+        assert_eq!(squares, ((n - 1) * n / 2 / 3).wrapping_mul(2 * n - 1));
+    }
+}
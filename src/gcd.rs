@@ -0,0 +1,123 @@
+/// Computes the greatest common divisor of `a` and `b` using Stein's binary algorithm.
+///
+/// Replaces divisions by shifts and subtractions, which is usually faster than the
+/// Euclidean algorithm on `u64`.
+///
+/// # Arguments
+///
+/// * `a` - The first number.
+/// * `b` - The second number.
+///
+/// # Returns
+///
+/// gcd(`a`, `b`), with gcd(0, 0) = 0.
+pub const fn binary_gcd(a: u64, b: u64) -> u64 {
+    if a == 0 {
+        return b;
+    }
+    if b == 0 {
+        return a;
+    }
+    let shift = (a | b).trailing_zeros();
+    let mut a = a >> a.trailing_zeros();
+    let mut b = b;
+    while b != 0 {
+        b >>= b.trailing_zeros();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+    }
+    a << shift
+}
+
+/// Computes the least common multiple of `a` and `b`.
+///
+/// # Arguments
+///
+/// * `a` - The first number.
+/// * `b` - The second number.
+///
+/// # Returns
+///
+/// lcm(`a`, `b`) (0 if either argument is 0), or `None` if it overflows a `u64`.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / binary_gcd(a, b)).checked_mul(b)
+}
+
+/// Computes the greatest common divisor of all numbers in `values`.
+///
+/// # Arguments
+///
+/// * `values` - The numbers.
+///
+/// # Returns
+///
+/// The gcd of `values` (0 for an empty slice).
+pub fn gcd_slice(values: &[u64]) -> u64 {
+    let mut retval = 0;
+    for &x in values {
+        retval = binary_gcd(retval, x);
+        if retval == 1 {
+            break;
+        }
+    }
+    retval
+}
+
+/// Computes the least common multiple of all numbers in `values`.
+///
+/// # Arguments
+///
+/// * `values` - The numbers.
+///
+/// # Returns
+///
+/// The lcm of `values` (1 for an empty slice), or `None` if it overflows a `u64`.
+pub fn lcm_slice(values: &[u64]) -> Option<u64> {
+    values.iter().try_fold(1, |acc, &x| lcm(acc, x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modexp::gcd;
+
+    #[test]
+    fn test_binary_gcd() {
+        assert_eq!(binary_gcd(0, 0), 0);
+        assert_eq!(binary_gcd(0, 12), 12);
+        assert_eq!(binary_gcd(12, 0), 12);
+        assert_eq!(binary_gcd(48, 180), 12);
+        assert_eq!(binary_gcd(17, 5), 1);
+        assert_eq!(binary_gcd(1 << 40, 3 << 20), 1 << 20);
+        for a in 0..200 {
+            for b in 0..200 {
+                assert_eq!(binary_gcd(a, b), gcd(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(7, 13), Some(91));
+        assert_eq!(lcm(1 << 40, 3 << 30), Some(3 << 40));
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_slices() {
+        assert_eq!(gcd_slice(&[]), 0);
+        assert_eq!(gcd_slice(&[12, 18, 30]), 6);
+        assert_eq!(gcd_slice(&[0, 7]), 7);
+        assert_eq!(lcm_slice(&[]), Some(1));
+        assert_eq!(lcm_slice(&[2, 3, 4, 5, 6]), Some(60));
+        assert_eq!(lcm_slice(&(1..=40).collect::<Vec<u64>>()), Some(5342931457063200));
+        assert_eq!(lcm_slice(&(1..=50).collect::<Vec<u64>>()), None);
+    }
+}
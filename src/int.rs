@@ -0,0 +1,79 @@
+//! Width-generic versions of the core entry points, which run natively on `u32`, `u64` and
+//! `u128` through the [`UInt`](crate::UInt) trait, callable as `int::factor(n)`.
+
+use crate::random::RandomSource;
+use crate::uint::{self, UInt};
+
+/// Computes the greatest common divisor of `a` and `b` with the Euclidean algorithm.
+///
+/// # Arguments
+///
+/// * `a` - The first number.
+/// * `b` - The second number.
+pub fn gcd<T: UInt>(a: T, b: T) -> T {
+    uint::gcd(a, b)
+}
+
+/// Calculates (base^exponent) % modulus by repeated squaring in the width of the arguments.
+///
+/// # Arguments
+///
+/// * `base` - The base.
+/// * `exponent` - The exponent.
+/// * `modulus` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `modulus` is 0.
+pub fn mod_exp<T: UInt>(base: T, exponent: T, modulus: T) -> T {
+    uint::mod_exp(base, exponent, modulus)
+}
+
+/// Checks whether `n` is prime.
+///
+/// A `u32` needs two Miller-Rabin rounds with `u64` products, and a `u64` goes through
+/// `prime::is_prime`. A `u128` beyond `u64::MAX` gets the Baillie–PSW test, a base-2
+/// Miller-Rabin round followed by a strong Lucas test, which is not known to accept any
+/// composite although none of its range has been verified exhaustively.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+pub fn is_prime<T: UInt>(n: T) -> bool {
+    n.is_prime()
+}
+
+/// Checks whether `n` is prime with `rounds` rounds of Miller-Rabin to random bases drawn
+/// from `rng`, instead of the Baillie–PSW test of `is_prime`. A composite `n` passes with
+/// probability at most 4^(−`rounds`). Values up to `u64::MAX` are decided exactly and draw
+/// nothing from `rng`.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+/// * `rounds` - The number of random bases to try.
+/// * `rng` - The source of the bases.
+pub fn is_probable_prime<T: UInt>(n: T, rounds: u32, rng: &mut impl RandomSource) -> bool {
+    uint::is_probable_prime(n, rounds, rng)
+}
+
+/// Factorizes `n` into its prime factors.
+///
+/// A `u64` goes through `prime::factor`; other widths strip the primes below 100 and split
+/// the rest with Brent's variant of Pollard's rho, so a `u128` factors quickly as long as
+/// its second largest prime factor stays well below 10^12.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+///
+/// # Returns
+///
+/// The prime factors of `n` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn factor<T: UInt>(n: T) -> Vec<T> {
+    n.factor()
+}
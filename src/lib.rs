@@ -1,9 +1,76 @@
+mod arith;
+pub mod arithmetic;
+mod cache;
+mod contfrac;
+mod error;
+mod fermat;
+mod floorsum;
+mod gcd;
+pub mod int;
+mod matrix;
+mod min25;
 mod modexp;
+mod modint;
+pub mod modular;
+mod mulmod;
+mod ntt;
 mod pollard;
+mod poly;
+mod polyfactor;
+mod primality;
+pub mod prime;
+mod primecount;
+mod progress;
+mod random;
+pub mod rational;
+mod rank;
+mod recurrence;
+pub mod sequence;
+mod series;
+mod sieve;
+mod smooth;
+mod squares;
+mod sternbrocot;
+mod stream;
+mod table;
+mod transform;
+pub mod transforms;
+mod uint;
+
+pub use arith::Abundance;
+pub use cache::{FactorCache, SyncFactorCache};
+pub use contfrac::Convergents;
+pub use error::MathCombError;
+pub use matrix::{MatMod, XorBasis};
+pub use modint::{ModComb, ModInt};
+pub use mulmod::{MulMod, MulStrategy};
+pub use ntt::OnlineConvolution;
+pub use pollard::Factors;
+pub use progress::{CancelToken, ProgressHook};
+pub use random::{RandomSource, SplitMix64};
+pub use poly::Poly;
+pub use sieve::{
+    DivisorSieve, GcdSumSieve, LiouvilleSieve, LpfSieve, MobiusSieve, MultiplicativeSieve,
+    OmegaSieve, PrimeSieve, RangeSpf, SegmentedSieve, SieveBackend, SquarefreeSieve,
+    TotientSieve, WheelSieve,
+};
+pub use smooth::SmoothNumbers;
+pub use sternbrocot::{Direction, FareySequence, Fraction};
+pub use stream::FactorStream;
+pub use uint::UInt;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use table::{Table, TableReader, TableWriter};
 
 /// A struct that provides methods for prime factorization using pollard rho algorithm and testing primality of numbers.
+#[deprecated(note = "use the free functions of the `math_comb::prime` module, e.g. `prime::factor(n)`")]
 pub struct Prime {}
 
+#[allow(deprecated)]
 impl Prime {
     /// Pollard's rho algorithm for integer factorization.
     ///
@@ -20,11 +87,61 @@ impl Prime {
     /// A prime divisor can be achieved with a probability around 0.5, in O(?d) <= O(`n`^(1/4)) iterations. 
     /// This is a heuristic claim, and rigorous analysis of the algorithm remains open.
     pub fn pollard(n: u64) -> u64 {
-       return pollard::pollard(n);
+        pollard::pollard(n)
+    }
+
+    /// Fermat's difference-of-squares factorization, which quickly splits `n` when it is
+    /// the product of two factors close to √`n`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    /// * `max_steps` - The number of candidate squares to try before giving up.
+    ///
+    /// # Returns
+    ///
+    /// A non-trivial factor of `n`, or `None` if none was found within `max_steps` steps.
+    pub fn fermat(n: u64, max_steps: u64) -> Option<u64> {
+        fermat::fermat(n, max_steps)
+    }
+
+    /// Pollard's p−1 method: finds a prime factor `p` of `n` whenever `p - 1` is
+    /// `bound`-smooth, independently of the size of `p`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    /// * `bound` - The smoothness bound B.
+    ///
+    /// # Returns
+    ///
+    /// A non-trivial factor of `n`, or `None` if the method failed for this bound.
+    pub fn pollard_p_minus_1(n: u64, bound: u64) -> Option<u64> {
+        pollard::pollard_p_minus_1(n, bound)
+    }
+
+    /// Williams' p+1 method: finds a prime factor `p` of `n` whenever `p + 1` is
+    /// `bound`-smooth. Complements `pollard_p_minus_1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    /// * `bound` - The smoothness bound B.
+    ///
+    /// # Returns
+    ///
+    /// A non-trivial factor of `n`, or `None` if the method failed for this bound.
+    pub fn williams_p_plus_1(n: u64, bound: u64) -> Option<u64> {
+        pollard::williams_p_plus_1(n, bound)
     }
 
     /// Factorizes `n` into its prime factors.
     ///
+    /// Primes below 100 are stripped by trial division on a 2·3·5 wheel. The cofactor is
+    /// then attacked with a short Fermat search (products of two close primes), Pollard's
+    /// p−1 and Williams' p+1 with small bounds (factors with smooth `p ∓ 1`) and finally
+    /// Pollard's rho.
+    ///
     /// # Arguments
     ///
     /// * `n` - The number to factorize.
@@ -32,259 +149,3457 @@ impl Prime {
     /// # Returns
     ///
     /// A vector containing the prime factors of `n` in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
     pub fn factor(n: u64) -> Vec<u64> {
-        return pollard::factor(n);
+        pollard::factor(n)
     }
 
-    /// Checks if `n` is a prime number.
+    /// Factorizes `n` like `factor`, returning an error instead of panicking.
     ///
     /// # Arguments
     ///
-    /// * `n` - The number to check.
+    /// * `n` - The number to factorize.
     ///
     /// # Returns
     ///
-    /// `true` if `n` is prime, `false` otherwise.
-    pub fn is_prime(n: u64) -> bool {
-        return pollard::is_prime(n);
+    /// The prime factors of `n` in sorted order, or `MathCombError::InvalidArgument` if `n`
+    /// is 0.
+    pub fn try_factor(n: u64) -> Result<Vec<u64>, MathCombError> {
+        prime::try_factor(n)
     }
-}
 
-pub struct Spf {
-    spf_max_limit: usize,
-    spf: Vec<u64>
-}
+    /// Factorizes `n` like `factor`, but with a custom bound for the trial-division pass.
+    ///
+    /// Raising `trial_limit` pays off when inputs are known to carry many small factors;
+    /// 0 skips trial division entirely.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    /// * `trial_limit` - Candidates below this bound are tried by division.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the prime factors of `n` in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn factor_with_trial_limit(n: u64, trial_limit: u64) -> Vec<u64> {
+        pollard::factor_with_trial_limit(n, trial_limit)
+    }
 
-/// A struct representing the smallest prime factor (SPF) computation.
-impl Spf {
-    /// Creates a new `Spf` instance with a given maximum limit.
+    /// Factorizes `n` into distinct primes together with their multiplicities.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(prime, exponent)` pairs sorted by prime.
+    pub fn factor_map(n: u64) -> Vec<(u64, u32)> {
+        pollard::factor_map(n)
+    }
+
+    /// Factorizes `n` like `factor_map`, returning an error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to factorize.
+    ///
+    /// # Returns
+    ///
+    /// The `(prime, exponent)` pairs sorted by prime, or `MathCombError::InvalidArgument` if
+    /// `n` is 0.
+    pub fn try_factor_map(n: u64) -> Result<Vec<(u64, u32)>, MathCombError> {
+        prime::try_factor_map(n)
+    }
+
+    /// Counts the distinct prime factors of `n`, ω(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The number of distinct primes dividing `n` (0 for `n` = 1).
+    pub fn omega(n: u64) -> u32 {
+        arith::omega(n)
+    }
+
+    /// Counts the prime factors of `n` with multiplicity, Ω(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the exponents in the prime factorization of `n` (0 for `n` = 1).
+    pub fn big_omega(n: u64) -> u32 {
+        arith::big_omega(n)
+    }
+
+    /// Computes the Liouville function λ(n) = (−1)^Ω(n). `LiouvilleSieve` tabulates it, with
+    /// its prefix sums, for all `n` up to a bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// 1 if Ω(n) is even, −1 otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn liouville(n: u64) -> i32 {
+        arith::liouville(n)
+    }
+
+    /// Finds the smallest prime factor of `n`, trial-dividing small primes before
+    /// falling back to Pollard's rho.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The smallest prime dividing `n`, or `None` if `n` < 2.
+    pub fn smallest_prime_factor(n: u64) -> Option<u64> {
+        pollard::smallest_prime_factor(n)
+    }
+
+    /// Finds the largest prime factor of `n` without allocating the full factorization.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The largest prime dividing `n`, or `None` if `n` < 2.
+    pub fn largest_prime_factor(n: u64) -> Option<u64> {
+        pollard::largest_prime_factor(n)
+    }
+
+    /// Finds the `k`-th smallest divisor of `n` (1-indexed), generating divisors lazily in
+    /// increasing order instead of materializing and sorting all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number whose divisors are considered.
+    /// * `k` - The rank of the divisor, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// The `k`-th smallest divisor, or `None` if `n` is 0, `k` is 0 or `n` has fewer than `k`
+    /// divisors.
+    pub fn kth_divisor(n: u64, k: u64) -> Option<u64> {
+        arith::kth_divisor(n, k)
+    }
+
+    /// Counts the integers in `[1, n]` coprime to `k` by inclusion–exclusion over the
+    /// distinct prime factors of `k`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    /// * `k` - The number to be coprime to; only 1 is coprime to 0.
+    ///
+    /// # Complexity
+    ///
+    /// The factorization of `k` plus at most 2^ω(k) terms.
+    pub fn count_coprime_up_to(n: u64, k: u64) -> u64 {
+        arith::count_coprime_up_to(n, k)
+    }
+
+    /// Finds the `idx`-th positive integer coprime to `k` (1-indexed), using the period `k`
+    /// of the coprime residues and a binary search over `count_coprime_up_to`.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The number to be coprime to.
+    /// * `idx` - The rank, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// The `idx`-th integer coprime to `k`, or `None` if `idx` is 0, `k` is 0 and `idx` is
+    /// not 1, or the answer does not fit in a `u64`.
+    pub fn kth_coprime(k: u64, idx: u64) -> Option<u64> {
+        arith::kth_coprime(k, idx)
+    }
+
+    /// Computes the sum of the divisors of `n`, σ(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The sum of all positive divisors of `n`, as a `u128` since it can exceed `u64::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn sigma(n: u64) -> u128 {
+        arith::sigma(n)
+    }
+
+    /// Computes Σ_{i≤n} d(i), the total number of divisors of `1..=n`, with Dirichlet's
+    /// hyperbola method.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound, far beyond the range of a divisor sieve.
+    ///
+    /// # Complexity
+    ///
+    /// O(√n).
+    pub fn divisor_summatory(n: u64) -> u128 {
+        arith::divisor_summatory(n)
+    }
+
+    /// Computes Σ_{i≤n} σ(i), the total sum of divisors of `1..=n`, with Dirichlet's
+    /// hyperbola method.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(√n).
+    pub fn sigma_summatory(n: u64) -> u128 {
+        arith::sigma_summatory(n)
+    }
+
+    /// Computes Pillai's gcd-sum function P(n) = Σ_{i=1}^{n} gcd(i, n) from the factorization
+    /// of `n`, using `P(p^e) = (e + 1)·p^e − e·p^(e−1)`. `GcdSumSieve` tabulates it for all
+    /// `n` up to a bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// P(n) as a `u128` (0 for `n` = 0).
+    pub fn gcd_sum(n: u64) -> u128 {
+        arith::gcd_sum(n)
+    }
+
+    /// Counts how many applications of Euler's φ take `n` down to 1, factorizing each term
+    /// of the chain. `TotientSieve::phi_iteration_lengths` tabulates them all up to a bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The start of the chain.
+    ///
+    /// # Returns
+    ///
+    /// The number of steps (0 for `n` = 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn phi_iteration_length(n: u64) -> u32 {
+        arith::phi_iteration_length(n)
+    }
+
+    /// Computes Jordan's totient J_k(n) = n^k·∏_{p|n} (1 − p^(−k)), the number of `k`-tuples
+    /// in `1..=n` whose gcd with `n` is 1, so that `J_1` is Euler's φ.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    /// * `k` - The length of the tuples.
+    ///
+    /// # Returns
+    ///
+    /// J_k(n), or `None` if it overflows a `u128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn jordan_totient(n: u64, k: u32) -> Option<u128> {
+        arith::jordan_totient(n, k)
+    }
+
+    /// Computes Jordan's totient J_k(n) modulo `modulus`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    /// * `k` - The length of the tuples.
+    /// * `modulus` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` or `modulus` is 0.
+    pub fn jordan_totient_mod(n: u64, k: u64, modulus: u64) -> u64 {
+        arith::jordan_totient_mod(n, k, modulus)
+    }
+
+    /// Computes Φ(n) = Σ_{i≤n} φ(i), the number of pairs `1 <= b <= a <= n` with
+    /// gcd(a, b) = 1, from a totient sieve up to about `n^(2/3)` and the identity
+    /// `Σ_g Φ(⌊n/g⌋) = n(n+1)/2`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `prime::MAX_TOTIENT_SUM` (5·10^13).
+    ///
+    /// # Complexity
+    ///
+    /// About O(n^(2/3)) time and memory, with the sieve capped at 10^7 entries and the
+    /// values above it at 5·10^6.
+    pub fn totient_sum(n: u64) -> u128 {
+        arith::totient_sum(n)
+    }
+
+    /// Counts the ordered pairs `(a, b)` in `[1, n]²` with gcd(a, b) = 1, which is `2Φ(n) − 1`
+    /// for the totient summatory function Φ.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound on both coordinates.
+    ///
+    /// # Complexity
+    ///
+    /// That of `totient_sum`: a sieve for small `n` and about O(n^(2/3)) beyond.
+    pub fn count_coprime_pairs(n: u64) -> u128 {
+        arith::count_coprime_pairs(n)
+    }
+
+    /// Computes the number of terms of the Farey sequence of order `n` (the reduced
+    /// fractions in `[0, 1]` with denominators up to `n`), which is `1 + Φ(n)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The order of the sequence.
+    ///
+    /// # Complexity
+    ///
+    /// That of `totient_sum`: a sieve for small `n` and about O(n^(2/3)) beyond.
+    pub fn farey_length(n: u64) -> u128 {
+        arith::farey_length(n)
+    }
+
+    /// Classifies `n` as deficient, perfect or abundant according to whether the sum of its
+    /// proper divisors is less than, equal to or greater than `n`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to classify.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn classify(n: u64) -> Abundance {
+        arith::classify(n)
+    }
+
+    /// Checks whether `a` and `b` form an amicable pair: they are distinct and each equals
+    /// the sum of the proper divisors of the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first number.
+    /// * `b` - The second number.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `a` and `b` are amicable, `false` otherwise (including when either is 0).
+    pub fn are_amicable(a: u64, b: u64) -> bool {
+        arith::are_amicable(a, b)
+    }
+
+    /// Checks whether `n` is squarefree, i.e. not divisible by the square of any prime.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no prime appears more than once in the factorization of `n`.
+    pub fn is_squarefree(n: u64) -> bool {
+        arith::is_squarefree(n)
+    }
+
+    /// Finds the squarefree part of `n`, i.e. `n` divided by its largest square divisor.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to reduce.
+    ///
+    /// # Returns
+    ///
+    /// The product of the primes dividing `n` an odd number of times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn squarefree_part(n: u64) -> u64 {
+        arith::squarefree_part(n)
+    }
+
+    /// Checks whether `n` is powerful, i.e. divisible by the square of each of its primes.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no prime divides `n` exactly once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn is_powerful(n: u64) -> bool {
+        arith::is_powerful(n)
+    }
+
+    /// Counts the squarefree numbers in `1..=n` with the Möbius inclusion–exclusion
+    /// Σ μ(k)·⌊n / k²⌋ over `k <= √n`, which works far beyond the range of a sieve.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(√n) time and memory.
+    pub fn count_squarefree(n: u64) -> u64 {
+        arith::count_squarefree(n)
+    }
+
+    /// Checks whether `n` is a Carmichael number using Korselt's criterion: `n` is composite,
+    /// squarefree, and `p - 1` divides `n - 1` for every prime factor `p`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `n` is a Carmichael number, `false` otherwise.
+    pub fn is_carmichael(n: u64) -> bool {
+        arith::is_carmichael(n)
+    }
+
+    /// Checks whether `n` is a prime power `p^k` (with `k >= 1`).
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to inspect.
+    ///
+    /// # Returns
+    ///
+    /// `Some((p, k))` if `n = p^k` for a prime `p`, `None` otherwise.
+    pub fn as_prime_power(n: u64) -> Option<(u64, u32)> {
+        arith::as_prime_power(n)
+    }
+
+    /// Counts the primes `<= n`, π(n), using the Lucy_Hedgehog method.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n^(3/4)) time and O(√n) memory, so `n` around 10^12 is answered in about a second
+    /// where a full sieve would be infeasible.
+    pub fn count_primes(n: u64) -> u64 {
+        primecount::prime_pi(n)
+    }
+
+    /// Sums the primes `<= n` using the Lucy_Hedgehog method.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n^(3/4)) time and O(√n) memory.
+    pub fn sum_primes(n: u64) -> u128 {
+        primecount::sum_primes(n)
+    }
+
+    /// Computes Σ p^k over the primes `p <= n` modulo the prime `m`, using the Lucy_Hedgehog
+    /// method seeded with the power sums Σ i^k.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    /// * `k` - The exponent.
+    /// * `m` - A prime modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero or `k + 1` is not less than `m`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n^(3/4) + k·√n) time and O(√n) memory.
+    pub fn sum_f_over_primes(n: u64, k: u32, m: u64) -> u64 {
+        primecount::sum_prime_powers(n, k, m)
+    }
+
+    /// Computes Σ_{i≤n} f(i) modulo the prime `m` for a multiplicative `f` with the Min_25
+    /// sieve, given `f` on prime powers and as a polynomial on the primes.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    /// * `prime_poly` - The coefficients `c_0, c_1, …` with `f(p) = Σ c_k·p^k` on the primes.
+    /// * `f` - The function on prime powers, called with the prime `p`, the exponent `e` and
+    ///   the power `p^e`.
+    /// * `m` - A prime modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero or the degree of `prime_poly` plus 1 is not less than `m`.
+    ///
+    /// # Complexity
+    ///
+    /// About O(n^(3/4) / log n) time and O(√n) memory per coefficient, so `n` up to
+    /// 10^12–10^13 is practical.
+    pub fn multiplicative_sum<F: Fn(u64, u32, u64) -> u64>(n: u64, prime_poly: &[u64], f: F, m: u64) -> u64 {
+        min25::multiplicative_sum(n, prime_poly, f, m)
+    }
+
+    /// Checks whether `n` has exactly `k` prime factors counted with multiplicity, i.e. is a
+    /// k-almost-prime. `OmegaSieve` lists them all up to a bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    /// * `k` - The number of prime factors.
+    ///
+    /// # Returns
+    ///
+    /// `true` if Ω(n) = `k` (1 is the only 0-almost-prime; 0 is never one).
+    pub fn is_k_almost_prime(n: u64, k: u32) -> bool {
+        arith::is_k_almost_prime(n, k)
+    }
+
+    /// Counts the numbers `<= n` with exactly `k` prime factors counted with multiplicity
+    /// (k-almost-primes, e.g. `k = 2` counts semiprimes), by recursing over the smallest prime
+    /// factor on top of a single sublinear prime-count table.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound.
+    /// * `k` - The number of prime factors.
+    pub fn count_almost_primes(n: u64, k: u32) -> u64 {
+        primecount::count_almost_primes(n, k)
+    }
+
+    /// Finds the `k`-th prime, counting from `nth_prime(1) = 2`.
+    ///
+    /// Small `k` are answered with a sieve; larger `k` (up to ~10^11) combine an analytic
+    /// estimate, an exact sublinear prime count and local sieving around the estimate.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The index of the prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is 0.
+    pub fn nth_prime(k: u64) -> u64 {
+        primecount::nth_prime(k)
+    }
+
+    /// Checks whether every prime factor of `n` is at most `b`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    /// * `b` - The smoothness bound.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `n` is `b`-smooth (`1` is smooth for every `b`, `0` never is).
+    pub fn is_smooth(n: u64, b: u64) -> bool {
+        smooth::is_smooth(n, b)
+    }
+
+    /// Returns an iterator over all `b`-smooth numbers in `[1, limit]` in increasing order.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The smoothness bound.
+    /// * `limit` - The inclusive upper bound of the generated numbers.
+    pub fn smooth_numbers(b: u64, limit: u64) -> SmoothNumbers {
+        SmoothNumbers::new(b, limit)
+    }
+
+    /// Computes the primorial n#, the product of all primes `<= n`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound on the primes.
+    ///
+    /// # Returns
+    ///
+    /// The primorial of `n`, or `None` if it overflows a `u64` (i.e. for `n` >= 53).
+    pub fn primorial(n: u64) -> Option<u64> {
+        arith::primorial(n)
+    }
+
+    /// Computes the primorial n# as a `u128`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound on the primes.
+    ///
+    /// # Returns
+    ///
+    /// The primorial of `n`, or `None` if it overflows a `u128`.
+    pub fn primorial_u128(n: u64) -> Option<u128> {
+        arith::primorial_u128(n)
+    }
+
+    /// Computes the primorial n# modulo `modulus`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive bound on the primes.
+    /// * `modulus` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is 0.
+    ///
+    /// # Complexity
+    ///
+    /// Sieves all primes up to `n`: O(n log log n) time and O(n) memory.
+    pub fn primorial_mod(n: u64, modulus: u64) -> u64 {
+        arith::primorial_mod(n, modulus)
+    }
+
+    /// Factorizes each number in `ns` into its prime factors.
+    ///
+    /// With the `rayon` feature enabled the numbers are factorized in parallel on all cores.
+    ///
+    /// # Arguments
+    ///
+    /// * `ns` - The numbers to factorize.
+    ///
+    /// # Returns
+    ///
+    /// A vector whose `i`-th entry contains the prime factors of `ns[i]` in sorted order.
+    pub fn factor_batch(ns: &[u64]) -> Vec<Vec<u64>> {
+        pollard::factor_batch(ns)
+    }
+
+    /// Checks if `n` is a prime number.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `n` is prime, `false` otherwise.
+    pub const fn is_prime(n: u64) -> bool {
+        pollard::is_prime(n)
+    }
+
+    /// Deterministic primality test for `u64` that needs only two or three modular
+    /// exponentiations instead of the seven Miller-Rabin rounds of `is_prime`.
+    ///
+    /// Numbers below 2^32 get a base-2 round plus one round with a base selected by hashing
+    /// `n` (Forišek–Jančina); larger numbers get a base-2 round plus a strong Lucas test
+    /// (Baillie–PSW, verified to have no counterexample below 2^64). Hashed bases for all of
+    /// `u64` would need a table of 2^18 bases, which the Lucas test avoids.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `n` is prime, `false` otherwise.
+    pub fn is_prime_fast(n: u64) -> bool {
+        primality::is_prime_fast(n)
+    }
+
+    /// Checks whether the Mersenne number 2^`p` - 1 is prime using the Lucas–Lehmer test
+    /// with shift-and-add reduction modulo 2^`p` - 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The exponent, at most 127.
+    ///
+    /// # Returns
+    ///
+    /// `true` if 2^`p` - 1 is prime, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is greater than 127.
+    pub fn is_mersenne_prime(p: u32) -> bool {
+        primality::is_mersenne_prime(p)
+    }
+
+    /// Checks whether the Proth number `k·2^n + 1` (with `k < 2^n`) is prime using Proth's
+    /// theorem, which needs a single modular exponentiation.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The multiplier.
+    /// * `n` - The exponent of 2.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `k·2^n + 1` is prime, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is 0, if `k >= 2^n` (after moving factors of 2 from `k` into `n`), or if
+    /// `k·2^n + 1` overflows a `u64`.
+    pub fn is_proth_prime(k: u64, n: u32) -> bool {
+        primality::is_proth_prime(k, n)
+    }
+
+    /// Checks whether the Fermat number `F_m = 2^(2^m) + 1` is prime using Pépin's test.
+    ///
+    /// # Arguments
+    ///
+    /// * `m` - The index of the Fermat number, at most 5.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `F_m` is prime, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is greater than 5.
+    pub fn is_fermat_prime(m: u32) -> bool {
+        primality::is_fermat_prime(m)
+    }
+
+    /// Checks whether `n` is a strong probable prime to base `a`, i.e. runs a single
+    /// Miller-Rabin round. Useful for studying pseudoprimes and building custom base sets.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to test.
+    /// * `a` - The base.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `n` is a strong probable prime to base `a`, `false` if `a` proves `n` composite.
+    /// Even `n` other than 2 and `n` < 2 always return `false`.
+    pub const fn miller_rabin_witness(n: u64, a: u64) -> bool {
+        pollard::miller_rabin_witness(n, a)
+    }
+
+    /// Finds a base that proves `n` composite, serving as a certificate of compositeness
+    /// that can be checked with `miller_rabin_witness`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number to test.
+    ///
+    /// # Returns
+    ///
+    /// The first of the deterministic Miller-Rabin bases used by `is_prime` that witnesses
+    /// `n` is composite, or `None` if none does (i.e. `n` is prime) or `n` < 2.
+    pub fn find_witness(n: u64) -> Option<u64> {
+        pollard::find_witness(n)
+    }
+}
+
+/// The magic bytes opening an `Spf` table file.
+const SPF_MAGIC: &[u8; 8] = b"MCSPF\0\0\x03";
+
+pub struct Spf {
+    spf_max_limit: usize,
+    // Smallest prime factors of the odd numbers, indexed by `x / 2`. Primes are stored as 0
+    // so that every stored value is at most √max_limit and fits in a u32; even numbers
+    // are not stored at all.
+    odd_spf: Table<u32>,
+    // For each odd x, the exponent e of spf(x) in x, so a factorization advances one
+    // distinct prime per lookup by dividing out spf(x)^e. Even numbers split off their power
+    // of two with `trailing_zeros`.
+    odd_exp: Table<u8>,
+    // The primes up to `max_limit`, in a u32 column while they fit in it, which is all of
+    // them for a limit below 2^32.
+    primes: Table<u32>,
+    large_primes: Table<u64>,
+}
+
+/// A struct representing the smallest prime factor (SPF) computation.
+impl Spf {
+    /// Creates a new `Spf` instance with a given maximum limit.
+    ///
+    /// The table is filled by a linear (Euler) sieve, which writes every composite exactly
+    /// once, from its smallest prime factor, and collects the primes up to `max_limit` as a
+    /// by-product. With the `rayon` feature enabled the table is instead sieved by segments
+    /// in parallel. Next to the smallest prime factor each odd number stores the exponent of
+    /// that prime, and only odd numbers are stored, taking 2.5 bytes per number up to
+    /// `max_limit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_limit` - The maximum limit up to which the smallest prime factors are computed.
+    ///
+    /// # Returns
+    ///
+    /// A new `Spf` instance with precomputed smallest prime factors up to `max_limit`.
+    ///
+    /// # Complexity
+    ///
+    /// O(`max_limit`) time.
+    pub fn new(max_limit: usize) -> Spf {
+        // `()` never cancels.
+        Spf::build(max_limit, &()).unwrap()
+    }
+
+    /// Creates a new `Spf` instance like `new`, reporting progress to `hook` and giving up
+    /// when it cancels.
+    ///
+    /// Progress is counted in numbers: `hook.report(done, max_limit)` is called after each
+    /// segment of 2^17 numbers with `done` the count of numbers sieved so far, and with
+    /// `(max_limit, max_limit)` at the end. With the `rayon` feature segments finish in
+    /// parallel and reports arrive from several threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_limit` - The maximum limit up to which the smallest prime factors are computed.
+    /// * `hook` - Receives progress reports and may cancel the sieve.
+    ///
+    /// # Returns
+    ///
+    /// The table, or `MathCombError::Cancelled` if `hook` cancelled first.
+    pub fn with_progress(max_limit: usize, hook: &impl ProgressHook) -> Result<Spf, MathCombError> {
+        Spf::build(max_limit, hook).ok_or(MathCombError::Cancelled)
+    }
+
+    // Builds the table for `new`, returning `None` as soon as `hook` cancels.
+    fn build(max_limit: usize, hook: &dyn ProgressHook) -> Option<Spf> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spf_new", max_limit).entered();
+        let (odd_spf, primes, large_primes) = sieve::odd_spf_table(max_limit, hook)?;
+        let mut spf = Spf {
+            spf_max_limit: max_limit,
+            odd_spf: odd_spf.into(),
+            odd_exp: Vec::new().into(),
+            primes: primes.into(),
+            large_primes: large_primes.into(),
+        };
+        spf.fill_exponents(1);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(primes = spf.primes.len() + spf.large_primes.len(), "spf table built");
+        Some(spf)
+    }
+
+    /// Sieves the prime factors of every number in the window `[l, r]` instead of starting
+    /// at 0, for factorizing ranges of large numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `l` - The lower end of the window, up to around 10^12.
+    /// * `r` - The upper end of the window, with `r - l` up to around 10^7.
+    ///
+    /// # Returns
+    ///
+    /// A `RangeSpf` answering `get_spf`, `is_prime` and factorization queries for the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`.
+    ///
+    /// # Complexity
+    ///
+    /// O(√r + (r - l) log log r) time, after which each factorization takes O(log x).
+    pub fn for_range(l: u64, r: u64) -> RangeSpf {
+        RangeSpf::new(l, r)
+    }
+
+    // Fills the exponent entries of the odd numbers above `from`, in increasing order so
+    // that x / spf(x), which is odd and smaller, is already filled.
+    fn fill_exponents(&mut self, from: usize) {
+        let odd_spf: &[u32] = &self.odd_spf;
+        let odd_exp = self.odd_exp.to_mut();
+        odd_exp.resize(odd_spf.len(), 0);
+        for k in from.div_ceil(2)..odd_spf.len() {
+            let x = 2 * k + 1;
+            if x > self.spf_max_limit {
+                break;
+            }
+            match odd_spf[k] {
+                0 => odd_exp[k] = 1,
+                p => {
+                    let j = x / p as usize / 2;
+                    let same = odd_spf[j] == p || (odd_spf[j] == 0 && 2 * j + 1 == p as usize);
+                    odd_exp[k] = if same { odd_exp[j] + 1 } else { 1 };
+                }
+            }
+        }
+    }
+
+    /// Grows the table to cover every number up to `new_limit`, sieving only the numbers
+    /// beyond the current limit. Does nothing if `new_limit` is not above the current limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_limit` - The new maximum limit.
+    ///
+    /// # Complexity
+    ///
+    /// O((new_limit - max_limit) log log new_limit) time. When √`new_limit` exceeds the
+    /// current limit the range is sieved in stages, each bounded by the square of the limit
+    /// before it, so that the base primes are always available.
+    pub fn extend_to(&mut self, new_limit: usize) {
+        if new_limit <= self.spf_max_limit {
+            return;
+        }
+        if self.spf_max_limit < 2 {
+            *self = Spf::new(new_limit);
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("spf_extend", from = self.spf_max_limit, to = new_limit).entered();
+        while self.spf_max_limit < new_limit {
+            let old = self.spf_max_limit;
+            let hi = new_limit.min(old.saturating_mul(old));
+            #[cfg(feature = "tracing")]
+            tracing::trace!(from = old, to = hi, "sieving stage");
+            let odd_spf = self.odd_spf.to_mut();
+            odd_spf.resize(hi / 2 + 1, 0);
+            for &p in &self.primes[1..] {
+                let p = p as usize;
+                if p > hi / p {
+                    break;
+                }
+                // The first odd multiple of p above the old limit, and at least p².
+                let mut start = (old / p + 1) * p;
+                if start.is_multiple_of(2) {
+                    start += p;
+                }
+                for m in (start.max(p * p)..=hi).step_by(2 * p) {
+                    if odd_spf[m / 2] == 0 {
+                        odd_spf[m / 2] = p as u32;
+                    }
+                }
+            }
+            let (primes, large_primes) = (self.primes.to_mut(), self.large_primes.to_mut());
+            for m in ((old + 1) | 1..=hi).step_by(2) {
+                if odd_spf[m / 2] == 0 {
+                    match u32::try_from(m) {
+                        Ok(p) => primes.push(p),
+                        Err(_) => large_primes.push(m as u64),
+                    }
+                }
+            }
+            self.spf_max_limit = hi;
+            self.fill_exponents(old);
+        }
+    }
+
+    // Splits the odd `y > 1` up to the limit into its smallest prime factor, that prime's
+    // exponent and the cofactor left after dividing out its power.
+    fn split_odd(&self, y: u64) -> (u64, u32, u64) {
+        let k = y as usize / 2;
+        match self.odd_spf[k] {
+            0 => (y, 1, 1),
+            p => {
+                let e = self.odd_exp[k] as u32;
+                (p as u64, e, y / (p as u64).pow(e))
+            }
+        }
+    }
+
+    /// Writes the table to a compact binary file at `path`, to be read back with `load`
+    /// or, with the `mmap` feature, `load_mmap`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to create or overwrite.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised while writing the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = TableWriter::create(path, SPF_MAGIC, &[self.spf_max_limit as u64])?;
+        writer.section::<u32>(&self.odd_spf)?;
+        writer.section::<u8>(&self.odd_exp)?;
+        writer.section::<u32>(&self.primes)?;
+        writer.section::<u64>(&self.large_primes)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Reads a table written by `save` into memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be read, or one of kind `InvalidData` if it
+    /// is not a complete `Spf` table file or its entries are inconsistent with its limit.
+    ///
+    /// # Complexity
+    ///
+    /// O(`max_limit`) time, reading the file once and checking every entry.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Spf> {
+        let mut reader = TableReader::open(path, SPF_MAGIC)?;
+        let spf_max_limit = Spf::read_limit(&mut reader)?;
+        let len = spf_max_limit / 2 + 1;
+        let spf = Spf {
+            spf_max_limit,
+            odd_spf: reader.section(len)?,
+            odd_exp: reader.section(len)?,
+            primes: reader.section(len)?,
+            large_primes: reader.section(len)?,
+        };
+        spf.validate()?;
+        Ok(spf)
+    }
+
+    // Reads the limit from the header of a table file.
+    fn read_limit<R: io::Read>(reader: &mut TableReader<R>) -> io::Result<usize> {
+        usize::try_from(reader.value()?)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "table limit does not fit in a usize"))
+    }
+
+    // Checks that every entry of a loaded table lies in its range, so that queries neither
+    // index out of bounds nor divide by a prime power that does not divide the number.
+    fn validate(&self) -> io::Result<()> {
+        let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "corrupt Spf table file");
+        let len = self.spf_max_limit / 2 + 1;
+        if self.odd_spf.len() != len || self.odd_exp.len() != len {
+            return Err(corrupt());
+        }
+        let mut prime_count = usize::from(self.spf_max_limit >= 2);
+        for k in 1..len {
+            let x = 2 * k + 1;
+            if x > self.spf_max_limit {
+                break;
+            }
+            let (p, e) = (self.odd_spf[k] as usize, self.odd_exp[k] as u32);
+            let valid = match p {
+                0 => {
+                    prime_count += 1;
+                    e == 1
+                }
+                p => {
+                    let power = p.checked_pow(e).filter(|&q| e >= 1 && x.is_multiple_of(q));
+                    p % 2 == 1 && p > 1 && p <= x / p && power.is_some()
+                }
+            };
+            if !valid {
+                return Err(corrupt());
+            }
+        }
+        let mut previous = None;
+        let mut count = 0;
+        let primes_valid = self.large_primes.iter().all(|&p| p > u32::MAX as u64)
+            && self.primes().all(|p| {
+                let increasing = previous.is_none_or(|q| q < p);
+                previous = Some(p);
+                count += 1;
+                increasing
+                    && p <= self.spf_max_limit as u64
+                    && (p == 2 || (p % 2 == 1 && p > 1 && self.odd_spf[p as usize / 2] == 0))
+            })
+            && count == prime_count;
+        if !primes_valid {
+            return Err(corrupt());
+        }
+        Ok(())
+    }
+
+    /// Memory-maps a table written by `save` instead of reading it, so the operating
+    /// system pages it in on demand and shares it between processes mapping the same file.
+    /// Extending a mapped table with `extend_to` first copies it into memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to map. It must not be modified while it is mapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be mapped, or one of kind `InvalidData` if
+    /// it is not a complete `Spf` table file, its entries are inconsistent with its limit
+    /// or the platform is big-endian.
+    ///
+    /// # Complexity
+    ///
+    /// O(`max_limit`) time, since every entry is checked once, which pages the whole file
+    /// in.
+    #[cfg(feature = "mmap")]
+    pub fn load_mmap<P: AsRef<Path>>(path: P) -> io::Result<Spf> {
+        let map = table::map_file(path)?;
+        let mut reader = TableReader::new(&map[..], SPF_MAGIC)?;
+        let spf_max_limit = Spf::read_limit(&mut reader)?;
+        let len = spf_max_limit / 2 + 1;
+        let spf = Spf {
+            spf_max_limit,
+            odd_spf: reader.mapped_section(&map, len)?,
+            odd_exp: reader.mapped_section(&map, len)?,
+            primes: reader.mapped_section(&map, len)?,
+            large_primes: reader.mapped_section(&map, len)?,
+        };
+        spf.validate()?;
+        Ok(spf)
+    }
+
+    /// Iterates over the primes up to `max_limit` in increasing order, which were collected
+    /// while sieving.
+    pub fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.primes.iter().map(|&p| p as u64).chain(self.large_primes.iter().copied())
+    }
+
+    /// Retrieves the smallest prime factor of a given number.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number for which the smallest prime factor is to be retrieved.
+    ///
+    /// # Returns
+    ///
+    /// The smallest prime factor of `x`, or 0 if `x` < 2.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the `max_limit` specified during the creation of the `Spf` instance.
+    pub fn get_spf(&self, x: u64) -> u64 {
+        if x > self.spf_max_limit as u64 {
+            panic!("x cannot be greater than max_limit!");
+        }
+        if x < 2 {
+            return 0;
+        }
+        if x.is_multiple_of(2) {
+            return 2;
+        }
+        match self.odd_spf[x as usize / 2] {
+            0 => x,
+            p => p as u64,
+        }
+    }
+
+    /// Retrieves the smallest prime factor of a given number without panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number for which the smallest prime factor is to be retrieved.
+    ///
+    /// # Returns
+    ///
+    /// The smallest prime factor of `x`, or `None` if `x` < 2 (which has no prime factor) or
+    /// `x` is greater than `max_limit`.
+    pub fn try_get_spf(&self, x: u64) -> Option<u64> {
+        if x < 2 || x > self.spf_max_limit as u64 {
+            return None;
+        }
+        Some(self.get_spf(x))
+    }
+
+    /// Checks whether a given number is prime with a table lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `x` is prime, `false` otherwise (in particular for 0 and 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the `max_limit` specified during the creation of the `Spf` instance.
+    pub fn is_prime(&self, x: u64) -> bool {
+        x >= 2 && self.get_spf(x) == x
+    }
+
+    /// Factorizes a given number into its prime factors.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing the prime factors of `x` (empty for `x` = 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    ///
+    /// # Complexity
+    ///
+    /// The factorization process takes O(log n) time after the SPF computation.
+    pub fn factorize(&self, x: u64) -> Vec<u64> {
+        if x > self.spf_max_limit as u64 {
+            panic!("x cannot be greater than max_limit!");
+        }
+        if x == 0 {
+            panic!("x cannot be zero!");
+        }
+        self.factorize_inline(x).to_vec()
+    }
+
+    /// Factorizes a given number like `factorize`, writing the prime factors into `buf`
+    /// instead of allocating a vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    /// * `buf` - The buffer receiving the factors; `prime::MAX_FACTORS` entries always suffice.
+    ///
+    /// # Returns
+    ///
+    /// The number of prime factors of `x`, which fill `buf[..count]` in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0, greater than the `max_limit` specified during the creation of the `Spf` instance,
+    /// or if `buf` is too short for the factors of `x`.
+    pub fn factorize_into(&self, x: u64, buf: &mut [u64]) -> usize {
+        if x > self.spf_max_limit as u64 {
+            panic!("x cannot be greater than max_limit!");
+        }
+        if x == 0 {
+            panic!("x cannot be zero!");
+        }
+        let mut len = 0;
+        let mut push = |p: u64, e: usize| {
+            if buf.len() - len < e {
+                panic!("buf is too short for the factors of x!");
+            }
+            buf[len..len + e].fill(p);
+            len += e;
+        };
+        let mut y = x;
+        if y.is_multiple_of(2) {
+            let e = y.trailing_zeros();
+            push(2, e as usize);
+            y >>= e;
+        }
+        while y != 1 {
+            let (p, e, cofactor) = self.split_odd(y);
+            push(p, e as usize);
+            y = cofactor;
+        }
+        len
+    }
+
+    /// Factorizes a given number like `factorize`, returning the factors stored inline in a
+    /// `Factors` rather than in a heap-allocated vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    ///
+    /// # Returns
+    ///
+    /// The prime factors of `x` in sorted order (empty for `x` = 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    pub fn factorize_inline(&self, x: u64) -> Factors {
+        Factors::from_fn(|buf| self.factorize_into(x, buf))
+    }
+
+    /// Lazily factorizes every number of `numbers`, yielding their factors in input order.
+    /// Call `chunked` on the stream to factorize the inputs by chunks in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `numbers` - The numbers to factorize.
+    ///
+    /// # Returns
+    ///
+    /// A `FactorStream` yielding the `Factors` of each input.
+    ///
+    /// # Panics
+    ///
+    /// The stream panics on an input that is 0 or greater than the `max_limit` specified during the creation
+    /// of the `Spf` instance.
+    pub fn factorize_stream<I: IntoIterator<Item = u64>>(&self, numbers: I) -> FactorStream<'_, I::IntoIter> {
+        FactorStream::new(numbers.into_iter(), Some(self))
+    }
+
+    /// Factorizes a given number into its prime factors without panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    ///
+    /// # Returns
+    ///
+    /// The prime factors of `x` in sorted order (empty for `x` = 1), or `None` if `x` is 0
+    /// or greater than `max_limit`.
+    pub fn try_factorize(&self, x: u64) -> Option<Vec<u64>> {
+        if x == 0 || x > self.spf_max_limit as u64 {
+            return None;
+        }
+        Some(self.factorize(x))
+    }
+
+    /// Factorizes a given number into distinct primes paired with their multiplicities.
+    ///
+    /// Each table lookup yields a whole `(prime, exponent)` pair, after which the prime
+    /// power is divided out, and primes come out in increasing order, so no sorting is
+    /// needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(prime, exponent)` pairs sorted by prime (empty for `x` = 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    ///
+    /// # Complexity
+    ///
+    /// O(ω(x)) table lookups after the SPF computation.
+    pub fn factorize_map(&self, x: u64) -> Vec<(u64, u32)> {
+        if x > self.spf_max_limit as u64 {
+            panic!("x cannot be greater than max_limit!");
+        }
+        if x == 0 {
+            panic!("x cannot be zero!");
+        }
+        let mut map: Vec<(u64, u32)> = Vec::new();
+        let mut y = x;
+        if y.is_multiple_of(2) {
+            let e = y.trailing_zeros();
+            map.push((2, e));
+            y >>= e;
+        }
+        while y != 1 {
+            let (p, e, cofactor) = self.split_odd(y);
+            map.push((p, e));
+            y = cofactor;
+        }
+        map
+    }
+
+    /// Factorizes a given number like `factorize_map`, returning an error instead of
+    /// panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to be factorized.
+    ///
+    /// # Returns
+    ///
+    /// The `(prime, exponent)` pairs sorted by prime, `MathCombError::AboveLimit` if `x` is
+    /// greater than `max_limit`, or `MathCombError::InvalidArgument` if `x` is 0.
+    pub fn try_factorize_map(&self, x: u64) -> Result<Vec<(u64, u32)>, MathCombError> {
+        self.check_query(x)?;
+        Ok(self.factorize_map(x))
+    }
+
+    // Checks that `x` can be factorized with the table.
+    fn check_query(&self, x: u64) -> Result<(), MathCombError> {
+        if x > self.spf_max_limit as u64 {
+            return Err(MathCombError::AboveLimit { value: x, limit: self.spf_max_limit as u64 });
+        }
+        if x == 0 {
+            return Err(MathCombError::InvalidArgument("x cannot be zero"));
+        }
+        Ok(())
+    }
+
+    /// Lists all divisors of a given number, expanded from its SPF factorization.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number whose divisors are listed.
+    ///
+    /// # Returns
+    ///
+    /// The divisors of `x` in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    ///
+    /// # Complexity
+    ///
+    /// O(log x + d(x) log d(x)) time, where d(x) is the number of divisors.
+    pub fn divisors(&self, x: u64) -> Vec<u64> {
+        let mut divisors = vec![1];
+        for (p, e) in self.factorize_map(x) {
+            let len = divisors.len();
+            let mut pk = 1;
+            for _ in 0..e {
+                pk *= p;
+                for i in 0..len {
+                    divisors.push(divisors[i] * pk);
+                }
+            }
+        }
+        divisors.sort_unstable();
+        divisors
+    }
+
+    /// Lists all divisors of a given number like `divisors`, returning an error instead of
+    /// panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number whose divisors are listed.
+    ///
+    /// # Returns
+    ///
+    /// The divisors of `x` in increasing order, `MathCombError::AboveLimit` if `x` is
+    /// greater than `max_limit`, or `MathCombError::InvalidArgument` if `x` is 0.
+    pub fn try_divisors(&self, x: u64) -> Result<Vec<u64>, MathCombError> {
+        self.check_query(x)?;
+        Ok(self.divisors(x))
+    }
+
+    /// Computes the radical of a given number, the product of its distinct prime factors.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number whose radical is computed.
+    ///
+    /// # Returns
+    ///
+    /// rad(x), with rad(1) = 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    pub fn radical(&self, x: u64) -> u64 {
+        self.factorize_map(x).iter().map(|&(p, _)| p).product()
+    }
+
+    /// Checks whether a given number is squarefree, stopping at the first repeated prime.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no prime divides `x` more than once, `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    pub fn is_squarefree(&self, x: u64) -> bool {
+        if x == 0 {
+            panic!("x cannot be zero!");
+        }
+        let mut y = x;
+        let mut last = 0;
+        while y != 1 {
+            let p = self.get_spf(y);
+            if p == last {
+                return false;
+            }
+            last = p;
+            y /= p;
+        }
+        true
+    }
+
+    /// Computes Euler's totient φ(x), the count of integers in `1..=x` coprime to `x`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The number whose totient is computed.
+    ///
+    /// # Returns
+    ///
+    /// φ(x), with φ(1) = 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or greater than the `max_limit` specified during the creation of the `Spf` instance.
+    ///
+    /// # Complexity
+    ///
+    /// O(log x) time after the SPF computation.
+    pub fn phi(&self, x: u64) -> u64 {
+        self.factorize_map(x)
+            .iter()
+            .fold(x, |acc, &(p, _)| acc / p * (p - 1))
+    }
+}
+
+/// A struct that provides methods for modular exponentiation and modular inverse calculations.
+#[deprecated(note = "use the free functions of the `math_comb::modular` module, e.g. `modular::mod_exp(b, e, m)`")]
+pub struct Modexp {}
+
+#[allow(deprecated)]
+impl Modexp {
+    /// Calculates (base^exponent) % modulus using modular exponentiation.
+    ///
+    /// # Arguments
+    ///
+    /// *   `base` - The base.
+    /// *   `exponent` - The exponent.
+    /// *   `modulus` - The modulus.
+    pub const fn mod_exp(base: u64, exponent: u64, modulus: u64) -> u64 {
+        modexp::mod_exp(base, exponent, modulus)
+    }
+
+    /// Calculates (base^exponent) % modulus, returning an error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `base` - The base.
+    /// *   `exponent` - The exponent.
+    /// *   `modulus` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The power, or `MathCombError::ZeroModulus` if `modulus` is 0.
+    pub fn try_mod_exp(base: u64, exponent: u64, modulus: u64) -> Result<u64, MathCombError> {
+        modular::try_mod_exp(base, exponent, modulus)
+    }
+
+    /// Calculates the modular multiplicative inverse of `x` modulo `modulus`.
+    ///
+    /// The modular inverse of `x` modulo `modulus` is an integer `y` such that
+    /// (x * y) % modulus == 1. It exists if and only if `x` and `modulus` are coprime
+    /// (i.e., their greatest common divisor is 1).
+    ///
+    /// This function uses the extended Euclidean algorithm, so `modulus` need not be prime:
+    /// it finds `y` with x·y + modulus·k = 1, which exists exactly when the two are coprime.
+    ///
+    /// # Arguments
+    ///
+    /// *   `x` - The number for which to calculate the inverse.
+    /// *   `modulus` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The modular inverse of `x` modulo `modulus`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if:
+    /// *   `modulus` is 0.
+    /// *   `x` and `modulus` are not coprime (their greatest common divisor is not 1).
+    pub fn mod_inv(x: u64, modulus: u64) -> u64 {
+        modexp::mod_inv(x, modulus)
+    }
+
+    /// Calculates the modular inverse of `x` modulo `modulus` like `mod_inv`, returning an
+    /// error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `x` - The number for which to calculate the inverse.
+    /// *   `modulus` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The inverse, `MathCombError::ZeroModulus` if `modulus` is 0, or
+    /// `MathCombError::NotInvertible` if `x` and `modulus` are not coprime.
+    pub fn try_mod_inv(x: u64, modulus: u64) -> Result<u64, MathCombError> {
+        modular::try_mod_inv(x, modulus)
+    }
+
+    /// Calculates a square root of `a` modulo the prime `p` using the Tonelli–Shanks algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The number whose square root is wanted.
+    /// *   `p` - A prime modulus.
+    ///
+    /// # Returns
+    ///
+    /// The smaller root `x` of `x² ≡ a (mod p)` (the other one is `p - x`), or `None` if `a`
+    /// is a quadratic non-residue modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is 0.
+    pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+        modexp::sqrt_mod(a, p)
+    }
+
+    /// Counts the square roots of `a` modulo a composite `n` from the factorization of `n`,
+    /// multiplying the counts modulo each prime power (with the special cases of powers of 2).
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The number whose square roots are counted.
+    /// *   `n` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The number of `x` in `[0, n)` with `x² ≡ a (mod n)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn count_sqrt_mod(a: u64, n: u64) -> u64 {
+        modexp::count_sqrt_mod(a, n)
+    }
+
+    /// Lists the nonzero quadratic residues modulo the prime `p` in increasing order.
+    ///
+    /// # Arguments
+    ///
+    /// *   `p` - A prime modulus.
+    ///
+    /// # Returns
+    ///
+    /// The `(p − 1) / 2` distinct squares of `1, …, p − 1` modulo `p` (just 1 for `p = 2`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is 0.
+    ///
+    /// # Complexity
+    ///
+    /// O(p) time and memory.
+    pub fn quadratic_residues(p: u64) -> Vec<u64> {
+        modexp::quadratic_residues(p)
+    }
+
+    /// Counts the numbers in `[l, r]` that are nonzero quadratic residues modulo the prime
+    /// `p`, counting whole periods at once and marking or testing the remaining partial one.
+    ///
+    /// # Arguments
+    ///
+    /// *   `p` - A prime modulus.
+    /// *   `l` - The lower end of the range.
+    /// *   `r` - The upper end of the range, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is 0.
+    ///
+    /// # Complexity
+    ///
+    /// O(min(r − l, p)·log p), or O(p) through a marking table for `p` up to 2^20.
+    pub fn count_residues_in_range(p: u64, l: u64, r: u64) -> u64 {
+        modexp::count_residues_in_range(p, l, r)
+    }
+
+    /// Recovers the fraction `p/q` with `p ≡ r·q (mod m)` and `|p|, q <= √((m − 1)/2)`,
+    /// stopping the extended Euclidean algorithm on `m` and `r` halfway.
+    ///
+    /// # Arguments
+    ///
+    /// *   `r` - The residue.
+    /// *   `m` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The unique such fraction `(p, q)` in lowest terms with `q` coprime to `m`, or `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is 0.
+    ///
+    /// # Complexity
+    ///
+    /// O(log m).
+    pub fn rational_reconstruct(r: u64, m: u64) -> Option<(i64, u64)> {
+        modexp::rational_reconstruct(r, m)
+    }
+
+    /// Finds the smallest primitive root modulo the prime `p`, i.e. a generator of the
+    /// multiplicative group modulo `p`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `p` - A prime modulus.
+    ///
+    /// # Returns
+    ///
+    /// The least `g` whose powers run through every non-zero residue modulo `p`.
+    pub fn primitive_root(p: u64) -> u64 {
+        ntt::primitive_root(p)
+    }
+}
+
+/// A struct that provides polynomial multiplication through the number-theoretic transform.
+#[deprecated(note = "use the free functions of the `math_comb::transforms` module, e.g. `transforms::convolution(&a, &b, p)`")]
+pub struct Ntt {}
+
+#[allow(deprecated)]
+impl Ntt {
+    /// Multiplies two polynomials modulo an NTT-friendly prime such as 998244353.
+    ///
+    /// The transform is iterative and works in place, with the twiddle factors of each
+    /// modulus computed once and cached.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The coefficients of the first polynomial, lowest degree first.
+    /// *   `b` - The coefficients of the second polynomial, lowest degree first.
+    /// *   `p` - A prime below 2^32 such that `p - 1` is divisible by a power of two at least
+    ///     `a.len() + b.len() - 1`.
+    ///
+    /// # Returns
+    ///
+    /// The `a.len() + b.len() - 1` coefficients of the product modulo `p`, or an empty vector
+    /// if either input is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not below 2^32 or the product is too long for the transform sizes
+    /// `p` supports.
+    ///
+    /// # Complexity
+    ///
+    /// O(N log N) for a product of N coefficients.
+    pub fn convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+        ntt::convolution(a, b, p)
+    }
+
+    /// Multiplies two polynomials modulo an arbitrary `m`, such as 1000000007, by convolving
+    /// under three NTT-friendly primes and recombining the results with the CRT.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The coefficients of the first polynomial, lowest degree first.
+    /// *   `b` - The coefficients of the second polynomial, lowest degree first.
+    /// *   `m` - The modulus, which need not be prime.
+    ///
+    /// # Returns
+    ///
+    /// The `a.len() + b.len() - 1` coefficients of the product modulo `m`, or an empty vector
+    /// if either input is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero or not below 2^32, or if `min(a.len(), b.len())·(m - 1)²` reaches
+    /// about 5.8·10^25, past which the exact coefficients cannot be recovered (for
+    /// `m = 10^9 + 7` inputs of up to 2^24 coefficients are supported).
+    ///
+    /// # Complexity
+    ///
+    /// O(N log N) for a product of N coefficients, about three times the cost of
+    /// `convolution`.
+    pub fn convolution_mod(a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+        ntt::convolution_mod(a, b, m)
+    }
+}
+
+/// A struct that provides in-place transforms over arrays indexed by bitmasks.
+#[deprecated(note = "use the free functions of the `math_comb::transforms` module, e.g. `transforms::subset_sum(&mut a, p)`")]
+pub struct Transform {}
+
+#[allow(deprecated)]
+impl Transform {
+    /// Replaces every `a[mask]` with the sum of `a[sub]` over all submasks `sub` of `mask`,
+    /// modulo `p` (the zeta transform over subsets, or sum over subsets).
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or `p` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn subset_sum(a: &mut [u64], p: u64) {
+        transform::subset_sum(a, p)
+    }
+
+    /// Inverts `subset_sum` (the Möbius transform over subsets), recovering the values from
+    /// their sums over submasks.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or `p` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn subset_sum_inverse(a: &mut [u64], p: u64) {
+        transform::subset_sum_inverse(a, p)
+    }
+
+    /// Replaces every `a[mask]` with the sum of `a[sup]` over all supermasks `sup` of `mask`,
+    /// modulo `p` (the zeta transform over supersets).
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or `p` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn superset_sum(a: &mut [u64], p: u64) {
+        transform::superset_sum(a, p)
+    }
+
+    /// Inverts `superset_sum` (the Möbius transform over supersets).
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or `p` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn superset_sum_inverse(a: &mut [u64], p: u64) {
+        transform::superset_sum_inverse(a, p)
+    }
+
+    /// Applies the Walsh–Hadamard transform modulo `p` in place, the xor analogue of the
+    /// Fourier transform.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or `p` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn walsh_hadamard(a: &mut [u64], p: u64) {
+        transform::walsh_hadamard(a, p)
+    }
+
+    /// Inverts `walsh_hadamard` by transforming again and dividing by the length with
+    /// `mod_inv`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The values, indexed by bitmask; its length must be a power of two.
+    /// *   `p` - A modulus in which the length is invertible, such as an odd prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `a` is not a power of two or is not invertible modulo `p`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn walsh_hadamard_inverse(a: &mut [u64], p: u64) {
+        transform::walsh_hadamard_inverse(a, p)
+    }
+
+    /// Computes the xor convolution `c[k] = Σ_{i ^ j = k} a[i]·b[j]` modulo a prime with the
+    /// Walsh–Hadamard transform.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first array; its length must be a power of two.
+    /// *   `b` - The second array, of the same length.
+    /// *   `p` - An odd prime modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` differ in length, the length is not a power of two, or it is not
+    /// invertible modulo `p`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn xor_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+        transform::xor_convolution(a, b, p)
+    }
+
+    /// Computes the and convolution `c[k] = Σ_{i & j = k} a[i]·b[j]` modulo `p` with superset
+    /// sums.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first array; its length must be a power of two.
+    /// *   `b` - The second array, of the same length.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` differ in length, the length is not a power of two, or `p` is
+    /// zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn and_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+        transform::and_convolution(a, b, p)
+    }
+
+    /// Computes the or convolution `c[k] = Σ_{i | j = k} a[i]·b[j]` modulo `p` with subset
+    /// sums.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first array; its length must be a power of two.
+    /// *   `b` - The second array, of the same length.
+    /// *   `p` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` differ in length, the length is not a power of two, or `p` is
+    /// zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n = `a.len()`.
+    pub fn or_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+        transform::or_convolution(a, b, p)
+    }
+
+    /// Computes the Dirichlet convolution `(f * g)(n) = Σ_{d | n} f(d)·g(n / d)` modulo `m`
+    /// for every `n` in the table, which multiplies arithmetic functions (e.g. `1 * 1 = d`
+    /// and `φ * 1 = id`).
+    ///
+    /// # Arguments
+    ///
+    /// *   `f` - The values `f(0), f(1), …, f(N)`; `f(0)` is ignored.
+    /// *   `g` - The values of `g`, of the same length.
+    /// *   `m` - The modulus.
+    ///
+    /// # Returns
+    ///
+    /// The values of `f * g` on `0..=N`, with 0 at index 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` and `g` differ in length or `m` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(N log N).
+    pub fn dirichlet_convolve(f: &[u64], g: &[u64], m: u64) -> Vec<u64> {
+        transform::dirichlet_convolve(f, g, m)
+    }
+
+    /// Computes the Dirichlet inverse of `f` modulo a prime, the function `g` with `f * g`
+    /// equal to 1 at 1 and 0 elsewhere (the inverse of the constant 1 is μ).
+    ///
+    /// # Arguments
+    ///
+    /// *   `f` - The values `f(0), f(1), …, f(N)`; `f(0)` is ignored.
+    /// *   `m` - A prime modulus.
+    ///
+    /// # Returns
+    ///
+    /// The values of the inverse on `0..=N`, with 0 at index 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero or `f(1)` is divisible by `m`.
+    ///
+    /// # Complexity
+    ///
+    /// O(N log N).
+    pub fn dirichlet_inverse(f: &[u64], m: u64) -> Vec<u64> {
+        transform::dirichlet_inverse(f, m)
+    }
+}
+
+/// A struct that provides methods for finding and evaluating linear recurrences.
+#[deprecated(note = "use the free functions of the `math_comb::sequence` module, e.g. `sequence::berlekamp_massey(&seq, p)`")]
+pub struct Recurrence {}
+
+#[allow(deprecated)]
+impl Recurrence {
+    /// Finds the shortest linear recurrence satisfied by a sequence modulo a prime, using the
+    /// Berlekamp–Massey algorithm.
+    ///
+    /// A recurrence of order `d` is determined by `2d` terms, so feeding in twice as many
+    /// terms as the expected order recovers it exactly.
+    ///
+    /// # Arguments
+    ///
+    /// *   `seq` - The terms of the sequence.
+    /// *   `p` - A prime modulus below 2^32.
+    ///
+    /// # Returns
+    ///
+    /// The coefficients `c` of the shortest recurrence
+    /// `a[n] = c[0]·a[n-1] + c[1]·a[n-2] + … + c[d-1]·a[n-d]` holding for all of `seq`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n²) for n terms.
+    pub fn berlekamp_massey(seq: &[u64], p: u64) -> Vec<u64> {
+        recurrence::berlekamp_massey(seq, p)
+    }
+
+    /// Computes the `k`-th term of a linear recurrence modulo a prime with the Bostan–Mori
+    /// algorithm, for indices far too large to iterate to.
+    ///
+    /// # Arguments
+    ///
+    /// *   `rec` - The coefficients `c` of `a[n] = c[0]·a[n-1] + … + c[d-1]·a[n-d]`, as
+    ///     returned by `berlekamp_massey`.
+    /// *   `init` - The initial terms `a[0], a[1], …`; at least `d` are needed, and any terms
+    ///     past the first `d` are returned as given.
+    /// *   `k` - The index of the wanted term, starting from 0.
+    /// *   `p` - A prime modulus below 2^32.
+    ///
+    /// # Returns
+    ///
+    /// `a[k]` modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` has fewer than `rec.len()` terms.
+    ///
+    /// # Complexity
+    ///
+    /// O(d log d log k).
+    pub fn linear_recurrence_kth(rec: &[u64], init: &[u64], k: u64, p: u64) -> u64 {
+        recurrence::linear_recurrence_kth(rec, init, k, p)
+    }
+
+    /// Computes the `k`-th term of a sequence modulo a prime from its first terms, assuming it
+    /// satisfies a linear recurrence: Berlekamp–Massey finds the shortest one, and Bostan–Mori
+    /// evaluates it at `k`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `seq` - The first terms of the sequence, at least twice the order of its recurrence
+    ///     plus one.
+    /// *   `k` - The index of the wanted term, starting from 0.
+    /// *   `p` - A prime modulus below 2^32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shortest recurrence for `seq` has order at least `seq.len() / 2`, in
+    /// which case `seq` is too short to pin it down.
+    ///
+    /// # Complexity
+    ///
+    /// O(n² + n log n log k) for n terms.
+    pub fn extrapolate(seq: &[u64], k: u64, p: u64) -> u64 {
+        // The facade keeps its panicking signature; `sequence::extrapolate` returns `None`.
+        match recurrence::extrapolate(seq, k, p) {
+            Some(term) => term,
+            None => panic!("No linear recurrence shorter than half the sequence was found!"),
+        }
+    }
+
+    /// Computes the Fibonacci number `F(n)` modulo `m` by fast doubling, with `F(0) = 0` and
+    /// `F(1) = 1`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The index.
+    /// *   `m` - The modulus; any value, since products are widened to `u128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(log n).
+    pub fn fibonacci_mod(n: u64, m: u64) -> u64 {
+        recurrence::fibonacci_mod(n, m)
+    }
+
+    /// Writes `n` as its Zeckendorf representation, the unique sum of non-consecutive
+    /// Fibonacci numbers, e.g. 100 = 89 + 8 + 3.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number to decompose.
+    ///
+    /// # Returns
+    ///
+    /// The Fibonacci numbers of the sum in decreasing order (empty for 0).
+    ///
+    /// # Complexity
+    ///
+    /// O(1): at most 92 Fibonacci numbers fit in a `u64`.
+    pub fn zeckendorf(n: u64) -> Vec<u64> {
+        recurrence::zeckendorf(n)
+    }
+
+    /// Encodes `n` with the Fibonacci code, a universal code whose codewords can be
+    /// concatenated and split again without separators.
+    ///
+    /// Bit `i` of the codeword tells whether `F(i + 2)` is in the Zeckendorf representation
+    /// of `n`, and a 1 is appended after the highest bit, so every codeword ends in the only
+    /// two consecutive 1s it contains: 1 is `11`, 4 = 3 + 1 is `1011`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number to encode.
+    ///
+    /// # Returns
+    ///
+    /// The bits of the codeword, at most 93 of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn fibonacci_encode(n: u64) -> Vec<bool> {
+        recurrence::fibonacci_encode(n)
+    }
+
+    /// Decodes a stream of concatenated Fibonacci codewords, as written by
+    /// `fibonacci_encode`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `bits` - The codewords one after the other.
+    ///
+    /// # Returns
+    ///
+    /// The encoded numbers in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stream ends inside a codeword, or if a codeword is too large for a
+    /// `u64`.
+    pub fn fibonacci_decode(bits: &[bool]) -> Vec<u64> {
+        recurrence::fibonacci_decode(bits)
+    }
+}
+
+/// A struct that provides methods for generating combinatorial sequences modulo a prime with
+/// formal power series.
+#[deprecated(note = "use the free functions of the `math_comb::sequence` module, e.g. `sequence::partitions(n, p)`")]
+pub struct Series {}
+
+#[allow(deprecated)]
+impl Series {
+    /// Computes the Bernoulli numbers `B_0, …, B_n_max` modulo a prime as the coefficients of
+    /// the exponential generating function `x / (e^x − 1)`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n_max` - The index of the last Bernoulli number wanted.
+    /// *   `p` - A prime modulus below 2^32, greater than `n_max + 1`.
+    ///
+    /// # Returns
+    ///
+    /// `B_0, …, B_n_max` modulo `p`, with the convention `B_1 = −1/2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_max + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn bernoulli_mod(n_max: usize, p: u64) -> Vec<u64> {
+        series::bernoulli_mod(n_max, p)
+    }
+
+    /// Counts the partitions `p(0), …, p(n)` modulo a prime, from the Euler product
+    /// `Π 1/(1 − x^k)` evaluated through its logarithm and the power series exponential.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The largest number to partition.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn partitions(n: usize, p: u64) -> Vec<u64> {
+        series::partitions(n, p)
+    }
+
+    /// Counts the partitions of `0, …, n` into distinct parts modulo a prime, from the
+    /// product `Π (1 + x^k)`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The largest number to partition.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn partitions_distinct(n: usize, p: u64) -> Vec<u64> {
+        series::partitions_distinct(n, p)
+    }
+
+    /// Counts the partitions of `0, …, n` into odd parts modulo a prime. By Euler's theorem
+    /// these agree with the partitions into distinct parts.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The largest number to partition.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn partitions_odd(n: usize, p: u64) -> Vec<u64> {
+        series::partitions_odd(n, p)
+    }
+
+    /// Counts the partitions of `0, …, n` into parts of size at most `k` modulo a prime.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The largest number to partition.
+    /// *   `k` - The largest allowed part.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn partitions_max_part(n: usize, k: usize, p: u64) -> Vec<u64> {
+        series::partitions_max_part(n, k, p)
+    }
+
+    /// Counts the partitions of `0, …, n` into exactly `k` parts modulo a prime.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The largest number to partition.
+    /// *   `k` - The number of parts.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn partitions_exact_parts(n: usize, k: usize, p: u64) -> Vec<u64> {
+        series::partitions_exact_parts(n, k, p)
+    }
+
+    /// Computes the row `S(n, 0), …, S(n, n)` of Stirling numbers of the second kind modulo a
+    /// prime, the number of ways to split `n` labeled items into `k` non-empty unlabeled sets.
+    ///
+    /// The row is a single convolution of `i^n / i!` with `(−1)^j / j!`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of items.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn stirling2_row(n: usize, p: u64) -> Vec<u64> {
+        series::stirling2_row(n, p)
+    }
+
+    /// Computes the row `[n, 0], …, [n, n]` of unsigned Stirling numbers of the first kind
+    /// modulo a prime, the number of permutations of `n` items with `k` cycles. The signed
+    /// numbers are `(−1)^(n−k)·[n, k]`.
+    ///
+    /// The row holds the coefficients of `x(x + 1)…(x + n − 1)`, which is built by doubling
+    /// `n` with Taylor shifts.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of items.
+    /// *   `p` - A prime modulus below 2^32, greater than `n + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn stirling1_row(n: usize, p: u64) -> Vec<u64> {
+        series::stirling1_row(n, p)
+    }
+}
+
+/// A struct that provides general integer arithmetic utilities such as gcd and lcm.
+#[deprecated(note = "use the free functions of the `math_comb::arithmetic` module, e.g. `arithmetic::gcd(a, b)`")]
+pub struct Arith {}
+
+#[allow(deprecated)]
+impl Arith {
+    /// Computes the greatest common divisor of `a` and `b` using the Euclidean algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first number.
+    /// *   `b` - The second number.
+    pub const fn gcd(a: u64, b: u64) -> u64 {
+        modexp::gcd(a, b)
+    }
+
+    /// Computes the greatest common divisor of `a` and `b` using Stein's binary algorithm,
+    /// which trades divisions for shifts and is usually the faster choice.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first number.
+    /// *   `b` - The second number.
+    pub const fn binary_gcd(a: u64, b: u64) -> u64 {
+        gcd::binary_gcd(a, b)
+    }
+
+    /// Computes the least common multiple of `a` and `b`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first number.
+    /// *   `b` - The second number.
+    ///
+    /// # Returns
+    ///
+    /// lcm(`a`, `b`) (0 if either argument is 0), or `None` if the result overflows a `u64`.
+    pub fn lcm(a: u64, b: u64) -> Option<u64> {
+        gcd::lcm(a, b)
+    }
+
+    /// Computes the greatest common divisor of all numbers in `values` (0 for an empty slice).
+    ///
+    /// # Arguments
+    ///
+    /// *   `values` - The numbers.
+    pub fn gcd_slice(values: &[u64]) -> u64 {
+        gcd::gcd_slice(values)
+    }
+
+    /// Computes the least common multiple of all numbers in `values` (1 for an empty slice).
+    ///
+    /// # Arguments
+    ///
+    /// *   `values` - The numbers.
+    ///
+    /// # Returns
+    ///
+    /// The lcm of `values`, or `None` if it overflows a `u64`.
+    pub fn lcm_slice(values: &[u64]) -> Option<u64> {
+        gcd::lcm_slice(values)
+    }
+
+    /// Computes `Σ_{i=0}^{n−1} ⌊(a·i + b) / m⌋` with a Euclidean-like recursion, which counts
+    /// the lattice points under a line.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of terms.
+    /// *   `m` - The denominator.
+    /// *   `a` - The slope.
+    /// *   `b` - The offset.
+    ///
+    /// # Returns
+    ///
+    /// The sum, wrapped modulo 2^128 if it does not fit in a `u128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(log m).
+    pub fn floor_sum(n: u64, m: u64, a: u64, b: u64) -> u128 {
+        floorsum::floor_sum(n, m, a, b)
+    }
+
+    /// Computes the weighted floor sums `Σ ⌊(a·i + b) / m⌋`, `Σ i·⌊(a·i + b) / m⌋` and
+    /// `Σ ⌊(a·i + b) / m⌋²` over `0 <= i < n` with the universal Euclidean algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of terms.
+    /// *   `m` - The denominator.
+    /// *   `a` - The slope.
+    /// *   `b` - The offset.
+    ///
+    /// # Returns
+    ///
+    /// The three sums in that order, each wrapped modulo 2^128 if it does not fit in a
+    /// `u128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(log² max(a, m)).
+    pub fn floor_sums(n: u64, m: u64, a: u64, b: u64) -> (u128, u128, u128) {
+        floorsum::floor_sums(n, m, a, b)
+    }
+
+    /// Expands `p/q` as a continued fraction `[a0; a1, a2, …]` by the Euclidean algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// *   `p` - The numerator.
+    /// *   `q` - The denominator.
+    ///
+    /// # Returns
+    ///
+    /// The terms of the expansion; `Convergents` turns them into the convergents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is zero.
+    pub fn continued_fraction(p: u64, q: u64) -> Vec<u64> {
+        contfrac::continued_fraction(p, q)
+    }
+
+    /// Expands `√d` as a periodic continued fraction `[a0; a1, …, ak]` (the bar over
+    /// `a1, …, ak`), whose convergents yield the solutions of Pell's equation.
+    ///
+    /// # Arguments
+    ///
+    /// *   `d` - The number under the root.
+    ///
+    /// # Returns
+    ///
+    /// `(a0, period)`, with an empty period if `d` is a perfect square.
+    ///
+    /// # Complexity
+    ///
+    /// O(k) for a period of length k, which is O(√d log d) at worst.
+    pub fn sqrt_continued_fraction(d: u64) -> (u64, Vec<u64>) {
+        contfrac::sqrt_continued_fraction(d)
+    }
+
+    /// Writes `n` as a sum of two squares, which is possible iff every prime `≡ 3 (mod 4)`
+    /// divides `n` to an even power.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number to decompose.
+    ///
+    /// # Returns
+    ///
+    /// `Some((a, b))` with `a² + b² = n` and `a >= b`, or `None` if no representation exists.
+    pub fn two_squares(n: u64) -> Option<(u64, u64)> {
+        squares::two_squares(n)
+    }
+
+    /// Writes `n` as a sum of four squares (Lagrange's four-square theorem) with the
+    /// randomized reduction of Rabin and Shallit, drawing from a fixed deterministic sequence.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number to decompose.
+    ///
+    /// # Returns
+    ///
+    /// `(a, b, c, d)` with `a² + b² + c² + d² = n` and `a >= b >= c >= d`.
+    ///
+    /// # Complexity
+    ///
+    /// O(log n) expected primality tests and one modular square root.
+    pub fn four_squares(n: u64) -> (u64, u64, u64, u64) {
+        squares::four_squares(n)
+    }
+
+    /// Writes `n` as a sum of four squares like `four_squares`, drawing the random
+    /// reduction from `rng`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number to decompose.
+    /// *   `rng` - The source of the random draws.
+    ///
+    /// # Returns
+    ///
+    /// `(a, b, c, d)` with `a² + b² + c² + d² = n` and `a >= b >= c >= d`.
+    pub fn four_squares_with_rng(n: u64, rng: &mut impl RandomSource) -> (u64, u64, u64, u64) {
+        squares::four_squares_with_rng(n, rng)
+    }
+}
+
+/// A struct that provides navigation of the Stern–Brocot tree of positive fractions and
+/// best rational approximations.
+#[deprecated(note = "use the free functions of the `math_comb::rational` module, e.g. `rational::best_approx(num, den, max_den)`")]
+pub struct SternBrocot {}
+
+#[allow(deprecated)]
+impl SternBrocot {
+    /// Finds the path from the root 1/1 of the Stern–Brocot tree to `num/den`, run-length
+    /// encoded so that its size is that of the continued fraction of `num/den`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `num` - The numerator.
+    /// *   `den` - The denominator.
+    ///
+    /// # Returns
+    ///
+    /// Runs `(direction, length)` of equal steps, empty for 1/1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `den` is zero.
+    pub fn path(num: u64, den: u64) -> Vec<(Direction, u64)> {
+        sternbrocot::path(num, den)
+    }
+
+    /// Finds the fraction at the end of a run-length encoded path from the root 1/1,
+    /// inverting `path`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `runs` - The runs `(direction, length)` of the path.
+    ///
+    /// # Returns
+    ///
+    /// The fraction `(num, den)` in lowest terms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fraction does not fit in a `u64`.
+    pub fn from_path(runs: &[(Direction, u64)]) -> (u64, u64) {
+        sternbrocot::from_path(runs)
+    }
+
+    /// Finds the two fractions whose mediant is `num/den`, its nearest ancestors on either
+    /// side in the tree.
+    ///
+    /// # Arguments
+    ///
+    /// *   `num` - The numerator.
+    /// *   `den` - The denominator.
+    ///
+    /// # Returns
+    ///
+    /// `(left, right)` with `left < num/den < right`, using 0/1 and 1/0 as the outermost
+    /// bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `den` is zero.
+    pub fn neighbors(num: u64, den: u64) -> ((u64, u64), (u64, u64)) {
+        sternbrocot::neighbors(num, den)
+    }
+
+    /// Finds the fraction closest to `x_num/x_den` with a denominator of at most `max_den`,
+    /// choosing between the last convergent and semiconvergent within the bound.
+    ///
+    /// # Arguments
+    ///
+    /// *   `x_num` - The numerator of the target.
+    /// *   `x_den` - The denominator of the target.
+    /// *   `max_den` - The largest allowed denominator.
+    ///
+    /// # Returns
+    ///
+    /// The closest fraction `(num, den)` in lowest terms, preferring the smaller denominator
+    /// on ties.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x_den` or `max_den` is zero.
+    ///
+    /// # Complexity
+    ///
+    /// O(log x_den).
+    pub fn best_approx(x_num: u64, x_den: u64, max_den: u64) -> (u64, u64) {
+        sternbrocot::best_approx(x_num, x_den, max_den)
+    }
+
+    /// Finds the neighbours of `p/q` in the Farey sequence F_n by extending its Stern–Brocot
+    /// bounds with the largest mediants allowed by `n`. `FareySequence` iterates over all of
+    /// F_n.
+    ///
+    /// # Arguments
+    ///
+    /// *   `p` - The numerator.
+    /// *   `q` - The denominator.
+    /// *   `n` - The order of the sequence.
+    ///
+    /// # Returns
+    ///
+    /// The fractions just before and after `p/q` in F_n, `None` past either end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` or `q` is zero, or if `p/q` is not in F_n.
+    ///
+    /// # Complexity
+    ///
+    /// O(log q).
+    pub fn farey_neighbors(p: u64, q: u64, n: u64) -> (Option<Fraction>, Option<Fraction>) {
+        sternbrocot::farey_neighbors(p, q, n)
+    }
+}
+
+/// A struct for pre-calculating factorials and their modular inverses,
+/// useful for efficient combination and permutation calculations under mod.
+///
+/// The tables hold values of the width `T` (`u64` unless chosen otherwise through
+/// `with_modulus`), so a `u32` modulus halves their memory and multiplies through `u64`.
+/// Products are reduced with the strategy `MulMod` picks for the magnitude of the modulus,
+/// so any prime modulus that fits in `T` is safe.
+pub struct Comb<T: UInt = u64> {
+    mod_value: T,
+    // The modulus prepared for multiplications, with the strategy picked for its magnitude.
+    mul: T::Modulus,
+    max_fact: usize,
+    // The largest `n` accepted by `nPr` and `nCr`: `max_fact`, or the modulus − 1 when the
+    // factorials past the tables are multiplied on demand.
+    limit: u64,
+    fact: Vec<T>,
+    inv_fact: Vec<T>
+}
+
+/// A `Comb` whose modulus fits in 32 bits, such as 998244353. Its tables take half the
+/// memory of a `Comb<u64>`; lookups take about as long (`cargo bench --bench backends`
+/// times both). Create it with `Comb32::with_modulus(998244353, max_fact)`.
+pub type Comb32 = Comb<u32>;
+
+impl<T: UInt> Comb<T> {
+    /// Creates a `Comb` instance of any width, pre-calculating factorials and their modular
+    /// inverses up to `max_fact`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `mod_value` - The prime modulus, whose type picks the width of the tables.
+    /// *   `max_fact` - The maximum number for which factorials and inverse factorials will
+    ///     be pre-calculated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is not prime, or if `max_fact` is not below it.
+    pub fn with_modulus(mod_value: T, max_fact: usize) -> Comb<T> {
+        if !mod_value.is_prime() {
+            panic!("modulus is not prime!");
+        }
+        let mul = T::prepare_modulus(mod_value);
+        let mut fact = vec![T::ONE; max_fact + 1];
+        let mut i = T::ZERO;
+        for k in 1..=max_fact {
+            i = i + T::ONE;
+            if i == mod_value {
+                panic!("max_fact must be less than the modulus!");
+            }
+            fact[k] = fact[k - 1].mul_prepared(i, &mul);
+        }
+        let mut inv_fact = vec![T::ONE; max_fact + 1];
+        inv_fact[max_fact] = uint::mod_exp(fact[max_fact], mod_value - T::from(2), mod_value);
+        for k in (0..max_fact).rev() {
+            inv_fact[k] = inv_fact[k + 1].mul_prepared(i, &mul);
+            i = i - T::ONE;
+        }
+        Comb { mod_value, mul, max_fact, limit: max_fact as u64, fact, inv_fact }
+    }
+
+    /// Calculates nPr (n permutations of r) under mod.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The total number of items.
+    /// *   `r` - The number of items to choose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is less than `r` or `n` > `max_fact` (or `n` is not below the modulus
+    /// for a `Comb` built with `auto_grow`).
+    #[allow(non_snake_case)]
+    pub fn nPr(&self, n: u64, r: u64) -> T {
+        if n < r {
+            panic!("n cannot be less than r!")
+        } else if n > self.limit {
+            panic!("n cannot be greater than {}!", self.limit);
+        } else {
+            self.falling(n, n - r)
+        }
+    }
+
+    /// Calculates nPr under mod like `nPr`, returning an error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The total number of items.
+    /// *   `r` - The number of items to choose.
+    ///
+    /// # Returns
+    ///
+    /// nPr modulo `mod_value`, `MathCombError::InvalidArgument` if `n < r`, or
+    /// `MathCombError::AboveLimit` if `n > max_fact`.
+    #[allow(non_snake_case)]
+    pub fn try_nPr(&self, n: u64, r: u64) -> Result<T, MathCombError> {
+        self.check_args(n, r)?;
+        Ok(self.nPr(n, r))
+    }
+
+    /// Calculates nCr (n combinations of r) under mod.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The total number of items.
+    /// *   `r` - The number of items to choose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is less than `r` or `n` > `max_fact` (or `n` is not below the modulus
+    /// for a `Comb` built with `auto_grow`).
+    #[allow(non_snake_case)]
+    pub fn nCr(&self, n: u64, r: u64) -> T {
+        if n < r {
+            panic!("n cannot be less than r!");
+        } else if n > self.limit {
+            panic!("n cannot be greater than {}!", self.limit);
+        } else {
+            // C(n, r) = C(n, n − r), and the smaller side keeps the product past the
+            // tables short.
+            let k = r.min(n - r);
+            self.falling(n, k).mul_prepared(self.inv_fact_at(k), &self.mul)
+        }
+    }
+
+    /// Calculates nCr under mod like `nCr`, returning an error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The total number of items.
+    /// *   `r` - The number of items to choose.
+    ///
+    /// # Returns
+    ///
+    /// nCr modulo `mod_value`, `MathCombError::InvalidArgument` if `n < r`, or
+    /// `MathCombError::AboveLimit` if `n > max_fact`.
+    #[allow(non_snake_case)]
+    pub fn try_nCr(&self, n: u64, r: u64) -> Result<T, MathCombError> {
+        self.check_args(n, r)?;
+        Ok(self.nCr(n, r))
+    }
+
+    // Checks the arguments of `nPr` and `nCr` in the order their panics do.
+    fn check_args(&self, n: u64, r: u64) -> Result<(), MathCombError> {
+        if n < r {
+            return Err(MathCombError::InvalidArgument("n cannot be less than r"));
+        }
+        if n > self.limit {
+            return Err(MathCombError::AboveLimit { value: n, limit: self.limit });
+        }
+        Ok(())
+    }
+
+    // Computes the falling factorial n·(n − 1)·…·(n − k + 1) = n! / (n − k)! for
+    // `k <= n <= limit`. Past the end of the table it multiplies either its k terms or n! on
+    // from the last tabulated factorial, whichever is shorter, so it takes
+    // O(min(k, n − max_fact)) steps.
+    fn falling(&self, n: u64, k: u64) -> T {
+        let max = self.max_fact as u64;
+        let r = n - k;
+        if n <= max {
+            return self.fact[n as usize].mul_prepared(self.inv_fact[r as usize], &self.mul);
+        }
+        let product = |lo: u64, init: T| {
+            (lo..=n).fold(init, |f, i| match T::try_from(i) {
+                Ok(i) => f.mul_prepared(i, &self.mul),
+                Err(_) => unreachable!("n is below the modulus"),
+            })
+        };
+        if r >= max {
+            product(r + 1, T::ONE)
+        } else {
+            product(max + 1, self.fact[self.max_fact]).mul_prepared(self.inv_fact[r as usize], &self.mul)
+        }
+    }
+
+    // Looks up the inverse of n!, inverting n! past the end of the table.
+    fn inv_fact_at(&self, n: u64) -> T {
+        if n <= self.max_fact as u64 {
+            return self.inv_fact[n as usize];
+        }
+        uint::mod_exp(self.falling(n, n), self.mod_value - T::from(2), self.mod_value)
+    }
+}
+
+impl Comb<u64> {
+    /// Creates a new `Comb` instance, pre-calculating factorials and their
+    /// modular inverses up to `max_fact`.
+    ///
+    /// This pre-computation allows for fast calculation of combinations and permutations
+    /// modulo `mod_value`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `mod_value` - The modulus to use for calculations.
+    /// *   `max_fact` - The maximum number for which factorials and inverse
+    ///     factorials will be pre-calculated. This determines the
+    ///     range of `n` that can be used in `nCr` and `nPr` without
+    ///     requiring further calculations.
+    /// # Panics
+    /// 
+    /// Panics if modulus is not prime.
+    pub fn new(mod_value: u64, max_fact: usize) -> Comb {
+        if !pollard::is_prime(mod_value) {
+            panic!("modulus is not prime!");
+        }
+
+        // The products go through a `MulMod`, so moduli above 2^32 do not overflow.
+        let (fact, inv_fact) = factorial_tables(mod_value, max_fact, false);
+
+        Comb {
+            mod_value,
+            mul: MulMod::new(mod_value),
+            max_fact,
+            limit: max_fact as u64,
+            fact,
+            inv_fact
+        }
+    }
+
+    /// Creates a new `Comb` instance like `new`, returning an error instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `mod_value` - The prime modulus to use for calculations.
+    /// *   `max_fact` - The maximum number for which factorials and inverse factorials will
+    ///     be pre-calculated, which has to be below `mod_value` for them to be invertible.
+    ///
+    /// # Returns
+    ///
+    /// The `Comb` instance, `MathCombError::ZeroModulus` or `MathCombError::ModulusNotPrime`
+    /// for an unusable modulus, or `MathCombError::AboveLimit` if `max_fact >= mod_value`.
+    pub fn try_new(mod_value: u64, max_fact: usize) -> Result<Comb, MathCombError> {
+        if mod_value == 0 {
+            return Err(MathCombError::ZeroModulus);
+        }
+        if !pollard::is_prime(mod_value) {
+            return Err(MathCombError::ModulusNotPrime(mod_value));
+        }
+        if max_fact as u64 >= mod_value {
+            return Err(MathCombError::AboveLimit { value: max_fact as u64, limit: mod_value - 1 });
+        }
+        Ok(Comb::new(mod_value, max_fact))
+    }
+
+    /// Starts a `CombBuilder`, which sets up a `Comb` one option at a time, e.g.
+    /// `Comb::builder().modulus(p).max_fact(n).auto_grow().build()`.
+    pub fn builder() -> CombBuilder {
+        CombBuilder::new()
+    }
+
+    /// Returns the process-wide `Comb` for `mod_value` with tables up to at least
+    /// `min_size`, building it on first use and rebuilding it larger when a call needs more.
+    ///
+    /// A rebuild at least doubles the tables (up to the modulus − 1), so rising requests cost
+    /// amortized linear time. `Arc`s handed out before a rebuild keep their smaller tables.
+    /// Tables are built without holding the registry lock, so a build never blocks callers
+    /// whose tables are already there. Two threads racing to build the same modulus may both
+    /// build, and the larger table is kept.
+    ///
+    /// # Arguments
+    ///
+    /// *   `mod_value` - The prime modulus.
+    /// *   `min_size` - The smallest `max_fact` the caller needs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is not prime, or if `min_size` is not below it.
+    pub fn shared(mod_value: u64, min_size: usize) -> Arc<Comb> {
+        static REGISTRY: OnceLock<Mutex<HashMap<u64, Arc<Comb>>>> = OnceLock::new();
+        // Builds, which may panic, run without the lock and the map is only changed by a
+        // single insert, so a poisoned lock is still safe to use.
+        let registry = REGISTRY.get_or_init(Default::default);
+        let lock = || registry.lock().unwrap_or_else(PoisonError::into_inner);
+        let size = match lock().get(&mod_value) {
+            Some(comb) if comb.max_fact >= min_size => return Arc::clone(comb),
+            // Clamped through `try_from`, since a 32-bit `usize` cannot hold every modulus.
+            Some(comb) => min_size.max(2 * comb.max_fact).min(usize::try_from(mod_value - 1).unwrap_or(usize::MAX)),
+            None => min_size,
+        };
+        let comb = Arc::new(Comb::builder().modulus(mod_value).max_fact(size).build());
+        // Another thread may have stored a table at least as large in the meantime.
+        let mut registry = lock();
+        match registry.get(&mod_value) {
+            Some(current) if current.max_fact >= comb.max_fact => Arc::clone(current),
+            _ => {
+                registry.insert(mod_value, Arc::clone(&comb));
+                comb
+            }
+        }
+    }
+    
+    /// Evaluates at `x` the polynomial of degree below `ys.len()` that takes the value
+    /// `ys[i]` at `x0 + i`, using Lagrange's formula with the precomputed factorials.
+    ///
+    /// This is the standard tool for polynomial prefix sums such as Σ i^k, which are known to
+    /// be polynomials of a bounded degree and are easy to tabulate at small arguments.
+    ///
+    /// # Arguments
+    ///
+    /// *   `ys` - The values at the consecutive points `x0, x0 + 1, …`.
+    /// *   `x0` - The first sample point.
+    /// *   `x` - The point at which to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The value of the interpolating polynomial at `x` modulo `mod_value` (0 if `ys` is
+    /// empty).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ys.len() - 1` is greater than `max_fact`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) for n samples.
+    pub fn lagrange_consecutive(&self, ys: &[u64], x0: u64, x: u64) -> u64 {
+        let n = ys.len();
+        if n == 0 {
+            return 0;
+        }
+        if n - 1 > self.max_fact {
+            panic!("n cannot be greater than {}!", self.max_fact);
+        }
+        let (m, mm) = (self.mod_value, &self.mul);
+        let t = primecount::sub_mod(x % m, x0 % m, m);
+        if t < n as u64 {
+            return ys[t as usize] % m;
+        }
+        // prefix[i] = Π_{j<i} (t - j) and suffix[i] = Π_{j>=i} (t - j).
+        let mut prefix = vec![1; n + 1];
+        let mut suffix = vec![1; n + 1];
+        for i in 0..n {
+            prefix[i + 1] = mm.mul(prefix[i], primecount::sub_mod(t, i as u64, m));
+        }
+        for i in (0..n).rev() {
+            suffix[i] = mm.mul(suffix[i + 1], primecount::sub_mod(t, i as u64, m));
+        }
+        let mut sum = 0;
+        for (i, &y) in ys.iter().enumerate() {
+            let term = mm.mul(mm.mul(y % m, mm.mul(prefix[i], suffix[i + 1])), mm.mul(self.inv_fact[i], self.inv_fact[n - 1 - i]));
+            sum = if (n - 1 - i).is_multiple_of(2) { primecount::add_mod(sum, term, m) } else { primecount::sub_mod(sum, term, m) };
+        }
+        sum
+    }
+
+    /// Computes the power sum `1^k + 2^k + … + n^k` modulo `mod_value`.
+    ///
+    /// The sum is a polynomial of degree `k + 1` in `n` (Faulhaber's formula), so it is
+    /// tabulated at `n = 0, 1, …, k + 1` and extended to `n` with `lagrange_consecutive`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of terms, which may be as large as `u64::MAX`.
+    /// *   `k` - The exponent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k + 1` is greater than `max_fact`.
+    ///
+    /// # Complexity
+    ///
+    /// O(k log k).
+    pub fn power_sum(&self, n: u64, k: u64) -> u64 {
+        if k.checked_add(1).is_none_or(|degree| degree > self.max_fact as u64) {
+            panic!("k + 1 cannot be greater than {}!", self.max_fact);
+        }
+        let m = self.mod_value;
+        let mut ys = vec![0];
+        for i in 1..=k + 1 {
+            ys.push(primecount::add_mod(ys[i as usize - 1], self.mul.pow(i, k), m));
+        }
+        self.lagrange_consecutive(&ys, 0, n)
+    }
+
+    /// Multiplies two sequences as exponential generating functions, giving
+    /// `c[n] = Σ_k C(n, k)·a[k]·b[n − k]`.
+    ///
+    /// Both inputs are scaled by the inverse factorials, convolved, and the product is scaled
+    /// back by the factorials. This counts labeled structures split into two labeled parts.
+    ///
+    /// The convolution goes through the number-theoretic transforms for a modulus below
+    /// 2^32. The transforms cannot handle larger moduli, so those are multiplied term by
+    /// term instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// *   `a` - The first sequence.
+    /// *   `b` - The second sequence.
+    ///
+    /// # Returns
+    ///
+    /// The `a.len() + b.len() - 1` terms of the product modulo `mod_value` (empty if either
+    /// input is empty).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a.len() + b.len() - 2` is greater than `max_fact`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for n output terms, or O(n²) for a modulus above 2^32.
+    pub fn egf_convolution(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let n = a.len() + b.len() - 1;
+        if n - 1 > self.max_fact {
+            panic!("n cannot be greater than {}!", self.max_fact);
+        }
+        let (m, mm) = (self.mod_value, &self.mul);
+        let scale = |x: &[u64]| -> Vec<u64> { x.iter().zip(&self.inv_fact).map(|(&v, &f)| mm.mul(v % m, f)).collect() };
+        let (a, b) = (scale(a), scale(b));
+        let mut c = if m >> 32 == 0 {
+            poly::multiply(&a, &b, m)
+        } else {
+            // The transforms need a modulus below 2^32, so larger ones multiply term by term.
+            let mut c = vec![0; n];
+            for (i, &x) in a.iter().enumerate() {
+                for (j, &y) in b.iter().enumerate() {
+                    c[i + j] = primecount::add_mod(c[i + j], mm.mul(x, y), m);
+                }
+            }
+            c
+        };
+        c.resize(n, 0);
+        c.iter_mut().zip(&self.fact).for_each(|(v, &f)| *v = mm.mul(*v, f));
+        c
+    }
+
+    /// Counts the labeled trees on `n` vertices with Cayley's formula `n^(n−2)`.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of vertices.
+    ///
+    /// # Returns
+    ///
+    /// The number of trees modulo `mod_value`, taken as 1 for `n <= 1`.
+    pub fn labeled_trees(&self, n: u64) -> u64 {
+        if n <= 1 {
+            return 1 % self.mod_value;
+        }
+        self.mul.pow(n, n - 2)
+    }
+
+    /// Counts the labeled forests on `n` vertices made of `k` trees whose roots are `k` given
+    /// vertices, which is `k·n^(n−k−1)` by the generalized Cayley formula.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number of vertices.
+    /// *   `k` - The number of trees, one rooted at each of the given vertices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is greater than `n`.
+    pub fn rooted_forests(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            panic!("k cannot be greater than n!");
+        }
+        let m = self.mod_value;
+        if k == n {
+            return 1 % m;
+        }
+        self.mul.mul(k % m, self.mul.pow(n, n - k - 1))
+    }
+
+    /// Counts the labeled trees on the vertices `0..n` in which vertex `i` has degree
+    /// `degrees[i]`, which is the multinomial `(n − 2)! / Π (degrees[i] − 1)!` read off the
+    /// Prüfer sequence.
+    ///
+    /// # Arguments
+    ///
+    /// *   `degrees` - The degree of every vertex.
+    ///
+    /// # Returns
+    ///
+    /// The number of trees modulo `mod_value`, which is 0 unless every degree is at least 1
+    /// and the degrees sum to `2(n − 1)` (a single vertex needs degree 0).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n − 2` is greater than `max_fact`.
+    pub fn trees_with_degrees(&self, degrees: &[u64]) -> u64 {
+        let n = degrees.len();
+        let m = self.mod_value;
+        if n <= 1 {
+            return if degrees.iter().all(|&d| d == 0) { 1 % m } else { 0 };
+        }
+        if degrees.contains(&0) || degrees.iter().try_fold(0u64, |acc, &d| acc.checked_add(d)) != Some(2 * (n as u64 - 1)) {
+            return 0;
+        }
+        if n - 2 > self.max_fact {
+            panic!("n cannot be greater than {}!", self.max_fact);
+        }
+        degrees.iter().fold(self.fact[n - 2], |acc, &d| self.mul.mul(acc, self.inv_fact[d as usize - 1]))
+    }
+
+    /// Computes `n!` modulo a prime `p` for `n` far beyond any factorial table.
+    ///
+    /// The product is split into `⌊√n⌋` blocks of `⌊√n⌋` consecutive factors, which are the
+    /// values of one polynomial at an arithmetic progression of points, found with a single
+    /// multipoint evaluation.
+    ///
+    /// # Arguments
+    ///
+    /// *   `n` - The number whose factorial is wanted.
+    /// *   `p` - A prime modulus below 2^32.
+    ///
+    /// # Returns
+    ///
+    /// `n!` modulo `p` (0 whenever `n >= p`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(√n log² n).
+    pub fn factorial_mod_large(n: u64, p: u64) -> u64 {
+        series::factorial_mod_large(n, p)
+    }
+
+    /// Finds the lexicographic rank of a permutation of `0..n` through its Lehmer code in
+    /// the factorial number system.
+    ///
+    /// # Arguments
+    ///
+    /// *   `perm` - A permutation of `0..perm.len()`.
+    ///
+    /// # Returns
+    ///
+    /// The rank, from 0 for the identity to `n! − 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `perm` is not a permutation, or if its rank does not fit in a `u128`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn rank_permutation(perm: &[usize]) -> u128 {
+        rank::rank_permutation(perm)
+    }
+
+    /// Finds the permutation of `0..n` with lexicographic rank `k`.
     ///
     /// # Arguments
     ///
-    /// * `max_limit` - The maximum limit up to which the smallest prime factors are computed.
+    /// *   `n` - The number of elements.
+    /// *   `k` - The rank, counting from 0.
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// A new `Spf` instance with precomputed smallest prime factors up to `max_limit`.
-    pub fn new(max_limit: usize) -> Spf {
-        let mut spf = vec![0; max_limit + 1];
-        for i in 2..=max_limit {
-            if spf[i] == 0 {
-                for j in (i..=max_limit).step_by(i) {
-                    if spf[j] == 0 {
-                        spf[j] = i as u64;
-                    }
-                }
-            }
-        }
-        Spf {
-            spf_max_limit: max_limit,
-            spf: spf,
-        }
+    /// Panics if `k >= n!`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn unrank_permutation(n: usize, k: u128) -> Vec<usize> {
+        rank::unrank_permutation(n, k)
     }
 
-    /// Retrieves the smallest prime factor of a given number.
+    /// Finds the colexicographic rank `Σ C(c_i, i)` of the subset `c_1 < … < c_k`.
     ///
     /// # Arguments
     ///
-    /// * `x` - The number for which the smallest prime factor is to be retrieved.
+    /// *   `comb` - The elements of the subset in increasing order.
     ///
     /// # Returns
     ///
-    /// The smallest prime factor of `x`.
+    /// The rank, from 0 for `{0, 1, …, k − 1}`.
     ///
     /// # Panics
     ///
-    /// Panics if `x` is greater than the `max_limit` specified during the creation of the `Spf` instance.
-    pub fn get_spf(&self, x: u64) -> u64 {
-        if x as usize > self.spf_max_limit {
-            panic!("x cannot be greater than max_limit!");
-        }
-        self.spf[x as usize]
+    /// Panics if `comb` is not strictly increasing, or if the rank does not fit in a `u128`.
+    pub fn rank_combination(comb: &[usize]) -> u128 {
+        rank::rank_combination(comb)
     }
 
-    /// Factorizes a given number into its prime factors.
+    /// Finds the `k`-subset of `0..n` with colexicographic rank `rank`.
     ///
     /// # Arguments
     ///
-    /// * `x` - The number to be factorized.
+    /// *   `n` - The size of the ground set.
+    /// *   `k` - The size of the subset.
+    /// *   `rank` - The rank, counting from 0.
     ///
     /// # Returns
     ///
-    /// A vector containing the prime factors of `x`.
+    /// The elements of the subset in increasing order.
     ///
     /// # Panics
     ///
-    /// Panics if `x` is greater than the `max_limit` specified during the creation of the `Spf` instance.
-    ///
-    /// # Complexity
-    ///
-    /// The factorization process takes O(log n) time after the SPF computation.
-    pub fn factorize(&self, x: u64) -> Vec<u64> {
-        if x as usize > self.spf_max_limit {
-            panic!("x cannot be greater than max_limit!");
-        }
-        let mut factors: Vec<u64> = Vec::new();
-        let mut y: usize = x as usize;
-        while y != 1 {
-            factors.push(self.spf[y]);
-            y /= self.spf[y] as usize;
-        }
-        factors.sort();
-        factors
+    /// Panics if `rank >= C(n, k)`.
+    pub fn unrank_combination(n: usize, k: usize, rank: u128) -> Vec<usize> {
+        rank::unrank_combination(n, k, rank)
     }
-}
-
-/// A struct that provides methods for modular exponentiation and modular inverse calculations.
-pub struct Modexp {}
 
-impl Modexp {
-    /// Calculates (base^exponent) % modulus using modular exponentiation.
+    /// Draws a uniformly random permutation of `0..n` with a Fisher–Yates shuffle.
     ///
     /// # Arguments
     ///
-    /// *   `base` - The base.
-    /// *   `exponent` - The exponent.
-    /// *   `modulus` - The modulus.
-    pub fn mod_exp(base: u64, exponent: u64, modulus: u64) -> u64 {
-        return modexp::mod_exp(base, exponent, modulus);
-    }
-
-    /// Calculates the modular multiplicative inverse of `x` modulo `modulus`.
+    /// *   `n` - The number of elements.
+    /// *   `rng` - The source of randomness, e.g. a seeded `SplitMix64` for reproducible
+    ///     samples.
     ///
-    /// The modular inverse of `x` modulo `modulus` is an integer `y` such that
-    /// (x * y) % modulus == 1. It exists if and only if `x` and `modulus` are coprime
-    /// (i.e., their greatest common divisor is 1).
+    /// # Complexity
     ///
-    /// This function uses Fermat's Little Theorem, which states that if `modulus` is a prime number,
-    /// then for any integer `x` not divisible by `modulus`, `x` ^ (`modulus` - 1) ≡ 1 (mod `modulus`).
-    /// Therefore, the modular inverse of `x` is `x` ^ (`modulus` - 2) (mod `modulus`).
+    /// O(n).
+    pub fn random_permutation(n: usize, rng: &mut impl RandomSource) -> Vec<usize> {
+        rank::random_permutation(n, rng)
+    }
+
+    /// Draws a uniformly random `k`-subset of `0..n` with Floyd's algorithm.
     ///
     /// # Arguments
     ///
-    /// *   `x` - The number for which to calculate the inverse.
-    /// *   `modulus` - The modulus.
+    /// *   `n` - The size of the ground set.
+    /// *   `k` - The size of the subset.
+    /// *   `rng` - The source of randomness.
     ///
     /// # Returns
     ///
-    /// The modular inverse of `x` modulo `modulus`.
+    /// The elements of the subset in increasing order.
     ///
     /// # Panics
     ///
-    /// This function will panic if:
-    /// *   `modulus` is 0.
-    /// *   `x` and `modulus` are not coprime (their greatest common divisor is not 1).
-    pub fn mod_inv(x: u64, modulus: u64) -> u64 {
-        return modexp::mod_inv(x, modulus);
+    /// Panics if `k > n`.
+    ///
+    /// # Complexity
+    ///
+    /// O(k log k), independent of `n`.
+    pub fn random_combination(n: usize, k: usize, rng: &mut impl RandomSource) -> Vec<usize> {
+        rank::random_combination(n, k, rng)
     }
+
 }
 
-/// A struct for pre-calculating factorials and their modular inverses,
-/// useful for efficient combination and permutation calculations under mod.
-pub struct Comb {
-    mod_value: u64,
+/// A builder for `Comb`, collecting the modulus, the size of the tables and the build
+/// options before anything is computed.
+#[derive(Clone, Debug, Default)]
+pub struct CombBuilder {
+    modulus: Option<u64>,
     max_fact: usize,
-    fact: Vec<u64>,
-    inv_fact: Vec<u64>
+    skip_prime_check: bool,
+    auto_grow: bool,
+    parallel_build: bool,
 }
 
-impl Comb {
-    /// Creates a new `Comb` instance, pre-calculating factorials and their
-    /// modular inverses up to `max_fact`.
+impl CombBuilder {
+    /// Creates a builder with no modulus, `max_fact` 0 and every option off.
+    pub fn new() -> CombBuilder {
+        CombBuilder::default()
+    }
+
+    /// Sets the prime modulus, which has to be set before building.
     ///
-    /// This pre-computation allows for fast calculation of combinations and permutations
-    /// modulo `mod_value`.
+    /// # Arguments
+    ///
+    /// *   `modulus` - The prime modulus.
+    pub fn modulus(mut self, modulus: u64) -> CombBuilder {
+        self.modulus = Some(modulus);
+        self
+    }
+
+    /// Sets the largest number whose factorial is tabulated (0 unless set).
     ///
     /// # Arguments
     ///
-    /// *   `mod_value` - The modulus to use for calculations.
-    /// *   `max_fact` - The maximum number for which factorials and inverse
-    ///                  factorials will be pre-calculated. This determines the
-    ///                  range of `n` that can be used in `nCr` and `nPr` without
-    ///                  requiring further calculations.
-    /// # Panics
-    /// 
-    /// Panics if modulus is not prime.
-    pub fn new(mod_value: u64, max_fact: usize) -> Comb {
-        if !Self::check_prime(mod_value) {
-            panic!("modulus is not prime!");
-        }
+    /// *   `max_fact` - The maximum number for which factorials and inverse factorials will
+    ///     be pre-calculated.
+    pub fn max_fact(mut self, max_fact: usize) -> CombBuilder {
+        self.max_fact = max_fact;
+        self
+    }
 
-        let mut fact: Vec<u64> = vec![0; max_fact + 1];
-        let mut inv_fact: Vec<u64> = vec![0; max_fact + 1];
-        
-        fact[0] = 1;
+    /// Trusts the modulus to be prime instead of testing it. The inverse factorials are
+    /// wrong if it is not.
+    pub fn skip_prime_check(mut self) -> CombBuilder {
+        self.skip_prime_check = true;
+        self
+    }
 
-        for i in 1..=max_fact {
-            fact[i] = (fact[i - 1] * (i as u64)) % mod_value;
-        }
-        inv_fact[max_fact] = Modexp::mod_inv(fact[max_fact] as u64, mod_value);
-        for i in (0..max_fact).rev() {
-            inv_fact[i] = (inv_fact[i + 1] * ((i + 1) as u64)) % mod_value;
-        }
+    /// Lets `nPr` and `nCr` take any `n` below the modulus instead of panicking above
+    /// `max_fact`. Past the tables they multiply a falling factorial on demand: `nPr(n, r)`
+    /// takes O(min(n − r, n − max_fact)) steps, and `nCr(n, r)` O(min(r, n − r)) steps plus
+    /// one modular inverse when min(r, n − r) exceeds `max_fact`.
+    pub fn auto_grow(mut self) -> CombBuilder {
+        self.auto_grow = true;
+        self
+    }
 
-        Comb { 
-            mod_value: mod_value, 
-            max_fact: max_fact, 
-            fact: fact, 
-            inv_fact: inv_fact
-        }
+    /// Fills the tables by segments in parallel. Without the `rayon` feature this has no
+    /// effect.
+    pub fn parallel_build(mut self) -> CombBuilder {
+        self.parallel_build = true;
+        self
     }
-    
-    /// Calculates nPr (n permutations of r) under mod.
-    ///
-    /// # Arguments
-    ///
-    /// *   `n` - The total number of items.
-    /// *   `r` - The number of items to choose.
+
+    /// Builds the `Comb`, pre-calculating factorials and their modular inverses up to
+    /// `max_fact`.
     ///
     /// # Panics
     ///
-    /// Panics if `n` is less than `r` or `n` > `max_fact`.
-    pub fn nPr(&self, n: u64, r: u64) -> u64 {
-        if n < r {
-            panic!("n cannot be less than r!")
-        } else if n > (self.max_fact as u64) {
-            panic!("n cannot be greater than {}!", self.max_fact);
-        } else {
-            return (self.fact[n as usize] * self.inv_fact[r as usize]) % self.mod_value;
+    /// Panics if no modulus was set, if it is 0 or 1 or (unless the check is skipped) not
+    /// prime, or if `max_fact` is not below it.
+    pub fn build(self) -> Comb {
+        let mod_value = match self.modulus {
+            Some(m) => m,
+            None => panic!("modulus is not set!"),
+        };
+        if mod_value == 0 {
+            panic!("Modulus cannot be zero.");
         }
+        if mod_value == 1 || (!self.skip_prime_check && !pollard::is_prime(mod_value)) {
+            panic!("modulus is not prime!");
+        }
+        if self.max_fact as u64 >= mod_value {
+            panic!("max_fact must be less than the modulus!");
+        }
+        let (fact, inv_fact) = factorial_tables(mod_value, self.max_fact, self.parallel_build);
+        let limit = if self.auto_grow { mod_value - 1 } else { self.max_fact as u64 };
+        Comb { mod_value, mul: MulMod::new(mod_value), max_fact: self.max_fact, limit, fact, inv_fact }
     }
 
-    /// Calculates nCr (n combinations of r) under mod.
+    /// Builds the `Comb` like `build`, returning an error instead of panicking.
     ///
-    /// # Arguments
-    ///
-    /// *   `n` - The total number of items.
-    /// *   `r` - The number of items to choose.
-    ///
-    /// # Panics
+    /// # Returns
     ///
-    /// Panics if `n` is less than `r` or `n` > `max_fact`.
-    pub fn nCr(&self, n: u64, r: u64) -> u64 {
-        if n < r {
-            panic!("n cannot be less than r!");
-        } else if n > (self.max_fact as u64) {
-            panic!("n cannot be greater than {}!", self.max_fact);
-        } else {
-            return (self.nPr(n, r) * self.inv_fact[(n - r) as usize]) % self.mod_value;
+    /// The `Comb` instance, `MathCombError::InvalidArgument` if no modulus was set,
+    /// `MathCombError::ZeroModulus` or `MathCombError::ModulusNotPrime` for an unusable
+    /// modulus, or `MathCombError::AboveLimit` if `max_fact` is not below the modulus.
+    pub fn try_build(self) -> Result<Comb, MathCombError> {
+        let mod_value = self.modulus.ok_or(MathCombError::InvalidArgument("modulus is not set"))?;
+        if mod_value == 0 {
+            return Err(MathCombError::ZeroModulus);
+        }
+        if mod_value == 1 || (!self.skip_prime_check && !pollard::is_prime(mod_value)) {
+            return Err(MathCombError::ModulusNotPrime(mod_value));
         }
+        if self.max_fact as u64 >= mod_value {
+            return Err(MathCombError::AboveLimit { value: self.max_fact as u64, limit: mod_value - 1 });
+        }
+        Ok(self.build())
     }
+}
 
-    fn check_prime(n: u64) -> bool {
-        let mut _x: u64 = 2;
-        while _x * _x <= n {
-            if n % _x == 0 {
-                return false
-            }
-            _x = _x + 1;
-        }
-        return true;
+/// The number of entries per segment when factorial tables are filled in parallel.
+#[cfg(feature = "rayon")]
+const FACT_SEGMENT: usize = 1 << 16;
+
+// Fills the tables of k! and (k!)^-1 modulo the prime `p` for `k <= max_fact`, by segments in
+// parallel if `parallel` is set and the `rayon` feature is enabled.
+fn factorial_tables(p: u64, max_fact: usize, parallel: bool) -> (Vec<u64>, Vec<u64>) {
+    #[cfg(feature = "rayon")]
+    if parallel {
+        return par_factorial_tables(p, max_fact);
+    }
+    #[cfg(not(feature = "rayon"))]
+    let _ = parallel;
+    let mm = MulMod::new(p);
+    let mut fact = vec![1; max_fact + 1];
+    for i in 1..=max_fact {
+        fact[i] = mm.mul(fact[i - 1], i as u64);
     }
+    let mut inv_fact = vec![1; max_fact + 1];
+    inv_fact[max_fact] = mm.pow(fact[max_fact], p - 2);
+    for i in (0..max_fact).rev() {
+        inv_fact[i] = mm.mul(inv_fact[i + 1], i as u64 + 1);
+    }
+    (fact, inv_fact)
+}
 
+#[cfg(feature = "rayon")]
+fn par_factorial_tables(p: u64, max_fact: usize) -> (Vec<u64>, Vec<u64>) {
+    use rayon::prelude::*;
+    let mm = MulMod::new(p);
+    // Each segment first holds the running products of its own terms, which are then scaled
+    // by the product of every earlier segment.
+    let mut fact = vec![1; max_fact + 1];
+    fact.par_chunks_mut(FACT_SEGMENT).enumerate().for_each(|(s, chunk)| {
+        let mut acc = 1;
+        for (j, f) in chunk.iter_mut().enumerate() {
+            acc = mm.mul(acc, (s * FACT_SEGMENT + j).max(1) as u64);
+            *f = acc;
+        }
+    });
+    let products: Vec<u64> = fact.chunks(FACT_SEGMENT).map(|chunk| chunk[chunk.len() - 1]).collect();
+    let mut before = vec![1; products.len()];
+    for s in 1..products.len() {
+        before[s] = mm.mul(before[s - 1], products[s - 1]);
+    }
+    fact.par_chunks_mut(FACT_SEGMENT).zip(&before).for_each(|(chunk, &b)| {
+        chunk.iter_mut().for_each(|f| *f = mm.mul(*f, b));
+    });
+    // (k!)^-1 is (max_fact!)^-1 times (k + 1)(k + 2)…max_fact, whose terms past k's own
+    // segment are the products of the later segments.
+    let mut after = vec![mm.pow(fact[max_fact], p - 2); products.len()];
+    for s in (0..products.len() - 1).rev() {
+        after[s] = mm.mul(after[s + 1], products[s + 1]);
+    }
+    let mut inv_fact = vec![1; max_fact + 1];
+    inv_fact.par_chunks_mut(FACT_SEGMENT).zip(&after).enumerate().for_each(|(s, (chunk, &a))| {
+        let mut acc = a;
+        for j in (0..chunk.len()).rev() {
+            chunk[j] = acc;
+            acc = mm.mul(acc, (s * FACT_SEGMENT + j) as u64);
+        }
+    });
+    (fact, inv_fact)
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
@@ -351,12 +3666,332 @@ mod tests {
         comb.nCr(10, 3);
     }
 
+    #[test]
+    fn test_lagrange_consecutive() {
+        let p = 1000000007;
+        let comb = Comb::new(p, 10);
+        // Σ_{i=1}^{x} i² sampled at x = 0..=3.
+        let ys = [0, 1, 5, 14];
+        assert_eq!(comb.lagrange_consecutive(&ys, 0, 2), 5);
+        assert_eq!(comb.lagrange_consecutive(&ys, 0, 100), 338350);
+        let x = 1000000000000u128;
+        let expected = (x * (x + 1) * (2 * x + 1) / 6 % p as u128) as u64;
+        assert_eq!(comb.lagrange_consecutive(&ys, 0, x as u64), expected);
+        // The same samples shifted to start at x0 = 5.
+        assert_eq!(comb.lagrange_consecutive(&ys, 5, 105), 338350);
+        assert_eq!(comb.lagrange_consecutive(&ys, p + 5, 105), 338350);
+        // Below x0 the polynomial is extended: f(-1) = 0 for the sum of squares.
+        assert_eq!(comb.lagrange_consecutive(&ys, 1, 0), 0);
+        assert_eq!(comb.lagrange_consecutive(&[7], 3, 99), 7);
+        assert_eq!(comb.lagrange_consecutive(&[], 0, 5), 0);
+        let small = Comb::new(13, 5);
+        assert_eq!(small.lagrange_consecutive(&[1, 3, 5], 0, 20), 41 % 13);
+    }
+
+    #[test]
+    fn test_power_sum() {
+        let p = 1000000007;
+        let comb = Comb::new(p, 100);
+        assert_eq!(comb.power_sum(0, 3), 0);
+        assert_eq!(comb.power_sum(10, 0), 10);
+        assert_eq!(comb.power_sum(10, 1), 55);
+        assert_eq!(comb.power_sum(10, 3), 3025);
+        for k in [2, 5, 17, 60] {
+            let direct = (1..=500).fold(0, |acc, i| (acc + Modexp::mod_exp(i, k, p)) % p);
+            assert_eq!(comb.power_sum(500, k), direct, "k = {}", k);
+        }
+        // Σ i³ = (n(n+1)/2)² at n = 10^18.
+        let n = 1000000000000000000u128;
+        let half = (n * (n + 1) / 2 % p as u128) as u64;
+        assert_eq!(comb.power_sum(n as u64, 3), half * half % p);
+        assert_eq!(Comb::new(13, 5).power_sum(100, 2), (100 * 101 * 201 / 6) % 13);
+    }
+
+    #[test]
+    #[should_panic(expected = "k + 1 cannot be greater than 100!")]
+    fn test_power_sum_max_exponent() {
+        Comb::new(1000000007, 100).power_sum(10, u64::MAX);
+    }
+
+    #[test]
+    fn test_egf_convolution() {
+        let p = 1000000007;
+        let comb = Comb::new(p, 300);
+        assert_eq!(comb.egf_convolution(&[], &[1, 2]), vec![]);
+        assert_eq!(comb.egf_convolution(&[3], &[4]), vec![12]);
+        // e^x · e^x = e^(2x), so all ones times all ones gives powers of two.
+        let ones = vec![1; 20];
+        let product = comb.egf_convolution(&ones, &ones);
+        assert_eq!(product[..20], (0..20).map(|n| 1 << n).collect::<Vec<u64>>()[..]);
+        // Splitting a labeled set into a permutation and a set: Σ_k C(n, k)·k! = ⌊e·n!⌋.
+        let fact: Vec<u64> = (0..10).map(|i| comb.nPr(i, 0)).collect();
+        let product = comb.egf_convolution(&fact, &[1; 10]);
+        assert_eq!(product[..6], [1, 2, 5, 16, 65, 326]);
+        let a: Vec<u64> = (0..150).map(|i| i * i * 7 + p - 3).collect();
+        let b: Vec<u64> = (0..151).map(|i| i * 1009 + 11).collect();
+        let product = comb.egf_convolution(&a, &b);
+        assert_eq!(product.len(), 300);
+        for n in [0, 1, 77, 149, 150, 299] {
+            let direct = (0..=n)
+                .filter(|&k| k < a.len() && n - k < b.len())
+                .fold(0, |acc, k| (acc + comb.nCr(n as u64, k as u64) * (a[k] % p) % p * b[n - k]) % p);
+            assert_eq!(product[n], direct, "n = {}", n);
+        }
+        assert_eq!(Comb::new(5, 4).egf_convolution(&[1, 1, 1], &[1, 1, 1]), vec![1, 2, 4, 1, 1]);
+    }
+
+    #[test]
+    fn test_tree_counts() {
+        let p = 1000000007;
+        let comb = Comb::new(p, 20);
+        let expected = [1, 1, 1, 3, 16, 125, 1296];
+        for (n, &count) in expected.iter().enumerate() {
+            assert_eq!(comb.labeled_trees(n as u64), count);
+        }
+        assert_eq!(comb.labeled_trees(1000000000000), Modexp::mod_exp(1000000000000, 999999999998, p));
+        // One root gives the trees themselves, n roots the empty forest.
+        for n in 1..8 {
+            assert_eq!(comb.rooted_forests(n, 1), comb.labeled_trees(n));
+            assert_eq!(comb.rooted_forests(n, n), 1);
+        }
+        assert_eq!(comb.rooted_forests(0, 0), 1);
+        assert_eq!(comb.rooted_forests(5, 0), 0);
+        assert_eq!(comb.rooted_forests(4, 2), 8);
+        // Summing over degree sequences recovers Cayley's formula.
+        let n = 6;
+        let mut total = 0;
+        for code in 0..6u64.pow(n) {
+            let degrees: Vec<u64> = (0..n).map(|i| code / 6u64.pow(i) % 6).collect();
+            total = (total + comb.trees_with_degrees(&degrees)) % p;
+        }
+        assert_eq!(total, comb.labeled_trees(n as u64));
+        assert_eq!(comb.trees_with_degrees(&[]), 1);
+        assert_eq!(comb.trees_with_degrees(&[0]), 1);
+        assert_eq!(comb.trees_with_degrees(&[1]), 0);
+        assert_eq!(comb.trees_with_degrees(&[1, 1]), 1);
+        assert_eq!(comb.trees_with_degrees(&[3, 1, 1, 1]), 1);
+        assert_eq!(comb.trees_with_degrees(&[2, 2, 1, 1]), 2);
+        assert_eq!(comb.trees_with_degrees(&[2, 2, 2, 0]), 0);
+        assert_eq!(comb.trees_with_degrees(&[u64::MAX, 1, 1]), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "k cannot be greater than n!")]
+    fn test_rooted_forests_too_many_roots() {
+        Comb::new(13, 3).rooted_forests(2, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be greater than 3!")]
+    fn test_egf_convolution_too_long() {
+        Comb::new(13, 3).egf_convolution(&[1, 2, 3], &[4, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be greater than 2!")]
+    fn test_lagrange_consecutive_too_many_samples() {
+        Comb::new(13, 2).lagrange_consecutive(&[1, 2, 3, 4], 0, 7);
+    }
+
     #[test]
     #[should_panic(expected = "modulus is not prime!")]
+    #[allow(unused_variables)]
     fn test_composite_mod() {
         let comb: Comb = Comb::new(4, 14);
     }
 
+    #[test]
+    fn test_comb_widths() {
+        let narrow = Comb::with_modulus(998244353u32, 1000);
+        let wide = Comb::new(998244353, 1000);
+        for n in 0..=1000 {
+            for r in (0..=n).step_by(7) {
+                assert_eq!(narrow.nCr(n, r) as u64, wide.nCr(n, r));
+                assert_eq!(narrow.nPr(n, r) as u64, wide.nPr(n, r));
+            }
+        }
+        // Binomials modulo the Mersenne prime 2^127 − 1, where C(100, 50) is still exact.
+        let comb = Comb::with_modulus((1u128 << 127) - 1, 100);
+        assert_eq!(comb.nCr(100, 50), 100891344545564193334812497256);
+        assert_eq!(comb.try_nCr(101, 50).err(), Some(MathCombError::AboveLimit { value: 101, limit: 100 }));
+        assert_eq!(Comb::with_modulus(7u32, 6).nCr(6, 3), 6);
+        let comb: Comb32 = Comb32::with_modulus(998244353, 200000);
+        assert_eq!(comb.nCr(200000, 3) as u64, Comb::new(998244353, 200000).nCr(200000, 3));
+    }
+
+    #[test]
+    fn test_comb_large_modulus() {
+        // Products of two residues overflow a u64 from 2^32 on.
+        let p = 1000000000000000003;
+        let exact = 100891344545564193334812497256u128;
+        let comb = Comb::new(p, 100);
+        assert_eq!(comb.nCr(100, 50) as u128, exact % p as u128);
+        assert_eq!(Comb::with_modulus(p, 100).nCr(100, 50), comb.nCr(100, 50));
+        let grown = Comb::builder().modulus(p).max_fact(10).auto_grow().build();
+        assert_eq!(grown.nCr(150, 75) as u128, Comb::with_modulus(p as u128, 150).nCr(150, 75));
+        let x = 1000000u128;
+        let ys = [0, 1, 5, 14];
+        assert_eq!(comb.lagrange_consecutive(&ys, 0, x as u64) as u128, x * (x + 1) * (2 * x + 1) / 6);
+        assert_eq!(comb.power_sum(x as u64, 3) as u128, (x * (x + 1) / 2).pow(2) % p as u128);
+        let ones = vec![1; 20];
+        assert_eq!(comb.egf_convolution(&ones, &ones)[..20], (0..20).map(|n| 1 << n).collect::<Vec<u64>>()[..]);
+        assert_eq!(comb.labeled_trees(10), 100000000);
+        assert_eq!(comb.trees_with_degrees(&[3, 1, 1, 1]), 1);
+        // The largest prime below 2^64, whose primality check must not overflow or take
+        // trial division.
+        let p = 18446744073709551557;
+        let comb = Comb::new(p, 10);
+        assert_eq!(comb.nCr(10, 5), 252);
+        assert_eq!(Comb::try_new(p, 10).map(|c| c.nPr(10, 5)), Ok(comb.nPr(10, 5)));
+        assert_eq!(Comb::try_new(p - 2, 10).err(), Some(MathCombError::ModulusNotPrime(p - 2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "max_fact must be less than the modulus!")]
+    fn test_comb_with_modulus_too_many_factorials() {
+        Comb::with_modulus(7u32, 7);
+    }
+
+    #[test]
+    fn test_int() {
+        assert_eq!(int::gcd(12u32, 18), 6);
+        assert_eq!(int::gcd(1u128 << 100, 3 << 90), 1 << 90);
+        assert_eq!(int::mod_exp(2u32, 31, u32::MAX), 1 << 31);
+        assert_eq!(int::mod_exp(2u64, 10, 1000), 24);
+        assert!(int::is_prime(4294967291u32));
+        assert!(!int::is_prime(4294967295u32));
+        assert!(int::is_prime((1u128 << 127) - 1));
+        assert_eq!(int::factor(360u32), vec![2, 2, 2, 3, 3, 5]);
+        assert_eq!(int::factor(600851475143u64), vec![71, 839, 1471, 6857]);
+        assert_eq!(int::factor((1u128 << 64) + 1), vec![274177, 67280421310721]);
+    }
+
+    #[test]
+    fn test_const_fns() {
+        const MOD: u64 = 998244353;
+        const _: () = assert!(prime::is_prime(MOD) && !prime::is_prime(MOD - 2));
+        const INV2: u64 = modular::mod_exp(2, MOD - 2, MOD);
+        const GCDS: (u64, u64) = (arithmetic::gcd(84, 36), arithmetic::binary_gcd(84, 36));
+        // A small table built entirely at compile time.
+        const POW3: [u64; 5] = {
+            let mut table = [0; 5];
+            let mut i = 0;
+            while i < 5 {
+                table[i] = modular::mod_exp(3, i as u64, MOD);
+                i += 1;
+            }
+            table
+        };
+        assert_eq!(INV2 * 2 % MOD, 1);
+        assert_eq!(GCDS, (12, 12));
+        assert_eq!(POW3, [1, 3, 9, 27, 81]);
+        assert!(Prime::miller_rabin_witness(2047, 2) && !Prime::miller_rabin_witness(2047, 3));
+    }
+
+    #[test]
+    fn test_comb_try() {
+        assert_eq!(Comb::try_new(0, 5).err(), Some(MathCombError::ZeroModulus));
+        assert_eq!(Comb::try_new(1, 0).err(), Some(MathCombError::ModulusNotPrime(1)));
+        assert_eq!(Comb::try_new(4, 14).err(), Some(MathCombError::ModulusNotPrime(4)));
+        assert_eq!(Comb::try_new(13, 13).err(), Some(MathCombError::AboveLimit { value: 13, limit: 12 }));
+        let comb = Comb::try_new(13, 5).unwrap();
+        assert_eq!(comb.try_nCr(5, 2), Ok(10));
+        assert_eq!(comb.try_nPr(5, 2), Ok(comb.nPr(5, 2)));
+        assert_eq!(comb.try_nCr(2, 5), Err(MathCombError::InvalidArgument("n cannot be less than r")));
+        assert_eq!(comb.try_nPr(10, 3), Err(MathCombError::AboveLimit { value: 10, limit: 5 }));
+    }
+
+    #[test]
+    fn test_comb_builder() {
+        let p = 1000000007;
+        let comb = Comb::builder().modulus(p).max_fact(10).build();
+        assert_eq!(comb.nCr(5, 2), 10);
+        assert_eq!(comb.try_nCr(11, 2), Err(MathCombError::AboveLimit { value: 11, limit: 10 }));
+        // Several segments, filled in parallel with the `rayon` feature.
+        let reference = Comb::new(p, 300000);
+        let parallel = Comb::builder().modulus(p).max_fact(300000).parallel_build().build();
+        assert!(parallel.fact == reference.fact && parallel.inv_fact == reference.inv_fact);
+        let tiny = Comb::builder().modulus(p).parallel_build().build();
+        assert_eq!((tiny.fact, tiny.inv_fact), (vec![1], vec![1]));
+        // Growing past the tables up to the modulus.
+        let grown = Comb::builder().modulus(p).max_fact(10).auto_grow().build();
+        assert_eq!(grown.nCr(1000, 500), Comb::new(p, 1000).nCr(1000, 500));
+        assert_eq!(grown.nPr(300000, 20), reference.nPr(300000, 20));
+        assert_eq!(grown.nCr(5, 2), 10);
+        assert_eq!(grown.nPr(12, 0), reference.nPr(12, 0));
+        assert_eq!(grown.nPr(300000, 299990), reference.nPr(300000, 299990));
+        assert_eq!(grown.nCr(300000, 299990), reference.nCr(300000, 10));
+        // Small r far past the tables multiplies only r terms.
+        let n = 999999999u64;
+        let falling = n as u128 * (n - 1) as u128 % p as u128 * (n - 2) as u128 % p as u128;
+        assert_eq!(grown.nCr(n, 3), (falling * modular::mod_inv(6, p) as u128 % p as u128) as u64);
+        assert_eq!(grown.nCr(n, n - 2), (n as u128 * (n - 1) as u128 / 2 % p as u128) as u64);
+        let small = Comb::builder().modulus(13).auto_grow().build();
+        assert_eq!(small.nCr(12, 4), 495 % 13);
+        assert_eq!(small.try_nCr(13, 1), Err(MathCombError::AboveLimit { value: 13, limit: 12 }));
+        // A 61-bit prime, whose products overflow the multiplications of `new`.
+        let big = Comb::builder().modulus((1 << 61) - 1).max_fact(100).build();
+        assert_eq!(big.nCr(100, 50), (100891344545564193334812497256u128 % ((1 << 61) - 1)) as u64);
+        assert!(Comb::builder().modulus(15).max_fact(4).skip_prime_check().try_build().is_ok());
+        let unset = Comb::builder().max_fact(4).try_build().err();
+        assert_eq!(unset, Some(MathCombError::InvalidArgument("modulus is not set")));
+        assert_eq!(Comb::builder().modulus(0).try_build().err(), Some(MathCombError::ZeroModulus));
+        let one = Comb::builder().modulus(1).skip_prime_check().try_build().err();
+        assert_eq!(one, Some(MathCombError::ModulusNotPrime(1)));
+        assert_eq!(Comb::builder().modulus(15).try_build().err(), Some(MathCombError::ModulusNotPrime(15)));
+        assert_eq!(
+            Comb::builder().modulus(13).max_fact(13).try_build().err(),
+            Some(MathCombError::AboveLimit { value: 13, limit: 12 })
+        );
+    }
+
+    #[test]
+    fn test_comb_shared() {
+        // A modulus of its own, since the registry is shared with the other tests.
+        let p = 1000000009;
+        let first = Comb::shared(p, 1000);
+        assert!(Arc::ptr_eq(&first, &Comb::shared(p, 500)));
+        let grown = Comb::shared(p, 1500);
+        assert!(!Arc::ptr_eq(&first, &grown));
+        assert_eq!(grown.max_fact, 2000);
+        assert_eq!(first.nCr(1000, 3), grown.nCr(1000, 3));
+        assert!(Arc::ptr_eq(&grown, &Comb::shared(p, 2000)));
+        assert_eq!(Comb::shared(13, 5).max_fact, 5);
+        assert_eq!(Comb::shared(13, 8).max_fact, 10);
+        assert_eq!(Comb::shared(13, 11).max_fact, 12);
+        let threads: Vec<_> = (0..8)
+            .map(|i| std::thread::spawn(move || Comb::shared(998244853, 100 + 100 * i).nCr(100, 50)))
+            .collect();
+        for t in threads {
+            assert_eq!(t.join().unwrap(), Comb::new(998244853, 100).nCr(100, 50));
+        }
+        assert!(std::panic::catch_unwind(|| Comb::shared(1000000011, 10)).is_err());
+        assert_eq!(Comb::shared(p, 10).max_fact, 2000);
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus is not set!")]
+    fn test_comb_builder_without_modulus() {
+        CombBuilder::new().max_fact(5).build();
+    }
+
+    #[test]
+    fn test_prime_and_modexp_try() {
+        assert_eq!(Prime::try_factor(0), Err(MathCombError::InvalidArgument("n cannot be zero")));
+        assert_eq!(Prime::try_factor(60), Ok(vec![2, 2, 3, 5]));
+        assert!(Prime::try_factor_map(0).is_err());
+        assert_eq!(Prime::try_factor_map(60), Ok(vec![(2, 2), (3, 1), (5, 1)]));
+        assert_eq!(Modexp::try_mod_exp(2, 10, 0), Err(MathCombError::ZeroModulus));
+        assert_eq!(Modexp::try_mod_exp(2, 10, 1000), Ok(24));
+        assert_eq!(Modexp::try_mod_exp(2, 0, 1), Ok(0));
+        assert_eq!(Modexp::try_mod_inv(3, 11), Ok(4));
+        assert_eq!(Modexp::try_mod_inv(14, 11), Ok(4));
+        assert_eq!(Modexp::try_mod_inv(3, 0), Err(MathCombError::ZeroModulus));
+        assert_eq!(Modexp::try_mod_inv(0, 7), Err(MathCombError::NotInvertible { x: 0, modulus: 7 }));
+        assert_eq!(Modexp::try_mod_inv(22, 11), Err(MathCombError::NotInvertible { x: 22, modulus: 11 }));
+        assert_eq!(Modexp::try_mod_inv(5, 1), Ok(0));
+    }
+
     #[test]
     pub fn test_spf() {
         let spf: Spf = Spf::new(10000000);
@@ -367,15 +4002,245 @@ mod tests {
         assert_eq!(spf.get_spf(81), 3);
     }
 
+    #[test]
+    fn test_spf_with_progress() {
+        use std::sync::Mutex;
+
+        let n = 1000000;
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        let spf = Spf::with_progress(n, &hook).unwrap();
+        assert_eq!(spf.factorize(999999), Spf::new(n).factorize(999999));
+        let reports = reports.into_inner().unwrap();
+        assert!(reports.iter().all(|&(done, total)| done <= total && total == n as u64));
+        assert_eq!(reports.iter().max(), Some(&(n as u64, n as u64)));
+
+        let token = CancelToken::new();
+        token.cancel();
+        assert!(matches!(Spf::with_progress(n, &token), Err(MathCombError::Cancelled)));
+    }
+
+    #[test]
+    fn test_spf_linear_sieve() {
+        let spf = Spf::new(100000);
+        assert!(spf.primes().eq(sieve::primes_up_to(100000)));
+        for x in 2..=100000u64 {
+            assert_eq!(spf.get_spf(x), Prime::smallest_prime_factor(x).unwrap(), "x = {}", x);
+        }
+        assert_eq!(Spf::new(1).primes().next(), None);
+        assert_eq!(Spf::new(2).primes().collect::<Vec<u64>>(), vec![2]);
+        assert_eq!(spf.primes().take_while(|&p| p < 30).count(), 10);
+        assert_eq!(spf.primes().last(), Some(99991));
+        let small = Spf::new(9);
+        let expected = [0, 0, 2, 3, 2, 5, 2, 7, 2, 3];
+        for (x, &p) in expected.iter().enumerate() {
+            assert_eq!(small.get_spf(x as u64), p);
+        }
+    }
+
+    #[test]
+    fn test_spf_extend_to() {
+        let full = Spf::new(200000);
+        for start in [0, 1, 2, 3, 10, 1000, 200000] {
+            let mut spf = Spf::new(start);
+            spf.extend_to(150001);
+            spf.extend_to(100);
+            spf.extend_to(200000);
+            assert!(spf.primes().eq(full.primes()), "start = {}", start);
+            for x in 0..=200000u64 {
+                assert_eq!(spf.get_spf(x), full.get_spf(x));
+            }
+            for x in 1..=200000u64 {
+                assert_eq!(spf.factorize_map(x), full.factorize_map(x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_spf_radical_and_squarefree() {
+        let spf: Spf = Spf::new(100000);
+        assert_eq!(spf.radical(1), 1);
+        assert_eq!(spf.radical(72), 6);
+        assert_eq!(spf.radical(99991), 99991);
+        assert!(spf.is_squarefree(1));
+        assert!(spf.is_squarefree(30));
+        assert!(!spf.is_squarefree(12));
+        assert!(!spf.is_squarefree(99991 - 91));
+        for x in 1..=100000u64 {
+            assert_eq!(spf.is_squarefree(x), Prime::is_squarefree(x));
+            assert_eq!(spf.is_squarefree(x), spf.radical(x) == x);
+        }
+    }
+
+    #[test]
+    fn test_spf_try_accessors() {
+        let spf: Spf = Spf::new(100);
+        assert_eq!(spf.try_get_spf(0), None);
+        assert_eq!(spf.try_get_spf(1), None);
+        assert_eq!(spf.try_get_spf(91), Some(7));
+        assert_eq!(spf.try_get_spf(100), Some(2));
+        assert_eq!(spf.try_get_spf(101), None);
+        assert_eq!(spf.try_factorize(0), None);
+        assert_eq!(spf.try_factorize(1), Some(vec![]));
+        assert_eq!(spf.try_factorize(60), Some(vec![2, 2, 3, 5]));
+        assert_eq!(spf.try_factorize(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_spf_try_results() {
+        let spf: Spf = Spf::new(100);
+        assert_eq!(spf.try_factorize_map(60), Ok(vec![(2, 2), (3, 1), (5, 1)]));
+        assert_eq!(spf.try_factorize_map(0), Err(MathCombError::InvalidArgument("x cannot be zero")));
+        assert_eq!(spf.try_factorize_map(101), Err(MathCombError::AboveLimit { value: 101, limit: 100 }));
+        // Above 2^32, where a 32-bit `usize` would wrap around.
+        let huge = (1 << 32) + 5;
+        assert_eq!(spf.try_factorize_map(huge), Err(MathCombError::AboveLimit { value: huge, limit: 100 }));
+        assert_eq!(spf.try_get_spf(huge), None);
+        assert_eq!(spf.try_divisors(12), Ok(vec![1, 2, 3, 4, 6, 12]));
+        assert_eq!(spf.try_divisors(1), Ok(vec![1]));
+        assert!(spf.try_divisors(0).is_err());
+        assert!(spf.try_divisors(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_spf_save_load() {
+        let path = std::env::temp_dir().join(format!("math-comb-spf-{}.bin", std::process::id()));
+        let spf = Spf::new(100001);
+        // Every prime fits in the u32 column.
+        assert_eq!((spf.primes.len(), spf.large_primes.len()), (9592, 0));
+        spf.save(&path).unwrap();
+        let mut loaded = Spf::load(&path).unwrap();
+        assert!(loaded.primes().eq(spf.primes()));
+        for x in 1..=100001u64 {
+            assert_eq!(loaded.factorize_map(x), spf.factorize_map(x));
+        }
+        #[cfg(feature = "mmap")]
+        {
+            let mut mapped = Spf::load_mmap(&path).unwrap();
+            for x in 1..=100001u64 {
+                assert_eq!(mapped.factorize_map(x), spf.factorize_map(x));
+            }
+            mapped.extend_to(200000);
+            assert_eq!(mapped.factorize(199999), Prime::factor(199999));
+        }
+        loaded.extend_to(200000);
+        assert_eq!(loaded.primes().count(), 17984);
+
+        // Truncated files and bogus entries are rejected instead of trusted.
+        let bytes = std::fs::read(&path).unwrap();
+        let invalid_data = |e: io::Error| e.kind() == io::ErrorKind::InvalidData;
+        for len in [4, 12, 20, bytes.len() / 2, bytes.len() - 1] {
+            std::fs::write(&path, &bytes[..len]).unwrap();
+            assert!(Spf::load(&path).is_err_and(invalid_data), "len = {}", len);
+        }
+        // The limit in the header, the exponent of 3 in 9 (made 3, so 27 would be divided
+        // out) and the spf of 15 (made 1, which would never shrink it). The exponents follow
+        // the magic, the limit and the length and padded values of the spf section.
+        let exponents = 16 + (8 + 200008) + 8;
+        assert_eq!(bytes[exponents + 4], 2);
+        for (offset, byte) in [(8, 0xff), (exponents + 4, 3), (24 + 4 * 7, 1)] {
+            let mut corrupt = bytes.clone();
+            corrupt[offset] = byte;
+            std::fs::write(&path, &corrupt).unwrap();
+            assert!(Spf::load(&path).is_err_and(invalid_data), "offset = {}", offset);
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert!(Spf::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_spf_is_prime() {
+        let spf: Spf = Spf::new(100000);
+        assert!(!spf.is_prime(0));
+        assert!(!spf.is_prime(1));
+        assert!(spf.is_prime(2));
+        assert!(!spf.is_prime(4));
+        assert!(spf.is_prime(99991));
+        for x in 0..=100000u64 {
+            assert_eq!(spf.is_prime(x), Prime::is_prime(x));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be zero!")]
+    fn test_spf_factorize_zero() {
+        Spf::new(15).factorize(0);
+    }
+
     #[test]
     fn test_get_factors_via_spf() {
         let spf: Spf = Spf::new(10000000);
         assert_eq!(spf.factorize(1000429), vec![1000429]);
+        assert_eq!(spf.factorize(1), vec![]);
         assert_eq!(spf.factorize(24), vec![2, 2, 2, 3]);
         assert_eq!(spf.factorize(45), vec![3, 3, 5]);
         assert_eq!(spf.factorize(346789), vec![239, 1451]);
     }
 
+    #[test]
+    fn test_spf_factorize_into() {
+        let spf: Spf = Spf::new(1 << 20);
+        let mut buf = [0; prime::MAX_FACTORS];
+        for x in [1, 2, 24, 45, 65536, 346789, 1000000] {
+            let count = spf.factorize_into(x, &mut buf);
+            assert_eq!(buf[..count], spf.factorize(x)[..]);
+            assert_eq!(*spf.factorize_inline(x), buf[..count]);
+        }
+        let mut exact = [0; 4];
+        assert_eq!(spf.factorize_into(24, &mut exact), 4);
+        assert_eq!(exact, [2, 2, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "buf is too short for the factors of x!")]
+    fn test_spf_factorize_into_short_buffer() {
+        Spf::new(100).factorize_into(64, &mut [0; 5]);
+    }
+
+    #[test]
+    fn test_spf_factorize_map() {
+        let spf: Spf = Spf::new(100000);
+        assert_eq!(spf.factorize_map(1), vec![]);
+        assert_eq!(spf.factorize_map(2), vec![(2, 1)]);
+        assert_eq!(spf.factorize_map(24), vec![(2, 3), (3, 1)]);
+        assert_eq!(spf.factorize_map(65536), vec![(2, 16)]);
+        for x in 1..=100000u64 {
+            assert_eq!(spf.factorize_map(x), Prime::factor_map(x));
+        }
+    }
+
+    #[test]
+    fn test_spf_divisors() {
+        let spf: Spf = Spf::new(100000);
+        assert_eq!(spf.divisors(1), vec![1]);
+        assert_eq!(spf.divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(spf.divisors(97), vec![1, 97]);
+        assert_eq!(spf.divisors(83160).len(), 128);
+        for x in 1..=2000u64 {
+            let expected: Vec<u64> = (1..=x).filter(|d| x % d == 0).collect();
+            assert_eq!(spf.divisors(x), expected);
+        }
+    }
+
+    #[test]
+    fn test_spf_phi() {
+        let spf: Spf = Spf::new(100000);
+        assert_eq!(spf.phi(1), 1);
+        assert_eq!(spf.phi(2), 1);
+        assert_eq!(spf.phi(36), 12);
+        assert_eq!(spf.phi(99991), 99990);
+        for x in 1..=2000u64 {
+            let expected = (1..=x).filter(|&k| arithmetic::gcd(k, x) == 1).count() as u64;
+            assert_eq!(spf.phi(x), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be zero!")]
+    fn test_spf_factorize_map_zero() {
+        Spf::new(15).factorize_map(0);
+    }
+
     #[test]
     #[should_panic(expected = "x cannot be greater than max_limit!")]
     fn test_spf_factorize_above_limit() {
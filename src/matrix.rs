@@ -0,0 +1,833 @@
+use std::ops::Mul;
+
+use crate::poly::Poly;
+use crate::{modexp, pollard};
+
+/// A dense matrix with entries modulo `p`, stored in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatMod {
+    rows: usize,
+    cols: usize,
+    data: Vec<u64>,
+    p: u64,
+}
+
+impl MatMod {
+    /// Creates a `rows × cols` matrix from its entries in row-major order.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The number of rows.
+    /// * `cols` - The number of columns.
+    /// * `data` - The `rows·cols` entries, row by row. They are reduced modulo `p`.
+    /// * `p` - The modulus, below 2^32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` does not hold `rows·cols` entries, or if `p` is zero or not below
+    /// 2^32.
+    pub fn new(rows: usize, cols: usize, data: Vec<u64>, p: u64) -> MatMod {
+        if p == 0 {
+            panic!("Modulus cannot be zero.");
+        }
+        if p >= 1 << 32 {
+            panic!("Modulus must be below 2^32!");
+        }
+        if data.len() != rows * cols {
+            panic!("data must have rows * cols entries!");
+        }
+        MatMod { rows, cols, data: data.into_iter().map(|x| x % p).collect(), p }
+    }
+
+    /// Creates the `rows × cols` zero matrix.
+    pub fn zeros(rows: usize, cols: usize, p: u64) -> MatMod {
+        MatMod::new(rows, cols, vec![0; rows * cols], p)
+    }
+
+    /// Creates the `n × n` identity matrix.
+    pub fn identity(n: usize, p: u64) -> MatMod {
+        let mut m = MatMod::zeros(n, n, p);
+        for i in 0..n {
+            m.data[i * n + i] = 1 % p;
+        }
+        m
+    }
+
+    /// Builds the Laplacian `D − A` of an undirected multigraph on the vertices `0..n`, where
+    /// `D` holds the degrees and `A` the edge multiplicities. Self-loops are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edge has an endpoint not below `n`.
+    pub fn laplacian(n: usize, edges: &[(usize, usize)], p: u64) -> MatMod {
+        let mut m = MatMod::zeros(n, n, p);
+        for &(u, v) in edges {
+            m.check_bounds(u, v);
+            if u == v {
+                continue;
+            }
+            for (i, j, add) in [(u, u, true), (v, v, true), (u, v, false), (v, u, false)] {
+                let cell = &mut m.data[i * n + j];
+                *cell = if add { (*cell + 1) % p } else { (*cell + p - 1) % p };
+            }
+        }
+        m
+    }
+
+    /// Counts the spanning trees of an undirected multigraph on the vertices `0..n` modulo the
+    /// prime `p` with Kirchhoff's matrix-tree theorem: the count is any cofactor of the
+    /// Laplacian, here the determinant with the last row and column removed.
+    ///
+    /// # Returns
+    ///
+    /// The number of spanning trees modulo `p`, which is 0 for a disconnected graph and for
+    /// `n = 0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edge has an endpoint not below `n` or the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³ + m) for m edges.
+    pub fn spanning_tree_count(n: usize, edges: &[(usize, usize)], p: u64) -> u64 {
+        let laplacian = MatMod::laplacian(n, edges, p);
+        if n == 0 {
+            laplacian.check_prime();
+            return 0;
+        }
+        let data = (0..n - 1).flat_map(|i| laplacian.data[i * n..i * n + n - 1].to_vec()).collect();
+        MatMod { rows: n - 1, cols: n - 1, data, p }.determinant()
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the modulus of the entries.
+    pub fn modulus(&self) -> u64 {
+        self.p
+    }
+
+    /// Returns the entries in row-major order.
+    pub fn data(&self) -> &[u64] {
+        &self.data
+    }
+
+    /// Returns the entry in row `i` and column `j`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn get(&self, i: usize, j: usize) -> u64 {
+        self.check_bounds(i, j);
+        self.data[i * self.cols + j]
+    }
+
+    /// Sets the entry in row `i` and column `j` to `value` modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn set(&mut self, i: usize, j: usize, value: u64) {
+        self.check_bounds(i, j);
+        self.data[i * self.cols + j] = value % self.p;
+    }
+
+    /// Raises a square matrix to the `k`-th power by binary exponentiation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³ log k).
+    pub fn pow(&self, mut k: u64) -> MatMod {
+        self.check_square();
+        let mut result = MatMod::identity(self.rows, self.p);
+        let mut base = self.clone();
+        while k > 0 {
+            if k & 1 == 1 {
+                result = &result * &base;
+            }
+            k >>= 1;
+            if k > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Computes `I + A + A² + … + A^k` by doubling on the number of terms, using
+    /// `S(2m) = S(m) + A^m·S(m)` and `S(m + 1) = I + A·S(m)`, so no inverse is needed and any
+    /// modulus works.
+    ///
+    /// For an adjacency matrix, entry `(i, j)` counts the walks from `i` to `j` of length at
+    /// most `k`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³ log k).
+    pub fn geometric_sum(&self, k: u64) -> MatMod {
+        self.check_square();
+        let n = self.rows;
+        let identity = MatMod::identity(n, self.p);
+        // (sum, power) = (S(m), A^m) for the prefix m of the bits of k + 1 read so far.
+        let mut sum = MatMod::zeros(n, n, self.p);
+        let mut power = identity.clone();
+        let terms = k as u128 + 1;
+        for bit in (0..128 - terms.leading_zeros()).rev() {
+            sum = sum.add(&(&power * &sum));
+            power = &power * &power;
+            if terms >> bit & 1 == 1 {
+                sum = identity.add(&(self * &sum));
+                power = &power * self;
+            }
+        }
+        sum
+    }
+
+    /// Computes the rank of the matrix modulo the prime `p` by Gaussian elimination.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(rows · cols · min(rows, cols)).
+    pub fn rank(&self) -> usize {
+        self.check_prime();
+        self.clone().row_reduce(self.cols).0.len()
+    }
+
+    /// Computes the determinant modulo the prime `p` by Gaussian elimination.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³).
+    pub fn determinant(&self) -> u64 {
+        self.check_square();
+        self.check_prime();
+        let (pivots, det) = self.clone().row_reduce(self.cols);
+        if pivots.len() < self.rows { 0 } else { det }
+    }
+
+    /// Computes the inverse modulo the prime `p` by Gauss–Jordan elimination on `[A | I]`.
+    ///
+    /// # Returns
+    ///
+    /// The inverse, or `None` if the matrix is singular modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³).
+    pub fn inverse(&self) -> Option<MatMod> {
+        self.check_square();
+        self.check_prime();
+        let n = self.rows;
+        let mut aug = MatMod::zeros(n, 2 * n, self.p);
+        for i in 0..n {
+            aug.data[i * 2 * n..i * 2 * n + n].copy_from_slice(&self.data[i * n..(i + 1) * n]);
+            aug.data[i * 2 * n + n + i] = 1;
+        }
+        if aug.row_reduce(n).0.len() < n {
+            return None;
+        }
+        let data = (0..n).flat_map(|i| aug.data[i * 2 * n + n..(i + 1) * 2 * n].to_vec()).collect();
+        Some(MatMod { rows: n, cols: n, data, p: self.p })
+    }
+
+    /// Solves the linear system `A·x = b` modulo the prime `p`.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The right-hand side, with one entry per row.
+    ///
+    /// # Returns
+    ///
+    /// A solution `x` with the free variables set to zero, or `None` if the system is
+    /// inconsistent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `b.len()` differs from the number of rows or the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(rows · cols · min(rows, cols)).
+    pub fn solve(&self, b: &[u64]) -> Option<Vec<u64>> {
+        if b.len() != self.rows {
+            panic!("b must have one entry per row!");
+        }
+        self.check_prime();
+        let (rows, cols) = (self.rows, self.cols);
+        let mut aug = MatMod::zeros(rows, cols + 1, self.p);
+        for (i, &rhs) in b.iter().enumerate() {
+            aug.data[i * (cols + 1)..i * (cols + 1) + cols].copy_from_slice(&self.data[i * cols..(i + 1) * cols]);
+            aug.data[i * (cols + 1) + cols] = rhs % self.p;
+        }
+        let pivots = aug.row_reduce(cols).0;
+        // A non-zero right-hand side below the pivot rows means 0 = b'.
+        if (pivots.len()..rows).any(|i| aug.data[i * (cols + 1) + cols] != 0) {
+            return None;
+        }
+        let mut x = vec![0; cols];
+        for (i, &c) in pivots.iter().enumerate() {
+            x[c] = aug.data[i * (cols + 1) + cols];
+        }
+        Some(x)
+    }
+
+    /// Computes the permanent modulo `p` with Ryser's formula
+    /// `perm A = (−1)^n Σ_S (−1)^|S| Π_i Σ_{j∈S} a_ij`, visiting the column subsets `S` in
+    /// Gray code order so that each step updates the row sums by one column.
+    ///
+    /// For a 0/1 matrix this counts the perfect matchings of the bipartite graph it describes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or has more than 32 rows.
+    ///
+    /// # Complexity
+    ///
+    /// O(2^n · n).
+    pub fn permanent(&self) -> u64 {
+        self.check_square();
+        let (n, p) = (self.rows, self.p);
+        if n > 32 {
+            panic!("Matrix is too large for the permanent!");
+        }
+        let mut sums = vec![0; n];
+        let mut total = 0;
+        let mut subset = 0u64;
+        for k in 1..1u64 << n {
+            let j = k.trailing_zeros() as usize;
+            subset ^= 1 << j;
+            let added = subset >> j & 1 == 1;
+            for (i, sum) in sums.iter_mut().enumerate() {
+                let a = self.data[i * n + j];
+                *sum = if added { (*sum + a) % p } else { (*sum + p - a) % p };
+            }
+            let product = sums.iter().fold(1 % p, |acc, &x| acc * x % p);
+            total = if (n as u32 - subset.count_ones()).is_multiple_of(2) { (total + product) % p } else { (total + p - product) % p };
+        }
+        if n == 0 { 1 % p } else { total }
+    }
+
+    /// Computes the characteristic polynomial `det(x·I − A)` modulo the prime `p`.
+    ///
+    /// The matrix is first brought to upper Hessenberg form by similarity transforms, after
+    /// which the characteristic polynomials of the leading blocks satisfy a short recurrence
+    /// along the diagonal. By Cayley–Hamilton, the coefficients give a linear recurrence for
+    /// every entry of `A^k`, which `sequence::linear_recurrence_kth` can evaluate at huge `k`.
+    ///
+    /// # Returns
+    ///
+    /// The monic characteristic polynomial, of degree `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or the modulus is not prime.
+    ///
+    /// # Complexity
+    ///
+    /// O(n³).
+    pub fn char_poly(&self) -> Poly {
+        self.check_square();
+        self.check_prime();
+        let (n, p) = (self.rows, self.p);
+        let mut h = self.data.clone();
+        for j in 0..n.saturating_sub(2) {
+            let Some(pivot) = (j + 1..n).find(|&i| h[i * n + j] != 0) else {
+                continue;
+            };
+            if pivot != j + 1 {
+                for c in 0..n {
+                    h.swap(pivot * n + c, (j + 1) * n + c);
+                }
+                for r in 0..n {
+                    h.swap(r * n + pivot, r * n + j + 1);
+                }
+            }
+            let inv = modexp::mod_inv(h[(j + 1) * n + j], p);
+            for i in j + 2..n {
+                let u = h[i * n + j] * inv % p;
+                if u == 0 {
+                    continue;
+                }
+                // Row i -= u·row (j + 1), then column (j + 1) += u·column i.
+                for c in 0..n {
+                    h[i * n + c] = (h[i * n + c] + p - u * h[(j + 1) * n + c] % p) % p;
+                }
+                for r in 0..n {
+                    h[r * n + j + 1] = (h[r * n + j + 1] + u * h[r * n + i]) % p;
+                }
+            }
+        }
+        // polys[k] is the characteristic polynomial of the leading k × k block.
+        let mut polys: Vec<Vec<u64>> = vec![vec![1 % p]];
+        for k in 0..n {
+            let mut next = vec![0; k + 2];
+            for (i, &c) in polys[k].iter().enumerate() {
+                next[i + 1] = (next[i + 1] + c) % p;
+                next[i] = (next[i] + p - c * h[k * n + k] % p) % p;
+            }
+            // Subtract h[i][k]·Π_{m=i+1..k} h[m][m−1]·polys[i] for every i < k.
+            let mut chain = 1 % p;
+            for i in (0..k).rev() {
+                chain = chain * h[(i + 1) * n + i] % p;
+                let coef = chain * h[i * n + k] % p;
+                if coef == 0 {
+                    continue;
+                }
+                for (d, &c) in polys[i].iter().enumerate() {
+                    next[d] = (next[d] + p - coef * c % p) % p;
+                }
+            }
+            polys.push(next);
+        }
+        Poly::from_reduced(polys.pop().unwrap(), p)
+    }
+
+    // Brings the first `pivot_cols` columns into reduced row echelon form, applying the same
+    // row operations to the remaining columns. Returns the pivot column of each pivot row and
+    // the determinant of the eliminated square block when every row gets a pivot.
+    fn row_reduce(&mut self, pivot_cols: usize) -> (Vec<usize>, u64) {
+        let (p, cols) = (self.p, self.cols);
+        let mut pivots = Vec::new();
+        let mut det = 1;
+        for c in 0..pivot_cols {
+            let r = pivots.len();
+            if r == self.rows {
+                break;
+            }
+            let Some(pivot) = (r..self.rows).find(|&i| self.data[i * cols + c] != 0) else {
+                continue;
+            };
+            if pivot != r {
+                for j in 0..cols {
+                    self.data.swap(r * cols + j, pivot * cols + j);
+                }
+                det = (p - det) % p;
+            }
+            let lead = self.data[r * cols + c];
+            det = det * lead % p;
+            let inv = modexp::mod_inv(lead, p);
+            for j in c..cols {
+                self.data[r * cols + j] = self.data[r * cols + j] * inv % p;
+            }
+            for i in 0..self.rows {
+                let factor = self.data[i * cols + c];
+                if i == r || factor == 0 {
+                    continue;
+                }
+                for j in c..cols {
+                    let sub = factor * self.data[r * cols + j] % p;
+                    self.data[i * cols + j] = (self.data[i * cols + j] + p - sub) % p;
+                }
+            }
+            pivots.push(c);
+        }
+        (pivots, det)
+    }
+
+    fn check_prime(&self) {
+        if !pollard::is_prime(self.p) {
+            panic!("modulus is not prime!");
+        }
+    }
+
+    fn check_bounds(&self, i: usize, j: usize) {
+        if i >= self.rows || j >= self.cols {
+            panic!("Index out of bounds!");
+        }
+    }
+
+    fn check_square(&self) {
+        if self.rows != self.cols {
+            panic!("Matrix must be square!");
+        }
+    }
+
+    fn add(&self, other: &MatMod) -> MatMod {
+        let data = self.data.iter().zip(&other.data).map(|(&a, &b)| (a + b) % self.p).collect();
+        MatMod { rows: self.rows, cols: self.cols, data, p: self.p }
+    }
+
+    // Multiplies in i-k-j order so the inner loop walks both rows contiguously.
+    fn multiply(&self, other: &MatMod) -> MatMod {
+        if self.p != other.p {
+            panic!("Matrices must share a modulus!");
+        }
+        if self.cols != other.rows {
+            panic!("Matrix dimensions do not match!");
+        }
+        let p = self.p;
+        let mut data = vec![0; self.rows * other.cols];
+        for i in 0..self.rows {
+            let out = &mut data[i * other.cols..(i + 1) * other.cols];
+            for k in 0..self.cols {
+                let a = self.data[i * self.cols + k];
+                if a == 0 {
+                    continue;
+                }
+                let row = &other.data[k * other.cols..(k + 1) * other.cols];
+                for (o, &b) in out.iter_mut().zip(row) {
+                    *o = (*o + a * b) % p;
+                }
+            }
+        }
+        MatMod { rows: self.rows, cols: other.cols, data, p }
+    }
+}
+
+impl Mul<&MatMod> for &MatMod {
+    type Output = MatMod;
+
+    fn mul(self, other: &MatMod) -> MatMod {
+        self.multiply(other)
+    }
+}
+
+impl Mul<MatMod> for MatMod {
+    type Output = MatMod;
+
+    fn mul(self, other: MatMod) -> MatMod {
+        self.multiply(&other)
+    }
+}
+
+/// A linear basis of 64-bit vectors over GF(2), where addition is xor.
+///
+/// Basis vector `i`, if present, has its highest set bit at position `i`, so reducing a value
+/// takes one pass from the top bit down.
+#[derive(Clone, Debug)]
+pub struct XorBasis {
+    basis: [u64; 64],
+    len: usize,
+}
+
+impl XorBasis {
+    /// Creates an empty basis, spanning only 0.
+    pub fn new() -> XorBasis {
+        XorBasis { basis: [0; 64], len: 0 }
+    }
+
+    // Xors basis vectors into x to clear its set bits from the top down, as far as possible.
+    fn reduce(&self, mut x: u64) -> u64 {
+        while x != 0 {
+            let bit = 63 - x.leading_zeros() as usize;
+            if self.basis[bit] == 0 {
+                break;
+            }
+            x ^= self.basis[bit];
+        }
+        x
+    }
+
+    /// Adds `x` to the spanned space.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `x` was independent of the basis and enlarged it, `false` if it was already
+    /// representable.
+    pub fn insert(&mut self, x: u64) -> bool {
+        let x = self.reduce(x);
+        if x == 0 {
+            return false;
+        }
+        self.basis[63 - x.leading_zeros() as usize] = x;
+        self.len += 1;
+        true
+    }
+
+    /// Checks whether `x` is the xor of some subset of the inserted values.
+    pub fn contains(&self, x: u64) -> bool {
+        self.reduce(x) == 0
+    }
+
+    /// Returns the largest xor of a subset of the inserted values (0 if the basis is empty).
+    pub fn max_xor(&self) -> u64 {
+        self.basis.iter().rev().fold(0, |acc, &b| acc.max(acc ^ b))
+    }
+
+    /// Returns the dimension of the spanned space, so that it holds `2^len` values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing but 0 is spanned.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for XorBasis {
+    fn default() -> Self {
+        XorBasis::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOD: u64 = 1_000_000_007;
+
+    #[test]
+    fn test_matmod_basics() {
+        let mut m = MatMod::new(2, 3, vec![1, 2, 3, 4, 5, MOD + 6], MOD);
+        assert_eq!((m.rows(), m.cols(), m.modulus()), (2, 3, MOD));
+        assert_eq!(m.get(1, 2), 6);
+        m.set(0, 0, MOD - 1 + MOD);
+        assert_eq!(m.data(), &[MOD - 1, 2, 3, 4, 5, 6]);
+        assert_eq!(MatMod::identity(2, MOD).data(), &[1, 0, 0, 1]);
+        assert_eq!(MatMod::identity(1, 1).data(), &[0]);
+        assert_eq!(MatMod::zeros(0, 0, MOD).pow(5).rows(), 0);
+    }
+
+    #[test]
+    fn test_matmod_mul() {
+        let a = MatMod::new(2, 3, vec![1, 2, 3, 4, 5, 6], MOD);
+        let b = MatMod::new(3, 2, vec![7, 8, 9, 10, 11, 12], MOD);
+        assert_eq!((&a * &b).data(), &[58, 64, 139, 154]);
+        assert_eq!((b * a).data(), &[39, 54, 69, 49, 68, 87, 59, 82, 105]);
+        let big = MatMod::new(1, 1, vec![MOD - 1], MOD);
+        assert_eq!((&big * &big).data(), &[1]);
+    }
+
+    #[test]
+    fn test_matmod_pow() {
+        let fib = MatMod::new(2, 2, vec![1, 1, 1, 0], MOD);
+        assert_eq!(fib.pow(0), MatMod::identity(2, MOD));
+        assert_eq!(fib.pow(10).get(0, 1), 55);
+        assert_eq!(fib.pow(90).get(0, 1), 2880067194370816120 % MOD);
+        // Walks of length 3 in a triangle between distinct vertices.
+        let triangle = MatMod::new(3, 3, vec![0, 1, 1, 1, 0, 1, 1, 1, 0], 97);
+        assert_eq!(triangle.pow(3).data(), &[2, 3, 3, 3, 2, 3, 3, 3, 2]);
+    }
+
+    #[test]
+    fn test_matmod_geometric_sum() {
+        let a = MatMod::new(2, 2, vec![1, 1, 1, 0], MOD);
+        assert_eq!(a.geometric_sum(0), MatMod::identity(2, MOD));
+        assert_eq!(MatMod::zeros(0, 0, MOD).geometric_sum(9).rows(), 0);
+        for (n, p) in [(3, MOD), (4, 12), (2, 1)] {
+            let a = MatMod::new(n, n, (0..(n * n) as u64).map(|i| i * 7 + 3).collect(), p);
+            let mut expected = MatMod::zeros(n, n, p);
+            let mut power = MatMod::identity(n, p);
+            for k in 0..40 {
+                expected = expected.add(&power);
+                power = &power * &a;
+                assert_eq!(a.geometric_sum(k), expected, "k = {}, p = {}", k, p);
+            }
+        }
+        // Σ_{i≤k} 2^i = 2^(k+1) − 1, also for k = u64::MAX where k + 1 overflows.
+        let two = MatMod::new(1, 1, vec![2], MOD);
+        assert_eq!(two.geometric_sum(1 << 40).get(0, 0), (modexp::mod_exp(2, (1 << 40) + 1, MOD) + MOD - 1) % MOD);
+        let expected = (modexp::mod_exp(2, u64::MAX, MOD) * 2 + MOD - 1) % MOD;
+        assert_eq!(two.geometric_sum(u64::MAX).get(0, 0), expected);
+        // Walks of length at most 3 between opposite vertices of a square.
+        let square = MatMod::new(4, 4, vec![0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0], MOD);
+        assert_eq!(square.geometric_sum(3).get(0, 2), 2);
+    }
+
+    #[test]
+    fn test_matmod_elimination() {
+        let a = MatMod::new(3, 3, vec![2, 0, 1, 1, 3, 2, 1, 1, 2], MOD);
+        assert_eq!(a.rank(), 3);
+        assert_eq!(a.determinant(), 6);
+        let inv = a.inverse().unwrap();
+        assert_eq!(&a * &inv, MatMod::identity(3, MOD));
+        assert_eq!(&inv * &a, MatMod::identity(3, MOD));
+        assert_eq!(a.solve(&[3, 6, 4]), Some(vec![1, 1, 1]));
+        // The permutation swapping two rows has determinant -1.
+        let swap = MatMod::new(2, 2, vec![0, 1, 1, 0], MOD);
+        assert_eq!(swap.determinant(), MOD - 1);
+        // A singular matrix.
+        let s = MatMod::new(3, 3, vec![1, 2, 3, 2, 4, 6, 1, 0, 1], MOD);
+        assert_eq!(s.rank(), 2);
+        assert_eq!(s.determinant(), 0);
+        assert_eq!(s.inverse(), None);
+        assert_eq!(s.solve(&[1, 3, 0]), None);
+        let x = s.solve(&[6, 12, 2]).unwrap();
+        assert_eq!((&s * &MatMod::new(3, 1, x, MOD)).data(), &[6, 12, 2]);
+        // Rectangular systems.
+        let wide = MatMod::new(2, 4, vec![1, 1, 0, 0, 0, 0, 1, 1], 7);
+        assert_eq!(wide.rank(), 2);
+        assert_eq!(wide.solve(&[3, 5]), Some(vec![3, 0, 5, 0]));
+        let tall = MatMod::new(3, 1, vec![1, 2, 3], 7);
+        assert_eq!(tall.solve(&[2, 4, 6]), Some(vec![2]));
+        assert_eq!(tall.solve(&[2, 4, 5]), None);
+        assert_eq!(MatMod::zeros(2, 2, 7).rank(), 0);
+        assert_eq!(MatMod::zeros(0, 0, 7).determinant(), 1);
+        // The determinant 13 - 6 = 7 vanishes modulo 7.
+        let m = MatMod::new(2, 2, vec![1, 3, 2, 13], 7);
+        assert_eq!(m.determinant(), 0);
+    }
+
+    #[test]
+    fn test_matmod_permanent() {
+        assert_eq!(MatMod::zeros(0, 0, MOD).permanent(), 1);
+        assert_eq!(MatMod::new(1, 1, vec![5], MOD).permanent(), 5);
+        assert_eq!(MatMod::new(2, 2, vec![1, 2, 3, 4], MOD).permanent(), 10);
+        assert_eq!(MatMod::new(3, 3, vec![1, 2, 3, 4, 5, 6, 7, 8, 9], MOD).permanent(), 450);
+        // The all-ones matrix has permanent n!, and J − I counts derangements.
+        assert_eq!(MatMod::new(8, 8, vec![1; 64], MOD).permanent(), 40320);
+        let mut derange = MatMod::new(9, 9, vec![1; 81], MOD);
+        (0..9).for_each(|i| derange.set(i, i, 0));
+        assert_eq!(derange.permanent(), 133496);
+        // Against the sum over all permutations, modulo a small prime.
+        let n = 6;
+        let data: Vec<u64> = (0..(n * n) as u64).map(|i| (i * i * 37 + 11) % 13).collect();
+        let m = MatMod::new(n, n, data, 13);
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut direct = 0;
+        loop {
+            direct = (direct + perm.iter().enumerate().fold(1, |acc, (i, &j)| acc * m.get(i, j) % 13)) % 13;
+            // Next permutation in lexicographic order.
+            let Some(i) = (0..n - 1).rev().find(|&i| perm[i] < perm[i + 1]) else { break };
+            let j = (i + 1..n).rev().find(|&j| perm[j] > perm[i]).unwrap();
+            perm.swap(i, j);
+            perm[i + 1..].reverse();
+        }
+        assert_eq!(m.permanent(), direct);
+    }
+
+    #[test]
+    fn test_spanning_tree_count() {
+        let path = [(0, 1), (1, 2)];
+        assert_eq!(MatMod::laplacian(3, &path, MOD).data(), &[1, MOD - 1, 0, MOD - 1, 2, MOD - 1, 0, MOD - 1, 1]);
+        assert_eq!(MatMod::spanning_tree_count(3, &path, MOD), 1);
+        assert_eq!(MatMod::spanning_tree_count(0, &[], MOD), 0);
+        assert_eq!(MatMod::spanning_tree_count(1, &[(0, 0)], MOD), 1);
+        assert_eq!(MatMod::spanning_tree_count(4, &[(0, 1), (2, 3)], MOD), 0);
+        // A cycle of length n has n spanning trees, and doubling every edge multiplies by 2^(n−1).
+        let cycle: Vec<(usize, usize)> = (0..5).map(|i| (i, (i + 1) % 5)).collect();
+        assert_eq!(MatMod::spanning_tree_count(5, &cycle, MOD), 5);
+        let doubled: Vec<(usize, usize)> = cycle.iter().chain(&cycle).copied().collect();
+        assert_eq!(MatMod::spanning_tree_count(5, &doubled, MOD), 5 * 16);
+        // Cayley's formula for complete graphs and m^(n−1)·n^(m−1) for complete bipartite ones.
+        for n in 1..12usize {
+            let complete: Vec<(usize, usize)> = (0..n).flat_map(|u| (u + 1..n).map(move |v| (u, v))).collect();
+            assert_eq!(MatMod::spanning_tree_count(n, &complete, MOD), modexp::mod_exp(n as u64, n.saturating_sub(2) as u64, MOD));
+        }
+        let bipartite: Vec<(usize, usize)> = (0..3).flat_map(|u| (3..7).map(move |v| (u, v))).collect();
+        assert_eq!(MatMod::spanning_tree_count(7, &bipartite, MOD), 27 * 16);
+        // K_7 has 7^5 = 16807 spanning trees, which is 16807 mod 101 = 41.
+        let k7: Vec<(usize, usize)> = (0..7).flat_map(|u| (u + 1..7).map(move |v| (u, v))).collect();
+        assert_eq!(MatMod::spanning_tree_count(7, &k7, 101), 16807 % 101);
+    }
+
+    #[test]
+    fn test_char_poly() {
+        assert_eq!(MatMod::zeros(0, 0, MOD).char_poly().coeffs(), &[1]);
+        assert_eq!(MatMod::new(1, 1, vec![5], MOD).char_poly().coeffs(), &[MOD - 5, 1]);
+        // x² − trace·x + det.
+        let a = MatMod::new(2, 2, vec![1, 2, 3, 4], MOD);
+        assert_eq!(a.char_poly().coeffs(), &[MOD - 2, MOD - 5, 1]);
+        assert_eq!(MatMod::zeros(3, 3, 7).char_poly().coeffs(), &[0, 0, 0, 1]);
+        // Cayley–Hamilton on dense matrices, one of which needs row swaps.
+        for (n, p) in [(6, MOD), (9, 13), (4, 2)] {
+            let data: Vec<u64> = (0..(n * n) as u64).map(|i| i * i * 31 % 17 * (i % 3)).collect();
+            let a = MatMod::new(n, n, data, p);
+            let c = a.char_poly();
+            assert_eq!(c.degree(), Some(n));
+            assert_eq!(c.coeff(0), if n % 2 == 0 { a.determinant() } else { (p - a.determinant()) % p });
+            let mut sum = vec![0; n * n];
+            let mut power = MatMod::identity(n, p);
+            for i in 0..=n {
+                sum.iter_mut().zip(power.data()).for_each(|(s, &x)| *s = (*s + c.coeff(i) * x) % p);
+                power = &power * &a;
+            }
+            assert!(sum.iter().all(|&x| x == 0), "n = {}, p = {}", n, p);
+        }
+        // Walks between two vertices of the Petersen graph at a huge length, via the recurrence.
+        let mut adj = MatMod::zeros(10, 10, MOD);
+        for i in 0..5 {
+            for (u, v) in [(i, (i + 1) % 5), (i, i + 5), (i + 5, (i + 2) % 5 + 5)] {
+                adj.set(u, v, 1);
+                adj.set(v, u, 1);
+            }
+        }
+        let c = adj.char_poly();
+        let rec: Vec<u64> = (0..10).map(|i| (MOD - c.coeff(9 - i)) % MOD).collect();
+        let init: Vec<u64> = (0..10).map(|k| adj.pow(k).get(0, 7)).collect();
+        let k = 1_000_000_000_000;
+        assert_eq!(crate::recurrence::linear_recurrence_kth(&rec, &init, k, MOD), adj.pow(k).get(0, 7));
+    }
+
+    #[test]
+    fn test_xor_basis() {
+        let mut basis = XorBasis::new();
+        assert!(basis.is_empty());
+        assert!(basis.contains(0));
+        assert_eq!(basis.max_xor(), 0);
+        assert!(basis.insert(0b1100));
+        assert!(basis.insert(0b1010));
+        assert!(!basis.insert(0b0110));
+        assert!(!basis.insert(0));
+        assert_eq!(basis.len(), 2);
+        assert!(basis.contains(0b0110));
+        assert!(!basis.contains(0b0001));
+        assert_eq!(basis.max_xor(), 0b1100);
+        assert!(basis.insert(u64::MAX));
+        assert_eq!(basis.max_xor(), u64::MAX);
+        // Brute force over all subsets of a few values.
+        let values = [3, 10, 5, 12, 7, 96, 100];
+        let mut basis = XorBasis::new();
+        values.iter().for_each(|&v| {
+            basis.insert(v);
+        });
+        let spanned: std::collections::HashSet<u64> = (0..1u32 << values.len())
+            .map(|mask| values.iter().enumerate().filter(|&(i, _)| mask >> i & 1 == 1).fold(0, |acc, (_, &v)| acc ^ v))
+            .collect();
+        assert_eq!(spanned.len(), 1 << basis.len());
+        assert_eq!(basis.max_xor(), *spanned.iter().max().unwrap());
+        assert!((0..128).all(|x| basis.contains(x) == spanned.contains(&x)));
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus is not prime!")]
+    fn test_matmod_elimination_composite_modulus() {
+        MatMod::identity(2, 10).determinant();
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds!")]
+    fn test_laplacian_bad_edge() {
+        MatMod::laplacian(3, &[(0, 3)], MOD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix dimensions do not match!")]
+    fn test_matmod_mul_mismatch() {
+        let _ = MatMod::zeros(2, 3, MOD) * MatMod::zeros(2, 3, MOD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix must be square!")]
+    fn test_matmod_pow_not_square() {
+        MatMod::zeros(2, 3, MOD).pow(2);
+    }
+}
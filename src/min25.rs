@@ -0,0 +1,255 @@
+use crate::error::MathCombError;
+use crate::primecount::{add_mod, mul_mod, sub_mod, PrimeSumTable};
+use crate::{sieve, ProgressHook};
+
+// The state shared by the recursion: the primes up to √n, the sums of f over the primes up
+// to every quotient of n, and the prefix sums of f over the first primes.
+struct Min25<F> {
+    m: u64,
+    primes: Vec<u64>,
+    prime_sums: PrimeSumTable<u64>,
+    // below[j] = Σ_{i<j} f(primes[i])
+    below: Vec<u64>,
+    f: F,
+}
+
+impl<F: Fn(u64, u32, u64) -> u64> Min25<F> {
+    // Σ f(i) over 2 <= i <= x whose smallest prime factor is at least primes[j], given that
+    // x is at least primes[j - 1].
+    fn sum(&self, x: u64, j: usize) -> u64 {
+        let m = self.m;
+        let mut total = sub_mod(self.prime_sums.get(x), self.below[j], m);
+        for k in j..self.primes.len() {
+            match self.term(x, k) {
+                Some(term) => total = add_mod(total, term, m),
+                None => break,
+            }
+        }
+        total
+    }
+
+    // The part of `sum(x, j)` from the numbers with smallest prime factor primes[k], or
+    // `None` once that prime squared exceeds x, as it then does for every later k.
+    fn term(&self, x: u64, k: usize) -> Option<u64> {
+        let m = self.m;
+        let p = self.primes[k];
+        if p > x / p {
+            return None;
+        }
+        // Numbers p^e · r with r > 1 free of primes up to p, and the powers p^(e + 1).
+        let mut total = 0;
+        let (mut e, mut pe, mut fe) = (1, p, (self.f)(p, 1, p) % m);
+        while pe <= x / p {
+            let next = (self.f)(p, e + 1, pe * p) % m;
+            total = add_mod(total, add_mod(mul_mod(fe, self.sum(x / pe, k + 1), m), next, m), m);
+            (e, pe, fe) = (e + 1, pe * p, next);
+        }
+        Some(total)
+    }
+}
+
+// Forwards the progress of one stage of `multiplicative_sum_with_progress`, which counts
+// up to √n, as part of the progress over all the stages.
+struct Stage<'a> {
+    hook: &'a dyn ProgressHook,
+    index: u64,
+    stages: u64,
+}
+
+impl ProgressHook for Stage<'_> {
+    fn report(&self, done: u64, total: u64) {
+        self.hook.report(self.index * total + done, self.stages * total);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.hook.is_cancelled()
+    }
+}
+
+/// Computes Σ_{i≤n} f(i) modulo the prime `m` for a multiplicative function `f` with the
+/// Min_25 sieve.
+///
+/// The values of `f` at the primes must agree with a polynomial, `f(p) = Σ c_k·p^k`, whose
+/// prime sums Σ_{p≤v} f(p) come from one Lucy_Hedgehog table per power over the quotients
+/// `v = n / i`. The remaining numbers are enumerated by their prime factors up to √n, each
+/// followed by a single prime (or nothing) above the last one, which the tables count in
+/// bulk.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `prime_poly` - The coefficients `c_0, c_1, …` of f on the primes, modulo `m`.
+/// * `f` - The function on prime powers, called with the prime `p`, the exponent `e` and
+///   the power `p^e`; it must agree with `prime_poly` at `e = 1`.
+/// * `m` - A prime modulus.
+///
+/// # Panics
+///
+/// Panics if `m` is zero or the degree of `prime_poly` plus 1 is not less than `m`.
+///
+/// # Complexity
+///
+/// About O(n^(3/4) / log n) time and O(k·√n) memory for a polynomial of degree k; `n` in
+/// the range 10^12–10^13 takes seconds.
+pub fn multiplicative_sum<F: Fn(u64, u32, u64) -> u64>(n: u64, prime_poly: &[u64], f: F, m: u64) -> u64 {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if prime_poly.len() as u64 >= m {
+        panic!("Degree is too large for the modulus!");
+    }
+    // `()` never cancels.
+    multiplicative_sum_by(n, prime_poly, f, m, &()).unwrap()
+}
+
+/// Computes Σ_{i≤n} f(i) modulo the prime `m` like `multiplicative_sum`, reporting
+/// progress to `hook` and giving up when it cancels.
+///
+/// Progress is counted in primes up to √n over one stage per prime sum table and a last
+/// stage for the enumeration: `hook.report(s·√n + p, (k + 2)·√n)` is called for the prime
+/// `p` reached in stage `s` of a polynomial of degree `k`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `prime_poly` - The coefficients `c_0, c_1, …` of f on the primes, modulo `m`.
+/// * `f` - The function on prime powers, as for `multiplicative_sum`.
+/// * `m` - A prime modulus.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// The sum, `MathCombError::ZeroModulus` if `m` is zero,
+/// `MathCombError::InvalidArgument` if the degree of `prime_poly` plus 1 is not less than
+/// `m`, or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn multiplicative_sum_with_progress<F: Fn(u64, u32, u64) -> u64>(
+    n: u64,
+    prime_poly: &[u64],
+    f: F,
+    m: u64,
+    hook: &impl ProgressHook,
+) -> Result<u64, MathCombError> {
+    if m == 0 {
+        return Err(MathCombError::ZeroModulus);
+    }
+    if prime_poly.len() as u64 >= m {
+        return Err(MathCombError::InvalidArgument("degree is too large for the modulus"));
+    }
+    multiplicative_sum_by(n, prime_poly, f, m, hook).ok_or(MathCombError::Cancelled)
+}
+
+// Computes the sum for valid arguments, returning `None` as soon as `hook` cancels.
+fn multiplicative_sum_by<F: Fn(u64, u32, u64) -> u64>(
+    n: u64,
+    prime_poly: &[u64],
+    f: F,
+    m: u64,
+    hook: &dyn ProgressHook,
+) -> Option<u64> {
+    if n == 0 {
+        return Some(0);
+    }
+    let stages = prime_poly.len() as u64 + 1;
+    let prime_poly: Vec<u64> = prime_poly.iter().map(|&c| c % m).collect();
+    let tables = (0..prime_poly.len())
+        .map(|k| PrimeSumTable::prime_powers_by(n, k as u32, m, &Stage { hook, index: k as u64, stages }))
+        .collect::<Option<Vec<_>>>()?;
+    let prime_sums = PrimeSumTable::from_fn(n, |v| {
+        tables.iter().zip(&prime_poly).fold(0, |sum, (table, &c)| add_mod(sum, mul_mod(c, table.get(v), m), m))
+    });
+    let primes = sieve::primes_up_to(n.isqrt() as usize);
+    let mut below = vec![0];
+    for &p in &primes {
+        let fp = prime_poly.iter().rev().fold(0, |acc, &c| add_mod(mul_mod(acc, p % m, m), c, m));
+        below.push(add_mod(below[below.len() - 1], fp, m));
+    }
+    let state = Min25 { m, primes, prime_sums, below, f };
+    // The top level of `sum(n, 0)`, one smallest prime at a time.
+    let stage = Stage { hook, index: stages - 1, stages };
+    let r = n.isqrt();
+    let mut total = sub_mod(state.prime_sums.get(n), state.below[0], m);
+    for k in 0..state.primes.len() {
+        if stage.is_cancelled() {
+            return None;
+        }
+        let Some(term) = state.term(n, k) else { break };
+        total = add_mod(total, term, m);
+        stage.report(state.primes[k], r);
+    }
+    stage.report(r, r);
+    Some(add_mod(total, 1 % m, m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{arith, modexp};
+    use crate::sieve::MultiplicativeSieve;
+
+    const MOD: u64 = 1_000_000_007;
+
+    fn phi(p: u64, _: u32, pe: u64) -> u64 {
+        pe / p * (p - 1)
+    }
+
+    #[test]
+    fn test_multiplicative_sum_small() {
+        let limit = 3000;
+        let mu = |_: u64, e: u32, _: u64| if e == 1 { MOD - 1 } else { 0 };
+        let sigma = |p: u64, _: u32, pe: u64| (pe * p - 1) / (p - 1);
+        let phi_sieve = MultiplicativeSieve::new(limit, phi);
+        let mu_sieve = MultiplicativeSieve::new(limit, |_, e, _| if e == 1 { -1i64 } else { 0 });
+        let sigma_sieve = MultiplicativeSieve::new(limit, sigma);
+        let (mut phi_sum, mut mu_sum, mut sigma_sum) = (0, 0i64, 0);
+        assert_eq!(multiplicative_sum(0, &[MOD - 1, 1], phi, MOD), 0);
+        for n in 1..=limit {
+            phi_sum += phi_sieve.get(n);
+            mu_sum += mu_sieve.get(n);
+            sigma_sum += sigma_sieve.get(n);
+            assert_eq!(multiplicative_sum(n, &[MOD - 1, 1], phi, MOD), phi_sum % MOD, "n = {}", n);
+            assert_eq!(multiplicative_sum(n, &[MOD - 1], mu, MOD), mu_sum.rem_euclid(MOD as i64) as u64, "n = {}", n);
+            assert_eq!(multiplicative_sum(n, &[1, 1], sigma, MOD), sigma_sum % MOD, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_sum_large() {
+        let n = 1000000000;
+        assert_eq!(multiplicative_sum(n, &[MOD - 1, 1], phi, MOD) as u128, arith::totient_sum(n) % MOD as u128);
+        let d = |_: u64, e: u32, _: u64| e as u64 + 1;
+        assert_eq!(multiplicative_sum(n, &[2], d, MOD) as u128, arith::divisor_summatory(n) % MOD as u128);
+        // Σ μ(i)² counts the squarefree numbers.
+        let mu2 = |_: u64, e: u32, _: u64| (e == 1) as u64;
+        assert_eq!(multiplicative_sum(1000000, &[1], mu2, (1 << 61) - 1), 607926);
+        // A small modulus, where f(p) = p² − 1 vanishes at some primes.
+        let m = 7;
+        let f = |p: u64, e: u32, _: u64| modexp::mod_exp(p, 2 * e as u64, m) + m - 1;
+        let sieve = MultiplicativeSieve::new(2000, |p, e, _| (modexp::mod_exp(p, 2 * e as u64, m) + m - 1) % m);
+        let brute = (1..=2000).fold(0, |s, i| (s + sieve.get(i)) % m);
+        assert_eq!(multiplicative_sum(2000, &[m - 1, 0, 1], f, m), brute);
+    }
+
+    #[test]
+    fn test_multiplicative_sum_with_progress() {
+        use crate::CancelToken;
+        use std::sync::Mutex;
+
+        // Stages of √n = 31622 for the two prime sum tables and the enumeration.
+        let n = 1000000000;
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        let sum = multiplicative_sum_with_progress(n, &[MOD - 1, 1], phi, MOD, &hook);
+        assert_eq!(sum, Ok(multiplicative_sum(n, &[MOD - 1, 1], phi, MOD)));
+        let reports = reports.into_inner().unwrap();
+        assert!(reports.iter().all(|&(_, total)| total == 3 * 31622));
+        assert!(reports.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(reports.last(), Some(&(3 * 31622, 3 * 31622)));
+
+        let token = CancelToken::new();
+        token.cancel();
+        assert_eq!(multiplicative_sum_with_progress(n, &[MOD - 1, 1], phi, MOD, &token), Err(MathCombError::Cancelled));
+        assert_eq!(multiplicative_sum_with_progress(n, &[1], phi, 0, &()), Err(MathCombError::ZeroModulus));
+        let degree = Err(MathCombError::InvalidArgument("degree is too large for the modulus"));
+        assert_eq!(multiplicative_sum_with_progress(n, &[1, 1, 1], phi, 3, &()), degree);
+    }
+}
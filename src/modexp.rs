@@ -1,11 +1,15 @@
+use crate::{pollard, primality};
+
 /// Calculates (base^exponent) % modulus using modular exponentiation.
 ///
+/// A `const fn`, so powers can be computed at compile time.
+///
 /// # Arguments
 ///
 /// *   `base` - The base.
 /// *   `exponent` - The exponent.
 /// *   `modulus` - The modulus.
-pub fn mod_exp(base: u64, exponent: u64, modulus: u64) -> u64 {
+pub const fn mod_exp(base: u64, exponent: u64, modulus: u64) -> u64 {
     let mut retval = 1;
     let mut exp = exponent;
     let mut b = base % modulus;
@@ -20,15 +24,83 @@ pub fn mod_exp(base: u64, exponent: u64, modulus: u64) -> u64 {
     retval
 }
 
+/// Calculates (base^exponent) % modulus for a modulus that fits in 32 bits. Products stay in
+/// `u64` and are reduced by Barrett reduction, with a multiplication in place of the `u128`
+/// division of `mod_exp`.
+///
+/// A `const fn`, so powers can be computed at compile time.
+///
+/// # Arguments
+///
+/// *   `base` - The base.
+/// *   `exponent` - The exponent.
+/// *   `modulus` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `modulus` is 0.
+pub const fn mod_exp_u32(base: u32, exponent: u64, modulus: u32) -> u32 {
+    if modulus == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if modulus == 1 {
+        return 0;
+    }
+    let m = modulus as u64;
+    let im = u64::MAX / m + 1;
+    let mut retval = 1;
+    let mut exp = exponent;
+    let mut b = base as u64 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            retval = barrett_mul(retval, b, m, im);
+        }
+        exp >>= 1;
+        b = barrett_mul(b, b, m, im);
+    }
+    retval as u32
+}
+
+// Computes a·b mod m for a, b < m < 2^32 by Barrett reduction with im = ⌈2^64 / m⌉: the
+// estimate ⌊a·b·im / 2^64⌋ of the quotient is exact or one too large, so no division is
+// needed.
+#[inline]
+pub(crate) const fn barrett_mul(a: u64, b: u64, m: u64, im: u64) -> u64 {
+    let z = a * b;
+    let q = ((z as u128 * im as u128) >> 64) as u64;
+    let (v, borrow) = z.overflowing_sub(q * m);
+    if borrow { v.wrapping_add(m) } else { v }
+}
+
+/// Calculates the modular inverse of `x` modulo `modulus` like `mod_inv`, for 32-bit
+/// values. `modulus` need not be prime.
+///
+/// # Arguments
+///
+/// *   `x` - The number for which to calculate the inverse.
+/// *   `modulus` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `modulus` is 0, or if `x` and `modulus` are not coprime.
+pub fn mod_inv_u32(x: u32, modulus: u32) -> u32 {
+    if modulus == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if gcd(x as u64, modulus as u64) != 1 {
+        panic!("x and modulus are not coprime. Inverse does not exist.");
+    }
+    inv_coprime((x % modulus) as u64, modulus as u64) as u32
+}
+
 /// Calculates the modular multiplicative inverse of `x` modulo `modulus`.
 ///
 /// The modular inverse of `x` modulo `modulus` is an integer `y` such that
 /// (x * y) % modulus == 1. It exists if and only if `x` and `modulus` are coprime
 /// (i.e., their greatest common divisor is 1).
 ///
-/// This function uses Fermat's Little Theorem, which states that if `modulus` is a prime number,
-/// then for any integer `x` not divisible by `modulus`, x^(modulus-1) ≡ 1 (mod modulus).
-/// Therefore, the modular inverse of `x` is x^(modulus-2) (mod modulus).
+/// This function uses the extended Euclidean algorithm, so `modulus` need not be prime:
+/// it finds `y` with x·y + modulus·k = 1, which exists exactly when the two are coprime.
 ///
 /// # Arguments
 ///
@@ -49,6 +121,10 @@ pub fn mod_inv(x: u64, modulus: u64) -> u64 {
         panic!("Modulus cannot be zero.");
     }
 
+    if modulus == 1 {
+        return 0;
+    }
+
     if x == 0 {
         panic!("x cannot be zero.");
     }
@@ -56,10 +132,315 @@ pub fn mod_inv(x: u64, modulus: u64) -> u64 {
     if gcd(x, modulus) != 1 {
         panic!("x and modulus are not coprime. Inverse does not exist.");
     }
-    mod_exp(x, modulus - 2, modulus)
+    inv_coprime(x % modulus, modulus)
+}
+
+/// Computes a square root of `a` modulo the prime `p` with the Tonelli–Shanks algorithm.
+///
+/// # Arguments
+///
+/// * `a` - The number whose root is wanted.
+/// * `p` - A prime modulus.
+///
+/// # Returns
+///
+/// The smaller of the two roots `x` with `x² ≡ a (mod p)`, or `None` if `a` is a
+/// quadratic non-residue modulo `p`.
+///
+/// # Panics
+///
+/// Panics if `p` is 0. The result is meaningless if `p` is not prime.
+pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+    if p == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    if mod_exp(a, (p - 1) / 2, p) != 1 {
+        return None;
+    }
+    let mul = |x: u64, y: u64| (x as u128 * y as u128 % p as u128) as u64;
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let mut z = 2;
+    while mod_exp(z, (p - 1) / 2, p) != p - 1 {
+        z += 1;
+    }
+    let mut m = s;
+    let mut c = mod_exp(z, q, p);
+    let mut t = mod_exp(a, q, p);
+    let mut r = mod_exp(a, q.div_ceil(2), p);
+    while t != 1 {
+        // Find the least i with t^(2^i) = 1.
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul(t2, t2);
+            i += 1;
+        }
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = mul(b, b);
+        }
+        m = i;
+        c = mul(b, b);
+        t = mul(t, c);
+        r = mul(r, b);
+    }
+    Some(r.min(p - r))
+}
+
+// Counts the solutions of x² ≡ a (mod p^e).
+fn count_sqrt_prime_power(a: u64, p: u64, e: u32) -> u64 {
+    let pe = p.pow(e);
+    let a = a % pe;
+    if a == 0 {
+        // x² ≡ 0 iff p^⌈e/2⌉ divides x.
+        return p.pow(e / 2);
+    }
+    // a = p^k·u with u a unit; x must be p^(k/2)·y with y² ≡ u (mod p^(e−k)).
+    let mut k = 0;
+    let mut u = a;
+    while u.is_multiple_of(p) {
+        u /= p;
+        k += 1;
+    }
+    if k % 2 == 1 {
+        return 0;
+    }
+    let f = e - k;
+    let units = if p == 2 {
+        match f {
+            1 => 1,
+            2 => if u % 4 == 1 { 2 } else { 0 },
+            _ => if u % 8 == 1 { 4 } else { 0 },
+        }
+    } else if mod_exp(u % p, (p - 1) / 2, p) == 1 {
+        2
+    } else {
+        0
+    };
+    // Each root y modulo p^(e−k) gives p^(k/2) roots x modulo p^e.
+    units * p.pow(k / 2)
+}
+
+/// Counts the square roots of `a` modulo `n`, the `x` in `[0, n)` with `x² ≡ a (mod n)`,
+/// without enumerating them.
+///
+/// By the Chinese remainder theorem the count is the product of the counts modulo the
+/// prime powers `p^e` dividing `n`. There, `a = p^k·u` with `u` a unit needs an even `k`,
+/// and `u` has 0 or 2 roots for odd `p`, while modulo `2^f` a unit has 1 root for `f = 1`,
+/// 2 if `u ≡ 1 (mod 4)` for `f = 2` and 4 if `u ≡ 1 (mod 8)` beyond.
+///
+/// # Arguments
+///
+/// * `a` - The number whose square roots are counted.
+/// * `n` - The modulus.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn count_sqrt_mod(a: u64, n: u64) -> u64 {
+    if n == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    pollard::factor_map(n).iter().map(|&(p, e)| count_sqrt_prime_power(a, p, e)).product()
+}
+
+/// Primes up to this bound have their residues counted in a marking table by
+/// `count_residues_in_range`; larger ones use Legendre symbols.
+const MARKING_LIMIT: u64 = 1 << 20;
+
+// Marks the nonzero quadratic residues modulo p.
+fn residue_table(p: u64) -> Vec<bool> {
+    let mut is_residue = vec![false; p as usize];
+    for i in 1..=p / 2 {
+        is_residue[(i * i % p) as usize] = true;
+    }
+    if p == 2 {
+        is_residue[1] = true;
+    }
+    is_residue
 }
 
-pub fn gcd(a: u64, b: u64) -> u64 {
+/// Lists the nonzero quadratic residues modulo the prime `p`, the squares of `1, …, p − 1`.
+///
+/// # Arguments
+///
+/// * `p` - A prime modulus.
+///
+/// # Returns
+///
+/// The `(p − 1) / 2` residues (just 1 for `p = 2`) in increasing order.
+///
+/// # Panics
+///
+/// Panics if `p` is 0.
+///
+/// # Complexity
+///
+/// O(p) time and memory.
+pub fn quadratic_residues(p: u64) -> Vec<u64> {
+    if p == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    residue_table(p).iter().enumerate().filter(|&(_, &r)| r).map(|(x, _)| x as u64).collect()
+}
+
+/// Counts the `x` in `[l, r]` that are nonzero quadratic residues modulo the prime `p`.
+///
+/// Every full period of `p` consecutive numbers holds `(p − 1) / 2` residues, so only the
+/// remaining partial period is inspected: through a marking table when `p` is at most 2^20
+/// and the partial period is not tiny, and with a Legendre symbol per number otherwise.
+///
+/// # Arguments
+///
+/// * `p` - A prime modulus.
+/// * `l` - The lower end of the range.
+/// * `r` - The upper end of the range, inclusive.
+///
+/// # Panics
+///
+/// Panics if `p` is 0.
+///
+/// # Complexity
+///
+/// O(min(r − l, p)·log p), or O(p) with the marking table.
+pub fn count_residues_in_range(p: u64, l: u64, r: u64) -> u64 {
+    if p == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if l > r {
+        return 0;
+    }
+    let len = (r - l) as u128 + 1;
+    let per_period = if p == 2 { 1 } else { (p - 1) / 2 };
+    let full = (len / p as u128) as u64 * per_period;
+    let rest = (len % p as u128) as u64;
+    let start = l % p;
+    let partial = if p == 2 || (p <= MARKING_LIMIT && rest >= p / 64) {
+        let table = residue_table(p);
+        (0..rest).filter(|&i| table[((start + i) % p) as usize]).count() as u64
+    } else {
+        (0..rest).filter(|&i| primality::jacobi((start + i) % p, p) == 1).count() as u64
+    };
+    full + partial
+}
+
+/// Recovers a fraction `p/q` with `p ≡ r·q (mod m)` from the residue `r`, the inverse of
+/// reducing a rational modulo `m`.
+///
+/// Running the extended Euclidean algorithm on `m` and `r` gives a sequence of remainders
+/// `r_i ≡ r·t_i (mod m)` with `|t_i|` growing as `r_i` shrinks, so the first remainder
+/// below `√((m − 1)/2)` is the only candidate numerator. With both bounds there, two
+/// fractions `p/q` and `p'/q'` would give `|p·q' − p'·q| < m`, so the fraction is unique
+/// when it exists.
+///
+/// # Arguments
+///
+/// *   `r` - The residue.
+/// *   `m` - The modulus.
+///
+/// # Returns
+///
+/// The fraction `(p, q)` in lowest terms with `|p|, q <= √((m − 1)/2)` and `q` coprime
+/// to `m`, or `None` if there is none.
+///
+/// # Panics
+///
+/// Panics if `m` is 0.
+///
+/// # Complexity
+///
+/// O(log m).
+pub fn rational_reconstruct(r: u64, m: u64) -> Option<(i64, u64)> {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    let bound = ((m - 1) / 2).isqrt();
+    let (mut r0, mut r1) = (m, r % m);
+    let (mut t0, mut t1): (i128, i128) = (0, 1);
+    while r1 > bound {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q as i128 * t1);
+    }
+    let q = t1.unsigned_abs() as u64;
+    if q > bound || gcd(r1, q) != 1 || gcd(q, m) != 1 {
+        return None;
+    }
+    let p = if t1 < 0 { -(r1 as i64) } else { r1 as i64 };
+    Some((p, q))
+}
+
+/// Solves a system of congruences `x ≡ a_i (mod m_i)` with the Chinese remainder theorem,
+/// merging them one at a time. The moduli need not be pairwise coprime.
+///
+/// Merging `x ≡ a (mod n)` into `x ≡ x0 (mod m)` with `g = gcd(m, n)` writes
+/// `x = x0 + m·t`, which needs `(m/g)·t ≡ (a − x0)/g (mod n/g)`: solvable exactly when `g`
+/// divides `a − x0`, by inverting `m/g` modulo `n/g`.
+///
+/// # Arguments
+///
+/// *   `congruences` - The pairs `(a_i, m_i)`.
+///
+/// # Returns
+///
+/// `(x, lcm)` with `0 <= x < lcm` the least solution and `lcm` the least common multiple
+/// of the moduli (`(0, 1)` for no congruences), or `None` if the congruences contradict
+/// each other or the least common multiple exceeds `u64::MAX`.
+///
+/// # Panics
+///
+/// Panics if a modulus is 0.
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let (mut x, mut m) = (0u64, 1u64);
+    for &(a, n) in congruences {
+        if n == 0 {
+            panic!("Modulus cannot be zero.");
+        }
+        let g = gcd(m, n);
+        let diff = ((a % n) as u128 + n as u128 - (x % n) as u128) % n as u128;
+        if !diff.is_multiple_of(g as u128) {
+            return None;
+        }
+        let ng = n / g;
+        let t = diff / g as u128 % ng as u128 * inv_coprime((m / g) % ng, ng) as u128 % ng as u128;
+        let lcm = m as u128 * ng as u128;
+        if lcm > u64::MAX as u128 {
+            return None;
+        }
+        // x < m and t < n/g, so the new solution stays below the lcm.
+        x = (x as u128 + m as u128 * t) as u64;
+        m = lcm as u64;
+    }
+    Some((x, m))
+}
+
+// Inverts `a` modulo `m` for coprime `a` and `m` with the extended Euclidean algorithm,
+// which does not need `m` to be prime.
+pub(crate) fn inv_coprime(a: u64, m: u64) -> u64 {
+    let (mut r0, mut r1) = (m as i128, a as i128);
+    let (mut t0, mut t1): (i128, i128) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    t0.rem_euclid(m as i128) as u64
+}
+
+/// Computes the greatest common divisor of `a` and `b` using the Euclidean algorithm.
+///
+/// A `const fn`, so it can be evaluated at compile time.
+///
+/// # Arguments
+///
+/// * `a` - The first number.
+/// * `b` - The second number.
+pub const fn gcd(a: u64, b: u64) -> u64 {
     if b == 0 {
         a
     } else {
@@ -84,11 +465,46 @@ mod tests {
         assert_eq!(mod_exp(10, 0, 100), 1);
     }
 
+    #[test]
+    fn test_u32_paths() {
+        const P: u32 = 998244353;
+        const G: u32 = mod_exp_u32(3, (P as u64 - 1) / 2, P);
+        assert_eq!(G, P - 1);
+        let mut x: u64 = 12345;
+        for _ in 0..2000 {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let (base, exponent, modulus) = ((x >> 32) as u32, x, (x >> 40) as u32 | 1);
+            assert_eq!(mod_exp_u32(base, exponent, modulus) as u64, mod_exp(base as u64, exponent, modulus as u64) % modulus as u64);
+            let a = (x >> 33) as u32 % (P - 1) + 1;
+            assert_eq!(mod_inv_u32(a, P) as u64, mod_inv(a as u64, P as u64));
+        }
+        assert_eq!(mod_exp_u32(5, 0, 1), 0);
+        assert_eq!(mod_exp_u32(u32::MAX, u64::MAX, u32::MAX - 4) as u64, mod_exp(u32::MAX as u64, u64::MAX, u32::MAX as u64 - 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "x and modulus are not coprime. Inverse does not exist.")]
+    fn test_mod_inv_u32_not_coprime() {
+        mod_inv_u32(8, 12);
+    }
+
     #[test]
     fn test_mod_inv() {
         assert_eq!(mod_inv(3, 11), 4);
         assert_eq!(mod_inv(7, 13), 2);
         assert_eq!(mod_inv(5, 7), 3);
+        // Composite moduli agree with `modular::try_mod_inv`.
+        assert_eq!(mod_inv(3, 8), 3);
+        assert_eq!(mod_inv(7, 10), 3);
+        assert_eq!(mod_inv(14, 15), 14);
+        assert_eq!(mod_inv(0, 1), 0);
+        for m in 1..60 {
+            for x in 0..2 * m {
+                if let Ok(inv) = crate::modular::try_mod_inv(x, m) {
+                    assert_eq!(mod_inv(x, m), inv);
+                }
+            }
+        }
     }
 
     #[test]
@@ -108,4 +524,133 @@ mod tests {
     fn test_mod_inv_x_zero() {
         mod_inv(0, 12);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sqrt_mod() {
+        assert_eq!(sqrt_mod(0, 7), Some(0));
+        assert_eq!(sqrt_mod(1, 2), Some(1));
+        assert_eq!(sqrt_mod(2, 7), Some(3));
+        assert_eq!(sqrt_mod(3, 7), None);
+        assert_eq!(sqrt_mod(10, 13), Some(6));
+        for p in [3, 5, 17, 97, 257, 65537, 998244353, 1000000007] {
+            for a in 0..200u64 {
+                if let Some(r) = sqrt_mod(a, p) {
+                    assert_eq!(r as u128 * r as u128 % p as u128, (a % p) as u128);
+                } else {
+                    assert_eq!(mod_exp(a, (p - 1) / 2, p), p - 1);
+                }
+            }
+        }
+        let p = 18446744073709551557;
+        let a = (12345678901234567u128 * 12345678901234567 % p as u128) as u64;
+        let r = sqrt_mod(a, p).unwrap();
+        assert_eq!((r as u128 * r as u128 % p as u128) as u64, a);
+    }
+
+    #[test]
+    fn test_count_sqrt_mod() {
+        for n in 1..300u64 {
+            for a in 0..n {
+                let brute = (0..n).filter(|&x| x * x % n == a).count() as u64;
+                assert_eq!(count_sqrt_mod(a, n), brute, "a = {}, n = {}", a, n);
+            }
+        }
+        assert_eq!(count_sqrt_mod(1, 1 << 63), 4);
+        assert_eq!(count_sqrt_mod(0, 1 << 63), 1 << 31);
+        assert_eq!(count_sqrt_mod(3, 1 << 63), 0);
+        assert_eq!(count_sqrt_mod(1, 3 * 5 * 7 * 11 * 13 * 17 * 8), 4 << 6);
+        assert_eq!(count_sqrt_mod(u64::MAX, u64::MAX), 1);
+        assert_eq!(count_sqrt_mod(4, 1000000007 * 998244353), 4);
+    }
+
+    #[test]
+    fn test_quadratic_residues() {
+        assert_eq!(quadratic_residues(2), vec![1]);
+        assert_eq!(quadratic_residues(3), vec![1]);
+        assert_eq!(quadratic_residues(11), vec![1, 3, 4, 5, 9]);
+        assert_eq!(quadratic_residues(13), vec![1, 3, 4, 9, 10, 12]);
+        for p in [5, 7, 101, 997, 65537] {
+            let residues = quadratic_residues(p);
+            assert_eq!(residues.len() as u64, (p - 1) / 2);
+            assert!(residues.iter().all(|&x| mod_exp(x, (p - 1) / 2, p) == 1));
+        }
+    }
+
+    #[test]
+    fn test_count_residues_in_range() {
+        for p in [2, 3, 5, 7, 13, 1000003] {
+            let residues = quadratic_residues(p.min(13));
+            for l in 0..40u64 {
+                for r in l.saturating_sub(1)..80 {
+                    let brute = if p > 13 {
+                        (l..=r).filter(|&x| x % p != 0 && mod_exp(x, (p - 1) / 2, p) == 1).count()
+                    } else {
+                        (l..=r).filter(|x| residues.contains(&(x % p))).count()
+                    };
+                    assert_eq!(count_residues_in_range(p, l, r), brute as u64, "p = {}, [{}, {}]", p, l, r);
+                }
+            }
+        }
+        // A long partial period for a prime below 2^20 goes through the marking table.
+        let p = 1000003;
+        let brute = (1..=600000).filter(|&x| mod_exp(x, (p - 1) / 2, p) == 1).count() as u64;
+        assert_eq!(count_residues_in_range(p, 2 * p, 2 * p + 600000), brute);
+        assert_eq!(count_residues_in_range(p, 2 * p + 1, 2 * p + 10) + count_residues_in_range(p, 2 * p + 11, 2 * p + 600000), brute);
+        assert_eq!(count_residues_in_range(7, 0, u64::MAX), u64::MAX / 7 * 3 + 1);
+        assert_eq!(count_residues_in_range(2, 0, u64::MAX), 1 << 63);
+        // 2^61 − 1 is prime, and the range covers one full period plus 100 numbers.
+        let p = (1 << 61) - 1;
+        let tail = (1..=100).filter(|&x| mod_exp(x, (p - 1) / 2, p) == 1).count() as u64;
+        assert_eq!(count_residues_in_range(p, 1, p + 100), (p - 1) / 2 + tail);
+    }
+
+    #[test]
+    fn test_rational_reconstruct() {
+        let m = 1000000007;
+        assert_eq!(rational_reconstruct(mod_inv(3, m), m), Some((1, 3)));
+        assert_eq!(rational_reconstruct(m - 2 * mod_inv(7, m) % m, m), Some((-2, 7)));
+        assert_eq!(rational_reconstruct(0, m), Some((0, 1)));
+        assert_eq!(rational_reconstruct(22360, m), Some((22360, 1)));
+        // 355/113 survives a round trip through the residues.
+        let r = 355 * mod_inv(113, m) % m;
+        assert_eq!(rational_reconstruct(r, m), Some((355, 113)));
+        for m in 1..200u64 {
+            let bound = ((m - 1) / 2).isqrt();
+            for r in 0..m {
+                let candidates: Vec<(i64, u64)> = (1..=bound)
+                    .flat_map(|q| (-(bound as i64)..=bound as i64).map(move |p| (p, q)))
+                    .filter(|&(p, q)| gcd(p.unsigned_abs(), q) == 1 && gcd(q, m) == 1)
+                    .filter(|&(p, q)| (p.rem_euclid(m as i64) as u64) == r * q % m)
+                    .collect();
+                assert!(candidates.len() <= 1);
+                assert_eq!(rational_reconstruct(r, m), candidates.first().copied(), "{} mod {}", r, m);
+            }
+        }
+        assert_eq!(rational_reconstruct(u64::MAX - 1, u64::MAX), Some((-1, 1)));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(5, 1), (7, 10)]), Some((7, 10)));
+        // Brute force over small systems with common factors.
+        for m1 in 1..13u64 {
+            for m2 in 1..13u64 {
+                for a1 in 0..m1 {
+                    for a2 in 0..m2 {
+                        let lcm = m1 / gcd(m1, m2) * m2;
+                        let expected = (0..lcm).find(|x| x % m1 == a1 && x % m2 == a2).map(|x| (x, lcm));
+                        assert_eq!(crt(&[(a1, m1), (a2, m2)]), expected);
+                    }
+                }
+            }
+        }
+        let (p, q) = (4294967291u64, 4294967279u64);
+        assert_eq!(crt(&[(p - 1, p), (q - 1, q)]), Some((p * q - 1, p * q)));
+        assert_eq!(crt(&[(0, u64::MAX), (1, u64::MAX - 1)]), None);
+        assert_eq!(crt(&[(u64::MAX - 1, u64::MAX), (u64::MAX - 1, u64::MAX)]), Some((u64::MAX - 1, u64::MAX)));
+    }
+}
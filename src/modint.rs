@@ -0,0 +1,365 @@
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::error::MathCombError;
+use crate::{modexp, pollard, MulMod};
+
+/// A residue modulo the compile-time modulus `M`, so modular arithmetic composes with the
+/// usual operators instead of a `% m` after every step:
+/// `(ModInt::<998244353>::from(3) * 4 + 5).pow(2)`.
+///
+/// Values are always stored reduced. Products go through a `MulMod` built for `M` at
+/// compile time, so `M` may be any `u64` and a small `M` never pays for `u128` arithmetic.
+/// Division and `inv` work for any modulus, prime or not, as long as the divisor is coprime
+/// to it. `ModComb<M>` computes binomial coefficients as `ModInt<M>` values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ModInt<M> {
+    // Fails the build for `M == 0` as soon as the type is used.
+    const MUL: MulMod = MulMod::new(M);
+
+    /// Creates the residue of `value` modulo `M`.
+    pub const fn new(value: u64) -> ModInt<M> {
+        ModInt { value: value % Self::MUL.modulus() }
+    }
+
+    /// Returns the residue in `[0, M)`.
+    pub const fn value(self) -> u64 {
+        self.value
+    }
+
+    /// Returns the modulus `M`.
+    pub const fn modulus() -> u64 {
+        M
+    }
+
+    /// Raises the residue to the power `exponent`, with `0^0 = 1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `exponent` - The exponent.
+    pub const fn pow(self, exponent: u64) -> ModInt<M> {
+        ModInt { value: Self::MUL.pow(self.value, exponent) }
+    }
+
+    /// Returns the inverse of the residue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the residue and `M` are not coprime.
+    pub fn inv(self) -> ModInt<M> {
+        match self.try_inv() {
+            Ok(inv) => inv,
+            Err(_) => panic!("x and modulus are not coprime. Inverse does not exist."),
+        }
+    }
+
+    /// Returns the inverse of the residue like `inv`, returning an error instead of
+    /// panicking.
+    ///
+    /// # Returns
+    ///
+    /// The inverse, or `MathCombError::NotInvertible` if the residue and `M` are not coprime.
+    pub fn try_inv(self) -> Result<ModInt<M>, MathCombError> {
+        if modexp::gcd(self.value, M) != 1 {
+            return Err(MathCombError::NotInvertible { x: self.value, modulus: M });
+        }
+        Ok(ModInt { value: modexp::inv_coprime(self.value, M) })
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> ModInt<M> {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> From<ModInt<M>> for u64 {
+    fn from(x: ModInt<M>) -> u64 {
+        x.value
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn add(self, other: ModInt<M>) -> ModInt<M> {
+        let value = if self.value >= M - other.value { self.value - (M - other.value) } else { self.value + other.value };
+        ModInt { value }
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn sub(self, other: ModInt<M>) -> ModInt<M> {
+        let value = if self.value >= other.value { self.value - other.value } else { self.value + (M - other.value) };
+        ModInt { value }
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn mul(self, other: ModInt<M>) -> ModInt<M> {
+        ModInt { value: Self::MUL.mul(self.value, other.value) }
+    }
+}
+
+// Dividing multiplies by the inverse, so it panics when the divisor is not invertible.
+impl<const M: u64> Div for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn div(self, other: ModInt<M>) -> ModInt<M> {
+        ModInt { value: Self::MUL.mul(self.value, other.inv().value) }
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn neg(self) -> ModInt<M> {
+        ModInt::default() - self
+    }
+}
+
+// Lets a plain `u64` stand on the right of every operator and assignment, reduced first.
+macro_rules! forward_ops {
+    ($($op:ident $method:ident $assign:ident $assign_method:ident),*) => {
+        $(
+            impl<const M: u64> $op<u64> for ModInt<M> {
+                type Output = ModInt<M>;
+
+                fn $method(self, other: u64) -> ModInt<M> {
+                    self.$method(ModInt::new(other))
+                }
+            }
+
+            impl<const M: u64> $assign for ModInt<M> {
+                fn $assign_method(&mut self, other: ModInt<M>) {
+                    *self = (*self).$method(other);
+                }
+            }
+
+            impl<const M: u64> $assign<u64> for ModInt<M> {
+                fn $assign_method(&mut self, other: u64) {
+                    *self = (*self).$method(ModInt::new(other));
+                }
+            }
+        )*
+    };
+}
+
+forward_ops!(Add add AddAssign add_assign, Sub sub SubAssign sub_assign, Mul mul MulAssign mul_assign, Div div DivAssign div_assign);
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = ModInt<M>>>(iter: I) -> ModInt<M> {
+        iter.fold(ModInt::default(), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = ModInt<M>>>(iter: I) -> ModInt<M> {
+        iter.fold(ModInt::new(1), Mul::mul)
+    }
+}
+
+/// Factorials and their inverses modulo the compile-time prime `M`, like `Comb` but with
+/// the tables and the results held as `ModInt<M>`, so they mix with other `ModInt<M>`
+/// arithmetic without conversions.
+pub struct ModComb<const M: u64> {
+    fact: Vec<ModInt<M>>,
+    inv_fact: Vec<ModInt<M>>,
+}
+
+impl<const M: u64> ModComb<M> {
+    // Fails the build for a composite `M` as soon as a `ModComb<M>` is created.
+    const PRIME: () = assert!(pollard::is_prime(M), "modulus is not prime!");
+
+    /// Creates a `ModComb`, pre-calculating factorials and their inverses up to `max_fact`.
+    /// A composite `M` fails the build.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_fact` - The largest `n` accepted by `nPr` and `nCr`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_fact` is not below `M`.
+    pub fn new(max_fact: usize) -> ModComb<M> {
+        let () = Self::PRIME;
+        if max_fact as u64 >= M {
+            panic!("max_fact must be less than the modulus!");
+        }
+        let mut fact = vec![ModInt::new(1); max_fact + 1];
+        for i in 1..=max_fact {
+            fact[i] = fact[i - 1] * i as u64;
+        }
+        let mut inv_fact = vec![fact[max_fact].inv(); max_fact + 1];
+        for i in (0..max_fact).rev() {
+            inv_fact[i] = inv_fact[i + 1] * (i + 1) as u64;
+        }
+        ModComb { fact, inv_fact }
+    }
+
+    /// Returns `n!`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number, at most `max_fact`.
+    pub fn fact(&self, n: usize) -> ModInt<M> {
+        self.fact[n]
+    }
+
+    /// Returns the inverse of `n!`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number, at most `max_fact`.
+    pub fn inv_fact(&self, n: usize) -> ModInt<M> {
+        self.inv_fact[n]
+    }
+
+    /// Calculates nPr like `Comb::nPr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The total number of items.
+    /// * `r` - The number of items to choose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is less than `r` or `n` > `max_fact`.
+    #[allow(non_snake_case)]
+    pub fn nPr(&self, n: u64, r: u64) -> ModInt<M> {
+        self.check_args(n, r);
+        self.fact[n as usize] * self.inv_fact[r as usize]
+    }
+
+    /// Calculates nCr like `Comb::nCr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The total number of items.
+    /// * `r` - The number of items to choose.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is less than `r` or `n` > `max_fact`.
+    #[allow(non_snake_case)]
+    pub fn nCr(&self, n: u64, r: u64) -> ModInt<M> {
+        self.check_args(n, r);
+        self.fact[n as usize] * self.inv_fact[r as usize] * self.inv_fact[(n - r) as usize]
+    }
+
+    // Panics on the arguments `Comb::nPr` and `Comb::nCr` reject, with the same messages.
+    fn check_args(&self, n: u64, r: u64) {
+        let max_fact = self.fact.len() as u64 - 1;
+        if n < r {
+            panic!("n cannot be less than r!");
+        }
+        if n > max_fact {
+            panic!("n cannot be greater than {}!", max_fact);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Mint = ModInt<998244353>;
+
+    #[test]
+    fn test_mod_int() {
+        let a = Mint::from(998244352);
+        let b = Mint::new(5);
+        assert_eq!((a + b).value(), 4);
+        assert_eq!((b - a).value(), 6);
+        assert_eq!((a * a).value(), 1);
+        assert_eq!(-Mint::new(0), Mint::new(0));
+        assert_eq!((-b).value(), 998244348);
+        assert_eq!(b / b, Mint::new(1));
+        assert_eq!((Mint::new(1) / 2 * 2).value(), 1);
+        assert_eq!(Mint::new(3).pow(998244352), Mint::new(1));
+        assert_eq!(Mint::new(3) * Mint::new(3).inv(), Mint::new(1));
+        assert_eq!(Mint::modulus(), 998244353);
+        assert_eq!(u64::from(Mint::new(998244353 * 3 + 7)), 7);
+
+        let mut x = Mint::new(10);
+        x += 5;
+        x -= Mint::new(20);
+        x *= 3;
+        x /= Mint::new(3);
+        assert_eq!(x, -Mint::new(5));
+        assert_eq!(format!("{}", x), "998244348");
+        let factorial: Mint = (1..=10).map(Mint::new).product();
+        assert_eq!(factorial.value(), 3628800);
+        assert_eq!((1..=100).map(Mint::new).sum::<Mint>().value(), 5050);
+    }
+
+    #[test]
+    fn test_mod_int_any_modulus() {
+        // Montgomery and widening strategies, and a composite modulus.
+        const P: u64 = 1000000000000000003;
+        let a = ModInt::<P>::new(P - 1);
+        assert_eq!(a * a, ModInt::new(1));
+        assert_eq!((a + a).value(), P - 2);
+        assert_eq!(ModInt::<P>::new(2).pow(P - 1).value(), 1);
+        let x = ModInt::<{ u64::MAX }>::new(u64::MAX - 1);
+        assert_eq!(x + x, ModInt::new(u64::MAX - 2));
+        assert_eq!(x * x, ModInt::new(1));
+        let e = ModInt::<{ 1 << 40 }>::new(3);
+        assert_eq!((e * e.inv()).value(), 1);
+        assert_eq!(ModInt::<12>::new(5).inv().value(), 5);
+        assert_eq!(ModInt::<12>::new(4).try_inv(), Err(MathCombError::NotInvertible { x: 4, modulus: 12 }));
+        assert_eq!(ModInt::<1>::new(5).value(), 0);
+    }
+
+    #[test]
+    fn test_mod_comb() {
+        let comb = ModComb::<998244353>::new(100);
+        let catalan: Mint = comb.nCr(100, 50) - comb.nCr(100, 49);
+        assert_eq!(catalan.value(), 512803529);
+        assert_eq!(comb.nCr(100, 50) * comb.fact(50) * comb.fact(50), comb.fact(100));
+        assert_eq!(comb.fact(10) * comb.inv_fact(10), Mint::new(1));
+        let plain = crate::Comb::new(998244353, 100);
+        for (n, r) in [(0, 0), (10, 3), (100, 1), (100, 100), (77, 40)] {
+            assert_eq!(comb.nCr(n, r).value(), plain.nCr(n, r));
+            assert_eq!(comb.nPr(n, r).value(), plain.nPr(n, r));
+        }
+        // A small prime, and a modulus above 2^32.
+        let small = ModComb::<13>::new(12);
+        assert_eq!(small.nCr(12, 6), ModInt::new(crate::Comb::new(13, 12).nCr(12, 6)));
+        let large = ModComb::<1000000000000000003>::new(100);
+        assert_eq!(large.nCr(100, 50).value(), (100891344545564193334812497256u128 % 1000000000000000003) as u64);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be greater than 10!")]
+    fn test_mod_comb_above_limit() {
+        ModComb::<998244353>::new(10).nCr(11, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_fact must be less than the modulus!")]
+    fn test_mod_comb_too_many_factorials() {
+        ModComb::<13>::new(13);
+    }
+
+    #[test]
+    #[should_panic(expected = "x and modulus are not coprime. Inverse does not exist.")]
+    fn test_mod_int_div_not_invertible() {
+        let _ = ModInt::<12>::new(1) / ModInt::new(6);
+    }
+}
@@ -0,0 +1,75 @@
+//! Modular exponentiation, inverses, square roots, quadratic residues, primitive roots and
+//! the Chinese remainder theorem.
+//!
+//! It holds the functions of the former `Modexp` facade, callable as
+//! `modular::mod_exp(base, exponent, modulus)`. Width-generic exponentiation lives in
+//! the [`int`](crate::int) module.
+
+use crate::error::MathCombError;
+use crate::modexp;
+
+pub use crate::modexp::{
+    count_residues_in_range, count_sqrt_mod, crt, mod_exp, mod_exp_u32, mod_inv, mod_inv_u32, quadratic_residues,
+    rational_reconstruct, sqrt_mod,
+};
+pub use crate::ntt::primitive_root;
+
+/// Calculates (base^exponent) % modulus, returning an error instead of panicking.
+///
+/// # Arguments
+///
+/// *   `base` - The base.
+/// *   `exponent` - The exponent.
+/// *   `modulus` - The modulus.
+///
+/// # Returns
+///
+/// The power, or `MathCombError::ZeroModulus` if `modulus` is 0.
+pub fn try_mod_exp(base: u64, exponent: u64, modulus: u64) -> Result<u64, MathCombError> {
+    if modulus == 0 {
+        return Err(MathCombError::ZeroModulus);
+    }
+    Ok(modexp::mod_exp(base, exponent, modulus) % modulus)
+}
+
+/// Calculates the modular inverse of `x` modulo `modulus` like `mod_inv`, returning an
+/// error instead of panicking. Like `mod_inv`, it accepts a composite `modulus`.
+///
+/// # Arguments
+///
+/// *   `x` - The number for which to calculate the inverse.
+/// *   `modulus` - The modulus.
+///
+/// # Returns
+///
+/// The inverse, `MathCombError::ZeroModulus` if `modulus` is 0, or
+/// `MathCombError::NotInvertible` if `x` and `modulus` are not coprime.
+pub fn try_mod_inv(x: u64, modulus: u64) -> Result<u64, MathCombError> {
+    if modulus == 0 {
+        return Err(MathCombError::ZeroModulus);
+    }
+    if modulus == 1 {
+        return Ok(0);
+    }
+    if modexp::gcd(x % modulus, modulus) != 1 {
+        return Err(MathCombError::NotInvertible { x, modulus });
+    }
+    Ok(modexp::inv_coprime(x % modulus, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_paths() {
+        assert_eq!(mod_exp(2, 10, 1000000007), 1024);
+        assert_eq!(mod_inv(3, 11), 4);
+        assert_eq!(primitive_root(998244353), 3);
+        assert_eq!(try_mod_exp(2, 10, 0), Err(MathCombError::ZeroModulus));
+        assert_eq!(try_mod_inv(22, 11), Err(MathCombError::NotInvertible { x: 22, modulus: 11 }));
+        assert_eq!(try_mod_inv(5, 1), Ok(0));
+        assert_eq!(try_mod_inv(3, 10), Ok(7));
+        assert_eq!(mod_inv_u32(3, 10), 7);
+    }
+}
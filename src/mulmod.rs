@@ -0,0 +1,202 @@
+use crate::modexp;
+
+/// The way a `MulMod` reduces its products, picked from the modulus by `MulMod::new`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MulStrategy {
+    /// Moduli below 2^32: the product fits in a `u64` and is reduced by Barrett reduction.
+    Plain,
+    /// Odd moduli from 2^32 on: Montgomery reduction, with multiplications in place of the
+    /// `u128` division.
+    ///
+    /// Only chains of products gain from it: `MulMod::pow` and the factorization routines
+    /// stay in Montgomery form throughout, but `MulMod::mul` (and so `Comb`) divides in
+    /// `u128` like `Widening`.
+    Montgomery,
+    /// Even moduli from 2^32 on (and the modulus 1): the product is widened to `u128` and
+    /// divided.
+    Widening,
+}
+
+/// A modulus prepared for repeated multiplications, which picks the fastest correct
+/// reduction for its magnitude once at construction.
+///
+/// `Comb` and the factorization routines multiply through it, so their products cannot
+/// overflow for a modulus above 2^32 and a small one never pays for 128-bit arithmetic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MulMod {
+    modulus: u64,
+    strategy: MulStrategy,
+    // ⌈2^64 / m⌉ for `Plain`, and m^-1 mod 2^64 for `Montgomery`.
+    inv: u64,
+    // 2^128 mod m for `Montgomery`, which moves a Montgomery product back to the plain form.
+    r2: u64,
+}
+
+impl MulMod {
+    /// Prepares `modulus` for multiplications, choosing the strategy from its magnitude.
+    ///
+    /// # Arguments
+    ///
+    /// * `modulus` - The modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is 0.
+    pub const fn new(modulus: u64) -> MulMod {
+        if modulus == 0 {
+            panic!("Modulus cannot be zero.");
+        }
+        if modulus == 1 {
+            return MulMod { modulus, strategy: MulStrategy::Widening, inv: 0, r2: 0 };
+        }
+        if modulus >> 32 == 0 {
+            return MulMod { modulus, strategy: MulStrategy::Plain, inv: u64::MAX / modulus + 1, r2: 0 };
+        }
+        if modulus.is_multiple_of(2) {
+            return MulMod { modulus, strategy: MulStrategy::Widening, inv: 0, r2: 0 };
+        }
+        // Newton's iteration doubles the correct low bits of the inverse each step, starting
+        // from the 3 bits of m·m ≡ 1 (mod 8).
+        let mut inv = modulus;
+        let mut i = 0;
+        while i < 5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(modulus.wrapping_mul(inv)));
+            i += 1;
+        }
+        let r = ((1u128 << 64) % modulus as u128) as u64;
+        let r2 = (r as u128 * r as u128 % modulus as u128) as u64;
+        MulMod { modulus, strategy: MulStrategy::Montgomery, inv, r2 }
+    }
+
+    /// Returns the modulus.
+    pub const fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Returns the reduction picked for the modulus.
+    pub const fn strategy(&self) -> MulStrategy {
+        self.strategy
+    }
+
+    /// Computes `(a * b) % modulus` for `a` and `b` below the modulus.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first factor.
+    /// * `b` - The second factor.
+    #[inline]
+    pub const fn mul(&self, a: u64, b: u64) -> u64 {
+        match self.strategy {
+            MulStrategy::Plain => modexp::barrett_mul(a, b, self.modulus, self.inv),
+            // A lone product would need two Montgomery reductions to leave the Montgomery
+            // form, which is slower than one division. Chains of products such as `pow` stay
+            // in that form instead.
+            MulStrategy::Montgomery | MulStrategy::Widening => (a as u128 * b as u128 % self.modulus as u128) as u64,
+        }
+    }
+
+    /// Computes `(base^exponent) % modulus`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base.
+    /// * `exponent` - The exponent.
+    pub const fn pow(&self, base: u64, exponent: u64) -> u64 {
+        self.leave(self.pow_scaled(self.enter(base % self.modulus), exponent))
+    }
+
+    // Converts `x < m` to the representation of the strategy: x·2^64 mod m for `Montgomery`,
+    // and `x` itself otherwise.
+    pub(crate) const fn enter(&self, x: u64) -> u64 {
+        match self.strategy {
+            MulStrategy::Montgomery => self.redc(x as u128 * self.r2 as u128),
+            _ => x,
+        }
+    }
+
+    // Converts back from the representation of the strategy.
+    pub(crate) const fn leave(&self, x: u64) -> u64 {
+        match self.strategy {
+            MulStrategy::Montgomery => self.redc(x as u128),
+            _ => x,
+        }
+    }
+
+    // Multiplies two values in the representation of the strategy, which for `Montgomery`
+    // is a single reduction giving a·b·2^-64. The scale is a unit, so gcds with the modulus
+    // can be taken directly on values in that representation.
+    #[inline]
+    pub(crate) const fn mul_scaled(&self, a: u64, b: u64) -> u64 {
+        match self.strategy {
+            MulStrategy::Montgomery => self.redc(a as u128 * b as u128),
+            _ => self.mul(a, b),
+        }
+    }
+
+    // Raises `base` to `exponent`, both the base and the power in the representation of the
+    // strategy.
+    pub(crate) const fn pow_scaled(&self, base: u64, exponent: u64) -> u64 {
+        let mut retval = self.enter(1 % self.modulus);
+        let mut exp = exponent;
+        let mut b = base;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                retval = self.mul_scaled(retval, b);
+            }
+            exp >>= 1;
+            b = self.mul_scaled(b, b);
+        }
+        retval
+    }
+
+    // Montgomery reduction: returns t·2^-64 mod m, fully reduced for t < m·2^64. Any larger
+    // t still gives a congruent value below 2^64, which a second reduction brings below m.
+    #[inline]
+    const fn redc(&self, t: u128) -> u64 {
+        let q = (t as u64).wrapping_mul(self.inv);
+        let qm = ((q as u128 * self.modulus as u128) >> 64) as u64;
+        let hi = (t >> 64) as u64;
+        if hi < qm { hi.wrapping_sub(qm).wrapping_add(self.modulus) } else { hi - qm }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies() {
+        let cases = [
+            (1, MulStrategy::Widening),
+            (2, MulStrategy::Plain),
+            (998244353, MulStrategy::Plain),
+            (u32::MAX as u64, MulStrategy::Plain),
+            (1 << 32, MulStrategy::Widening),
+            ((1 << 32) + 15, MulStrategy::Montgomery),
+            (1000000000000000003, MulStrategy::Montgomery),
+            ((1 << 61) - 1, MulStrategy::Montgomery),
+            (u64::MAX - 1, MulStrategy::Widening),
+            (u64::MAX, MulStrategy::Montgomery),
+        ];
+        for (m, strategy) in cases {
+            let mm = MulMod::new(m);
+            assert_eq!(mm.strategy(), strategy);
+            assert_eq!(mm.modulus(), m);
+            for a in [0, 1, 2, m / 3, m / 2, m - 1] {
+                for b in [0, 1, 3, m / 5, m.saturating_sub(2), m - 1] {
+                    let (a, b) = (a % m, b % m);
+                    assert_eq!(mm.mul(a, b), (a as u128 * b as u128 % m as u128) as u64, "{a}·{b} mod {m}");
+                    assert_eq!(mm.leave(mm.mul_scaled(mm.enter(a), mm.enter(b))), mm.mul(a, b));
+                }
+            }
+            assert_eq!(mm.pow(3, 1000), modexp::mod_exp(3, 1000, m));
+            assert_eq!(mm.pow(m - 1, 0), 1 % m);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Modulus cannot be zero.")]
+    fn test_zero_modulus() {
+        MulMod::new(0);
+    }
+}
@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::{modexp, pollard, poly};
+
+/// Below this length one of the operands is small enough for schoolbook multiplication to
+/// beat the transforms.
+const NAIVE_THRESHOLD: usize = 60;
+
+/// Twiddle factors of an NTT-friendly prime, computed once per modulus and thread.
+struct Roots {
+    // The largest k with 2^k | p - 1.
+    rank2: u32,
+    // rate2[i] moves the twiddle of butterfly block s to block s + 1 when the lowest
+    // unset bit of s is bit i; irate2 does the same for the inverse transform.
+    rate2: Vec<u64>,
+    irate2: Vec<u64>,
+}
+
+impl Roots {
+    fn new(p: u64) -> Roots {
+        let g = primitive_root(p);
+        let rank2 = (p - 1).trailing_zeros();
+        let mut root = vec![0; rank2 as usize + 1];
+        let mut iroot = vec![0; rank2 as usize + 1];
+        root[rank2 as usize] = modexp::mod_exp(g, (p - 1) >> rank2, p);
+        iroot[rank2 as usize] = modexp::mod_inv(root[rank2 as usize], p);
+        for i in (0..rank2 as usize).rev() {
+            root[i] = root[i + 1] * root[i + 1] % p;
+            iroot[i] = iroot[i + 1] * iroot[i + 1] % p;
+        }
+        let mut rate2 = Vec::new();
+        let mut irate2 = Vec::new();
+        let (mut prod, mut iprod) = (1, 1);
+        for i in 0..rank2.saturating_sub(1) as usize {
+            rate2.push(root[i + 2] * prod % p);
+            irate2.push(iroot[i + 2] * iprod % p);
+            prod = prod * iroot[i + 2] % p;
+            iprod = iprod * root[i + 2] % p;
+        }
+        Roots { rank2, rate2, irate2 }
+    }
+
+    // Returns the cached roots of `p`, computing them on first use in this thread.
+    fn get(p: u64) -> Rc<Roots> {
+        thread_local! {
+            static CACHE: RefCell<HashMap<u64, Rc<Roots>>> = RefCell::new(HashMap::new());
+        }
+        CACHE.with(|cache| Rc::clone(cache.borrow_mut().entry(p).or_insert_with(|| Rc::new(Roots::new(p)))))
+    }
+}
+
+/// Finds the smallest primitive root modulo the prime `p`.
+///
+/// # Arguments
+///
+/// * `p` - A prime modulus.
+///
+/// # Returns
+///
+/// The least `g` whose powers run through every non-zero residue modulo `p`.
+pub fn primitive_root(p: u64) -> u64 {
+    if p == 2 {
+        return 1;
+    }
+    let divisors: Vec<u64> = pollard::factor_map(p - 1).iter().map(|&(q, _)| (p - 1) / q).collect();
+    (2..).find(|&g| divisors.iter().all(|&d| modexp::mod_exp(g, d, p) != 1)).unwrap()
+}
+
+// Forward transform in place, leaving the output in bit-reversed order.
+fn butterfly(a: &mut [u64], p: u64, roots: &Roots) {
+    let h = a.len().trailing_zeros();
+    for len in 0..h {
+        let half = 1 << (h - len - 1);
+        let mut rot = 1;
+        for s in 0..1usize << len {
+            let offset = s << (h - len);
+            for i in offset..offset + half {
+                let l = a[i];
+                let r = a[i + half] * rot % p;
+                a[i] = if l + r >= p { l + r - p } else { l + r };
+                a[i + half] = if l >= r { l - r } else { l + p - r };
+            }
+            if s + 1 != 1 << len {
+                rot = rot * roots.rate2[(!s).trailing_zeros() as usize] % p;
+            }
+        }
+    }
+}
+
+// Inverse of `butterfly` up to a factor of the length, taking bit-reversed input.
+fn butterfly_inv(a: &mut [u64], p: u64, roots: &Roots) {
+    let h = a.len().trailing_zeros();
+    for len in (1..=h).rev() {
+        let half = 1 << (h - len);
+        let mut irot = 1;
+        for s in 0..1usize << (len - 1) {
+            let offset = s << (h - len + 1);
+            for i in offset..offset + half {
+                let l = a[i];
+                let r = a[i + half];
+                a[i] = if l + r >= p { l + r - p } else { l + r };
+                a[i + half] = (l + p - r) * irot % p;
+            }
+            if s + 1 != 1 << (len - 1) {
+                irot = irot * roots.irate2[(!s).trailing_zeros() as usize] % p;
+            }
+        }
+    }
+}
+
+fn convolution_naive(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    let mut c = vec![0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            c[i + j] = (c[i + j] + x * y) % p;
+        }
+    }
+    c
+}
+
+/// Multiplies the polynomials with coefficients `a` and `b` modulo the NTT-friendly prime
+/// `p` with the number-theoretic transform.
+///
+/// # Arguments
+///
+/// * `a` - The coefficients of the first polynomial, lowest degree first.
+/// * `b` - The coefficients of the second polynomial, lowest degree first.
+/// * `p` - A prime below 2^32 such that a power of two at least `a.len() + b.len() - 1`
+///   divides `p - 1`, such as 998244353 = 119·2^23 + 1.
+///
+/// # Returns
+///
+/// The `a.len() + b.len() - 1` coefficients of the product reduced modulo `p`, or an empty
+/// vector if either input is empty.
+///
+/// # Panics
+///
+/// Panics if `p` is not below 2^32 or the product is too long for the transform sizes
+/// supported by `p`.
+pub fn convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    if p >= 1 << 32 {
+        panic!("Modulus must be below 2^32!");
+    }
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let a: Vec<u64> = a.iter().map(|&x| x % p).collect();
+    let b: Vec<u64> = b.iter().map(|&x| x % p).collect();
+    let len = a.len() + b.len() - 1;
+    if a.len().min(b.len()) <= NAIVE_THRESHOLD {
+        return convolution_naive(&a, &b, p);
+    }
+    let z = len.next_power_of_two();
+    let roots = Roots::get(p);
+    if z.trailing_zeros() > roots.rank2 {
+        panic!("Convolution is too long for this modulus!");
+    }
+    let (mut fa, mut fb) = (a, b);
+    fa.resize(z, 0);
+    fb.resize(z, 0);
+    butterfly(&mut fa, p, &roots);
+    butterfly(&mut fb, p, &roots);
+    for (x, &y) in fa.iter_mut().zip(fb.iter()) {
+        *x = *x * y % p;
+    }
+    butterfly_inv(&mut fa, p, &roots);
+    fa.truncate(len);
+    let iz = modexp::mod_inv(z as u64 % p, p);
+    for x in fa.iter_mut() {
+        *x = *x * iz % p;
+    }
+    fa
+}
+
+// NTT-friendly primes used by `convolution_mod`, together supporting transforms of length
+// 2^24 and recombining coefficients below their product, about 5.8·10^25.
+const CRT_PRIMES: [u64; 3] = [754974721, 167772161, 469762049];
+
+/// Multiplies the polynomials with coefficients `a` and `b` modulo an arbitrary `m`.
+///
+/// The product is computed exactly under three NTT-friendly primes and recombined with
+/// Garner's algorithm before being reduced modulo `m`.
+///
+/// # Arguments
+///
+/// * `a` - The coefficients of the first polynomial, lowest degree first.
+/// * `b` - The coefficients of the second polynomial, lowest degree first.
+/// * `m` - The modulus, which need not be prime.
+///
+/// # Returns
+///
+/// The `a.len() + b.len() - 1` coefficients of the product reduced modulo `m`, or an empty
+/// vector if either input is empty.
+///
+/// # Panics
+///
+/// Panics if `m` is zero or not below 2^32, or if the exact coefficients of the product may
+/// reach the product of the three primes, i.e. `min(a.len(), b.len())·(m - 1)²` is too large.
+pub fn convolution_mod(a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if m >= 1 << 32 {
+        panic!("Modulus must be below 2^32!");
+    }
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let [p1, p2, p3] = CRT_PRIMES;
+    let bound = a.len().min(b.len()) as u128 * (m as u128 - 1) * (m as u128 - 1);
+    if bound >= p1 as u128 * p2 as u128 * p3 as u128 {
+        panic!("Convolution is too long for this modulus!");
+    }
+    let a: Vec<u64> = a.iter().map(|&x| x % m).collect();
+    let b: Vec<u64> = b.iter().map(|&x| x % m).collect();
+    if a.len().min(b.len()) <= NAIVE_THRESHOLD {
+        let mut c = vec![0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                c[i + j] = (c[i + j] + x * y) % m;
+            }
+        }
+        return c;
+    }
+    let c1 = convolution(&a, &b, p1);
+    let c2 = convolution(&a, &b, p2);
+    let c3 = convolution(&a, &b, p3);
+    let inv_p1 = modexp::mod_inv(p1 % p2, p2);
+    let inv_p1p2 = modexp::mod_inv(p1 * p2 % p3, p3);
+    let (p1_m, p1p2_m) = (p1 % m, p1 * p2 % m);
+    c1.iter()
+        .zip(c2.iter().zip(c3.iter()))
+        .map(|(&x1, (&r2, &r3))| {
+            // x = x1 + x2·p1 + x3·p1·p2 with each digit reduced modulo its own prime.
+            let x2 = (r2 + p2 - x1 % p2) % p2 * inv_p1 % p2;
+            let low = (x1 + x2 * p1) % p3;
+            let x3 = (r3 + p3 - low) % p3 * inv_p1p2 % p3;
+            ((x1 % m + x2 % m * p1_m) % m + x3 % m * p1p2_m) % m
+        })
+        .collect()
+}
+
+/// A relaxed (online) convolution modulo a prime: the coefficients of both operands arrive
+/// one at a time, and coefficient `n` of the product is available as soon as the `n`-th
+/// coefficients of both operands are known.
+///
+/// This is what self-referential generating functions such as `T = x·exp(T)` need, where
+/// each new coefficient depends on the product of the ones before it.
+///
+/// Pairs `(i, j)` with both indices positive are grouped into blocks `[s, 2s) × [t, t + s)`
+/// with `s` a power of two, each multiplied with one convolution as soon as its last
+/// coefficient arrives, so the total work is O(n log² n).
+pub struct OnlineConvolution {
+    f: Vec<u64>,
+    g: Vec<u64>,
+    h: Vec<u64>,
+    p: u64,
+}
+
+impl OnlineConvolution {
+    /// Creates an empty convolution modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not a prime below 2^32.
+    pub fn new(p: u64) -> OnlineConvolution {
+        if p >= 1 << 32 {
+            panic!("Modulus must be below 2^32!");
+        }
+        if !pollard::is_prime(p) {
+            panic!("modulus is not prime!");
+        }
+        OnlineConvolution { f: vec![], g: vec![], h: vec![], p }
+    }
+
+    /// Returns the number of coefficients pushed so far.
+    pub fn len(&self) -> usize {
+        self.f.len()
+    }
+
+    /// Returns `true` if no coefficients have been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.f.is_empty()
+    }
+
+    // Adds the product of f[fs..fs + len] and g[gs..gs + len] to h, starting at fs + gs.
+    fn add_block(&mut self, fs: usize, gs: usize, len: usize) {
+        let product = poly::multiply(&self.f[fs..fs + len], &self.g[gs..gs + len], self.p);
+        let start = fs + gs;
+        if self.h.len() < start + product.len() {
+            self.h.resize(start + product.len(), 0);
+        }
+        for (h, x) in self.h[start..].iter_mut().zip(product) {
+            *h = (*h + x) % self.p;
+        }
+    }
+
+    /// Appends `a` and `b` as the next coefficients `f[n]` and `g[n]` of the operands.
+    ///
+    /// # Returns
+    ///
+    /// The coefficient `h[n] = Σ f[i]·g[n − i]` of the product.
+    pub fn push(&mut self, a: u64, b: u64) -> u64 {
+        let p = self.p;
+        let n = self.f.len();
+        self.f.push(a % p);
+        self.g.push(b % p);
+        if self.h.len() <= n {
+            self.h.resize(n + 1, 0);
+        }
+        let direct = if n == 0 { self.f[0] * self.g[0] % p } else { (self.f[n] * self.g[0] + self.f[0] * self.g[n]) % p };
+        self.h[n] = (self.h[n] + direct) % p;
+        // Every block whose last coefficient is n: the square [s, 2s)² when n + 1 = 2s, and
+        // the rectangles [s, 2s) × [n + 1 − s, n + 1) and their mirrors when n + 1 ≥ 3s.
+        let q = n + 1;
+        let mut s = 1;
+        while 2 * s <= q && q.is_multiple_of(s) {
+            if q == 2 * s {
+                self.add_block(s, s, s);
+            } else if q >= 3 * s {
+                self.add_block(s, q - s, s);
+                self.add_block(q - s, s, s);
+            }
+            s *= 2;
+        }
+        self.h[n]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOD: u64 = 998244353;
+
+    // A small deterministic generator for test data.
+    fn random_vec(len: usize, seed: u64, p: u64) -> Vec<u64> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x % p
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_primitive_root() {
+        assert_eq!(primitive_root(2), 1);
+        assert_eq!(primitive_root(7), 3);
+        assert_eq!(primitive_root(998244353), 3);
+        assert_eq!(primitive_root(167772161), 3);
+        assert_eq!(primitive_root(469762049), 3);
+        assert_eq!(primitive_root(754974721), 11);
+    }
+
+    #[test]
+    fn test_convolution() {
+        assert_eq!(convolution(&[], &[1, 2], MOD), vec![]);
+        assert_eq!(convolution(&[1, 2, 3], &[4, 5], MOD), vec![4, 13, 22, 15]);
+        assert_eq!(convolution(&[MOD + 1], &[MOD - 1], MOD), vec![MOD - 1]);
+        for (n, m) in [(61, 61), (100, 200), (1000, 1), (1000, 999), (513, 1537)] {
+            let a = random_vec(n, n as u64 + 1, MOD);
+            let b = random_vec(m, m as u64 + 7, MOD);
+            assert_eq!(convolution(&a, &b, MOD), convolution_naive(&a, &b, MOD), "{} x {}", n, m);
+        }
+        let p = 7340033; // 7·2^20 + 1
+        let a = random_vec(300, 3, p);
+        let b = random_vec(400, 5, p);
+        assert_eq!(convolution(&a, &b, p), convolution_naive(&a, &b, p));
+    }
+
+    #[test]
+    fn test_convolution_mod() {
+        assert_eq!(convolution_mod(&[5, 6], &[7], 1), vec![0, 0]);
+        assert_eq!(convolution_mod(&[1, 2, 3], &[4, 5], 7), vec![4, 6, 1, 1]);
+        for m in [1_000_000_007, 998244353, (1 << 32) - 1, 12] {
+            for (n, k) in [(100, 100), (1000, 777), (2000, 61)] {
+                let a = random_vec(n, n as u64 ^ m, m);
+                let b = random_vec(k, k as u64 + m, m);
+                let expected: Vec<u64> = (0..n + k - 1)
+                    .map(|i| {
+                        let lo = i.saturating_sub(k - 1);
+                        (lo..=i.min(n - 1)).fold(0u128, |acc, j| acc + a[j] as u128 * b[i - j] as u128)
+                            % m as u128
+                    } as u64)
+                    .collect();
+                assert_eq!(convolution_mod(&a, &b, m), expected, "m = {}, {} x {}", m, n, k);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Convolution is too long for this modulus!")]
+    fn test_convolution_mod_too_long() {
+        convolution_mod(&vec![1; 1 << 22], &vec![1; 1 << 22], (1 << 32) - 1);
+    }
+
+    #[test]
+    fn test_online_convolution() {
+        let a = random_vec(700, 11, MOD);
+        let b = random_vec(700, 13, MOD);
+        let expected = convolution_naive(&a, &b, MOD);
+        let mut online = OnlineConvolution::new(MOD);
+        assert!(online.is_empty());
+        for n in 0..700 {
+            assert_eq!(online.push(a[n], b[n]), expected[n], "n = {}", n);
+        }
+        assert_eq!(online.len(), 700);
+        // Rooted labeled trees: T = x·exp(T) has t[n] = n^(n-1) / n!. With E = exp(T), the
+        // identities t[n] = e[n-1] and n·e[n] = Σ_{k=1..n} k·t[k]·e[n-k] make each e[n] a
+        // coefficient of the online product of (k + 1)·t[k + 1] and e[k].
+        let p = 1_000_000_007;
+        let n_max = 100;
+        let inv = poly::inverses(n_max, p);
+        let mut e = vec![1];
+        let mut t = vec![0];
+        let mut online = OnlineConvolution::new(p);
+        for n in 1..=n_max {
+            t.push(e[n - 1]);
+            let sum = online.push(n as u64 * t[n] % p, e[n - 1]);
+            e.push(sum * inv[n] % p);
+        }
+        let mut fact = 1;
+        for n in 1..=n_max as u64 {
+            fact = fact * n % p;
+            assert_eq!(t[n as usize] * fact % p, modexp::mod_exp(n, n - 1, p), "n = {}", n);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Convolution is too long for this modulus!")]
+    fn test_convolution_too_long() {
+        // 97 - 1 = 3·2^5 only supports transforms of length 32.
+        convolution(&[1; 64], &[1; 64], 97);
+    }
+}
@@ -1,4 +1,37 @@
-use crate::modexp;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use crate::error::MathCombError;
+use crate::random::RandomSource;
+use crate::{fermat, modexp, sieve, MulMod, ProgressHook};
+
+/// The primes below 100, used to strip small factors before resorting to Pollard's rho.
+const SMALL_PRIMES: [u64; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// The default bound for the trial-division pass of `factor`.
+pub const DEFAULT_TRIAL_LIMIT: u64 = 100;
+
+/// The smoothness bound of the p−1 attempt inside `factor`.
+const P_MINUS_1_BOUND: u64 = 2000;
+
+/// The smoothness bound of the p+1 attempt inside `factor`.
+const P_PLUS_1_BOUND: u64 = 500;
+
+/// The most prime factors a `u64` can have, counted with multiplicity (2^63 has 63), and so
+/// a buffer length that `factor_into` never outgrows.
+pub const MAX_FACTORS: usize = 63;
+
+/// Miller-Rabin bases that together decide primality for every 64-bit integer.
+const MR_BASES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+/// Gaps between consecutive integers coprime to 30, starting from 7.
+const WHEEL_30: [u64; 8] = [4, 2, 4, 2, 4, 6, 2, 6];
+
+/// The number of Fermat steps `factor` tries before falling back to Pollard's rho.
+/// Enough to split products of two primes that agree in their leading half of digits.
+const FERMAT_STEPS: u64 = 64;
 
 /// Performs modular multiplication of `a` and `b` under modulus `modulus`.
 ///
@@ -11,14 +44,15 @@ use crate::modexp;
 /// # Returns
 ///
 /// The result of `(a * b) % modulus`.
-pub fn modmul(a: u64, b: u64, modulus: u64) -> u64 {
-    let mut ret: u64 = a % modulus;
-    ret = (ret * (b % modulus)) % modulus;
-    return ret;
+pub const fn modmul(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
 }
 
 /// Checks if `n` is a prime number.
 ///
+/// Deterministic for every `u64` (Miller-Rabin with seven fixed bases), and usable in const
+/// contexts, e.g. to check a modulus at compile time.
+///
 /// # Arguments
 ///
 /// * `n` - The number to check.
@@ -26,25 +60,78 @@ pub fn modmul(a: u64, b: u64, modulus: u64) -> u64 {
 /// # Returns
 ///
 /// `true` if `n` is prime, `false` otherwise.
-pub fn is_prime(n: u64) -> bool {
+pub const fn is_prime(n: u64) -> bool {
     if n < 2 || n % 6 % 4 != 1 {
-        return (n | 3) == 3;
-    }
-    let A: Vec<u64> = vec![2, 325, 9375, 28178, 450775, 9780504, 1795265022];
-    let s: u64 = (n - 1).trailing_zeros() as u64;
-    let d: u64 = n >> s;
-    for &a in &A {
-        let mut p = modexp::mod_exp(a % n, d, n);
-        let mut i = s;
-        while p != 1 && p != n - 1 && a % n != 0 && i != 0 {
-            p = modmul(p, p, n);
-            i -= 1;
-        }
-        if p != n - 1 && i != s {
+        return (n | 1) == 3;
+    }
+    // An index loop, since iterators are not available in const fns.
+    let mut i = 0;
+    while i < MR_BASES.len() {
+        if !miller_rabin_witness(n, MR_BASES[i]) {
             return false;
         }
+        i += 1;
+    }
+    true
+}
+
+/// Finds a Miller-Rabin base that proves `n` composite.
+///
+/// # Arguments
+///
+/// * `n` - The number to test.
+///
+/// # Returns
+///
+/// The first of the deterministic bases used by `is_prime` for which `n` is not a strong
+/// probable prime, or `None` if `n` passes all of them (i.e. `n` is prime) or `n` < 2.
+pub fn find_witness(n: u64) -> Option<u64> {
+    if n < 2 {
+        return None;
+    }
+    MR_BASES.iter().copied().find(|&a| !miller_rabin_witness(n, a))
+}
+
+/// Checks whether `n` is a strong probable prime to base `a` (one round of Miller-Rabin).
+///
+/// Writing `n - 1 = d * 2^s` with `d` odd, `n` passes if `a^d ≡ 1 (mod n)` or
+/// `a^(d * 2^r) ≡ -1 (mod n)` for some `0 <= r < s`. Bases divisible by `n` pass trivially.
+///
+/// # Arguments
+///
+/// * `n` - The number to test.
+/// * `a` - The base.
+///
+/// # Returns
+///
+/// `true` if `n` is a strong probable prime to base `a`, `false` if `a` witnesses that
+/// `n` is composite. Even `n` other than 2 and `n` < 2 always return `false`.
+pub const fn miller_rabin_witness(n: u64, a: u64) -> bool {
+    if n < 3 || n.is_multiple_of(2) {
+        return n == 2;
+    }
+    let a = a % n;
+    if a == 0 {
+        return true;
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    // Compared in the representation of the strategy, so the powers never leave it.
+    let mm = MulMod::new(n);
+    let (one, minus_one) = (mm.enter(1), mm.enter(n - 1));
+    let mut p = mm.pow_scaled(mm.enter(a), d);
+    if p == one || p == minus_one {
+        return true;
+    }
+    let mut r = 1;
+    while r < s {
+        p = mm.mul_scaled(p, p);
+        if p == minus_one {
+            return true;
+        }
+        r += 1;
     }
-    return true;
+    false
 }
 
 /// Pollard's rho algorithm for integer factorization.
@@ -57,19 +144,58 @@ pub fn is_prime(n: u64) -> bool {
 ///
 /// A non-trivial factor of `n`.
 pub fn pollard(n: u64) -> u64 {
-    let f = |x| modmul(x, x, n) + 1;
+    // `()` never cancels.
+    pollard_until(n, &(), &mut Restarts::default()).unwrap()
+}
+
+/// Pollard's rho algorithm like `pollard`, drawing the starting point of every restart
+/// from `rng` instead of trying 1, 2, 3, … in turn.
+///
+/// # Arguments
+///
+/// * `n` - The composite number to split.
+/// * `rng` - The source of the starting points.
+///
+/// # Returns
+///
+/// A non-trivial factor of `n`.
+pub fn pollard_with_rng(n: u64, rng: &mut impl RandomSource) -> u64 {
+    pollard_until(n, &(), rng).unwrap()
+}
+
+// The starting points 1, 2, 3, … of the deterministic restarts of `pollard`.
+#[derive(Default)]
+struct Restarts(u64);
+
+impl RandomSource for Restarts {
+    fn next_u64(&mut self) -> u64 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+// Runs Pollard's rho like `pollard` with restarts drawn from `rng`, polling `hook` at every
+// gcd and returning `None` once it cancels.
+fn pollard_until(n: u64, hook: &dyn ProgressHook, rng: &mut dyn RandomSource) -> Option<u64> {
+    // The walk stays in the representation of the strategy (Montgomery form for odd n above
+    // 2^32), which only rescales it by a unit and so finds the same gcds.
+    let mm = MulMod::new(n);
+    let f = |x| mm.mul_scaled(x, x) + 1;
     let mut x = 0;
     let mut y = 0;
     let mut t = 30;
     let mut prd = 2;
-    let mut i = 1;
     while t % 40 != 0 || modexp::gcd(prd, n) == 1 {
+        if t % 40 == 0 && hook.is_cancelled() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(n, iterations = t - 30, "rho cancelled");
+            return None;
+        }
         if x == y {
-            x = i;
-            i += 1;
+            x = rng.next_u64() % n;
             y = f(x);
         }
-        let q = modmul(prd, (x.max(y) - x.min(y)) % n, n);
+        let q = mm.mul_scaled(prd, (x.max(y) - x.min(y)) % n);
         if q != 0 {
             prd = q;
         }
@@ -77,11 +203,177 @@ pub fn pollard(n: u64) -> u64 {
         y = f(f(y));
         t += 1;
     }
-    return modexp::gcd(prd, n);
+    #[cfg(feature = "tracing")]
+    tracing::trace!(n, iterations = t - 30, "rho finished");
+    Some(modexp::gcd(prd, n))
+}
+
+/// Pollard's p−1 factorization method (stage 1).
+///
+/// Computes `a = 2^M mod n` where `M` is the product of all maximal prime powers `<= bound`.
+/// If some prime `p | n` has a `bound`-smooth `p - 1`, then `p` divides `a - 1` and is
+/// revealed by gcd(`a - 1`, `n`).
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `bound` - The smoothness bound B.
+///
+/// # Returns
+///
+/// A non-trivial factor of `n`, or `None` if the method failed for this bound.
+pub fn pollard_p_minus_1(n: u64, bound: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let primes = sieve::primes_up_to(bound as usize);
+    p_minus_1(n, &primes, bound)
+}
+
+// Runs p−1 over the primes `<= bound` for a composite `n`.
+fn p_minus_1(n: u64, primes: &[u64], bound: u64) -> Option<u64> {
+    // A base only fails unconditionally when every prime of n turns smooth at the same
+    // step, in which case another base may separate them.
+    for base in [2, 3, 5, 7, 11, 13] {
+        if let Ok(retval) = p_minus_1_stage1(n, primes, bound, base) {
+            return retval;
+        }
+    }
+    None
+}
+
+// The primes up to `P_MINUS_1_BOUND`, sieved once and shared by every p−1 and p+1 attempt
+// of `factor`, so factorizing does not allocate.
+fn stage1_primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| sieve::primes_up_to(P_MINUS_1_BOUND as usize))
+}
+
+// Runs stage 1 of p−1 from `base`, returning `Err(())` if all primes of `n` were
+// found at once and could not be separated.
+fn p_minus_1_stage1(n: u64, primes: &[u64], bound: u64, base: u64) -> Result<Option<u64>, ()> {
+    let g = modexp::gcd(base % n, n);
+    if g > 1 && g < n {
+        return Ok(Some(g));
+    }
+    // The powers stay in the representation of the strategy, whose scale is a unit, so
+    // gcd(a − 1, n) is taken on the scaled a − 1.
+    let mm = MulMod::new(n);
+    let one = mm.enter(1 % n);
+    let minus_one = |x: u64| if x >= one { x - one } else { x + (n - one) };
+    let mut a = mm.enter(base % n);
+    for &q in primes {
+        let mut qe = q;
+        while qe <= bound / q {
+            qe *= q;
+        }
+        let next = mm.pow_scaled(a, qe);
+        let g = modexp::gcd(minus_one(next), n);
+        if g == n {
+            // Retry this prime one power at a time.
+            let mut b = a;
+            loop {
+                b = mm.pow_scaled(b, q);
+                let g = modexp::gcd(minus_one(b), n);
+                if g > 1 && g < n {
+                    return Ok(Some(g));
+                }
+                if b == 0 || b == one {
+                    return Err(());
+                }
+            }
+        }
+        if g > 1 {
+            return Ok(Some(g));
+        }
+        a = next;
+    }
+    Ok(None)
+}
+
+/// Williams' p+1 factorization method (stage 1).
+///
+/// Replaces the powering of p−1 by the Lucas sequence `V_0 = 2, V_1 = A,
+/// V_k = A·V_(k-1) - V_(k-2)`. When `A² - 4` is a quadratic non-residue modulo a prime
+/// `p | n` and `p + 1` is `bound`-smooth, `p` divides `V_M(A) - 2` for `M` the product of
+/// all maximal prime powers `<= bound`. Several seeds `A` are tried since the residue
+/// condition cannot be checked without knowing `p`.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `bound` - The smoothness bound B.
+///
+/// # Returns
+///
+/// A non-trivial factor of `n`, or `None` if the method failed for this bound.
+pub fn williams_p_plus_1(n: u64, bound: u64) -> Option<u64> {
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let primes = sieve::primes_up_to(bound as usize);
+    [3, 5, 7, 9, 11, 17].iter().find_map(|&a| p_plus_1_stage1(n, &primes, bound, a))
+}
+
+// Runs stage 1 of p+1 with the Lucas seed `a`.
+fn p_plus_1_stage1(n: u64, primes: &[u64], bound: u64, a: u64) -> Option<u64> {
+    let g = modexp::gcd(a % n, n);
+    if g > 1 && g < n {
+        return Some(g);
+    }
+    // Like p−1, the sequence stays in the representation of the strategy.
+    let mm = MulMod::new(n);
+    let two = mm.enter(2 % n);
+    let mut v = mm.enter(a % n);
+    for &q in primes {
+        let mut qe = q;
+        while qe <= bound / q {
+            qe *= q;
+        }
+        v = lucas_v(&mm, v, qe);
+        let g = modexp::gcd(if v >= two { v - two } else { v + (n - two) }, n);
+        if g == n {
+            return None;
+        }
+        if g > 1 {
+            return Some(g);
+        }
+    }
+    None
+}
+
+// Computes the Lucas sequence term V_m(a) modulo the modulus of `mm` with a Montgomery
+// ladder, using V_2k = V_k² - 2 and V_(2k+1) = V_k·V_(k+1) - a. Both `a` and the result are
+// in the representation of the strategy.
+fn lucas_v(mm: &MulMod, a: u64, m: u64) -> u64 {
+    let n = mm.modulus();
+    let sub = |x: u64, y: u64| if x >= y { x - y } else { x + (n - y) };
+    let two = mm.enter(2 % n);
+    let mut x = a;
+    let mut y = sub(mm.mul_scaled(a, a), two);
+    for bit in (0..63 - m.leading_zeros()).rev() {
+        if (m >> bit) & 1 == 1 {
+            x = sub(mm.mul_scaled(x, y), a);
+            y = sub(mm.mul_scaled(y, y), two);
+        } else {
+            y = sub(mm.mul_scaled(x, y), a);
+            x = sub(mm.mul_scaled(x, x), two);
+        }
+    }
+    x
 }
 
 /// Factorizes `n` into its prime factors.
 ///
+/// Primes below `DEFAULT_TRIAL_LIMIT` are stripped by trial division first, the remaining
+/// cofactor is split with Fermat's method, Pollard's p−1, Williams' p+1 and Pollard's rho.
+///
 /// # Arguments
 ///
 /// * `n` - The number to factorize.
@@ -89,19 +381,393 @@ pub fn pollard(n: u64) -> u64 {
 /// # Returns
 ///
 /// A vector containing the prime factors of `n` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
 pub fn factor(n: u64) -> Vec<u64> {
+    factor_with_trial_limit(n, DEFAULT_TRIAL_LIMIT)
+}
+
+/// Factorizes `n`, trial-dividing by every candidate below `trial_limit` on a 2·3·5 wheel
+/// before handing the cofactor to Fermat's method, Pollard's p−1, Williams' p+1 and
+/// Pollard's rho.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `trial_limit` - Candidates below this bound are tried by division.
+///
+/// # Returns
+///
+/// A vector containing the prime factors of `n` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn factor_with_trial_limit(n: u64, trial_limit: u64) -> Vec<u64> {
+    if n == 0 {
+        panic!("n cannot be zero!");
+    }
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("factor", n, trial_limit).entered();
+    let mut factors = Vec::new();
+    let mut push = |p| factors.push(p);
+    let m = trial_divide(n, trial_limit, &mut push);
+    #[cfg(feature = "tracing")]
+    tracing::trace!(cofactor = m, "trial division done");
+    factor_rho(m, &mut push, &(), None);
+    factors.sort();
+    factors
+}
+
+/// Factorizes `n` like `factor`, reporting progress to `hook` and giving up when it
+/// cancels, so that a hard factorization can be aborted from another thread.
+///
+/// Progress is counted in bits: `hook.report(done, total)` is called whenever a prime
+/// factor is found, with `done` the bit length of the part of `n` factorized so far and
+/// `total` that of `n`.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `hook` - Receives progress reports and may cancel the factorization.
+///
+/// # Returns
+///
+/// The prime factors of `n` in sorted order, `MathCombError::InvalidArgument` if `n` is 0,
+/// or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn factor_with_progress(n: u64, hook: &impl ProgressHook) -> Result<Vec<u64>, MathCombError> {
+    if n == 0 {
+        return Err(MathCombError::InvalidArgument("n cannot be zero"));
+    }
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("factor_with_progress", n).entered();
+    let mut factors = Vec::new();
+    let mut found: u64 = 1;
+    let mut push = |p| {
+        factors.push(p);
+        found *= p;
+        hook.report(bit_length(found), bit_length(n));
+    };
+    let m = trial_divide(n, DEFAULT_TRIAL_LIMIT, &mut push);
+    if factor_rho(m, &mut push, hook, None).is_none() {
+        return Err(MathCombError::Cancelled);
+    }
+    factors.sort();
+    Ok(factors)
+}
+
+fn bit_length(x: u64) -> u64 {
+    (u64::BITS - x.leading_zeros()) as u64
+}
+
+/// Factorizes `n` like `factor`, drawing the starting points of Pollard's rho restarts
+/// from `rng`. The factors do not depend on `rng`, only the running time does.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `rng` - The source of the starting points.
+///
+/// # Returns
+///
+/// A vector containing the prime factors of `n` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn factor_with_rng(n: u64, rng: &mut impl RandomSource) -> Vec<u64> {
+    if n == 0 {
+        panic!("n cannot be zero!");
+    }
+    let mut factors = Vec::new();
+    let mut push = |p| factors.push(p);
+    let m = trial_divide(n, DEFAULT_TRIAL_LIMIT, &mut push);
+    factor_rho(m, &mut push, &(), Some(rng));
+    factors.sort();
+    factors
+}
+
+/// Factorizes `n` like `factor`, writing the prime factors into `buf` instead of allocating
+/// a vector, for hot loops over many numbers.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+/// * `buf` - The buffer receiving the factors; `MAX_FACTORS` entries always suffice.
+///
+/// # Returns
+///
+/// The number of prime factors of `n`, which fill `buf[..count]` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0 or `buf` is too short for the factors of `n`.
+pub fn factor_into(n: u64, buf: &mut [u64]) -> usize {
+    if n == 0 {
+        panic!("n cannot be zero!");
+    }
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("factor_into", n).entered();
+    let mut len = 0;
+    let mut push = |p| {
+        if len == buf.len() {
+            panic!("buf is too short for the factors of n!");
+        }
+        buf[len] = p;
+        len += 1;
+    };
+    let m = trial_divide(n, DEFAULT_TRIAL_LIMIT, &mut push);
+    factor_rho(m, &mut push, &(), None);
+    buf[..len].sort_unstable();
+    len
+}
+
+/// Factorizes `n` like `factor`, returning the factors stored inline in a `Factors` rather
+/// than in a heap-allocated vector.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+///
+/// # Returns
+///
+/// The prime factors of `n` in sorted order.
+///
+/// # Panics
+///
+/// Panics if `n` is 0.
+pub fn factor_inline(n: u64) -> Factors {
+    Factors::from_fn(|buf| factor_into(n, buf))
+}
+
+/// The prime factors of a `u64`, held in a fixed inline array of `MAX_FACTORS` entries so
+/// that no heap allocation is made. It dereferences to the slice of factors.
+#[derive(Clone, Copy, Debug)]
+pub struct Factors {
+    buf: [u64; MAX_FACTORS],
+    len: usize,
+}
+
+impl Factors {
+    // Lets `fill` write the factors into a fresh buffer and return their count.
+    pub(crate) fn from_fn(fill: impl FnOnce(&mut [u64]) -> usize) -> Factors {
+        let mut buf = [0; MAX_FACTORS];
+        let len = fill(&mut buf);
+        Factors { buf, len }
+    }
+}
+
+impl Deref for Factors {
+    type Target = [u64];
+
+    fn deref(&self) -> &[u64] {
+        &self.buf[..self.len]
+    }
+}
+
+impl PartialEq for Factors {
+    fn eq(&self, other: &Factors) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Factors {}
+
+impl<'a> IntoIterator for &'a Factors {
+    type Item = &'a u64;
+    type IntoIter = std::slice::Iter<'a, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Passes every prime factor of `n` below `limit` to `push` and returns the cofactor.
+fn trial_divide(n: u64, limit: u64, push: &mut impl FnMut(u64)) -> u64 {
+    let mut m = n;
+    for p in [2, 3, 5] {
+        if p >= limit {
+            return m;
+        }
+        while m.is_multiple_of(p) {
+            push(p);
+            m /= p;
+        }
+    }
+    let mut d: u64 = 7;
+    let mut i = 0;
+    while d < limit && d <= m / d {
+        while m.is_multiple_of(d) {
+            push(d);
+            m /= d;
+        }
+        d += WHEEL_30[i];
+        i = (i + 1) % WHEEL_30.len();
+    }
+    // No divisor up to √m remains, so the cofactor is prime.
+    if m > 1 && d > m / d {
+        push(m);
+        m = 1;
+    }
+    m
+}
+
+// Factorizes `n` without trial division by trying Fermat, p−1, p+1 and finally rho, passing
+// the prime factors to `push` in no particular order. Rho restarts from the points drawn
+// from `rng`, or from 1, 2, 3, … without one. Returns `None` if `hook` cancels during rho,
+// the only stage without a bound on its running time.
+fn factor_rho<'r>(
+    n: u64,
+    push: &mut impl FnMut(u64),
+    hook: &dyn ProgressHook,
+    mut rng: Option<&mut (dyn RandomSource + 'r)>,
+) -> Option<()> {
     if n == 1 {
-        return vec![];
+        return Some(());
+    }
+    if is_prime(n) {
+        push(n);
+        return Some(());
+    }
+    let primes = stage1_primes();
+    let x = fermat::fermat(n, FERMAT_STEPS)
+        .inspect(|&x| trace_split(n, x, "fermat"))
+        .or_else(|| p_minus_1(n, primes, P_MINUS_1_BOUND).inspect(|&x| trace_split(n, x, "p-1")))
+        .or_else(|| {
+            let k = primes.partition_point(|&q| q <= P_PLUS_1_BOUND);
+            p_plus_1_stage1(n, &primes[..k], P_PLUS_1_BOUND, 3).inspect(|&x| trace_split(n, x, "p+1"))
+        })
+        .or_else(|| {
+            let x = match rng.as_deref_mut() {
+                Some(rng) => pollard_until(n, hook, rng),
+                None => pollard_until(n, hook, &mut Restarts::default()),
+            };
+            x.inspect(|&x| trace_split(n, x, "rho"))
+        })?;
+    factor_rho(x, push, hook, rng.as_deref_mut())?;
+    factor_rho(n / x, push, hook, rng)
+}
+
+// Records with the `tracing` feature which method of `factor_rho` split `n`.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn trace_split(n: u64, factor: u64, method: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(n, factor, method, "split");
+}
+
+/// Factorizes `n` into distinct primes paired with their multiplicities.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+///
+/// # Returns
+///
+/// A vector of `(prime, exponent)` pairs sorted by prime.
+pub fn factor_map(n: u64) -> Vec<(u64, u32)> {
+    let mut map: Vec<(u64, u32)> = Vec::new();
+    for p in factor(n) {
+        match map.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => map.push((p, 1)),
+        }
+    }
+    map
+}
+
+/// Finds the smallest prime factor of `n`.
+///
+/// Small primes are trial-divided first; only if none of them divides `n` is the
+/// factorization split further with Pollard's rho.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// The smallest prime dividing `n`, or `None` if `n` < 2.
+pub fn smallest_prime_factor(n: u64) -> Option<u64> {
+    if n < 2 {
+        return None;
+    }
+    for &p in &SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return Some(p);
+        }
+    }
+    Some(min_prime_factor(n))
+}
+
+// Smallest prime factor of an `n` > 1 without any factor below 100.
+fn min_prime_factor(n: u64) -> u64 {
+    if is_prime(n) {
+        return n;
+    }
+    let x = pollard(n);
+    min_prime_factor(x).min(min_prime_factor(n / x))
+}
+
+/// Finds the largest prime factor of `n` without materializing the full factorization.
+///
+/// # Arguments
+///
+/// * `n` - The number to inspect.
+///
+/// # Returns
+///
+/// The largest prime dividing `n`, or `None` if `n` < 2.
+pub fn largest_prime_factor(n: u64) -> Option<u64> {
+    if n < 2 {
+        return None;
+    }
+    let mut m = n;
+    let mut largest = 1;
+    for &p in &SMALL_PRIMES {
+        while m.is_multiple_of(p) {
+            m /= p;
+            largest = p;
+        }
     }
+    if m > 1 {
+        largest = max_prime_factor(m);
+    }
+    Some(largest)
+}
+
+// Largest prime factor of an `n` > 1.
+fn max_prime_factor(n: u64) -> u64 {
     if is_prime(n) {
-        return vec![n];
+        return n;
     }
     let x = pollard(n);
-    let mut l = factor(x);
-    let mut r = factor(n / x);
-    l.append(&mut r);
-    l.sort();
-    return l;
+    max_prime_factor(x).max(max_prime_factor(n / x))
+}
+
+/// Factorizes every number in `ns` into its prime factors.
+///
+/// With the `rayon` feature enabled the work is spread across all available cores,
+/// otherwise the numbers are factorized one after another.
+///
+/// # Arguments
+///
+/// * `ns` - The numbers to factorize.
+///
+/// # Returns
+///
+/// A vector whose `i`-th entry holds the sorted prime factors of `ns[i]`.
+pub fn factor_batch(ns: &[u64]) -> Vec<Vec<u64>> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        ns.par_iter().map(|&n| factor(n)).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        ns.iter().map(|&n| factor(n)).collect()
+    }
 }
 
 #[cfg(test)]
@@ -110,13 +776,64 @@ mod tests {
 
     #[test]
     fn test_is_prime() {
-        assert_eq!(is_prime(2), true);
-        assert_eq!(is_prime(1000000007), true);
-        assert_eq!(is_prime(21), false);
-        assert_eq!(is_prime(10079), true);
-        assert_eq!(is_prime(1000429), true);
-        assert_eq!(is_prime(1000013), false);
-        assert_eq!(is_prime(1000067), false);
+        assert!(is_prime(2));
+        assert!(is_prime(1000000007));
+        assert!(!is_prime(21));
+        assert!(is_prime(10079));
+        assert!(is_prime(1000429));
+        assert!(!is_prime(1000013));
+        assert!(!is_prime(1000067));
+    }
+
+    #[test]
+    fn test_modmul() {
+        // Reducing both operands and multiplying in u64 overflowed for moduli above 2^32.
+        assert_eq!(modmul(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+        assert_eq!(modmul(1 << 40, 1 << 40, (1 << 61) - 1), 1 << 19);
+        assert_eq!(modmul(999999999999999988, 2, 999999999999999989), 999999999999999987);
+        assert!(is_prime(999999999999999989));
+    }
+
+    #[test]
+    fn test_miller_rabin_witness() {
+        // 2047 = 23 * 89 is the smallest strong pseudoprime to base 2.
+        assert!(miller_rabin_witness(2047, 2));
+        assert!(!miller_rabin_witness(2047, 3));
+        // 3215031751 is a strong pseudoprime to bases 2, 3, 5 and 7 but not 11.
+        for a in [2, 3, 5, 7] {
+            assert!(miller_rabin_witness(3215031751, a));
+        }
+        assert!(!miller_rabin_witness(3215031751, 11));
+        assert!(miller_rabin_witness(1000000007, 2));
+        assert!(miller_rabin_witness(7, 14));
+        assert!(miller_rabin_witness(2, 5));
+        assert!(!miller_rabin_witness(1, 2));
+        assert!(!miller_rabin_witness(10, 3));
+    }
+
+    #[test]
+    fn test_find_witness() {
+        assert_eq!(find_witness(0), None);
+        assert_eq!(find_witness(1), None);
+        assert_eq!(find_witness(2), None);
+        assert_eq!(find_witness(1000000007), None);
+        assert_eq!(find_witness(4), Some(2));
+        assert_eq!(find_witness(2047), Some(325));
+        assert_eq!(find_witness(3215031751), Some(325));
+        for n in 2..10000u64 {
+            match find_witness(n) {
+                Some(a) => assert!(!is_prime(n) && !miller_rabin_witness(n, a)),
+                None => assert!(is_prime(n)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_prime_small() {
+        let primes = crate::sieve::primes_up_to(100000);
+        for n in 0..100000u64 {
+            assert_eq!(is_prime(n), primes.binary_search(&n).is_ok(), "n = {}", n);
+        }
     }
 
     #[test]
@@ -127,5 +844,210 @@ mod tests {
         assert_eq!(factor(1000000007), vec![1000000007]);
         assert_eq!(factor(346789), vec![239, 1451]);
         assert_eq!(factor(34486788), vec![2, 2, 3, 7, 7, 89, 659]);
+        assert_eq!(factor(4294967291 * 4294967279), vec![4294967279, 4294967291]);
+    }
+
+    #[test]
+    fn test_pollard_p_minus_1() {
+        // 1000005751 - 1 = 2 * 3^7 * 5^3 * 31 * 59, while 4294967291 - 1 has the prime factor 22605091.
+        let n = 1000005751 * 4294967291;
+        assert_eq!(pollard_p_minus_1(n, 2200), Some(1000005751));
+        assert_eq!(pollard_p_minus_1(n, 50), None);
+        assert_eq!(pollard_p_minus_1(1000000007, 10000), None);
+        assert_eq!(pollard_p_minus_1(1 << 40, 10), Some(2));
+        assert_eq!(pollard_p_minus_1(9, 10), Some(3));
+        // With base 2 both primes of 91 = 7 * 13 are found at the same step.
+        let x = pollard_p_minus_1(7 * 13, 100).unwrap();
+        assert!(x == 7 || x == 13);
+    }
+
+    #[test]
+    fn test_lucas_v() {
+        // V_k(3) = 2, 3, 7, 18, 47, 123, 322, ...
+        let seq = [3, 7, 18, 47, 123, 322];
+        // The second modulus runs in Montgomery form.
+        for n in [1000000007, 1000000000000000003] {
+            let mm = MulMod::new(n);
+            for (k, &v) in seq.iter().enumerate() {
+                assert_eq!(mm.leave(lucas_v(&mm, mm.enter(3), k as u64 + 1)), v);
+            }
+        }
+        assert_eq!(lucas_v(&MulMod::new(100), 3, 6), 22);
+    }
+
+    #[test]
+    fn test_williams_p_plus_1() {
+        // 4294967291 + 1 = 2^2 * 3^2 * 7 * 11 * 31 * 151 * 331, while both 1000000007 - 1
+        // and 1000000007 + 1 contain large prime factors.
+        let n = 4294967291 * 1000000007;
+        assert_eq!(williams_p_plus_1(n, 400), Some(4294967291));
+        assert_eq!(williams_p_plus_1(n, 100), None);
+        assert_eq!(williams_p_plus_1(1000000007, 10000), None);
+        assert_eq!(williams_p_plus_1(1 << 20, 10), Some(2));
+    }
+
+    #[test]
+    fn test_factor_with_trial_limit() {
+        let n = 2 * 2 * 3 * 7 * 97 * 101 * 1000003;
+        for limit in [0, 2, 3, 7, 50, 100, 1000, 2000000] {
+            assert_eq!(factor_with_trial_limit(n, limit), vec![2, 2, 3, 7, 97, 101, 1000003]);
+        }
+        assert_eq!(factor_with_trial_limit(1, 100), vec![]);
+        assert_eq!(factor_with_trial_limit(97 * 97, 100), vec![97, 97]);
+        assert_eq!(factor_with_trial_limit(1000000007, 1 << 20), vec![1000000007]);
+        for n in 1..2000 {
+            assert_eq!(factor_with_trial_limit(n, 30).iter().product::<u64>(), n);
+        }
+    }
+
+    #[test]
+    fn test_factor_into() {
+        let mut buf = [0; MAX_FACTORS];
+        let ns = [1, 2, 360, 1 << 63, 97 * 97, 1000000007, 999999999999999989, 1152921504606846883, u64::MAX];
+        for n in ns {
+            let count = factor_into(n, &mut buf);
+            assert_eq!(buf[..count], factor(n)[..]);
+            assert_eq!(*factor_inline(n), factor(n)[..]);
+        }
+        assert_eq!(factor_into(1 << 63, &mut buf), 63);
+        assert_eq!(factor_inline(12).iter().sum::<u64>(), 7);
+        assert_eq!((&factor_inline(30)).into_iter().count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "buf is too short for the factors of n!")]
+    fn test_factor_into_short_buffer() {
+        factor_into(1 << 10, &mut [0; 9]);
+    }
+
+    #[test]
+    fn test_factor_with_progress() {
+        use crate::CancelToken;
+        use std::sync::Mutex;
+
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        let n = 360 * 1000000007;
+        assert_eq!(factor_with_progress(n, &hook), Ok(factor(n)));
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 7);
+        assert_eq!(reports.last(), Some(&(39, 39)));
+        assert!(reports.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(factor_with_progress(0, &()), Err(MathCombError::InvalidArgument("n cannot be zero")));
+        assert_eq!(factor_with_progress(1, &()), Ok(vec![]));
+
+        // A semiprime that only rho splits stops at once; easy numbers still finish.
+        let token = CancelToken::new();
+        token.cancel();
+        assert_eq!(factor_with_progress(1000000007 * 998244853, &token), Err(MathCombError::Cancelled));
+        assert_eq!(factor_with_progress(2147483647 * 1000000009, &token), Ok(vec![1000000009, 2147483647]));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_split_methods() {
+        use std::fmt::Debug;
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // Collects the `method` field of every event.
+        struct Methods(Mutex<Vec<String>>);
+        struct MethodVisitor<'a>(&'a mut Vec<String>);
+        impl Visit for MethodVisitor<'_> {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "method" {
+                    self.0.push(value.to_string());
+                }
+            }
+            fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+        }
+        impl Subscriber for &'static Methods {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut MethodVisitor(&mut self.0.lock().unwrap()));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let methods: &'static Methods = Box::leak(Box::new(Methods(Mutex::new(Vec::new()))));
+        tracing::subscriber::with_default(methods, || {
+            assert_eq!(factor(1000003 * 1000033), vec![1000003, 1000033]);
+            assert_eq!(factor(2147483647 * 1000000009), vec![1000000009, 2147483647]);
+            assert_eq!(factor(1000000007 * 998244853), vec![998244853, 1000000007]);
+        });
+        assert_eq!(*methods.0.lock().unwrap(), vec!["fermat", "p-1", "rho"]);
+    }
+
+    #[test]
+    fn test_with_rng() {
+        use crate::SplitMix64;
+
+        let n = 1000000007 * 998244853;
+        for seed in 0..5 {
+            let mut rng = SplitMix64::new(seed);
+            let d = pollard_with_rng(n, &mut rng);
+            assert!(d == 1000000007 || d == 998244853);
+            assert_eq!(factor_with_rng(n * 12, &mut rng), factor(n * 12));
+        }
+        // The same seed takes the same path.
+        let (mut a, mut b) = (SplitMix64::new(9), SplitMix64::new(9));
+        assert_eq!(pollard_with_rng(1000003 * 999983, &mut a), pollard_with_rng(1000003 * 999983, &mut b));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be zero!")]
+    fn test_factor_zero() {
+        factor(0);
+    }
+
+    #[test]
+    fn test_factor_map() {
+        assert_eq!(factor_map(1), vec![]);
+        assert_eq!(factor_map(24), vec![(2, 3), (3, 1)]);
+        assert_eq!(factor_map(34486788), vec![(2, 2), (3, 1), (7, 2), (89, 1), (659, 1)]);
+        assert_eq!(factor_map(1000000007), vec![(1000000007, 1)]);
+    }
+
+    #[test]
+    fn test_smallest_prime_factor() {
+        assert_eq!(smallest_prime_factor(0), None);
+        assert_eq!(smallest_prime_factor(1), None);
+        assert_eq!(smallest_prime_factor(2), Some(2));
+        assert_eq!(smallest_prime_factor(45), Some(3));
+        assert_eq!(smallest_prime_factor(346789), Some(239));
+        assert_eq!(smallest_prime_factor(1000000007), Some(1000000007));
+        assert_eq!(smallest_prime_factor(1000003 * 1000033), Some(1000003));
+    }
+
+    #[test]
+    fn test_largest_prime_factor() {
+        assert_eq!(largest_prime_factor(1), None);
+        assert_eq!(largest_prime_factor(2), Some(2));
+        assert_eq!(largest_prime_factor(1024), Some(2));
+        assert_eq!(largest_prime_factor(1000429 * 15), Some(1000429));
+        assert_eq!(largest_prime_factor(34486788), Some(659));
+        assert_eq!(largest_prime_factor(1000003 * 1000033), Some(1000033));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_factor_batch() {
+        let ns = [1, 24, 1000000007, 1000429 * 15, 999999999999999989, 1152921504606846883];
+        let factors = factor_batch(&ns);
+        assert_eq!(factors.len(), ns.len());
+        for (&n, f) in ns.iter().zip(factors.iter()) {
+            assert_eq!(*f, factor(n));
+            assert_eq!(f.iter().product::<u64>(), n);
+        }
+    }
+}
@@ -0,0 +1,1486 @@
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use crate::{modexp, ntt, pollard};
+
+/// A polynomial with coefficients modulo a prime `p`, stored lowest degree first without
+/// trailing zero coefficients.
+///
+/// Products use the number-theoretic transform directly when `p` is NTT-friendly, such as
+/// 998244353, and fall back to the three-prime CRT convolution otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Poly {
+    coeffs: Vec<u64>,
+    p: u64,
+}
+
+// Computes the inverses of 1..=n modulo the prime p, with inv[0] unused.
+pub(crate) fn inverses(n: usize, p: u64) -> Vec<u64> {
+    let mut inv = vec![0, 1];
+    for i in 2..=n as u64 {
+        inv.push((p - p / i) * inv[(p % i) as usize] % p);
+    }
+    inv.truncate(n + 1);
+    inv
+}
+
+// Returns 0!, 1!, …, n! and their inverses modulo the prime p > n.
+pub(crate) fn factorials(n: usize, p: u64) -> (Vec<u64>, Vec<u64>) {
+    let inv = inverses(n, p);
+    let mut fact = vec![1; n + 1];
+    let mut inv_fact = vec![1; n + 1];
+    for i in 1..=n {
+        fact[i] = fact[i - 1] * i as u64 % p;
+        inv_fact[i] = inv_fact[i - 1] * inv[i] % p;
+    }
+    (fact, inv_fact)
+}
+
+/// Divisors with at most this many coefficients use schoolbook long division.
+const NAIVE_DIVISOR_LEN: usize = 32;
+
+/// Below this many points, polynomials are evaluated one point at a time with Horner's rule.
+const NAIVE_EVAL_LEN: usize = 32;
+
+/// Below this degree, the half-GCD algorithm takes plain Euclidean steps.
+const NAIVE_GCD_DEGREE: usize = 64;
+
+/// A 2 × 2 matrix of polynomials `[[a, b], [c, d]]` acting on pairs of consecutive remainders.
+struct PolyMatrix([Poly; 4]);
+
+impl PolyMatrix {
+    fn identity(p: u64) -> PolyMatrix {
+        let (zero, one) = (Poly::from_reduced(vec![], p), Poly::from_reduced(vec![1], p));
+        PolyMatrix([one.clone(), zero.clone(), zero, one])
+    }
+
+    // The step (a, b) ↦ (b, a − q·b) of the Euclidean algorithm.
+    fn quotient(q: &Poly) -> PolyMatrix {
+        let p = q.p;
+        let (zero, one) = (Poly::from_reduced(vec![], p), Poly::from_reduced(vec![1], p));
+        PolyMatrix([zero, one.clone(), one, -q])
+    }
+
+    fn apply(&self, a: &Poly, b: &Poly) -> (Poly, Poly) {
+        let [m00, m01, m10, m11] = &self.0;
+        (&(m00 * a) + &(m01 * b), &(m10 * a) + &(m11 * b))
+    }
+
+    // Returns self · other, the transform that applies other first.
+    fn then(&self, other: &PolyMatrix) -> PolyMatrix {
+        let [a, b, c, d] = &self.0;
+        let [e, f, g, h] = &other.0;
+        PolyMatrix([&(a * e) + &(b * g), &(a * f) + &(b * h), &(c * e) + &(d * g), &(c * f) + &(d * h)])
+    }
+}
+
+// Drops the k lowest coefficients, i.e. computes f div x^k.
+fn shifted_down(f: &Poly, k: usize) -> Poly {
+    Poly::from_reduced(f.coeffs.get(k..).unwrap_or(&[]).to_vec(), f.p)
+}
+
+// Divides a by b, records the degree and leading coefficient of the quotient, and returns it
+// with the remainder.
+fn euclid_step(a: &Poly, b: &Poly, quotients: &mut Vec<(usize, u64)>) -> (Poly, Poly) {
+    let (q, r) = a.div_rem(b);
+    quotients.push((q.coeffs.len() - 1, *q.coeffs.last().unwrap()));
+    (q, r)
+}
+
+// For deg a = n > deg b, returns the product M of the Euclidean steps that take (a, b) to the
+// consecutive remainders (c, d) with deg c ≥ ⌈n/2⌉ > deg d, recording their quotients.
+//
+// The quotients while the remainders stay above degree ⌈n/2⌉ + ⌈n/4⌉ only depend on the
+// coefficients of degree ⌈n/2⌉ and up, which a first recursive call handles; after one
+// explicit step, a second recursive call on the top coefficients finishes the way down.
+fn half_gcd(a: &Poly, b: &Poly, quotients: &mut Vec<(usize, u64)>) -> PolyMatrix {
+    let p = a.p;
+    let n = a.coeffs.len() - 1;
+    let m = n.div_ceil(2);
+    if b.coeffs.len() <= m {
+        return PolyMatrix::identity(p);
+    }
+    if n < NAIVE_GCD_DEGREE {
+        let mut matrix = PolyMatrix::identity(p);
+        let (mut a, mut b) = (a.clone(), b.clone());
+        while b.coeffs.len() > m {
+            let (q, r) = euclid_step(&a, &b, quotients);
+            matrix = PolyMatrix::quotient(&q).then(&matrix);
+            (a, b) = (b, r);
+        }
+        return matrix;
+    }
+    let first = half_gcd(&shifted_down(a, m), &shifted_down(b, m), quotients);
+    let (c, d) = first.apply(a, b);
+    if d.coeffs.len() <= m {
+        return first;
+    }
+    let (q, e) = euclid_step(&c, &d, quotients);
+    let k = 2 * m - (d.coeffs.len() - 1);
+    let second = half_gcd(&shifted_down(&d, k), &shifted_down(&e, k), quotients);
+    second.then(&PolyMatrix::quotient(&q)).then(&first)
+}
+
+// Runs the Euclidean algorithm on a and b with deg a ≥ deg b, recording every quotient, and
+// returns the last non-zero remainder (zero if both are zero).
+fn euclid(a: &Poly, b: &Poly, quotients: &mut Vec<(usize, u64)>) -> Poly {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while !b.is_zero() {
+        if a.coeffs.len() > b.coeffs.len() && a.coeffs.len() > NAIVE_GCD_DEGREE {
+            (a, b) = half_gcd(&a, &b, quotients).apply(&a, &b);
+            if b.is_zero() {
+                break;
+            }
+        }
+        let (_, r) = euclid_step(&a, &b, quotients);
+        (a, b) = (b, r);
+    }
+    a
+}
+
+/// The products of the linear factors `x - points[i]` over the nodes of a segment tree, with
+/// node `v` covering a range of points and its children `2v` and `2v + 1` splitting it.
+struct ProductTree<'a> {
+    points: &'a [u64],
+    nodes: Vec<Poly>,
+}
+
+impl<'a> ProductTree<'a> {
+    fn new(points: &'a [u64], p: u64) -> ProductTree<'a> {
+        let mut tree = ProductTree { points, nodes: vec![Poly::from_reduced(vec![], p); 4 * points.len()] };
+        tree.build(1, 0, points.len(), p);
+        tree
+    }
+
+    fn build(&mut self, v: usize, l: usize, r: usize, p: u64) {
+        if r - l == 1 {
+            self.nodes[v] = Poly::from_reduced(vec![(p - self.points[l] % p) % p, 1], p);
+            return;
+        }
+        let mid = (l + r) / 2;
+        self.build(2 * v, l, mid, p);
+        self.build(2 * v + 1, mid, r, p);
+        self.nodes[v] = &self.nodes[2 * v] * &self.nodes[2 * v + 1];
+    }
+
+    // Appends the values at points[l..r] of f, which is already reduced modulo node v.
+    fn eval(&self, v: usize, l: usize, r: usize, f: &Poly, values: &mut Vec<u64>) {
+        if r - l <= NAIVE_EVAL_LEN {
+            values.extend(self.points[l..r].iter().map(|&x| f.eval(x)));
+            return;
+        }
+        let mid = (l + r) / 2;
+        self.eval(2 * v, l, mid, &(f % &self.nodes[2 * v]), values);
+        self.eval(2 * v + 1, mid, r, &(f % &self.nodes[2 * v + 1]), values);
+    }
+
+    // Computes Σ weights[i]·Π_{j≠i} (x - points[j]) over i, j in l..r.
+    fn combine(&self, v: usize, l: usize, r: usize, weights: &[u64]) -> Poly {
+        let p = self.nodes[v].p;
+        if r - l == 1 {
+            return Poly::from_reduced(vec![weights[l]], p);
+        }
+        let mid = (l + r) / 2;
+        let left = self.combine(2 * v, l, mid, weights);
+        let right = self.combine(2 * v + 1, mid, r, weights);
+        &(&left * &self.nodes[2 * v + 1]) + &(&right * &self.nodes[2 * v])
+    }
+}
+
+impl Poly {
+    /// Creates the polynomial `coeffs[0] + coeffs[1]·x + …` modulo `p`.
+    ///
+    /// # Arguments
+    ///
+    /// * `coeffs` - The coefficients, lowest degree first. They are reduced modulo `p` and
+    ///   trailing zeros are dropped.
+    /// * `p` - A prime modulus below 2^32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not below 2^32 or is not prime.
+    pub fn new(coeffs: Vec<u64>, p: u64) -> Poly {
+        if p >= 1 << 32 {
+            panic!("Modulus must be below 2^32!");
+        }
+        if !pollard::is_prime(p) {
+            panic!("modulus is not prime!");
+        }
+        Poly::from_reduced(coeffs.into_iter().map(|c| c % p).collect(), p)
+    }
+
+    // Wraps coefficients that are already reduced modulo the checked prime p.
+    pub(crate) fn from_reduced(mut coeffs: Vec<u64>, p: u64) -> Poly {
+        while coeffs.last() == Some(&0) {
+            coeffs.pop();
+        }
+        Poly { coeffs, p }
+    }
+
+    /// Returns the coefficients, lowest degree first, without trailing zeros.
+    pub fn coeffs(&self) -> &[u64] {
+        &self.coeffs
+    }
+
+    /// Returns the coefficient of `x^i`, which is 0 past the degree.
+    pub fn coeff(&self, i: usize) -> u64 {
+        self.coeffs.get(i).copied().unwrap_or(0)
+    }
+
+    /// Returns the modulus of the coefficients.
+    pub fn modulus(&self) -> u64 {
+        self.p
+    }
+
+    /// Returns the degree of the polynomial, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    /// Returns `true` if this is the zero polynomial.
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// Evaluates the polynomial at `x` with Horner's rule.
+    pub fn eval(&self, x: u64) -> u64 {
+        let x = x % self.p;
+        self.coeffs.iter().rev().fold(0, |acc, &c| (acc * x + c) % self.p)
+    }
+
+    /// Returns the formal derivative.
+    pub fn derivative(&self) -> Poly {
+        let coeffs = self.coeffs.iter().enumerate().skip(1).map(|(i, &c)| i as u64 % self.p * c % self.p).collect();
+        Poly::from_reduced(coeffs, self.p)
+    }
+
+    /// Returns the antiderivative with zero constant term.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the degree plus one is a multiple of `p`, so that a coefficient would have to
+    /// be divided by zero.
+    pub fn integral(&self) -> Poly {
+        if self.coeffs.len() as u64 >= self.p {
+            panic!("Degree is too large for the modulus!");
+        }
+        let inv = inverses(self.coeffs.len(), self.p);
+        let mut coeffs = vec![0];
+        coeffs.extend(self.coeffs.iter().enumerate().map(|(i, &c)| c * inv[i + 1] % self.p));
+        Poly::from_reduced(coeffs, self.p)
+    }
+
+    /// Returns the polynomial reduced modulo `x^n`, i.e. its first `n` coefficients.
+    pub fn truncated(&self, n: usize) -> Poly {
+        Poly::from_reduced(self.coeffs[..n.min(self.coeffs.len())].to_vec(), self.p)
+    }
+
+    /// Computes the shifted polynomial `f(x + c)` with a single convolution, from
+    /// `[x^j] f(x + c) = (1/j!)·Σ_i f_i·i!·c^(i−j)/(i−j)!`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the degree is not less than `p`, so that a factorial would vanish.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for degree n.
+    pub fn taylor_shift(&self, c: u64) -> Poly {
+        let (n, p) = (self.coeffs.len(), self.p);
+        if n == 0 {
+            return self.clone();
+        }
+        if n as u64 > p {
+            panic!("Degree is too large for the modulus!");
+        }
+        let (fact, inv_fact) = factorials(n - 1, p);
+        let a: Vec<u64> = self.coeffs.iter().zip(&fact).map(|(&x, &f)| x * f % p).rev().collect();
+        let mut power = 1;
+        let b: Vec<u64> = inv_fact
+            .iter()
+            .map(|&f| {
+                let term = power * f % p;
+                power = power * (c % p) % p;
+                term
+            })
+            .collect();
+        let product = multiply(&a, &b, p);
+        let coeffs = (0..n).map(|j| product[n - 1 - j] * inv_fact[j] % p).collect();
+        Poly::from_reduced(coeffs, p)
+    }
+
+    /// Computes the first `n` coefficients of the formal power series `1 / f` with Newton's
+    /// iteration `g ← g·(2 − f·g)`, which doubles the number of correct coefficients per step.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of coefficients wanted.
+    ///
+    /// # Returns
+    ///
+    /// The polynomial `g` of degree below `n` with `f·g ≡ 1 (mod x^n)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constant term of `f` is zero, so that `f` is not invertible.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn inv(&self, n: usize) -> Poly {
+        if self.coeff(0) == 0 {
+            panic!("Constant term cannot be zero!");
+        }
+        let p = self.p;
+        let mut g = vec![modexp::mod_inv(self.coeff(0), p)];
+        let mut len = 1;
+        while len < n {
+            len *= 2;
+            // e = f·g - 1 vanishes below x^(len/2), so g - g·e is correct below x^len.
+            let mut e = multiply(&self.coeffs[..len.min(self.coeffs.len())], &g, p);
+            e.resize(len, 0);
+            e[0] = (e[0] + p - 1) % p;
+            let mut ge = multiply(&g, &e[len / 2..], p);
+            ge.resize(len - len / 2, 0);
+            g.extend(ge.iter().map(|&x| (p - x) % p));
+        }
+        g.truncate(n);
+        Poly::from_reduced(g, p)
+    }
+
+    /// Computes the first `n` coefficients of the formal power series `log f` as the integral
+    /// of `f' / f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constant term of `f` is not 1, or if `n` exceeds `p` so that the integral
+    /// would divide by `p`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn log(&self, n: usize) -> Poly {
+        if self.coeff(0) != 1 {
+            panic!("Constant term must be one!");
+        }
+        if n == 0 {
+            return Poly::from_reduced(vec![], self.p);
+        }
+        (&self.derivative().truncated(n) * &self.inv(n)).truncated(n - 1).integral()
+    }
+
+    /// Computes the first `n` coefficients of the formal power series `exp f` with Newton's
+    /// iteration `g ← g·(1 − log g + f)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constant term of `f` is not zero, or if `n` exceeds `p`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn exp(&self, n: usize) -> Poly {
+        if self.coeff(0) != 0 {
+            panic!("Constant term must be zero!");
+        }
+        let one = Poly::from_reduced(vec![1], self.p);
+        let mut g = one.clone();
+        let mut len = 1;
+        while len < n {
+            len = (2 * len).min(n);
+            g = (&g * &(&(&self.truncated(len) - &g.log(len)) + &one)).truncated(len);
+        }
+        g.truncated(n)
+    }
+
+    /// Computes the first `n` coefficients of `f^k`.
+    ///
+    /// The lowest non-zero term `c·x^i` is factored out, so `f` may have a zero constant term,
+    /// and the rest is raised to the `k`-th power as `exp(k·log(f / (c·x^i)))`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds `p`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn pow(&self, k: u64, n: usize) -> Poly {
+        let p = self.p;
+        if k == 0 {
+            return Poly::from_reduced(vec![1], p).truncated(n);
+        }
+        let i = match self.coeffs.iter().position(|&c| c != 0) {
+            Some(i) if (i as u128) * (k as u128) < n as u128 => i,
+            _ => return Poly::from_reduced(vec![], p),
+        };
+        let shift = i * k as usize;
+        let c = self.coeffs[i];
+        let h = &Poly::from_reduced(self.coeffs[i..].to_vec(), p) / c;
+        let g = (&h.log(n - shift) * k).exp(n - shift) * modexp::mod_exp(c, k, p);
+        let mut coeffs = vec![0; shift];
+        coeffs.extend_from_slice(&g.coeffs);
+        Poly::from_reduced(coeffs, p)
+    }
+
+    /// Computes the first `n` coefficients of a square root of `f` with Newton's iteration
+    /// `g ← (g + f / g) / 2`.
+    ///
+    /// # Returns
+    ///
+    /// A power series `g` with `g² ≡ f (mod x^n)`, or `None` if `f` has no square root: its
+    /// lowest non-zero term `c·x^i` must have an even `i` and a quadratic residue `c`. Of the
+    /// two roots, the one whose lowest coefficient is the smaller square root of `c` is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is 2.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n).
+    pub fn sqrt(&self, n: usize) -> Option<Poly> {
+        let p = self.p;
+        if p == 2 {
+            panic!("Modulus cannot be 2!");
+        }
+        let i = match self.coeffs.iter().position(|&c| c != 0) {
+            Some(i) if i / 2 < n => i,
+            _ => return Some(Poly::from_reduced(vec![], p)),
+        };
+        if i % 2 == 1 {
+            return None;
+        }
+        let c = self.coeffs[i];
+        let shift = i / 2;
+        let f = Poly::from_reduced(self.coeffs[i..].to_vec(), p);
+        let mut g = Poly::from_reduced(vec![modexp::sqrt_mod(c, p)?], p);
+        let inv2 = p.div_ceil(2);
+        let mut len = 1;
+        while len < n - shift {
+            len *= 2;
+            g = (&g + &(&f.truncated(len) * &g.inv(len)).truncated(len)) * inv2;
+        }
+        let mut coeffs = vec![0; shift];
+        coeffs.extend_from_slice(&g.truncated(n - shift).coeffs);
+        Some(Poly::from_reduced(coeffs, p))
+    }
+
+    /// Divides by `other` with remainder.
+    ///
+    /// The quotient is the reversal of `rev(f)·rev(g)^(-1)` modulo `x^(deg f - deg g + 1)`, so
+    /// no long division is needed once the divisor has more than a few dozen terms.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The divisor `g`.
+    ///
+    /// # Returns
+    ///
+    /// `(q, r)` with `f = q·g + r` and `r` of lower degree than `g`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is the zero polynomial or the moduli differ.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n) for a dividend of degree n.
+    pub fn div_rem(&self, other: &Poly) -> (Poly, Poly) {
+        self.check_modulus(other);
+        if other.is_zero() {
+            panic!("Divisor cannot be zero!");
+        }
+        let p = self.p;
+        let (n, m) = (self.coeffs.len(), other.coeffs.len());
+        if n < m {
+            return (Poly::from_reduced(vec![], p), self.clone());
+        }
+        let k = n - m + 1;
+        if m <= NAIVE_DIVISOR_LEN {
+            let lead_inv = modexp::mod_inv(other.coeffs[m - 1], p);
+            let mut r = self.coeffs.clone();
+            let mut q = vec![0; k];
+            for i in (0..k).rev() {
+                let c = r[i + m - 1] * lead_inv % p;
+                q[i] = c;
+                for (j, &g) in other.coeffs.iter().enumerate() {
+                    r[i + j] = (r[i + j] + p - c * g % p) % p;
+                }
+            }
+            r.truncate(m - 1);
+            return (Poly::from_reduced(q, p), Poly::from_reduced(r, p));
+        }
+        let rev_f: Vec<u64> = self.coeffs.iter().rev().take(k).copied().collect();
+        let rev_g = Poly::from_reduced(other.coeffs.iter().rev().copied().collect(), p);
+        let mut q = multiply(&rev_f, &rev_g.inv(k).coeffs, p);
+        q.resize(k, 0);
+        q.reverse();
+        let q = Poly::from_reduced(q, p);
+        let r = (self - &(&q * other)).truncated(m - 1);
+        (q, r)
+    }
+
+    /// Computes the monic greatest common divisor.
+    ///
+    /// Large inputs use the half-GCD algorithm, which finds the quotients of the first half of
+    /// the Euclidean remainder sequence from the top halves of the coefficients alone, so
+    /// that the whole sequence costs a logarithmic number of fast multiplications per level.
+    ///
+    /// # Returns
+    ///
+    /// The monic gcd, or the zero polynomial if both inputs are zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the moduli differ.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log² n) for polynomials of degree n.
+    pub fn gcd(&self, other: &Poly) -> Poly {
+        self.check_modulus(other);
+        let (a, b) = if self.coeffs.len() >= other.coeffs.len() { (self, other) } else { (other, self) };
+        let g = euclid(a, b, &mut vec![]);
+        match g.coeffs.last() {
+            Some(&lead) => g.scale(modexp::mod_inv(lead, self.p)),
+            None => g,
+        }
+    }
+
+    /// Computes the resultant `res(f, g)`, the determinant of the Sylvester matrix, which
+    /// vanishes exactly when `f` and `g` share a root in the algebraic closure.
+    ///
+    /// Along the Euclidean remainder sequence, `res(f, g) = (−1)^(nm)·lc(g)^(n−k)·res(g, r)`
+    /// for `r = f mod g`, `deg f = n`, `deg g = m` and `deg r = k`. The degrees and leading
+    /// coefficients of the remainders follow from those of the quotients, which the half-GCD
+    /// algorithm produces without the remainders themselves.
+    ///
+    /// # Returns
+    ///
+    /// The resultant modulo `p`, taken as 0 if either polynomial is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the moduli differ.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log² n) for polynomials of degree n.
+    pub fn resultant(&self, other: &Poly) -> u64 {
+        self.check_modulus(other);
+        let p = self.p;
+        let (Some(n), Some(m)) = (self.degree(), other.degree()) else {
+            return 0;
+        };
+        let (f, g, mut result) = if n >= m { (self, other, 1) } else { (other, self, if n * m % 2 == 1 { p - 1 } else { 1 }) };
+        let mut quotients = vec![];
+        euclid(f, g, &mut quotients);
+        // (d0, c0) and (d1, c1) are the degrees and leading coefficients of consecutive
+        // remainders; every quotient after the first gives the next remainder.
+        let (mut d0, mut d1) = (f.coeffs.len() - 1, g.coeffs.len() - 1);
+        let mut c1 = g.coeffs[d1];
+        for &(deg_q, lead_q) in &quotients[1..] {
+            let d2 = d1 - deg_q;
+            if d0 * d1 % 2 == 1 {
+                result = (p - result) % p;
+            }
+            result = result * modexp::mod_exp(c1, (d0 - d2) as u64, p) % p;
+            c1 = c1 * modexp::mod_inv(lead_q, p) % p;
+            (d0, d1) = (d1, d2);
+        }
+        // The last remainder before zero is the gcd, which must be a constant.
+        if d1 > 0 {
+            return 0;
+        }
+        result * modexp::mod_exp(c1, d0 as u64, p) % p
+    }
+
+    /// Evaluates the polynomial at every point of `points` by reducing it down a product tree
+    /// of the linear factors `x - points[i]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The evaluation points.
+    ///
+    /// # Returns
+    ///
+    /// The values `f(points[i])`, in the order of `points`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log² n) for n points and a polynomial of degree O(n).
+    pub fn multi_eval(&self, points: &[u64]) -> Vec<u64> {
+        if points.len() <= NAIVE_EVAL_LEN {
+            return points.iter().map(|&x| self.eval(x)).collect();
+        }
+        let tree = ProductTree::new(points, self.p);
+        let mut values = Vec::with_capacity(points.len());
+        tree.eval(1, 0, points.len(), &(self % &tree.nodes[1]), &mut values);
+        values
+    }
+
+    /// Computes the first `n` coefficients of the composition `f(g(x))` with the
+    /// baby-step giant-step method of Brent and Kung.
+    ///
+    /// The coefficients of `f` are cut into about `√deg f` chunks; every chunk becomes a linear
+    /// combination of the baby steps `g^0, …, g^(k-1)`, and the chunks are joined with Horner's
+    /// rule in the giant step `g^k`.
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - The inner series.
+    /// * `n` - The number of coefficients wanted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the moduli differ.
+    ///
+    /// # Complexity
+    ///
+    /// O(m·n + √m·n log n) for `f` of degree m.
+    pub fn compose(&self, g: &Poly, n: usize) -> Poly {
+        self.check_modulus(g);
+        let p = self.p;
+        if n == 0 || self.is_zero() {
+            return Poly::from_reduced(vec![], p);
+        }
+        let m = self.coeffs.len();
+        let k = m.isqrt().max(1);
+        let g = g.truncated(n);
+        let mut baby = vec![Poly::from_reduced(vec![1], p)];
+        for i in 1..=k {
+            baby.push((&baby[i - 1] * &g).truncated(n));
+        }
+        let giant = baby.pop().unwrap();
+        let mut result = Poly::from_reduced(vec![], p);
+        for chunk in self.coeffs.chunks(k).rev() {
+            let mut sum = vec![0; n];
+            for (&c, power) in chunk.iter().zip(&baby) {
+                for (s, &x) in sum.iter_mut().zip(&power.coeffs) {
+                    *s = (*s + c * x) % p;
+                }
+            }
+            result = &(&result * &giant).truncated(n) + &Poly::from_reduced(sum, p);
+        }
+        result
+    }
+
+    /// Computes the modular composition `f(g) mod h` with the baby-step giant-step method of
+    /// Brent and Kung, as in `compose` but reducing modulo `h` instead of truncating.
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - The inner polynomial.
+    /// * `h` - The modulus polynomial.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `h` is the zero polynomial or the moduli differ.
+    ///
+    /// # Complexity
+    ///
+    /// O(m·n + √m·n log n) for `f` of degree m and `h` of degree n: √m baby steps and √m
+    /// giant steps cost a multiplication modulo `h` each, and combining the baby steps with
+    /// the coefficients of `f` costs O(n) per coefficient. For m ≈ n that is O(n²), not the
+    /// O(n^1.5) often quoted for Brent–Kung, which needs the combination to be done as a
+    /// fast matrix product.
+    pub fn compose_mod(&self, g: &Poly, h: &Poly) -> Poly {
+        self.check_modulus(g);
+        self.check_modulus(h);
+        let p = self.p;
+        let n = h.coeffs.len().saturating_sub(1);
+        if self.is_zero() {
+            return &Poly::from_reduced(vec![], p) % h;
+        }
+        let k = self.coeffs.len().isqrt().max(1);
+        let mut baby = vec![&Poly::from_reduced(vec![1], p) % h];
+        let g = g % h;
+        for i in 1..=k {
+            baby.push(&(&baby[i - 1] * &g) % h);
+        }
+        let giant = baby.pop().unwrap();
+        let mut result = Poly::from_reduced(vec![], p);
+        for chunk in self.coeffs.chunks(k).rev() {
+            let mut sum = vec![0; n];
+            for (&c, power) in chunk.iter().zip(&baby) {
+                for (s, &x) in sum.iter_mut().zip(&power.coeffs) {
+                    *s = (*s + c * x) % p;
+                }
+            }
+            result = &(&(&result * &giant) % h) + &Poly::from_reduced(sum, p);
+        }
+        result
+    }
+
+    /// Computes the first `n` coefficients of the compositional inverse `h` of `f`, the power
+    /// series with `f(h(x)) = h(f(x)) = x`, by Newton's iteration
+    /// `h ← h − (f(h) − x) / f'(h)`.
+    ///
+    /// Lagrange inversion, `[x^k] h = (1/k)·[x^(k−1)] (x / f)^k`, is the better tool for a
+    /// single coefficient: one `pow` of `x / f` gives it in O(k log k). It needs a different
+    /// power for every coefficient, though, so all `n` of them would cost O(n² log n).
+    /// Newton's iteration instead doubles the number of correct coefficients with two
+    /// compositions per step, and is used here for the whole series.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `f` has a zero constant term and a non-zero linear term.
+    ///
+    /// # Complexity
+    ///
+    /// O(n²): a step at length L costs O(L²) for its two compositions, and the lengths
+    /// double up to `n`.
+    pub fn comp_inv(&self, n: usize) -> Poly {
+        let p = self.p;
+        if self.coeff(0) != 0 || self.coeff(1) == 0 {
+            panic!("f must have a zero constant term and a non-zero linear term!");
+        }
+        let x = Poly::from_reduced(vec![0, 1], p);
+        let mut h = Poly::from_reduced(vec![0, modexp::mod_inv(self.coeff(1), p)], p);
+        let derivative = self.derivative();
+        let mut len = 2;
+        while len < n {
+            len = (2 * len).min(n);
+            let residual = &self.truncated(len).compose(&h, len) - &x;
+            let slope = derivative.truncated(len).compose(&h, len).inv(len);
+            h = (&h - &(&residual * &slope)).truncated(len);
+        }
+        h.truncated(n)
+    }
+
+    /// Evaluates the polynomial at the `m` points `a, a·r, a·r², …, a·r^(m-1)` with the
+    /// chirp-Z (Bluestein) transform.
+    ///
+    /// Writing `ik = C(i + k, 2) − C(i, 2) − C(k, 2)` turns the evaluation into a single
+    /// convolution. With `a = 1` and `r` a primitive `m`-th root of unity this is a discrete
+    /// Fourier transform of any length `m`, not just a power of two.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first point.
+    /// * `r` - The ratio between consecutive points.
+    /// * `m` - The number of points.
+    ///
+    /// # Returns
+    ///
+    /// The values `f(a·r^k)` for `k` in `0..m`.
+    ///
+    /// # Complexity
+    ///
+    /// O((n + m) log(n + m)) for a polynomial of degree below n.
+    pub fn chirp_z(&self, a: u64, r: u64, m: usize) -> Vec<u64> {
+        let p = self.p;
+        let (a, r) = (a % p, r % p);
+        let n = self.coeffs.len();
+        if n == 0 || m == 0 {
+            return vec![0; m];
+        }
+        if r == 0 {
+            // Every point but the first is 0.
+            let mut values = vec![self.coeff(0); m];
+            values[0] = self.eval(a);
+            return values;
+        }
+        let r_inv = modexp::mod_inv(r, p);
+        // chirp[j] = r^C(j, 2) for j < n + m - 1, and likewise for r^-1.
+        let chirp = |r: u64, len: usize| {
+            let mut values = Vec::with_capacity(len);
+            let (mut value, mut step) = (1, 1);
+            for _ in 0..len {
+                values.push(value);
+                value = value * step % p;
+                step = step * r % p;
+            }
+            values
+        };
+        let up = chirp(r, n + m - 1);
+        let down = chirp(r_inv, n.max(m));
+        let mut a_pow = 1;
+        let mut b: Vec<u64> = Vec::with_capacity(n);
+        for (i, &c) in self.coeffs.iter().enumerate() {
+            b.push(c * a_pow % p * down[i] % p);
+            a_pow = a_pow * a % p;
+        }
+        b.reverse();
+        let conv = multiply(&b, &up, p);
+        (0..m).map(|k| conv[n - 1 + k] * down[k] % p).collect()
+    }
+
+    /// Finds the polynomial of degree below `xs.len()` through the points `(xs[i], ys[i])`.
+    ///
+    /// Small inputs use Lagrange's formula directly; larger ones evaluate the derivative of
+    /// `Π (x - xs[i])` with `multi_eval` and combine the weighted terms up the product tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `xs` - The distinct abscissas.
+    /// * `ys` - The values at `xs`.
+    /// * `p` - A prime modulus below 2^32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `xs` and `ys` differ in length, if two points of `xs` are congruent modulo
+    /// `p`, or if `p` is not a prime below 2^32.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log² n) for n points, or O(n²) below a few dozen points.
+    pub fn interpolate(xs: &[u64], ys: &[u64], p: u64) -> Poly {
+        if xs.len() != ys.len() {
+            panic!("xs and ys must have the same length!");
+        }
+        let zero = Poly::new(vec![], p);
+        if xs.is_empty() {
+            return zero;
+        }
+        let n = xs.len();
+        if n <= NAIVE_EVAL_LEN {
+            // m = Π (x - xs[i]); each Lagrange basis numerator is m / (x - xs[i]).
+            let mut m = vec![1];
+            for &x in xs {
+                let x = x % p;
+                m.insert(0, 0);
+                for i in 0..m.len() - 1 {
+                    m[i] = (m[i] + (p - x) * m[i + 1]) % p;
+                }
+            }
+            let mut coeffs = vec![0; n];
+            let mut basis = vec![0; n];
+            for (&x, &y) in xs.iter().zip(ys) {
+                let x = x % p;
+                let mut carry = 0;
+                for i in (0..n).rev() {
+                    carry = (m[i + 1] + carry * x) % p;
+                    basis[i] = carry;
+                }
+                let denom = basis.iter().rev().fold(0, |acc, &c| (acc * x + c) % p);
+                if denom == 0 {
+                    panic!("Interpolation points must be distinct!");
+                }
+                let w = y % p * modexp::mod_inv(denom, p) % p;
+                for (c, &b) in coeffs.iter_mut().zip(&basis) {
+                    *c = (*c + w * b) % p;
+                }
+            }
+            return Poly::from_reduced(coeffs, p);
+        }
+        let tree = ProductTree::new(xs, p);
+        let mut denoms = Vec::with_capacity(n);
+        let derivative = tree.nodes[1].derivative();
+        tree.eval(1, 0, n, &(&derivative % &tree.nodes[1]), &mut denoms);
+        let weights: Vec<u64> = denoms
+            .iter()
+            .zip(ys)
+            .map(|(&d, &y)| {
+                if d == 0 {
+                    panic!("Interpolation points must be distinct!");
+                }
+                y % p * modexp::mod_inv(d, p) % p
+            })
+            .collect();
+        tree.combine(1, 0, n, &weights)
+    }
+
+    // Multiplies every coefficient by c.
+    fn scale(&self, c: u64) -> Poly {
+        let c = c % self.p;
+        Poly::from_reduced(self.coeffs.iter().map(|&x| x * c % self.p).collect(), self.p)
+    }
+
+    fn check_modulus(&self, other: &Poly) {
+        if self.p != other.p {
+            panic!("Polynomials must share a modulus!");
+        }
+    }
+}
+
+// Multiplies coefficient vectors modulo p, using a single NTT when p supports the length.
+pub(crate) fn multiply(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let len = a.len() + b.len() - 1;
+    if len.next_power_of_two() <= 1 << (p - 1).trailing_zeros() {
+        ntt::convolution(a, b, p)
+    } else {
+        ntt::convolution_mod(a, b, p)
+    }
+}
+
+impl Add<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn add(self, other: &Poly) -> Poly {
+        self.check_modulus(other);
+        let p = self.p;
+        let coeffs = (0..self.coeffs.len().max(other.coeffs.len()))
+            .map(|i| (self.coeff(i) + other.coeff(i)) % p)
+            .collect();
+        Poly::from_reduced(coeffs, p)
+    }
+}
+
+impl Sub<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn sub(self, other: &Poly) -> Poly {
+        self.check_modulus(other);
+        let p = self.p;
+        let coeffs = (0..self.coeffs.len().max(other.coeffs.len()))
+            .map(|i| (self.coeff(i) + p - other.coeff(i)) % p)
+            .collect();
+        Poly::from_reduced(coeffs, p)
+    }
+}
+
+impl Mul<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn mul(self, other: &Poly) -> Poly {
+        self.check_modulus(other);
+        Poly::from_reduced(multiply(&self.coeffs, &other.coeffs, self.p), self.p)
+    }
+}
+
+impl Mul<u64> for &Poly {
+    type Output = Poly;
+
+    fn mul(self, c: u64) -> Poly {
+        self.scale(c)
+    }
+}
+
+impl Neg for &Poly {
+    type Output = Poly;
+
+    fn neg(self) -> Poly {
+        Poly::from_reduced(self.coeffs.iter().map(|&x| (self.p - x) % self.p).collect(), self.p)
+    }
+}
+
+macro_rules! forward_owned {
+    ($($op:ident $method:ident),*) => {
+        $(
+            impl $op<Poly> for Poly {
+                type Output = Poly;
+
+                fn $method(self, other: Poly) -> Poly {
+                    (&self).$method(&other)
+                }
+            }
+
+            impl $op<&Poly> for Poly {
+                type Output = Poly;
+
+                fn $method(self, other: &Poly) -> Poly {
+                    (&self).$method(other)
+                }
+            }
+
+            impl $op<Poly> for &Poly {
+                type Output = Poly;
+
+                fn $method(self, other: Poly) -> Poly {
+                    self.$method(&other)
+                }
+            }
+        )*
+    };
+}
+
+forward_owned!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+impl Mul<u64> for Poly {
+    type Output = Poly;
+
+    fn mul(self, c: u64) -> Poly {
+        &self * c
+    }
+}
+
+impl Neg for Poly {
+    type Output = Poly;
+
+    fn neg(self) -> Poly {
+        -&self
+    }
+}
+
+// Dividing by a scalar multiplies by its inverse modulo p.
+impl Div<u64> for &Poly {
+    type Output = Poly;
+
+    fn div(self, c: u64) -> Poly {
+        self * modexp::mod_inv(c % self.p, self.p)
+    }
+}
+
+impl Div<u64> for Poly {
+    type Output = Poly;
+
+    fn div(self, c: u64) -> Poly {
+        &self / c
+    }
+}
+
+impl Div<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn div(self, other: &Poly) -> Poly {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn rem(self, other: &Poly) -> Poly {
+        self.div_rem(other).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOD: u64 = 998244353;
+
+    fn poly(coeffs: &[u64]) -> Poly {
+        Poly::new(coeffs.to_vec(), MOD)
+    }
+
+    #[test]
+    fn test_poly_basics() {
+        let f = Poly::new(vec![1, 2, 3, MOD, 0], MOD);
+        assert_eq!(f.coeffs(), &[1, 2, 3]);
+        assert_eq!(f.degree(), Some(2));
+        assert_eq!(f.coeff(5), 0);
+        assert!(poly(&[0, 0]).is_zero());
+        assert_eq!(poly(&[]).degree(), None);
+        assert_eq!(f.eval(2), 17);
+        assert_eq!(f.eval(MOD - 1), 2);
+        assert_eq!(poly(&[]).eval(5), 0);
+    }
+
+    #[test]
+    fn test_poly_arithmetic() {
+        let f = poly(&[1, 2, 3]);
+        let g = poly(&[MOD - 1, 5]);
+        assert_eq!(&f + &g, poly(&[0, 7, 3]));
+        assert_eq!(&f - &f, poly(&[]));
+        assert_eq!(&g - &f, poly(&[MOD - 2, 3, MOD - 3]));
+        assert_eq!(&f * &g, poly(&[MOD - 1, 3, 7, 15]));
+        assert_eq!(&f * 2, poly(&[2, 4, 6]));
+        assert_eq!(&f * MOD, poly(&[]));
+        assert_eq!(&(&f * 3) / 3, f);
+        assert_eq!(-&g + g.clone(), poly(&[]));
+        assert_eq!((f.clone() * poly(&[])).degree(), None);
+        // Products at points agree with products of values.
+        let a = Poly::new((1..300).collect(), MOD);
+        let b = Poly::new((0..500).map(|i| i * i + 7).collect(), MOD);
+        let c = &a * &b;
+        assert_eq!(c.degree(), Some(298 + 499));
+        for x in [0, 1, 12345, MOD - 2] {
+            assert_eq!(c.eval(x), a.eval(x) * b.eval(x) % MOD);
+        }
+        // Primes that are not NTT-friendly fall back to the CRT convolution.
+        let p = 1_000_000_007;
+        let a = Poly::new((1..300).collect(), p);
+        let b = Poly::new((0..500).map(|i| i * i + 7).collect(), p);
+        assert_eq!((&a * &b).eval(99), a.eval(99) * b.eval(99) % p);
+    }
+
+    #[test]
+    fn test_poly_calculus() {
+        let f = poly(&[5, 2, 3, 4]);
+        assert_eq!(f.derivative(), poly(&[2, 6, 12]));
+        assert_eq!(poly(&[7]).derivative(), poly(&[]));
+        assert_eq!(f.derivative().integral(), poly(&[0, 2, 3, 4]));
+        assert_eq!(poly(&[3, 6]).integral(), poly(&[0, 3, 3]));
+        let g = Poly::new((1..1000).collect(), 7);
+        assert_eq!(g.derivative().coeff(6), 0);
+    }
+
+    #[test]
+    fn test_poly_taylor_shift() {
+        // (1 + x)² shifted by 2 is (3 + x)².
+        assert_eq!(poly(&[1, 2, 1]).taylor_shift(2), poly(&[9, 6, 1]));
+        assert_eq!(poly(&[]).taylor_shift(5), poly(&[]));
+        assert_eq!(poly(&[4, 0, 0, 1]).taylor_shift(0), poly(&[4, 0, 0, 1]));
+        let f = Poly::new((0..300).map(|i| i * 31 + 2).collect(), MOD);
+        let shifted = f.taylor_shift(MOD - 7);
+        for x in [0, 1, 99, 12345] {
+            assert_eq!(shifted.eval(x), f.eval(x + MOD - 7));
+        }
+        assert_eq!(shifted.taylor_shift(7), f);
+        // Degree p − 1 is the largest that can be shifted modulo p.
+        let g = Poly::new(vec![1, 2, 3, 4, 5, 6, 1], 7);
+        assert_eq!(g.taylor_shift(3).eval(1), g.eval(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Degree is too large for the modulus!")]
+    fn test_poly_taylor_shift_degree_too_large() {
+        Poly::new(vec![1; 8], 7).taylor_shift(1);
+    }
+
+    #[test]
+    fn test_poly_inv() {
+        let f = poly(&[1, MOD - 1]);
+        assert_eq!(f.inv(5), poly(&[1, 1, 1, 1, 1]));
+        assert_eq!(f.inv(0), poly(&[]));
+        assert_eq!(poly(&[5]).inv(3), poly(&[modexp::mod_inv(5, MOD)]));
+        assert_eq!(poly(&[1, 2, 3]).truncated(2), poly(&[1, 2]));
+        for (f, n) in [(Poly::new((1..1000).collect(), MOD), 3000), (Poly::new(vec![3, 0, 0, 7], MOD), 777)] {
+            let g = f.inv(n);
+            assert!(g.degree().unwrap() < n);
+            assert_eq!((&f * &g).truncated(n), poly(&[1]));
+        }
+        let p = 1_000_000_007;
+        let f = Poly::new((0..500).map(|i| i * i + 2).collect(), p);
+        assert_eq!((&f * &f.inv(600)).truncated(600), Poly::new(vec![1], p));
+    }
+
+    #[test]
+    #[should_panic(expected = "Constant term cannot be zero!")]
+    fn test_poly_inv_not_invertible() {
+        poly(&[0, 1]).inv(4);
+    }
+
+    #[test]
+    fn test_poly_log_exp() {
+        // log(1 / (1 - x)) = x + x²/2 + x³/3 + …
+        let f = poly(&[1, MOD - 1]).inv(10);
+        let inv = inverses(9, MOD);
+        assert_eq!(f.log(10), poly(&inv));
+        assert_eq!(f.log(10).exp(10), f);
+        // exp(x) = Σ x^k / k!
+        let e = poly(&[0, 1]).exp(8);
+        let mut fact = 1;
+        for k in 0..8 {
+            assert_eq!(e.coeff(k) * fact % MOD, 1);
+            fact = fact * (k as u64 + 1) % MOD;
+        }
+        assert_eq!(poly(&[]).exp(3), poly(&[1]));
+        assert_eq!(poly(&[0, 1]).exp(0), poly(&[]));
+        let g = Poly::new((0..2000).map(|i| i * 31 % 1009).collect(), MOD);
+        assert_eq!(g.exp(3000).log(3000), g);
+    }
+
+    #[test]
+    fn test_poly_exp_up_to_p() {
+        // Doubling the length past `n` made the last step take the logarithm to 128 terms,
+        // which needs the inverses of 1..128 modulo 97 and panicked.
+        let p = 97;
+        let e = Poly::new(vec![0, 1], p).exp(97);
+        let mut fact = 1;
+        for k in 0..97 {
+            assert_eq!(e.coeff(k) * fact % p, 1);
+            fact = fact * (k as u64 + 1) % p;
+        }
+        assert_eq!(Poly::new(vec![0, 5, 3], p).exp(97).log(97), Poly::new(vec![0, 5, 3], p));
+    }
+
+    #[test]
+    fn test_poly_pow() {
+        let f = poly(&[1, 1]);
+        assert_eq!(f.pow(3, 10), poly(&[1, 3, 3, 1]));
+        assert_eq!(f.pow(5, 3), poly(&[1, 5, 10]));
+        assert_eq!(f.pow(0, 3), poly(&[1]));
+        assert_eq!(poly(&[]).pow(0, 3), poly(&[1]));
+        assert_eq!(poly(&[]).pow(2, 3), poly(&[]));
+        assert_eq!(poly(&[0, 0, 2, 1]).pow(2, 6), poly(&[0, 0, 0, 0, 4, 4]));
+        assert_eq!(poly(&[0, 1]).pow(u64::MAX, 100), poly(&[]));
+        // (2 + x)^p = 2 + x^p modulo p, so (2 + x)^(p + 1) = 4 + 2x + O(x^p).
+        assert_eq!(poly(&[2, 1]).pow(MOD + 1, 3), poly(&[4, 2]));
+        let g = Poly::new((1..500).collect(), MOD);
+        let mut expected = poly(&[1]);
+        for _ in 0..7 {
+            expected = (&expected * &g).truncated(800);
+        }
+        assert_eq!(g.pow(7, 800), expected);
+    }
+
+    #[test]
+    fn test_poly_sqrt() {
+        let f = poly(&[0, 0, 4, 4, 1]);
+        assert_eq!(f.sqrt(3), Some(poly(&[0, 2, 1])));
+        assert_eq!(f.sqrt(1), Some(poly(&[])));
+        assert_eq!(poly(&[0, 1]).sqrt(4), None);
+        assert_eq!(poly(&[3]).sqrt(4), None); // 3 is a non-residue modulo 998244353
+        assert_eq!(poly(&[]).sqrt(4), Some(poly(&[])));
+        let g = Poly::new((0..1000).map(|i| i * i + 5).collect(), MOD);
+        let f = (&g * &g).truncated(1000);
+        let h = f.sqrt(1000).unwrap();
+        assert!(h == g || h == -&g);
+        assert_eq!((&h * &h).truncated(1000), f);
+    }
+
+    #[test]
+    fn test_poly_div_rem() {
+        let f = poly(&[5, 0, 3, 1]);
+        let g = poly(&[1, 1]);
+        assert_eq!(f.div_rem(&g), (poly(&[MOD - 2, 2, 1]), poly(&[7])));
+        assert_eq!(&f / &g, poly(&[MOD - 2, 2, 1]));
+        assert_eq!(&f % &f, poly(&[]));
+        assert_eq!(g.div_rem(&f), (poly(&[]), g.clone()));
+        assert_eq!(f.div_rem(&poly(&[2])), (&f / 2, poly(&[])));
+        for (n, m) in [(100, 40), (2000, 700), (3000, 2999), (1000, 1)] {
+            let f = Poly::new((0..n).map(|i| i * i * 7 + 3).collect(), MOD);
+            let g = Poly::new((0..m).map(|i| i * 13 + 1).collect(), MOD);
+            let (q, r) = f.div_rem(&g);
+            assert!(r.degree().is_none_or(|d| d < m as usize - 1));
+            assert_eq!(&(&q * &g) + &r, f, "{} / {}", n, m);
+        }
+    }
+
+    #[test]
+    fn test_poly_gcd_resultant() {
+        // (x − 1)(x − 2) and (x − 2)(x − 3) share the factor x − 2.
+        let f = poly(&[2, MOD - 3, 1]);
+        let g = poly(&[6, MOD - 5, 1]);
+        assert_eq!(f.gcd(&g), poly(&[MOD - 2, 1]));
+        assert_eq!((&f * 5).gcd(&poly(&[])), f);
+        assert_eq!(poly(&[]).gcd(&poly(&[])), poly(&[]));
+        assert_eq!(f.gcd(&poly(&[7])), poly(&[1]));
+        assert_eq!(f.resultant(&g), 0);
+        // res(f, g) = Π g(roots of f) for monic f.
+        let h = poly(&[5, 0, 1]);
+        assert_eq!(f.resultant(&h), h.eval(1) * h.eval(2) % MOD);
+        assert_eq!(h.resultant(&f), h.eval(1) * h.eval(2) % MOD);
+        assert_eq!(poly(&[3]).resultant(&f), 9);
+        assert_eq!(f.resultant(&poly(&[3])), 9);
+        assert_eq!(poly(&[]).resultant(&f), 0);
+        // Against Π g(r) over chosen roots, with a leading coefficient and antisymmetry.
+        let roots = [3, 17, 123456, MOD - 9, 42];
+        let mut f = poly(&[1]);
+        for &r in &roots {
+            f = &f * &poly(&[MOD - r, 1]);
+        }
+        let g = poly(&[7, 0, 5, 1, 9, 2, 11]);
+        let expected = roots.iter().fold(1, |acc, &r| acc * g.eval(r) % MOD);
+        assert_eq!(f.resultant(&g), expected);
+        // res(g, f) = (−1)^(5·6) res(f, g) and res(3f, g) = 3^6 res(f, g).
+        assert_eq!(g.resultant(&f), expected);
+        assert_eq!((&f * 3).resultant(&g), modexp::mod_exp(3, 6, MOD) * expected % MOD);
+        let common = &(&f * &poly(&[1, 1, 1])).gcd(&(&g * &poly(&[1, 1, 1])));
+        assert_eq!(common, &poly(&[1, 1, 1]));
+    }
+
+    // The quadratic Euclidean algorithm, as a reference for the half-GCD path.
+    fn naive_gcd_resultant(f: &Poly, g: &Poly) -> (Poly, u64) {
+        let p = f.p;
+        let (mut a, mut b) = (f.clone(), g.clone());
+        let mut result = 1;
+        while !b.is_zero() {
+            let (n, m) = (a.degree().unwrap(), b.degree().unwrap());
+            let r = &a % &b;
+            let k = r.degree().unwrap_or(0);
+            if n * m % 2 == 1 {
+                result = (p - result) % p;
+            }
+            result = if m == 0 { result * modexp::mod_exp(b.coeffs[0], n as u64, p) % p } else { result * modexp::mod_exp(b.coeffs[m], (n - k) as u64, p) % p };
+            if r.is_zero() && m > 0 {
+                result = 0;
+            }
+            (a, b) = (b, r);
+        }
+        (&a / *a.coeffs.last().unwrap(), result)
+    }
+
+    #[test]
+    fn test_poly_half_gcd() {
+        let mut state = 1u64;
+        let mut random = |len: usize, p: u64| {
+            let coeffs = (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state % p
+                })
+                .collect();
+            Poly::new(coeffs, p)
+        };
+        for (n, m, common, p) in [(300, 200, 0, MOD), (500, 499, 40, MOD), (700, 100, 150, 7), (257, 256, 1, 2), (900, 900, 0, 1_000_000_007)] {
+            let c = random(common + 1, p);
+            let f = &random(n, p) * &c;
+            let g = &random(m, p) * &c;
+            let (gcd, res) = naive_gcd_resultant(&f, &g);
+            assert_eq!(f.gcd(&g), gcd, "n = {}, m = {}, p = {}", n, m, p);
+            assert_eq!(f.resultant(&g), res, "n = {}, m = {}, p = {}", n, m, p);
+            let (f, g) = (random(n, p), random(m, p));
+            assert_eq!(f.resultant(&g), naive_gcd_resultant(&f, &g).1);
+            assert_eq!(g.resultant(&f), naive_gcd_resultant(&g, &f).1);
+        }
+        // Fibonacci-like inputs make every quotient linear, the longest remainder sequence.
+        let (mut a, mut b) = (poly(&[1]), poly(&[0, 1]));
+        for _ in 0..200 {
+            (a, b) = (b.clone(), &(&b * &poly(&[3, 1])) + &a);
+        }
+        assert_eq!(a.gcd(&b), poly(&[1]));
+        assert_eq!(b.resultant(&a), naive_gcd_resultant(&b, &a).1);
+    }
+
+    #[test]
+    fn test_poly_multi_eval() {
+        let f = poly(&[1, 2, 3]);
+        assert_eq!(f.multi_eval(&[0, 1, 2, MOD + 2]), vec![1, 6, 17, 17]);
+        assert_eq!(f.multi_eval(&[]), vec![]);
+        assert_eq!(poly(&[]).multi_eval(&(0..100).collect::<Vec<_>>()), vec![0; 100]);
+        for (deg, n) in [(1000, 1000), (10, 500), (2000, 100), (700, 1300)] {
+            let f = Poly::new((0..deg).map(|i| i * i * 3 + 11).collect(), MOD);
+            let points: Vec<u64> = (0..n).map(|i| i * 7919 % 1009 + i).collect();
+            let expected: Vec<u64> = points.iter().map(|&x| f.eval(x)).collect();
+            assert_eq!(f.multi_eval(&points), expected, "degree {} at {} points", deg, n);
+        }
+    }
+
+    #[test]
+    fn test_poly_compose() {
+        let f = poly(&[1, 2, 3]);
+        let g = poly(&[5, 1]);
+        // f(x + 5) = 1 + 2(x + 5) + 3(x + 5)² = 86 + 32x + 3x².
+        assert_eq!(f.compose(&g, 5), poly(&[86, 32, 3]));
+        assert_eq!(f.compose(&g, 2), poly(&[86, 32]));
+        assert_eq!(f.compose(&g, 0), poly(&[]));
+        assert_eq!(poly(&[]).compose(&g, 4), poly(&[]));
+        assert_eq!(f.compose(&poly(&[]), 4), poly(&[1]));
+        // exp(x) ∘ log(1 + x) = 1 + x.
+        let exp = poly(&[0, 1]).exp(50);
+        let log = poly(&[1, 1]).log(50);
+        assert_eq!(exp.compose(&log, 50), poly(&[1, 1]));
+        let a = Poly::new((0..300).map(|i| i * 7 + 1).collect(), MOD);
+        let b = Poly::new((0..200).map(|i| i * i + 3).collect(), MOD);
+        let composed = a.compose(&b, 400);
+        let mut expected = poly(&[]);
+        for &c in a.coeffs().iter().rev() {
+            expected = &(&expected * &b).truncated(400) + &poly(&[c]);
+        }
+        assert_eq!(composed, expected);
+    }
+
+    #[test]
+    fn test_poly_compose_mod() {
+        let f = poly(&[1, 2, 3]);
+        let g = poly(&[0, 0, 1]);
+        let h = poly(&[1, 0, 0, 1]);
+        // f(x²) = 1 + 2x² + 3x⁴ ≡ 1 − 3x + 2x² modulo x³ + 1.
+        assert_eq!(f.compose_mod(&g, &h), poly(&[1, MOD - 3, 2]));
+        assert_eq!(poly(&[]).compose_mod(&g, &h), poly(&[]));
+        assert_eq!(poly(&[5]).compose_mod(&g, &poly(&[3])), poly(&[]));
+        assert_eq!(poly(&[5, 1]).compose_mod(&g, &poly(&[0, 1])), poly(&[5]));
+        // Against composition followed by reduction, and the values at the roots of h.
+        for p in [MOD, 1_000_000_007, 13] {
+            let f = Poly::new((0..150).map(|i| i * i * 13 + 5).collect(), p);
+            let g = Poly::new((0..90).map(|i| i * 7 + 1).collect(), p);
+            let h = Poly::new((0..60).map(|i| i * i + 3 * i + 2).collect(), p);
+            let expected = &f.compose(&g, 150 * 90) % &h;
+            assert_eq!(f.compose_mod(&g, &h), expected, "p = {}", p);
+        }
+        let h = &(&poly(&[MOD - 3, 1]) * &poly(&[MOD - 10, 1])) * &poly(&[MOD - 12345, 1]);
+        let f = Poly::new((0..500).map(|i| i * 31 + 7).collect(), MOD);
+        let g = Poly::new((0..300).map(|i| i * i + 1).collect(), MOD);
+        let composed = f.compose_mod(&g, &h);
+        for x in [3, 10, 12345] {
+            assert_eq!(composed.eval(x), f.eval(g.eval(x)));
+        }
+    }
+
+    #[test]
+    fn test_poly_comp_inv() {
+        // The inverse of x / (1 − x) = x + x² + … is x / (1 + x) = x − x² + x³ − ….
+        let geometric = Poly::new((0..20).map(|i| (i > 0) as u64).collect(), MOD);
+        let alternating = Poly::new((0..20u64).map(|i| match i {
+            0 => 0,
+            _ if i % 2 == 1 => 1,
+            _ => MOD - 1,
+        }).collect(), MOD);
+        assert_eq!(geometric.comp_inv(20), alternating);
+        let f = Poly::new((0..200).map(|i| i * i + i + 2).collect(), MOD) * poly(&[0, 1]);
+        let h = f.comp_inv(200);
+        assert_eq!(f.compose(&h, 200), poly(&[0, 1]));
+        assert_eq!(h.compose(&f, 200), poly(&[0, 1]));
+        // Lagrange inversion: [x^k] h = (1/k)·[x^(k-1)] (x/f)^k.
+        let phi = Poly::new(f.coeffs()[1..].to_vec(), MOD).inv(30);
+        for k in 1..30 {
+            let coeff = phi.pow(k as u64, k).coeff(k - 1) * modexp::mod_inv(k as u64, MOD) % MOD;
+            assert_eq!(h.coeff(k), coeff);
+        }
+        assert_eq!(poly(&[0, 3]).comp_inv(1), poly(&[]));
+    }
+
+    #[test]
+    #[should_panic(expected = "f must have a zero constant term and a non-zero linear term!")]
+    fn test_poly_comp_inv_not_invertible() {
+        poly(&[0, 0, 1]).comp_inv(5);
+    }
+
+    #[test]
+    fn test_poly_chirp_z() {
+        let f = Poly::new((0..300).map(|i| i * i + 1).collect(), MOD);
+        for (a, r, m) in [(1, 3, 500), (5, 2, 1), (7, 1, 4), (9, MOD - 1, 300), (2, 0, 5), (0, 5, 3)] {
+            let points: Vec<u64> = (0..m).scan(a, |x, _| {
+                let current = *x;
+                *x = *x * r % MOD;
+                Some(current)
+            }).collect();
+            assert_eq!(f.chirp_z(a, r, m), f.multi_eval(&points), "a = {}, r = {}", a, r);
+        }
+        assert_eq!(f.chirp_z(1, 2, 0), vec![]);
+        assert_eq!(poly(&[]).chirp_z(1, 2, 3), vec![0; 3]);
+        // A discrete Fourier transform of length 7 modulo 29, where 7 divides 28.
+        let w = modexp::mod_exp(2, 4, 29);
+        let g = Poly::new(vec![1, 2, 3, 4, 5, 6, 7], 29);
+        let dft: Vec<u64> = (0..7).map(|k| g.eval(modexp::mod_exp(w, k, 29))).collect();
+        assert_eq!(g.chirp_z(1, w, 7), dft);
+        let back = Poly::new(dft, 29).chirp_z(1, modexp::mod_inv(w, 29), 7);
+        assert_eq!(back, (1..=7).map(|x| x * 7 % 29).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_poly_interpolate() {
+        assert_eq!(Poly::interpolate(&[], &[], MOD), poly(&[]));
+        assert_eq!(Poly::interpolate(&[5], &[9], MOD), poly(&[9]));
+        assert_eq!(Poly::interpolate(&[0, 1, 2], &[1, 6, 17], MOD), poly(&[1, 2, 3]));
+        assert_eq!(Poly::interpolate(&[1, 2, 3], &[4, 4, 4], MOD), poly(&[4]));
+        for n in [20, 33, 200, 1000] {
+            let f = Poly::new((0..n).map(|i| i * i * 5 + 2).collect(), MOD);
+            let xs: Vec<u64> = (0..n).map(|i| i * i + 3).collect();
+            let ys = f.multi_eval(&xs);
+            assert_eq!(Poly::interpolate(&xs, &ys, MOD), f, "{} points", n);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Interpolation points must be distinct!")]
+    fn test_poly_interpolate_repeated_point() {
+        Poly::interpolate(&[1, 2, MOD + 1], &[1, 2, 3], MOD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Interpolation points must be distinct!")]
+    fn test_poly_interpolate_repeated_point_fast() {
+        let xs: Vec<u64> = (0..100).map(|i| i % 99).collect();
+        Poly::interpolate(&xs, &[0; 100], MOD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Divisor cannot be zero!")]
+    fn test_poly_div_by_zero() {
+        poly(&[1, 2]).div_rem(&poly(&[]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Polynomials must share a modulus!")]
+    fn test_poly_modulus_mismatch() {
+        let _ = poly(&[1]) + Poly::new(vec![1], 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus is not prime!")]
+    fn test_poly_composite_modulus() {
+        Poly::new(vec![1], 15);
+    }
+}
@@ -0,0 +1,399 @@
+use crate::modexp;
+use crate::poly::Poly;
+use crate::random::{RandomSource, SplitMix64};
+
+// Computes base^k modulo m by binary exponentiation.
+pub(crate) fn pow_mod(base: &Poly, mut k: u64, m: &Poly) -> Poly {
+    let p = base.modulus();
+    let mut result = &Poly::from_reduced(vec![1], p) % m;
+    let mut base = base % m;
+    while k > 0 {
+        if k & 1 == 1 {
+            result = &(&result * &base) % m;
+        }
+        k >>= 1;
+        if k > 0 {
+            base = &(&base * &base) % m;
+        }
+    }
+    result
+}
+
+fn one(p: u64) -> Poly {
+    Poly::from_reduced(vec![1], p)
+}
+
+fn monic(f: &Poly) -> Poly {
+    match f.coeffs().last() {
+        Some(&lead) => f * modexp::mod_inv(lead, f.modulus()),
+        None => f.clone(),
+    }
+}
+
+// Writes f = g(x^p), which is possible when the derivative of f vanishes, and returns g. Over
+// F_p every coefficient is its own p-th root, so g^p = f.
+fn pth_root(f: &Poly) -> Poly {
+    let p = f.modulus();
+    Poly::from_reduced(f.coeffs().iter().step_by(p as usize).copied().collect(), p)
+}
+
+// Splits a monic f into square-free parts: f = Π g^e over the returned pairs (g, e), with the
+// parts pairwise coprime (Yun's algorithm, with p-th roots for the inseparable parts).
+fn square_free(f: &Poly) -> Vec<(Poly, usize)> {
+    let p = f.modulus();
+    let mut parts = vec![];
+    if f.degree().unwrap_or(0) == 0 {
+        return parts;
+    }
+    let mut c = f.gcd(&f.derivative());
+    let mut w = f / &c;
+    let mut i = 1;
+    while w != one(p) {
+        let y = w.gcd(&c);
+        let factor = &w / &y;
+        if factor != one(p) {
+            parts.push((factor, i));
+        }
+        c = &c / &y;
+        w = y;
+        i += 1;
+    }
+    if c != one(p) {
+        for (g, e) in square_free(&pth_root(&c)) {
+            parts.push((g, e * p as usize));
+        }
+    }
+    parts
+}
+
+// Splits a square-free monic f into the products of its irreducible factors of each degree d,
+// using that x^(p^d) − x is the product of all monic irreducibles of degree dividing d.
+fn distinct_degree(f: &Poly) -> Vec<(Poly, usize)> {
+    let p = f.modulus();
+    let x = Poly::from_reduced(vec![0, 1], p);
+    let mut parts = vec![];
+    let mut f = f.clone();
+    let mut h = x.clone();
+    let mut d = 0;
+    while 2 * (d + 1) <= f.degree().unwrap_or(0) {
+        d += 1;
+        h = pow_mod(&h, p, &f);
+        let g = f.gcd(&(&h - &x));
+        if g != one(p) {
+            f = &f / &g;
+            h = &h % &f;
+            parts.push((g, d));
+        }
+    }
+    if let Some(n @ 1..) = f.degree() {
+        parts.push((f, n));
+    }
+    parts
+}
+
+// Splits a monic f whose irreducible factors all have degree d into those factors (the
+// Cantor–Zassenhaus step). A random a gives a^((p^d − 1)/2) = ±1 modulo each factor
+// independently, or for p = 2 a trace of 0 or 1, so gcd(f, that − 1) is a proper factor about
+// half of the time.
+fn equal_degree(f: &Poly, d: usize, rng: &mut dyn RandomSource, factors: &mut Vec<Poly>) {
+    let p = f.modulus();
+    let n = f.degree().unwrap();
+    if n == d {
+        factors.push(f.clone());
+        return;
+    }
+    loop {
+        let coeffs = (0..n).map(|_| rng.next_u64() % p).collect();
+        let a = Poly::from_reduced(coeffs, p);
+        if a.degree().unwrap_or(0) == 0 {
+            continue;
+        }
+        let mut power = a.clone();
+        let mut acc = a.clone();
+        for _ in 1..d {
+            power = pow_mod(&power, p, f);
+            acc = if p == 2 { &acc + &power } else { &(&acc * &power) % f };
+        }
+        let b = if p == 2 { acc } else { &pow_mod(&acc, (p - 1) / 2, f) - &one(p) };
+        let g = f.gcd(&b);
+        if g.degree().is_some_and(|k| k > 0 && k < n) {
+            equal_degree(&g, d, rng, factors);
+            equal_degree(&(f / &g), d, rng, factors);
+            return;
+        }
+    }
+}
+
+impl Poly {
+    /// Factors the polynomial into monic irreducible factors over F_p with the
+    /// Cantor–Zassenhaus algorithm: a square-free decomposition, then a distinct-degree
+    /// split using `x^(p^d) − x`, then randomized equal-degree splitting.
+    ///
+    /// # Returns
+    ///
+    /// The pairs `(g, e)` of distinct monic irreducible factors `g` and their multiplicities
+    /// `e`, sorted by degree and then by coefficients, so that `f` is its leading coefficient
+    /// times `Π g^e`. Constants have no factors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial is zero.
+    ///
+    /// # Complexity
+    ///
+    /// Expected O(n³ log p) coefficient operations for degree n, and far less when the
+    /// factors have small degree.
+    pub fn factor(&self) -> Vec<(Poly, usize)> {
+        self.factor_with_rng(&mut SplitMix64::default())
+    }
+
+    /// Factors the polynomial like `factor`, drawing the random polynomials of the
+    /// equal-degree splitting from `rng`. The factors do not depend on `rng`, only the
+    /// running time does.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The source of randomness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial is zero.
+    pub fn factor_with_rng(&self, rng: &mut impl RandomSource) -> Vec<(Poly, usize)> {
+        if self.is_zero() {
+            panic!("Cannot factor the zero polynomial!");
+        }
+        let mut factors = vec![];
+        for (part, e) in square_free(&monic(self)) {
+            for (group, d) in distinct_degree(&part) {
+                let mut split = vec![];
+                equal_degree(&group, d, rng, &mut split);
+                factors.extend(split.into_iter().map(|g| (g, e)));
+            }
+        }
+        factors.sort_by(|(f, _), (g, _)| (f.degree(), f.coeffs()).cmp(&(g.degree(), g.coeffs())));
+        factors
+    }
+
+    /// Finds the distinct roots in F_p. The roots are the linear factors of
+    /// `gcd(f, x^p − x)`, which are split apart as in `factor`; polynomials of degree at most
+    /// 2 are solved directly with the quadratic formula and a modular square root.
+    ///
+    /// # Returns
+    ///
+    /// The distinct roots in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial is zero.
+    ///
+    /// # Complexity
+    ///
+    /// Expected O(n log n log p) for degree n, with fast multiplication.
+    pub fn roots_mod_p(&self) -> Vec<u64> {
+        self.roots_mod_p_with_rng(&mut SplitMix64::default())
+    }
+
+    /// Finds the distinct roots in F_p like `roots_mod_p`, drawing the random polynomials
+    /// that split the linear factors apart from `rng`. The roots do not depend on `rng`,
+    /// only the running time does.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The source of randomness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial is zero.
+    pub fn roots_mod_p_with_rng(&self, rng: &mut impl RandomSource) -> Vec<u64> {
+        if self.is_zero() {
+            panic!("Cannot find the roots of the zero polynomial!");
+        }
+        let p = self.modulus();
+        let f = monic(self);
+        let mut roots = match f.degree() {
+            Some(0) => vec![],
+            Some(1) => vec![(p - f.coeff(0)) % p],
+            Some(2) if p == 2 => (0..2).filter(|&x| f.eval(x) == 0).collect(),
+            Some(2) => {
+                // x = (−b ± √(b² − 4c)) / 2 for the monic x² + bx + c.
+                let (b, c) = (f.coeff(1), f.coeff(0));
+                let disc = (b * b % p + p - 4 * c % p) % p;
+                match modexp::sqrt_mod(disc, p) {
+                    Some(r) => {
+                        let half = modexp::mod_inv(2, p);
+                        [(p - b + r) % p, (2 * p - b - r) % p].iter().map(|&x| x * half % p).collect()
+                    }
+                    None => vec![],
+                }
+            }
+            _ => {
+                let x = Poly::from_reduced(vec![0, 1], p);
+                let g = f.gcd(&(&pow_mod(&x, p, &f) - &x));
+                let mut linear = vec![];
+                if g.degree() != Some(0) {
+                    equal_degree(&g, 1, rng, &mut linear);
+                }
+                linear.iter().map(|l| (p - l.coeff(0)) % p).collect()
+            }
+        };
+        roots.sort_unstable();
+        roots.dedup();
+        roots
+    }
+
+    /// Returns `true` if the polynomial has positive degree and no factorization into two
+    /// polynomials of lower degree over F_p (Rabin's test: `x^(p^n) ≡ x` modulo `f`, and
+    /// `gcd(x^(p^(n/q)) − x, f) = 1` for every prime `q` dividing `n`).
+    ///
+    /// # Complexity
+    ///
+    /// O(n log n log p) products modulo `f` for degree n.
+    pub fn is_irreducible(&self) -> bool {
+        let Some(n @ 1..) = self.degree() else {
+            return false;
+        };
+        let p = self.modulus();
+        let f = monic(self);
+        let x = Poly::from_reduced(vec![0, 1], p);
+        // frobenius[i] = x^(p^i) modulo f.
+        let mut frobenius = vec![&x % &f];
+        for i in 0..n {
+            frobenius.push(pow_mod(&frobenius[i], p, &f));
+        }
+        if frobenius[n] != &x % &f {
+            return false;
+        }
+        crate::pollard::factor_map(n as u64).into_iter().all(|(q, _)| f.gcd(&(&frobenius[n / q as usize] - &x)) == one(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(coeffs: &[u64], p: u64) -> Poly {
+        Poly::new(coeffs.to_vec(), p)
+    }
+
+    // Multiplies the factors back together.
+    fn expand(factors: &[(Poly, usize)], p: u64) -> Poly {
+        factors.iter().fold(one(p), |acc, (g, e)| (0..*e).fold(acc, |acc, _| &acc * g))
+    }
+
+    #[test]
+    fn test_factor_small() {
+        let p = 7;
+        assert_eq!(poly(&[3], p).factor(), vec![]);
+        assert_eq!(poly(&[1, 1], p).factor(), vec![(poly(&[1, 1], p), 1)]);
+        // 2(x − 1)(x − 2) = 2x² − 6x + 4.
+        assert_eq!(poly(&[4, 1, 2], p).factor(), vec![(poly(&[5, 1], p), 1), (poly(&[6, 1], p), 1)]);
+        // x² + 1 is irreducible modulo 7 but splits modulo 5.
+        assert_eq!(poly(&[1, 0, 1], 7).factor(), vec![(poly(&[1, 0, 1], 7), 1)]);
+        assert_eq!(poly(&[1, 0, 1], 5).factor(), vec![(poly(&[2, 1], 5), 1), (poly(&[3, 1], 5), 1)]);
+        // (x + 1)^7 = x^7 + 1 has a vanishing derivative modulo 7.
+        assert_eq!(poly(&[1, 0, 0, 0, 0, 0, 0, 1], 7).factor(), vec![(poly(&[1, 1], 7), 7)]);
+        // x^4 + x + 1 is irreducible over F_2, and x^2 + x + 1 is the only irreducible quadratic.
+        assert_eq!(poly(&[1, 1, 0, 0, 1], 2).factor(), vec![(poly(&[1, 1, 0, 0, 1], 2), 1)]);
+        let f = &(&poly(&[1, 1, 1], 2) * &poly(&[1, 1, 1], 2)) * &poly(&[0, 1], 2);
+        assert_eq!(f.factor(), vec![(poly(&[0, 1], 2), 1), (poly(&[1, 1, 1], 2), 2)]);
+    }
+
+    #[test]
+    fn test_factor_all_irreducibles() {
+        // x^(p^d) − x is the product of the monic irreducibles of degree dividing d, and there
+        // are (1/d)·Σ_{k | d} μ(k)·p^(d/k) of degree exactly d.
+        for (p, d, counts) in [(3u64, 4u32, [3, 3, 0, 18]), (2, 4, [2, 1, 0, 3]), (5, 2, [5, 10, 0, 0])] {
+            let n = p.pow(d) as usize;
+            let mut coeffs = vec![0; n + 1];
+            coeffs[1] = p - 1;
+            coeffs[n] = 1;
+            let f = Poly::new(coeffs, p);
+            let factors = f.factor();
+            assert_eq!(expand(&factors, p), f);
+            for (k, &count) in counts.iter().enumerate() {
+                assert_eq!(factors.iter().filter(|(g, _)| g.degree() == Some(k + 1)).count(), count, "p = {}, d = {}", p, k + 1);
+            }
+            assert!(factors.iter().all(|(g, e)| *e == 1 && g.is_irreducible()));
+        }
+    }
+
+    #[test]
+    fn test_factor_mixed() {
+        let p = 998244353;
+        let a = poly(&[5, 0, 0, 1], p);
+        let b = poly(&[1, 2, 3, 4, 5, 6, 1], p);
+        let c = poly(&[p - 3, 1], p);
+        let f = &(&(&(&a * &a) * &b) * &(&c * &c)) * &(&c * 17);
+        let factors = f.factor();
+        assert_eq!(&expand(&factors, p) * 17, f);
+        assert!(factors.iter().all(|(g, _)| g.is_irreducible()));
+        assert_eq!(factors.iter().find(|(g, _)| *g == c).map(|(_, e)| *e), Some(3));
+        assert_eq!(factors.iter().map(|(g, e)| g.degree().unwrap() * e).sum::<usize>(), 6 + 6 + 3);
+        // Inseparable parts combined with separable ones over a small field.
+        let p = 3;
+        let g = poly(&[2, 1, 0, 1], p);
+        let f = (0..6).fold(poly(&[1, 1, 1], p), |acc, _| &acc * &g);
+        let factors = f.factor();
+        assert_eq!(expand(&factors, p), f);
+        assert!(factors.iter().all(|(g, _)| g.is_irreducible()));
+        for seed in 0..4 {
+            assert_eq!(f.factor_with_rng(&mut SplitMix64::new(seed)), factors);
+        }
+    }
+
+    #[test]
+    fn test_is_irreducible() {
+        assert!(!poly(&[], 5).is_irreducible());
+        assert!(!poly(&[3], 5).is_irreducible());
+        assert!(poly(&[3, 2], 5).is_irreducible());
+        assert!(poly(&[2, 0, 1], 5).is_irreducible());
+        assert!(!poly(&[1, 0, 1], 5).is_irreducible());
+        // Degree 6 over F_2: x^6 + x + 1 is irreducible, (x^3 + x + 1)^2 and
+        // (x^2 + x + 1)(x^4 + x + 1) are not.
+        assert!(poly(&[1, 1, 0, 0, 0, 0, 1], 2).is_irreducible());
+        assert!(!poly(&[1, 0, 1, 0, 0, 0, 1], 2).is_irreducible());
+        assert!(!(&poly(&[1, 1, 1], 2) * &poly(&[1, 1, 0, 0, 1], 2)).is_irreducible());
+    }
+
+    #[test]
+    fn test_roots_mod_p() {
+        assert_eq!(poly(&[4], 7).roots_mod_p(), vec![]);
+        assert_eq!(poly(&[3, 2], 7).roots_mod_p(), vec![2]);
+        assert_eq!(poly(&[1, 0, 1], 7).roots_mod_p(), vec![]);
+        assert_eq!(poly(&[1, 0, 1], 5).roots_mod_p(), vec![2, 3]);
+        assert_eq!(poly(&[4, 3, 2], 7).roots_mod_p(), vec![]);
+        // 3(x − 4)² has the single root 4.
+        assert_eq!(poly(&[48, 13 * 2 - 24, 3], 13).roots_mod_p(), vec![4]);
+        assert_eq!(poly(&[0, 1, 1], 2).roots_mod_p(), vec![0, 1]);
+        assert_eq!(poly(&[1, 1, 1], 2).roots_mod_p(), vec![]);
+        // x^p − x vanishes everywhere, and x^3 − 2 has three cube roots modulo 31.
+        let p = 13;
+        let mut coeffs = vec![0; p as usize + 1];
+        coeffs[1] = p - 1;
+        coeffs[p as usize] = 1;
+        assert_eq!(Poly::new(coeffs, p).roots_mod_p(), (0..p).collect::<Vec<u64>>());
+        let cube = poly(&[29, 0, 0, 1], 31);
+        assert_eq!(cube.roots_mod_p(), (0..31).filter(|&x| cube.eval(x) == 0).collect::<Vec<u64>>());
+        // Chosen roots with repeats and an irreducible cofactor, modulo a large prime.
+        let p = 998244353;
+        let roots = [0, 5, 5, 123456789, p - 1];
+        let f = roots.iter().fold(poly(&[1, 0, 3], p), |acc, &r| &acc * &poly(&[(p - r) % p, 1], p));
+        assert_eq!(f.roots_mod_p(), vec![0, 5, 123456789, p - 1]);
+        for seed in 0..5 {
+            assert_eq!(f.roots_mod_p_with_rng(&mut SplitMix64::new(seed)), vec![0, 5, 123456789, p - 1]);
+        }
+        // The quadratic fast path agrees with a brute-force search.
+        for a in 1..11 {
+            for b in 0..11 {
+                let f = poly(&[b * 7 % 11, b, a], 11);
+                assert_eq!(f.roots_mod_p(), (0..11).filter(|&x| f.eval(x) == 0).collect::<Vec<u64>>());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot factor the zero polynomial!")]
+    fn test_factor_zero() {
+        poly(&[], 5).factor();
+    }
+}
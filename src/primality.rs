@@ -0,0 +1,463 @@
+use crate::modexp::mod_exp;
+use crate::pollard::{miller_rabin_witness, modmul};
+
+/// Second Miller-Rabin base for odd `n < 2^32`, indexed by `fj_hash(n)`. For every
+/// bucket the base rejects all base-2 strong pseudoprimes below 2^32 hashing into it,
+/// following the construction of Forišek and Jančina.
+pub(crate) const FJ32_BASES: [u16; 256] = [
+    6, 5, 5, 5, 3, 3, 5, 3, 3, 7, 3, 3, 5, 5, 3, 17,
+    3, 5, 3, 5, 3, 7, 3, 3, 3, 3, 3, 14, 3, 5, 3, 3,
+    5, 3, 3, 3, 3, 3, 3, 5, 3, 3, 3, 5, 3, 3, 5, 3,
+    3, 5, 3, 3, 7, 3, 5, 3, 3, 3, 3, 3, 3, 5, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 5, 3, 5, 3, 3, 3, 5,
+    3, 3, 3, 3, 7, 5, 11, 5, 7, 5, 3, 5, 3, 5, 7, 7,
+    3, 5, 3, 3, 3, 3, 3, 3, 3, 5, 3, 5, 3, 3, 3, 5,
+    7, 3, 3, 3, 5, 3, 7, 5, 3, 3, 3, 3, 3, 7, 3, 3,
+    5, 3, 3, 3, 5, 3, 3, 3, 3, 5, 3, 11, 3, 3, 3, 5,
+    7, 3, 5, 3, 3, 3, 3, 15, 3, 7, 3, 5, 5, 5, 3, 3,
+    3, 3, 3, 3, 5, 3, 5, 3, 3, 7, 7, 3, 5, 7, 5, 3,
+    3, 5, 5, 5, 3, 3, 5, 15, 3, 3, 3, 5, 3, 3, 5, 3,
+    5, 5, 5, 3, 5, 3, 7, 3, 3, 3, 3, 5, 5, 3, 3, 5,
+    3, 11, 3, 5, 3, 3, 3, 3, 3, 3, 3, 3, 5, 5, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 7, 3, 3, 5, 17,
+    3, 3, 3, 5, 3, 3, 3, 3, 3, 5, 3, 7, 3, 5, 11, 7,
+];
+
+// Multiplicative hash spreading 32-bit inputs over the 256 buckets of `FJ32_BASES`.
+pub(crate) fn fj_hash(n: u32) -> usize {
+    let mut h = n;
+    h = ((h >> 16) ^ h).wrapping_mul(0x45d9f3b);
+    h = ((h >> 16) ^ h).wrapping_mul(0x45d9f3b);
+    h = (h >> 16) ^ h;
+    (h & 255) as usize
+}
+
+/// Computes the Jacobi symbol (`a` / `n`).
+///
+/// # Arguments
+///
+/// * `a` - The numerator.
+/// * `n` - The denominator, an odd positive number.
+///
+/// # Returns
+///
+/// 1, -1 or 0 (the latter iff gcd(`a`, `n`) > 1).
+///
+/// # Panics
+///
+/// Panics if `n` is even or 0.
+pub fn jacobi(a: u64, n: u64) -> i32 {
+    if n.is_multiple_of(2) {
+        panic!("n must be odd!");
+    }
+    let mut a = a % n;
+    let mut n = n;
+    let mut retval = 1;
+    while a != 0 {
+        let tz = a.trailing_zeros();
+        a >>= tz;
+        if tz % 2 == 1 && (n % 8 == 3 || n % 8 == 5) {
+            retval = -retval;
+        }
+        if a % 4 == 3 && n % 4 == 3 {
+            retval = -retval;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= n;
+    }
+    if n == 1 {
+        retval
+    } else {
+        0
+    }
+}
+
+/// Strong Lucas probable prime test with Selfridge's parameters (`P = 1`,
+/// `Q = (1 - D) / 4` for the first `D` in 5, -7, 9, -11, ... with (`D` / `n`) = -1).
+///
+/// # Arguments
+///
+/// * `n` - An odd number greater than 2.
+///
+/// # Returns
+///
+/// `true` if `n` is a strong Lucas probable prime, `false` if it is certainly composite.
+pub fn strong_lucas(n: u64) -> bool {
+    let r = n.isqrt();
+    if r * r == n {
+        // No D with (D / n) = -1 exists for perfect squares.
+        return false;
+    }
+    let mut d: i64 = 5;
+    loop {
+        let dm = (d as i128).rem_euclid(n as i128) as u64;
+        match jacobi(dm, n) {
+            -1 => break,
+            0 if d.unsigned_abs() != n => return false,
+            _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+        }
+    }
+    let n128 = n as u128;
+    let dm = (d as i128).rem_euclid(n as i128) as u64;
+    let q = (((1 - d) / 4) as i128).rem_euclid(n as i128) as u64;
+    let half = |x: u64| -> u64 {
+        if x.is_multiple_of(2) {
+            x / 2
+        } else {
+            ((x as u128 + n128) / 2) as u64
+        }
+    };
+    let sub = |x: u64, y: u64| if x >= y { x - y } else { x + (n - y) };
+    let add = |x: u64, y: u64| ((x as u128 + y as u128) % n128) as u64;
+
+    let k = n as u128 + 1;
+    let s = k.trailing_zeros();
+    let k = k >> s;
+    // U_1 = 1, V_1 = P = 1, Q^1 = Q
+    let mut u: u64 = 1;
+    let mut v: u64 = 1;
+    let mut qk = q;
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = modmul(u, v, n);
+        v = sub(modmul(v, v, n), add(qk, qk));
+        qk = modmul(qk, qk, n);
+        if (k >> bit) & 1 == 1 {
+            let nu = half(add(u, v));
+            let nv = half(add(modmul(dm, u, n), v));
+            u = nu;
+            v = nv;
+            qk = modmul(qk, q, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub(modmul(v, v, n), add(qk, qk));
+        qk = modmul(qk, qk, n);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Deterministic primality test for `u64` using only two or three modular exponentiations.
+///
+/// After trial division by primes below 100, `n` must pass a base-2 Miller-Rabin round.
+/// For `n < 2^32` a second round uses a base looked up from a hash of `n` (Forišek–Jančina),
+/// which is guaranteed to reject every remaining base-2 strong pseudoprime. Larger `n` get
+/// a strong Lucas test instead (Baillie–PSW), which has no counterexample below 2^64.
+///
+/// Forišek and Jančina also hash bases for all of `u64`, but that needs a table of 2^18
+/// bases (512 KiB) built from every base-2 strong pseudoprime below 2^64. The Lucas test
+/// costs about two more rounds and needs no table.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+///
+/// # Returns
+///
+/// `true` if `n` is prime, `false` otherwise.
+pub fn is_prime_fast(n: u64) -> bool {
+    const SMALL_PRIMES: [u64; 25] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    ];
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < 97 * 97 {
+        return true;
+    }
+    if !miller_rabin_witness(n, 2) {
+        return false;
+    }
+    if n >> 32 == 0 {
+        return miller_rabin_witness(n, FJ32_BASES[fj_hash(n as u32)] as u64);
+    }
+    strong_lucas(n)
+}
+
+// Full 256-bit product of two u128 values as (high, low) halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+    let cross = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let low = (cross << 64) | (lo_lo & MASK);
+    let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+    (high, low)
+}
+
+// Squares `x` modulo the Mersenne number 2^p - 1 using x mod M = (x & M) + (x >> p).
+fn square_mod_mersenne(x: u128, p: u32) -> u128 {
+    let m = (1u128 << p) - 1;
+    let (high, low) = mul_wide(x, x);
+    let shifted = (high << (128 - p)) | (low >> p);
+    let mut r = (low & m) + shifted;
+    r = (r & m) + (r >> p);
+    if r >= m {
+        r -= m;
+    }
+    r
+}
+
+/// Checks whether the Mersenne number 2^`p` - 1 is prime with the Lucas–Lehmer test.
+///
+/// Iterates `s ← s² - 2` from `s = 4` modulo `M = 2^p - 1`; for an odd prime `p`, `M` is
+/// prime iff the `(p - 2)`-th term is 0. Reductions modulo `M` use shifts and additions only.
+///
+/// # Arguments
+///
+/// * `p` - The exponent, at most 127 so that 2^`p` - 1 fits in a `u128`.
+///
+/// # Returns
+///
+/// `true` if 2^`p` - 1 is prime, `false` otherwise.
+///
+/// # Panics
+///
+/// Panics if `p` is greater than 127.
+pub fn is_mersenne_prime(p: u32) -> bool {
+    if p > 127 {
+        panic!("p cannot be greater than 127!");
+    }
+    if p == 2 {
+        return true;
+    }
+    if !crate::pollard::is_prime(p as u64) {
+        return false;
+    }
+    let m = (1u128 << p) - 1;
+    let mut s: u128 = 4;
+    for _ in 0..p - 2 {
+        s = square_mod_mersenne(s, p);
+        s = if s >= 2 { s - 2 } else { s + m - 2 };
+    }
+    s == 0
+}
+
+/// Checks whether the Proth number `N = k·2^n + 1` is prime using Proth's theorem.
+///
+/// For `k < 2^n`, `N` is prime iff `a^((N-1)/2) ≡ -1 (mod N)` for some `a`. Choosing `a` as a
+/// quadratic non-residue modulo `N` makes a single exponentiation decisive. Even `k` are
+/// normalized by moving their factors of 2 into the exponent.
+///
+/// # Arguments
+///
+/// * `k` - The multiplier.
+/// * `n` - The exponent of 2.
+///
+/// # Returns
+///
+/// `true` if `k·2^n + 1` is prime, `false` otherwise.
+///
+/// # Panics
+///
+/// Panics if `k` is 0, if (after normalization) `k >= 2^n`, or if `k·2^n + 1` overflows a `u64`.
+pub fn is_proth_prime(k: u64, n: u32) -> bool {
+    if k == 0 {
+        panic!("k cannot be zero!");
+    }
+    let tz = k.trailing_zeros();
+    let (k, n) = (k >> tz, n + tz);
+    if n >= 64 || k >> n != 0 {
+        panic!("k must be less than 2^n!");
+    }
+    let big_n = match k.checked_mul(1 << n).and_then(|x| x.checked_add(1)) {
+        Some(x) => x,
+        None => panic!("k * 2^n + 1 must fit in a u64!"),
+    };
+    if big_n == 3 {
+        return true;
+    }
+    let mut a = 3;
+    loop {
+        match jacobi(a, big_n) {
+            -1 => break,
+            0 => return a == big_n,
+            _ => a += 1,
+        }
+    }
+    mod_exp(a, (big_n - 1) / 2, big_n) == big_n - 1
+}
+
+/// Checks whether the Fermat number `F_m = 2^(2^m) + 1` is prime using Pépin's test:
+/// `F_m` is prime iff `3^((F_m - 1)/2) ≡ -1 (mod F_m)`.
+///
+/// # Arguments
+///
+/// * `m` - The index of the Fermat number, at most 5 so that `F_m` fits in a `u64`.
+///
+/// # Returns
+///
+/// `true` if `F_m` is prime, `false` otherwise.
+///
+/// # Panics
+///
+/// Panics if `m` is greater than 5.
+pub fn is_fermat_prime(m: u32) -> bool {
+    if m > 5 {
+        panic!("m cannot be greater than 5!");
+    }
+    if m == 0 {
+        return true;
+    }
+    let f = (1u64 << (1 << m)) + 1;
+    mod_exp(3, (f - 1) / 2, f) == f - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pollard::is_prime;
+
+    #[test]
+    fn test_jacobi() {
+        assert_eq!(jacobi(1, 1), 1);
+        assert_eq!(jacobi(2, 7), 1);
+        assert_eq!(jacobi(3, 7), -1);
+        assert_eq!(jacobi(30, 7), 1);
+        assert_eq!(jacobi(21, 7), 0);
+        assert_eq!(jacobi(1001, 9907), -1);
+        assert_eq!(jacobi(19, 45), 1);
+        assert_eq!(jacobi(8, 21), -1);
+    }
+
+    #[test]
+    fn test_strong_lucas() {
+        // The smallest strong Lucas pseudoprimes.
+        for n in [5459, 5777, 10877, 16109, 18971] {
+            assert!(strong_lucas(n));
+            assert!(!is_prime(n));
+        }
+        for n in (3..20000u64).step_by(2) {
+            if is_prime(n) {
+                assert!(strong_lucas(n), "n = {}", n);
+            }
+        }
+        assert!(!strong_lucas(49));
+        assert!(strong_lucas(18446744073709551557));
+    }
+
+    #[test]
+    fn test_is_prime_fast() {
+        for n in 0..200000 {
+            assert_eq!(is_prime_fast(n), is_prime(n), "n = {}", n);
+        }
+        // Strong pseudoprimes to many bases and a few large primes.
+        for n in [2047, 3215031751, 341550071728321, 3825123056546413051, 4294967291, 4294967297,
+                  1000000007, 1000003 * 1000033, 18446744073709551557, 18446744073709551559] {
+            assert_eq!(is_prime_fast(n), is_prime(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_is_prime_fast_strong_pseudoprimes() {
+        // Base-2 strong pseudoprimes: small ones, all of them from 2^32 − 10^8 up to 2^32
+        // (each must be rejected by its `FJ32_BASES` entry), and the first ones above 2^32
+        // (rejected by the Lucas test).
+        let below = [
+            2047, 3277, 4033, 4681, 8321, 3215031751, 4195843037, 4196323561, 4201014949,
+            4204344601, 4206006229, 4206295433, 4212105409, 4218900001, 4232966251, 4234224601,
+            4237212061, 4244022301, 4244663651, 4247990917, 4250920459, 4251904273, 4255695013,
+            4271267333, 4275011401, 4277526901, 4278305651, 4282867213, 4294901761,
+        ];
+        let above = [
+            4294967297, 4297078001, 4297753027, 4298473121, 4304942281, 4310100169, 4312294417,
+            4313635201, 4318449049, 4319596849, 4322383451, 4331240033, 4333670797, 4340265931,
+            4340886929, 4343011081, 4345848721, 4347019741, 4359333637, 4367580493, 4367711261,
+            4373235001, 4374332749, 4374704977, 4377840307, 4380446657, 4394140633,
+            341550071728321, 3825123056546413051,
+        ];
+        for n in below.into_iter().chain(above) {
+            assert!(miller_rabin_witness(n, 2), "n = {}", n);
+            assert!(!is_prime(n), "n = {}", n);
+            assert!(!is_prime_fast(n), "n = {}", n);
+        }
+        for n in below {
+            assert!(!miller_rabin_witness(n, FJ32_BASES[fj_hash(n as u32)] as u64), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_is_prime_fast_random() {
+        let mut x: u64 = 88172645463325252;
+        for _ in 0..20000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            assert_eq!(is_prime_fast(x), is_prime(x), "n = {}", x);
+            assert_eq!(is_prime_fast(x >> 32), is_prime(x >> 32), "n = {}", x >> 32);
+        }
+    }
+
+    #[test]
+    fn test_mul_wide() {
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(mul_wide(1 << 100, 1 << 100), (1 << 72, 0));
+        assert_eq!(mul_wide(12345, 67890), (0, 12345 * 67890));
+    }
+
+    #[test]
+    fn test_is_mersenne_prime() {
+        let exponents = [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127];
+        for p in 0..=127 {
+            assert_eq!(is_mersenne_prime(p), exponents.contains(&p), "p = {}", p);
+        }
+        for p in 2..64 {
+            assert_eq!(is_mersenne_prime(p), is_prime((1u64 << p) - 1), "p = {}", p);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "p cannot be greater than 127!")]
+    fn test_is_mersenne_prime_too_large() {
+        is_mersenne_prime(128);
+    }
+
+    #[test]
+    fn test_is_proth_prime() {
+        for n in 1..20u32 {
+            for k in (1..(1u64 << n).min(2000)).step_by(2) {
+                let big_n = (k << n) + 1;
+                assert_eq!(is_proth_prime(k, n), is_prime(big_n), "k = {}, n = {}", k, n);
+            }
+        }
+        // 3 * 2^n + 1 for n = 1, 2, 5, 6, 8, 12, 18, 30, 36, 41, 66 ...
+        assert!(is_proth_prime(3, 41));
+        assert!(!is_proth_prime(3, 40));
+        assert!(is_proth_prime(6, 17));
+        assert!(is_proth_prime(1, 16));
+    }
+
+    #[test]
+    #[should_panic(expected = "k must be less than 2^n!")]
+    fn test_is_proth_prime_not_proth() {
+        is_proth_prime(9, 3);
+    }
+
+    #[test]
+    fn test_is_fermat_prime() {
+        for m in 0..=4 {
+            assert!(is_fermat_prime(m));
+        }
+        assert!(!is_fermat_prime(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "m cannot be greater than 5!")]
+    fn test_is_fermat_prime_too_large() {
+        is_fermat_prime(6);
+    }
+}
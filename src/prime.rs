@@ -0,0 +1,132 @@
+//! Prime factorization, primality tests, prime counting and the arithmetic functions built
+//! on factorizations, for `u64`.
+//!
+//! These are the functions of the former `Prime` facade, callable as `prime::factor(n)`.
+//! The primality test and factorization are also methods of every [`UInt`](crate::UInt)
+//! width, as in `42u64.factor()`.
+
+use crate::error::MathCombError;
+use crate::pollard;
+use crate::smooth::SmoothNumbers;
+use crate::stream::FactorStream;
+
+pub use crate::arith::{
+    are_amicable, as_prime_power, big_omega, classify, count_coprime_pairs, count_coprime_up_to, count_squarefree,
+    count_squarefree_with_progress, divisor_summatory, divisor_summatory_with_progress, farey_length, gcd_sum,
+    is_carmichael, is_k_almost_prime, is_powerful, is_squarefree, jordan_totient, jordan_totient_mod, kth_coprime,
+    kth_divisor, liouville, omega, phi_iteration_length, primorial, primorial_mod, primorial_u128, sigma,
+    sigma_summatory, squarefree_part, totient_sum, totient_sum_with_progress, MAX_TOTIENT_SUM,
+};
+pub use crate::fermat::fermat;
+pub use crate::min25::{multiplicative_sum, multiplicative_sum_with_progress};
+pub use crate::pollard::{
+    factor, factor_batch, factor_inline, factor_into, factor_map, factor_with_progress, factor_with_rng,
+    factor_with_trial_limit, find_witness, is_prime, largest_prime_factor, miller_rabin_witness, pollard,
+    pollard_p_minus_1, pollard_with_rng, smallest_prime_factor, williams_p_plus_1, MAX_FACTORS,
+};
+pub use crate::primality::{is_fermat_prime, is_mersenne_prime, is_prime_fast, is_proth_prime};
+pub use crate::primecount::{
+    count_almost_primes, nth_prime, prime_pi as count_primes, prime_pi_with_progress as count_primes_with_progress,
+    sum_prime_powers as sum_f_over_primes, sum_primes, sum_primes_with_progress,
+};
+pub use crate::smooth::is_smooth;
+
+/// Factorizes `n` like `factor`, returning an error instead of panicking.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+///
+/// # Returns
+///
+/// The prime factors of `n` in sorted order, or `MathCombError::InvalidArgument` if `n`
+/// is 0.
+pub fn try_factor(n: u64) -> Result<Vec<u64>, MathCombError> {
+    if n == 0 {
+        return Err(MathCombError::InvalidArgument("n cannot be zero"));
+    }
+    Ok(pollard::factor(n))
+}
+
+/// Factorizes `n` like `factor_map`, returning an error instead of panicking.
+///
+/// # Arguments
+///
+/// * `n` - The number to factorize.
+///
+/// # Returns
+///
+/// The `(prime, exponent)` pairs sorted by prime, or `MathCombError::InvalidArgument` if
+/// `n` is 0.
+pub fn try_factor_map(n: u64) -> Result<Vec<(u64, u32)>, MathCombError> {
+    if n == 0 {
+        return Err(MathCombError::InvalidArgument("n cannot be zero"));
+    }
+    Ok(pollard::factor_map(n))
+}
+
+/// Computes the totient summatory function Φ(n) like `totient_sum`, returning an error
+/// instead of panicking.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Returns
+///
+/// Φ(n), or `MathCombError::AboveLimit` if `n` is greater than `MAX_TOTIENT_SUM`.
+pub fn try_totient_sum(n: u64) -> Result<u128, MathCombError> {
+    if n > MAX_TOTIENT_SUM {
+        return Err(MathCombError::AboveLimit { value: n, limit: MAX_TOTIENT_SUM });
+    }
+    Ok(totient_sum(n))
+}
+
+/// Lazily factorizes every number of `numbers`, yielding their factors in input order.
+/// Call `chunked` on the stream to factorize the inputs by chunks in parallel.
+///
+/// # Arguments
+///
+/// * `numbers` - The numbers to factorize.
+///
+/// # Returns
+///
+/// A `FactorStream` yielding the `Factors` of each input.
+///
+/// # Panics
+///
+/// The stream panics on an input that is 0.
+pub fn factor_stream<I: IntoIterator<Item = u64>>(numbers: I) -> FactorStream<'static, I::IntoIter> {
+    FactorStream::new(numbers.into_iter(), None)
+}
+
+/// Returns an iterator over all `b`-smooth numbers in `[1, limit]` in increasing order.
+///
+/// # Arguments
+///
+/// * `b` - The smoothness bound.
+/// * `limit` - The inclusive upper bound of the generated numbers.
+pub fn smooth_numbers(b: u64, limit: u64) -> SmoothNumbers {
+    SmoothNumbers::new(b, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UInt;
+
+    #[test]
+    fn test_module_paths() {
+        assert_eq!(factor(15006435), vec![3, 5, 1000429]);
+        assert_eq!(15006435u64.factor(), factor(15006435));
+        assert!(is_prime(1000000007) && 1000000007u64.is_prime());
+        assert_eq!(count_primes(1000000), 78498);
+        assert_eq!(sum_f_over_primes(100, 0, 1000000007), 25);
+        assert_eq!(smooth_numbers(3, 10).collect::<Vec<u64>>(), vec![1, 2, 3, 4, 6, 8, 9]);
+        assert_eq!(try_factor(0), Err(MathCombError::InvalidArgument("n cannot be zero")));
+        assert_eq!(try_factor_map(12), Ok(vec![(2, 2), (3, 1)]));
+        assert_eq!(try_totient_sum(10), Ok(32));
+        let above = Err(MathCombError::AboveLimit { value: MAX_TOTIENT_SUM + 1, limit: MAX_TOTIENT_SUM });
+        assert_eq!(try_totient_sum(MAX_TOTIENT_SUM + 1), above);
+    }
+}
@@ -0,0 +1,556 @@
+use crate::error::MathCombError;
+use crate::pollard::modmul;
+use crate::{modexp, sieve, ProgressHook};
+
+/// Prime sums Σ_{p≤v} g(p) for every `v` of the form `n / i`, computed by the
+/// Lucy_Hedgehog dynamic programme over the O(√n) distinct quotients.
+///
+/// The table starts from Σ_{2≤i≤v} g(i) for a completely multiplicative `g` and sieves out
+/// the composites one prime at a time: those with smallest prime factor `p` contribute
+/// `g(p)·(S(v / p) − S(p − 1))`.
+pub struct PrimeSumTable<T> {
+    n: u64,
+    r: usize,
+    // small[v] = S(v) for v <= r
+    small: Vec<T>,
+    // large[i] = S(n / i) for i <= r
+    large: Vec<T>,
+}
+
+impl<T: Copy> PrimeSumTable<T> {
+    /// Builds the table for `n` in O(n^(3/4) / log n) steps and O(√n) memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The largest quotient.
+    /// * `init` - Σ_{2≤i≤v} g(i) as a function of `v`.
+    /// * `weight` - g(p) as a function of the prime `p`.
+    /// * `step` - Given `S(v)`, `g(p)`, `S(v / p)` and `S(p − 1)`, the value of `S(v)` with
+    ///   the multiples of `p` removed.
+    pub fn new(n: u64, init: impl Fn(u64) -> T, weight: impl Fn(u64) -> T, step: impl Fn(T, T, T, T) -> T) -> PrimeSumTable<T> {
+        // `()` never cancels.
+        PrimeSumTable::build(n, init, weight, step, &()).unwrap()
+    }
+
+    // Builds the table like `new`, reporting the sieving prime out of √n to `hook` and
+    // returning `None` as soon as it cancels.
+    fn build(
+        n: u64,
+        init: impl Fn(u64) -> T,
+        weight: impl Fn(u64) -> T,
+        step: impl Fn(T, T, T, T) -> T,
+        hook: &dyn ProgressHook,
+    ) -> Option<PrimeSumTable<T>> {
+        let r = n.isqrt() as usize;
+        let mut small: Vec<T> = (0..=r as u64).map(&init).collect();
+        let mut large: Vec<T> = (0..=r as u64).map(|i| init(n.checked_div(i).unwrap_or(0))).collect();
+        for p in sieve::primes_up_to(r) {
+            if hook.is_cancelled() {
+                return None;
+            }
+            hook.report(p, r as u64);
+            let (p, w) = (p as usize, weight(p));
+            let below = small[p - 1];
+            let p2 = p * p;
+            let lim = r.min((n / p2 as u64) as usize);
+            for i in 1..=lim {
+                let d = i * p;
+                let v = if d <= r { large[d] } else { small[(n / d as u64) as usize] };
+                large[i] = step(large[i], w, v, below);
+            }
+            for j in (p2..=r).rev() {
+                small[j] = step(small[j], w, small[j / p], below);
+            }
+        }
+        hook.report(r as u64, r as u64);
+        Some(PrimeSumTable { n, r, small, large })
+    }
+
+    /// Tabulates an arbitrary function of the quotients `v = n / i` in the same layout.
+    pub fn from_fn(n: u64, f: impl Fn(u64) -> T) -> PrimeSumTable<T> {
+        let r = n.isqrt() as usize;
+        let small = (0..=r as u64).map(&f).collect();
+        let large = (0..=r as u64).map(|i| f(n.checked_div(i).unwrap_or(0))).collect();
+        PrimeSumTable { n, r, small, large }
+    }
+
+    /// Returns S(`v`) for a `v` of the form `n / i` (or any `v <= √n`).
+    pub fn get(&self, v: u64) -> T {
+        if v <= self.r as u64 {
+            self.small[v as usize]
+        } else {
+            self.large[(self.n / v) as usize]
+        }
+    }
+}
+
+/// π(v) for every `v` of the form `n / i`.
+pub type PrimePiTable = PrimeSumTable<u64>;
+
+impl PrimeSumTable<u64> {
+    /// Builds the prime-counting table for `n` in O(n^(3/4)) time and O(√n) memory.
+    pub fn prime_pi(n: u64) -> PrimePiTable {
+        PrimeSumTable::new(n, |v| v.saturating_sub(1), |_| 1, |s, _, q, b| s - (q - b))
+    }
+
+    /// Builds the table of Σ p^k modulo the prime `m`, seeded with the power sums Σ i^k.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is zero or `k + 1` is not less than `m`.
+    pub fn prime_powers(n: u64, k: u32, m: u64) -> PrimeSumTable<u64> {
+        if m == 0 {
+            panic!("Modulus cannot be zero.");
+        }
+        if k as u64 + 1 >= m {
+            panic!("Degree is too large for the modulus!");
+        }
+        // `()` never cancels.
+        PrimeSumTable::prime_powers_by(n, k, m, &()).unwrap()
+    }
+
+    // Builds the table of `prime_powers` for valid arguments, reporting to `hook` as
+    // `build` does and returning `None` as soon as it cancels.
+    pub(crate) fn prime_powers_by(n: u64, k: u32, m: u64, hook: &dyn ProgressHook) -> Option<PrimeSumTable<u64>> {
+        let power_sum = PowerSum::new(k, m);
+        let init = |v: u64| if v == 0 { 0 } else { sub_mod(power_sum.eval(v), 1, m) };
+        let weight = |p: u64| modexp::mod_exp(p % m, k as u64, m);
+        let step = |s: u64, w: u64, q: u64, b: u64| sub_mod(s, mul_mod(w, sub_mod(q, b, m), m), m);
+        PrimeSumTable::build(n, init, weight, step, hook)
+    }
+}
+
+/// Counts the primes `<= n`, π(n), with the Lucy_Hedgehog dynamic programme over the
+/// O(√n) distinct values of `n / i`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Complexity
+///
+/// O(n^(3/4)) time and O(√n) memory.
+pub fn prime_pi(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    PrimePiTable::prime_pi(n).get(n)
+}
+
+/// Counts the primes `<= n` like `prime_pi`, reporting progress to `hook` and giving up
+/// when it cancels.
+///
+/// Progress is counted in sieving primes: `hook.report(p, √n)` is called before the
+/// composites with smallest prime factor `p` are removed, and with `(√n, √n)` at the end.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// π(n), or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn prime_pi_with_progress(n: u64, hook: &impl ProgressHook) -> Result<u64, MathCombError> {
+    if n < 2 {
+        return Ok(0);
+    }
+    PrimeSumTable::build(n, |v| v.saturating_sub(1), |_| 1, |s, _, q, b| s - (q - b), hook)
+        .map(|table| table.get(n))
+        .ok_or(MathCombError::Cancelled)
+}
+
+/// Sums the primes `<= n` with the Lucy_Hedgehog dynamic programme over the O(√n)
+/// distinct values of `n / i`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+///
+/// # Complexity
+///
+/// O(n^(3/4)) time and O(√n) memory.
+pub fn sum_primes(n: u64) -> u128 {
+    // `()` never cancels.
+    sum_primes_with_progress(n, &()).unwrap()
+}
+
+/// Sums the primes `<= n` like `sum_primes`, reporting progress to `hook` and giving up
+/// when it cancels.
+///
+/// Progress is counted in sieving primes as for `prime_pi_with_progress`.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `hook` - Receives progress reports and may cancel the computation.
+///
+/// # Returns
+///
+/// The sum of the primes `<= n`, or `MathCombError::Cancelled` if `hook` cancelled first.
+pub fn sum_primes_with_progress(n: u64, hook: &impl ProgressHook) -> Result<u128, MathCombError> {
+    // v(v + 1) / 2 − 1, halving the even factor first so that no product overflows.
+    let init = |v: u64| match v {
+        0 => 0,
+        _ if v.is_multiple_of(2) => (v / 2) as u128 * (v as u128 + 1) - 1,
+        _ => v as u128 * (v / 2 + 1) as u128 - 1,
+    };
+    let step = |s: u128, w: u128, q: u128, b: u128| s - w * (q - b);
+    PrimeSumTable::build(n, init, |p| p as u128, step, hook).map(|table| table.get(n)).ok_or(MathCombError::Cancelled)
+}
+
+pub(crate) fn add_mod(a: u64, b: u64, m: u64) -> u64 {
+    if a >= m - b { a - (m - b) } else { a + b }
+}
+
+pub(crate) fn sub_mod(a: u64, b: u64, m: u64) -> u64 {
+    if a >= b { a - b } else { a + (m - b) }
+}
+
+// Multiplies modulo m, avoiding the slow 128-bit division for moduli below 2^32.
+pub(crate) fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    if m >> 32 == 0 { a * b % m } else { modmul(a, b, m) }
+}
+
+// Evaluates Σ_{i≤v} i^k modulo the prime m, a polynomial of degree k + 1 in v, by
+// Lagrange interpolation through v = 0, 1, …, k + 1.
+struct PowerSum {
+    m: u64,
+    // ys[t] = Σ_{i≤t} i^k
+    ys: Vec<u64>,
+    // coeffs[t] = ys[t] / Π_{j≠t} (t − j)
+    coeffs: Vec<u64>,
+}
+
+impl PowerSum {
+    fn new(k: u32, m: u64) -> PowerSum {
+        let d = k as usize + 2;
+        let mut ys = vec![0; d];
+        for t in 1..d {
+            ys[t] = add_mod(ys[t - 1], modexp::mod_exp(t as u64, k as u64, m), m);
+        }
+        let mut fact = vec![1; d];
+        for i in 1..d {
+            fact[i] = modmul(fact[i - 1], i as u64, m);
+        }
+        let coeffs = (0..d)
+            .map(|t| {
+                let c = modmul(ys[t], modexp::mod_inv(modmul(fact[t], fact[d - 1 - t], m), m), m);
+                if (d - 1 - t).is_multiple_of(2) { c } else { sub_mod(0, c, m) }
+            })
+            .collect();
+        PowerSum { m, ys, coeffs }
+    }
+
+    fn eval(&self, v: u64) -> u64 {
+        let (m, d) = (self.m, self.ys.len());
+        let x = v % m;
+        if x < d as u64 {
+            return self.ys[x as usize];
+        }
+        // suffix[t] = Π_{j>=t} (x − j)
+        let mut suffix = vec![1; d + 1];
+        for t in (0..d).rev() {
+            suffix[t] = modmul(suffix[t + 1], x - t as u64, m);
+        }
+        let (mut sum, mut prefix) = (0, 1);
+        for t in 0..d {
+            sum = add_mod(sum, modmul(self.coeffs[t], modmul(prefix, suffix[t + 1], m), m), m);
+            prefix = modmul(prefix, x - t as u64, m);
+        }
+        sum
+    }
+}
+
+/// Computes Σ p^k over the primes `p <= n`, modulo the prime `m`, with the Lucy_Hedgehog
+/// dynamic programme; the starting sums Σ i^k come from Faulhaber's polynomial.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `k` - The exponent.
+/// * `m` - A prime modulus.
+///
+/// # Panics
+///
+/// Panics if `m` is zero or `k + 1` is not less than `m`.
+///
+/// # Complexity
+///
+/// O(n^(3/4) + k·√n) time and O(√n) memory.
+pub fn sum_prime_powers(n: u64, k: u32, m: u64) -> u64 {
+    PrimeSumTable::prime_powers(n, k, m).get(n)
+}
+
+/// Counts the numbers `<= n` with exactly `k` prime factors counted with multiplicity
+/// (k-almost-primes; `k = 2` gives the semiprimes).
+///
+/// Recurses over the smallest prime factor: a k-almost-prime `<= n` with smallest prime `p`
+/// is `p` times a (k−1)-almost-prime `<= n / p` whose primes are all `>= p`. The innermost
+/// level is a prime count, and every quotient that occurs has the form `n / i`, so a single
+/// Lucy_Hedgehog table answers all of them.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive bound.
+/// * `k` - The number of prime factors.
+pub fn count_almost_primes(n: u64, k: u32) -> u64 {
+    if k == 0 {
+        return (n >= 1) as u64;
+    }
+    if n < 2 {
+        return 0;
+    }
+    let table = PrimePiTable::prime_pi(n);
+    let primes = sieve::primes_up_to(n.isqrt() as usize);
+    count_almost_primes_from(n, k, 0, &primes, &table)
+}
+
+// Counts k-almost-primes <= m whose prime factors are all >= primes[i].
+fn count_almost_primes_from(m: u64, k: u32, i: usize, primes: &[u64], table: &PrimePiTable) -> u64 {
+    if k == 1 {
+        return table.get(m).saturating_sub(i as u64);
+    }
+    let mut retval = 0;
+    for j in i..primes.len() {
+        let p = primes[j];
+        if p.checked_pow(k).is_none_or(|pk| pk > m) {
+            break;
+        }
+        retval += count_almost_primes_from(m / p, k - 1, j, primes, table);
+    }
+    retval
+}
+
+// Logarithmic integral li(x) via Ramanujan's rapidly converging series.
+fn li(x: f64) -> f64 {
+    const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+    let ln_x = x.ln();
+    let mut sum = 0.0;
+    let mut term = 1.0;
+    let mut inner = 0.0;
+    for n in 1..200 {
+        term *= ln_x / n as f64;
+        if (n - 1) % 2 == 0 {
+            inner += 1.0 / n as f64;
+        }
+        let sign = if n % 2 == 1 { 1.0 } else { -1.0 };
+        let delta = sign * term / (1u64 << (n - 1).min(63)) as f64 * inner;
+        sum += delta;
+        if delta.abs() < 1e-18 * sum.abs() {
+            break;
+        }
+    }
+    EULER_GAMMA + ln_x.ln() + x.sqrt() * sum
+}
+
+// Estimates the `k`-th prime by inverting li with Newton's method.
+fn nth_prime_estimate(k: u64) -> u64 {
+    let kf = k as f64;
+    let ln_k = kf.ln();
+    let ln_ln_k = ln_k.ln();
+    let mut x = kf * (ln_k + ln_ln_k - 1.0 + (ln_ln_k - 2.0) / ln_k);
+    for _ in 0..20 {
+        let next = x - (li(x) - kf) * x.ln();
+        if (next - x).abs() < 0.5 {
+            x = next;
+            break;
+        }
+        x = next;
+    }
+    x as u64
+}
+
+/// Finds the `k`-th prime (1-indexed, so `nth_prime(1)` is 2).
+///
+/// Small `k` are answered with a plain sieve. For large `k` the answer is estimated
+/// through the inverse logarithmic integral, the estimate is corrected with an exact
+/// prime count, and the remaining gap is closed by sieving windows around it.
+///
+/// # Arguments
+///
+/// * `k` - The index of the prime.
+///
+/// # Panics
+///
+/// Panics if `k` is 0.
+pub fn nth_prime(k: u64) -> u64 {
+    if k == 0 {
+        panic!("k must be at least 1!");
+    }
+    if k <= 1_000_000 {
+        // Rosser's bound p_k < k (ln k + ln ln k) holds for k >= 6.
+        let kf = k as f64;
+        let bound = if k < 6 { 15 } else { (kf * (kf.ln() + kf.ln().ln())) as usize + 1 };
+        return sieve::primes_up_to(bound)[(k - 1) as usize];
+    }
+    const WINDOW: u64 = 1 << 18;
+    let x = nth_prime_estimate(k);
+    let count = prime_pi(x);
+    let base = sieve::primes_up_to(((x + x / 8 + WINDOW) as f64).sqrt() as usize + 1);
+    if count >= k {
+        // The answer is the (count - k + 1)-th prime scanning down from x.
+        let mut need = count - k + 1;
+        let mut hi = x;
+        loop {
+            let lo = hi.saturating_sub(WINDOW - 1);
+            let window = sieve::sieve_segment(lo, hi, &base);
+            for i in (0..window.len()).rev() {
+                if window[i] {
+                    need -= 1;
+                    if need == 0 {
+                        return lo + i as u64;
+                    }
+                }
+            }
+            // The k-th prime lies in [2, x] since prime_pi(x) >= k, so the scan stops before
+            // the window reaches 0.
+            hi = match lo.checked_sub(1) {
+                Some(hi) => hi,
+                None => unreachable!("prime_pi(x) counted more primes than there are up to x"),
+            };
+        }
+    } else {
+        let mut need = k - count;
+        let mut lo = x + 1;
+        loop {
+            let hi = lo + WINDOW - 1;
+            let window = sieve::sieve_segment(lo, hi, &base);
+            for (i, &is_prime) in window.iter().enumerate() {
+                if is_prime {
+                    need -= 1;
+                    if need == 0 {
+                        return lo + i as u64;
+                    }
+                }
+            }
+            lo = hi + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prime_pi() {
+        assert_eq!(prime_pi(0), 0);
+        assert_eq!(prime_pi(1), 0);
+        assert_eq!(prime_pi(2), 1);
+        assert_eq!(prime_pi(10), 4);
+        assert_eq!(prime_pi(100), 25);
+        assert_eq!(prime_pi(1000000), 78498);
+        assert_eq!(prime_pi(1000000000), 50847534);
+        assert_eq!(prime_pi(10000000000), 455052511);
+        assert_eq!(prime_pi(1 << 32), 203280221);
+        let primes = sieve::primes_up_to(10000);
+        for n in 0..10000u64 {
+            assert_eq!(prime_pi(n), primes.partition_point(|&p| p <= n) as u64);
+        }
+    }
+
+    #[test]
+    fn test_sum_primes() {
+        assert_eq!(sum_primes(0), 0);
+        assert_eq!(sum_primes(1), 0);
+        assert_eq!(sum_primes(2), 2);
+        assert_eq!(sum_primes(10), 17);
+        let primes = sieve::primes_up_to(5000);
+        for n in 0..5000u64 {
+            let brute: u64 = primes.iter().take_while(|&&p| p <= n).sum();
+            assert_eq!(sum_primes(n), brute as u128);
+        }
+        assert_eq!(sum_primes(2000000), 142913828922);
+        assert_eq!(sum_primes(1000000000), 24739512092254535);
+        assert_eq!(sum_primes(10000000000), 2220822432581729238);
+    }
+
+    #[test]
+    fn test_with_progress() {
+        use crate::CancelToken;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let last = AtomicU64::new(0);
+        let hook = |done, total| {
+            assert!(done <= total && total == 1000);
+            last.store(done, Ordering::Relaxed);
+        };
+        assert_eq!(prime_pi_with_progress(1000000, &hook), Ok(78498));
+        assert_eq!(last.into_inner(), 1000);
+        assert_eq!(prime_pi_with_progress(1, &()), Ok(0));
+        assert_eq!(sum_primes_with_progress(2000000, &()), Ok(142913828922));
+
+        // Cancels once the sieve passes 100.
+        struct CancelAt(CancelToken);
+        impl ProgressHook for CancelAt {
+            fn report(&self, done: u64, _: u64) {
+                if done > 100 {
+                    self.0.cancel();
+                }
+            }
+            fn is_cancelled(&self) -> bool {
+                self.0.is_cancelled()
+            }
+        }
+        let hook = CancelAt(CancelToken::new());
+        assert_eq!(prime_pi_with_progress(1000000, &hook), Err(MathCombError::Cancelled));
+        assert_eq!(sum_primes_with_progress(1000000, &hook.0), Err(MathCombError::Cancelled));
+    }
+
+    #[test]
+    fn test_sum_prime_powers() {
+        let primes = sieve::primes_up_to(3000);
+        for m in [5, 1_000_000_007, (1 << 61) - 1] {
+            for k in 0..4 {
+                for n in (0..3000u64).step_by(37) {
+                    let brute = primes.iter().take_while(|&&p| p <= n).fold(0, |s, &p| (s + modexp::mod_exp(p, k as u64, m)) % m);
+                    assert_eq!(sum_prime_powers(n, k, m), brute, "n = {}, k = {}, m = {}", n, k, m);
+                }
+            }
+        }
+        let n = 1000000000;
+        assert_eq!(sum_prime_powers(n, 0, 1_000_000_007), prime_pi(n) % 1_000_000_007);
+        assert_eq!(sum_prime_powers(n, 1, (1 << 61) - 1) as u128, sum_primes(n) % ((1 << 61) - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Degree is too large for the modulus!")]
+    fn test_sum_prime_powers_small_modulus() {
+        sum_prime_powers(100, 4, 5);
+    }
+
+    #[test]
+    fn test_count_almost_primes() {
+        let n = 5000;
+        for k in 0..14 {
+            let brute = (1..=n).filter(|&x| crate::arith::big_omega(x) == k).count() as u64;
+            assert_eq!(count_almost_primes(n, k), brute, "k = {}", k);
+        }
+        assert_eq!(count_almost_primes(0, 0), 0);
+        assert_eq!(count_almost_primes(1, 1), 0);
+        assert_eq!(count_almost_primes(100000000, 2), 17427258);
+        assert_eq!(count_almost_primes(1 << 30, 30), 1);
+        assert_eq!(count_almost_primes((1 << 30) - 1, 30), 0);
+        assert_eq!(count_almost_primes(100, 64), 0);
+    }
+
+    #[test]
+    fn test_nth_prime() {
+        assert_eq!(nth_prime(1), 2);
+        assert_eq!(nth_prime(2), 3);
+        assert_eq!(nth_prime(5), 11);
+        assert_eq!(nth_prime(6), 13);
+        assert_eq!(nth_prime(1000), 7919);
+        assert_eq!(nth_prime(1000000), 15485863);
+        assert_eq!(nth_prime(1000001), 15485867);
+        assert_eq!(nth_prime(10000000), 179424673);
+        assert_eq!(nth_prime(100000000), 2038074743);
+    }
+
+    #[test]
+    #[should_panic(expected = "k must be at least 1!")]
+    fn test_nth_prime_zero() {
+        nth_prime(0);
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Observes a long computation: the `*_with_progress` routines call `report` as work gets
+/// done and stop early with `MathCombError::Cancelled` once `is_cancelled` returns `true`.
+///
+/// Both methods may be called from several threads at once when the `rayon` feature
+/// splits the work. `()` is the hook that ignores progress and never cancels, and any
+/// `Fn(u64, u64)` closure receives the progress reports.
+pub trait ProgressHook: Sync {
+    /// Called with the units of work `done` out of `total`. The unit depends on the
+    /// routine and is documented there.
+    fn report(&self, done: u64, total: u64) {
+        let _ = (done, total);
+    }
+
+    /// Polled regularly by the routine, which gives up as soon as this returns `true`.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl ProgressHook for () {}
+
+impl<F: Fn(u64, u64) + Sync> ProgressHook for F {
+    fn report(&self, done: u64, total: u64) {
+        self(done, total)
+    }
+}
+
+/// A shareable flag for aborting a computation from another thread: clones observe the
+/// same flag, so one clone can be handed to the computation and another kept to `cancel` it.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a token that is not cancelled yet.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Asks every computation watching this token (or one of its clones) to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether `cancel` has been called on this token or one of its clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl ProgressHook for CancelToken {
+    fn is_cancelled(&self) -> bool {
+        CancelToken::is_cancelled(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_cancel_token() {
+        let token = CancelToken::new();
+        let watcher = token.clone();
+        assert!(!ProgressHook::is_cancelled(&watcher));
+        token.cancel();
+        assert!(ProgressHook::is_cancelled(&watcher));
+        assert!(!ProgressHook::is_cancelled(&()));
+
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        hook.report(1, 2);
+        assert!(!hook.is_cancelled());
+        assert_eq!(*reports.lock().unwrap(), vec![(1, 2)]);
+    }
+}
@@ -0,0 +1,99 @@
+/// A source of random 64-bit words for the randomized algorithms of the crate: Pollard's
+/// rho restarts, probabilistic primality, polynomial factorization and random sampling.
+///
+/// Each of them has a `*_with_rng` variant taking a source, while the plain variant uses
+/// a fixed deterministic sequence, so results are reproducible across runs and platforms
+/// unless a source is passed. Implement it for any generator, e.g. by forwarding to
+/// `rand::RngCore::next_u64`.
+pub trait RandomSource {
+    /// Returns the next random word.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a uniformly distributed integer in `[0, bound)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is 0.
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            panic!("bound cannot be zero!");
+        }
+        // Rejects the words of the incomplete last block so that every residue is equally likely.
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let x = self.next_u64();
+            if x <= zone {
+                return x % bound;
+            }
+        }
+    }
+}
+
+impl<R: RandomSource + ?Sized> RandomSource for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// The SplitMix64 generator: a 64-bit counter scrambled by a bijective mixer. It is fast and
+/// passes BigCrush, but is not cryptographically secure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a generator whose sequence is determined by `seed`.
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl Default for SplitMix64 {
+    /// The generator with the fixed seed behind the deterministic variants.
+    fn default() -> SplitMix64 {
+        SplitMix64::new(0x2545F4914F6CDD1D)
+    }
+}
+
+impl RandomSource for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_mix_64() {
+        // Reference outputs for seed 0.
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        assert_eq!(SplitMix64::default(), SplitMix64::new(0x2545F4914F6CDD1D));
+
+        // Goes through the forwarding impl for `&mut R`.
+        fn roll(mut rng: impl RandomSource) -> u64 {
+            rng.below(6)
+        }
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            counts[roll(&mut rng) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)));
+        assert_eq!(rng.below(1), 0);
+        assert!(rng.below(u64::MAX) < u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "bound cannot be zero!")]
+    fn test_below_zero() {
+        SplitMix64::default().below(0);
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashSet;
+
+use crate::modexp;
+use crate::random::RandomSource;
+
+// A Fenwick tree over 0..n holding 1 for every element still unused.
+struct Unused {
+    tree: Vec<usize>,
+}
+
+impl Unused {
+    fn new(n: usize) -> Unused {
+        // With every count 1, node i covers lowbit(i) elements.
+        Unused { tree: (0..=n).map(|i| i & i.wrapping_neg()).collect() }
+    }
+
+    // Counts the unused elements below `x`.
+    fn count_below(&self, x: usize) -> usize {
+        let mut i = x;
+        let mut count = 0;
+        while i > 0 {
+            count += self.tree[i];
+            i &= i - 1;
+        }
+        count
+    }
+
+    fn remove(&mut self, x: usize) {
+        let mut i = x + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // Finds the `k`-th (0-based) unused element by descending the tree.
+    fn kth(&self, k: usize) -> usize {
+        let n = self.tree.len() - 1;
+        let (mut pos, mut rest) = (0, k);
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= rest {
+                pos += step;
+                rest -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// Finds the lexicographic rank of a permutation of `0..n` among all `n!` of them.
+///
+/// The rank is read off the factorial number system: its digit for position `i` is the
+/// number of later elements smaller than `perm[i]` (the Lehmer code).
+///
+/// # Arguments
+///
+/// * `perm` - A permutation of `0..perm.len()`.
+///
+/// # Returns
+///
+/// The rank, from 0 for the identity to `n! − 1` for the reversal.
+///
+/// # Panics
+///
+/// Panics if `perm` is not a permutation of `0..perm.len()`, or if its rank does not fit
+/// in a `u128` (only possible for `n >= 35`).
+///
+/// # Complexity
+///
+/// O(n log n).
+pub fn rank_permutation(perm: &[usize]) -> u128 {
+    let n = perm.len();
+    let mut seen = vec![false; n];
+    let mut unused = Unused::new(n);
+    let mut rank: u128 = 0;
+    for (i, &x) in perm.iter().enumerate() {
+        if x >= n || seen[x] {
+            panic!("Not a permutation!");
+        }
+        seen[x] = true;
+        let digit = unused.count_below(x) as u128;
+        unused.remove(x);
+        rank = match rank.checked_mul((n - i) as u128).and_then(|r| r.checked_add(digit)) {
+            Some(r) => r,
+            None => panic!("Rank does not fit in a u128!"),
+        };
+    }
+    rank
+}
+
+/// Finds the permutation of `0..n` with lexicographic rank `k`, inverting
+/// `rank_permutation`.
+///
+/// # Arguments
+///
+/// * `n` - The number of elements.
+/// * `k` - The rank.
+///
+/// # Returns
+///
+/// The `k`-th permutation of `0..n` in lexicographic order, counting from 0.
+///
+/// # Panics
+///
+/// Panics if `k >= n!`.
+///
+/// # Complexity
+///
+/// O(n log n).
+pub fn unrank_permutation(n: usize, k: u128) -> Vec<usize> {
+    // The factorial-base digits of k, the last one in base 1.
+    let mut digits = vec![0usize; n];
+    let mut rest = k;
+    for i in 1..=n {
+        digits[n - i] = (rest % i as u128) as usize;
+        rest /= i as u128;
+    }
+    if rest != 0 {
+        panic!("Rank is out of range!");
+    }
+    let mut unused = Unused::new(n);
+    digits
+        .into_iter()
+        .map(|d| {
+            let x = unused.kth(d);
+            unused.remove(x);
+            x
+        })
+        .collect()
+}
+
+// Computes C(n, k) exactly, or `None` if it does not fit in a u128.
+fn binomial(n: u128, k: u128) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut retval: u128 = 1;
+    for j in 0..k {
+        // retval·(n − j) is divisible by j + 1, so dividing out their gcd first keeps every
+        // step exact without overflowing early.
+        let g = modexp::gcd((retval % (j + 1)) as u64, (j + 1) as u64) as u128;
+        retval = (retval / g).checked_mul((n - j) / ((j + 1) / g))?;
+    }
+    Some(retval)
+}
+
+/// Finds the rank of a `k`-subset in colexicographic order, where subsets are compared by
+/// their largest element first.
+///
+/// The subset `c_1 < c_2 < … < c_k` has rank `Σ C(c_i, i)`, which does not depend on the
+/// size of the ground set.
+///
+/// # Arguments
+///
+/// * `comb` - The elements of the subset in increasing order.
+///
+/// # Returns
+///
+/// The rank, from 0 for `{0, 1, …, k − 1}`.
+///
+/// # Panics
+///
+/// Panics if `comb` is not strictly increasing, or if the rank does not fit in a `u128`.
+pub fn rank_combination(comb: &[usize]) -> u128 {
+    let mut rank: u128 = 0;
+    for (i, &c) in comb.iter().enumerate() {
+        if i > 0 && comb[i - 1] >= c {
+            panic!("Combination must be strictly increasing!");
+        }
+        rank = match binomial(c as u128, i as u128 + 1).and_then(|b| rank.checked_add(b)) {
+            Some(r) => r,
+            None => panic!("Rank does not fit in a u128!"),
+        };
+    }
+    rank
+}
+
+/// Finds the `k`-subset of `0..n` with colexicographic rank `rank`, inverting
+/// `rank_combination`.
+///
+/// The largest element is the largest `c` with `C(c, k) <= rank`, and the rest is the
+/// `(k − 1)`-subset with rank `rank − C(c, k)`.
+///
+/// # Arguments
+///
+/// * `n` - The size of the ground set.
+/// * `k` - The size of the subset.
+/// * `rank` - The rank.
+///
+/// # Returns
+///
+/// The elements of the subset in increasing order.
+///
+/// # Panics
+///
+/// Panics if `rank >= C(n, k)`.
+pub fn unrank_combination(n: usize, k: usize, rank: u128) -> Vec<usize> {
+    if binomial(n as u128, k as u128).is_some_and(|total| rank >= total) {
+        panic!("Rank is out of range!");
+    }
+    let mut comb = vec![0; k];
+    let (mut rest, mut hi) = (rank, n);
+    for i in (1..=k).rev() {
+        // C(c, i) <= rest holds for c = i − 1 and fails for the element chosen before
+        // (or for c = n).
+        let mut lo = i - 1;
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            match binomial(mid as u128, i as u128) {
+                Some(b) if b <= rest => lo = mid,
+                _ => hi = mid,
+            }
+        }
+        rest -= binomial(lo as u128, i as u128).unwrap();
+        comb[i - 1] = lo;
+        hi = lo;
+    }
+    comb
+}
+
+/// A uniformly random permutation of `0..n`, shuffled with Fisher–Yates.
+///
+/// # Complexity
+///
+/// O(n).
+pub fn random_permutation(n: usize, rng: &mut dyn RandomSource) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..n).collect();
+    for i in (1..n).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        perm.swap(i, j);
+    }
+    perm
+}
+
+/// A uniformly random `k`-subset of `0..n` in increasing order, drawn with Floyd's
+/// algorithm.
+///
+/// # Panics
+///
+/// Panics if `k > n`.
+///
+/// # Complexity
+///
+/// O(k log k), independent of `n`.
+pub fn random_combination(n: usize, k: usize, rng: &mut dyn RandomSource) -> Vec<usize> {
+    if k > n {
+        panic!("k cannot be greater than n!");
+    }
+    // Each j in n − k..n adds a uniform element of 0..=j, or j itself if that one is taken.
+    let mut chosen = HashSet::with_capacity(k);
+    for j in n - k..n {
+        let t = rng.below(j as u64 + 1) as usize;
+        if !chosen.insert(t) {
+            chosen.insert(j);
+        }
+    }
+    let mut comb: Vec<usize> = chosen.into_iter().collect();
+    comb.sort_unstable();
+    comb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_sampling() {
+        use crate::random::SplitMix64;
+
+        let mut rng = SplitMix64::new(7);
+        let mut seen = HashSet::new();
+        for _ in 0..600 {
+            let perm = random_permutation(4, &mut rng);
+            let mut sorted = perm.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, vec![0, 1, 2, 3]);
+            seen.insert(perm);
+        }
+        assert_eq!(seen.len(), 24);
+        assert_eq!(random_permutation(0, &mut rng), vec![]);
+
+        let mut counts = [0; 5];
+        for _ in 0..2000 {
+            let comb = random_combination(5, 2, &mut rng);
+            assert!(comb.len() == 2 && comb[0] < comb[1] && comb[1] < 5);
+            for x in comb {
+                counts[x] += 1;
+            }
+        }
+        // Each element lies in 2/5 of the subsets.
+        assert!(counts.iter().all(|&c| (700..900).contains(&c)));
+        assert_eq!(random_combination(1000000000, 3, &mut rng).len(), 3);
+        assert_eq!(random_combination(4, 4, &mut rng), vec![0, 1, 2, 3]);
+        // The same seed gives the same samples.
+        assert_eq!(random_permutation(50, &mut SplitMix64::new(1)), random_permutation(50, &mut SplitMix64::new(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "k cannot be greater than n!")]
+    fn test_random_combination_too_large() {
+        random_combination(2, 3, &mut crate::random::SplitMix64::default());
+    }
+
+    #[test]
+    fn test_rank_permutation() {
+        assert_eq!(rank_permutation(&[]), 0);
+        assert_eq!(rank_permutation(&[0, 1, 2]), 0);
+        assert_eq!(rank_permutation(&[2, 1, 0]), 5);
+        assert_eq!(rank_permutation(&[1, 0, 2]), 2);
+        // Every permutation of 0..6 in lexicographic order.
+        let mut perm: Vec<usize> = (0..6).collect();
+        for k in 0..720u128 {
+            assert_eq!(rank_permutation(&perm), k);
+            assert_eq!(unrank_permutation(6, k), perm);
+            if let Some(i) = (0..5).rev().find(|&i| perm[i] < perm[i + 1]) {
+                let j = (i + 1..6).rev().find(|&j| perm[j] > perm[i]).unwrap();
+                perm.swap(i, j);
+                perm[i + 1..].reverse();
+            }
+        }
+        let reversal: Vec<usize> = (0..34).rev().collect();
+        let last = (1..=34u128).product::<u128>() - 1;
+        assert_eq!(rank_permutation(&reversal), last);
+        assert_eq!(unrank_permutation(34, last), reversal);
+        let identity: Vec<usize> = (0..100000).collect();
+        assert_eq!(rank_permutation(&identity), 0);
+        assert_eq!(unrank_permutation(100000, 0), identity);
+        assert_eq!(unrank_permutation(0, 0), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a permutation!")]
+    fn test_rank_permutation_repeated() {
+        rank_permutation(&[0, 2, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rank is out of range!")]
+    fn test_unrank_permutation_out_of_range() {
+        unrank_permutation(4, 24);
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(2, 5), Some(0));
+        assert_eq!(binomial(130, 65), Some(95067625827960698145584333020095113100));
+        assert_eq!(binomial(131, 65), Some(188694833082770476622296176145946360850));
+        assert_eq!(binomial(132, 66), None);
+        assert_eq!(binomial(u64::MAX as u128, 1), Some(u64::MAX as u128));
+    }
+
+    #[test]
+    fn test_rank_combination() {
+        assert_eq!(rank_combination(&[]), 0);
+        assert_eq!(rank_combination(&[0, 1, 2]), 0);
+        assert_eq!(rank_combination(&[0, 1, 3]), 1);
+        assert_eq!(rank_combination(&[2, 3, 4]), 9);
+        // The 3-subsets of 0..8 in colex order.
+        let mut subsets: Vec<Vec<usize>> = Vec::new();
+        for c in 2..8 {
+            for b in 1..c {
+                for a in 0..b {
+                    subsets.push(vec![a, b, c]);
+                }
+            }
+        }
+        for (r, s) in subsets.iter().enumerate() {
+            assert_eq!(rank_combination(s), r as u128);
+            assert_eq!(unrank_combination(8, 3, r as u128), *s);
+        }
+        assert_eq!(unrank_combination(5, 0, 0), vec![]);
+        let top: Vec<usize> = (70..130).collect();
+        let r = rank_combination(&top);
+        assert_eq!(r, binomial(130, 60).unwrap() - 1);
+        assert_eq!(unrank_combination(130, 60, r), top);
+        assert_eq!(unrank_combination(usize::MAX, 1, 12345), vec![12345]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Combination must be strictly increasing!")]
+    fn test_rank_combination_unsorted() {
+        rank_combination(&[1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rank is out of range!")]
+    fn test_unrank_combination_out_of_range() {
+        unrank_combination(5, 2, 10);
+    }
+}
@@ -0,0 +1,25 @@
+//! Navigation of the Stern–Brocot tree of positive fractions, Farey neighbours and best
+//! rational approximations.
+//!
+//! It holds the functions of the former `SternBrocot` facade, callable as
+//! `rational::best_approx(num, den, max_den)`. `Fraction`, `Direction` and
+//! `FareySequence` are exported at the crate root.
+
+pub use crate::sternbrocot::{best_approx, farey_neighbors, from_path, neighbors, path};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direction;
+
+    #[test]
+    fn test_module_paths() {
+        let runs = path(3, 5);
+        assert_eq!(runs[0], (Direction::Left, 1));
+        assert_eq!(from_path(&runs), (3, 5));
+        assert_eq!(neighbors(3, 5), ((1, 2), (2, 3)));
+        assert_eq!(best_approx(314159, 100000, 10), (22, 7));
+        let (before, after) = farey_neighbors(1, 2, 5);
+        assert_eq!((before.is_some(), after.is_some()), (true, true));
+    }
+}
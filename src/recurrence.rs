@@ -0,0 +1,381 @@
+use crate::modexp;
+use crate::poly;
+use crate::pollard::modmul;
+
+/// Finds the shortest linear recurrence satisfied by `seq` modulo the prime `p` with the
+/// Berlekamp–Massey algorithm.
+///
+/// # Arguments
+///
+/// * `seq` - The terms of the sequence.
+/// * `p` - A prime modulus below 2^32.
+///
+/// # Returns
+///
+/// The coefficients `c` of the shortest recurrence `a[n] = c[0]·a[n-1] + … + c[d-1]·a[n-d]`
+/// that holds for every `n` in `d..seq.len()`.
+pub fn berlekamp_massey(seq: &[u64], p: u64) -> Vec<u64> {
+    // The current recurrence and the one before the last length change, both as connection
+    // polynomials with an implicit leading 1.
+    let mut cur: Vec<u64> = vec![];
+    let mut prev: Vec<u64> = vec![];
+    let mut prev_delta = 1;
+    let mut shift = 1;
+    for n in 0..seq.len() {
+        let predicted = cur.iter().enumerate().fold(0, |acc, (i, &c)| (acc + c * (seq[n - 1 - i] % p)) % p);
+        let delta = (seq[n] % p + p - predicted) % p;
+        if delta == 0 {
+            shift += 1;
+            continue;
+        }
+        // cur += delta / prev_delta · x^shift · (1 - prev).
+        let coef = delta * modexp::mod_inv(prev_delta, p) % p;
+        let mut next = cur.clone();
+        if next.len() < prev.len() + shift {
+            next.resize(prev.len() + shift, 0);
+        }
+        next[shift - 1] = (next[shift - 1] + coef) % p;
+        for (i, &c) in prev.iter().enumerate() {
+            next[i + shift] = (next[i + shift] + p - coef * c % p) % p;
+        }
+        if 2 * cur.len() <= n {
+            prev = std::mem::replace(&mut cur, next);
+            prev_delta = delta;
+            shift = 1;
+        } else {
+            cur = next;
+            shift += 1;
+        }
+    }
+    cur
+}
+
+/// Computes the `k`-th term of a linear recurrence modulo the prime `p` with the Bostan–Mori
+/// algorithm.
+///
+/// The sequence is the power series `P / Q` with `Q = 1 - c[0]·x - … - c[d-1]·x^d` and
+/// `P = A·Q mod x^d` for the initial terms `A`. Multiplying both by `Q(-x)` makes the
+/// denominator even, which halves `k` at every step.
+///
+/// # Arguments
+///
+/// * `rec` - The coefficients `c` of `a[n] = c[0]·a[n-1] + … + c[d-1]·a[n-d]`.
+/// * `init` - The initial terms; terms past the first `d` are returned as given.
+/// * `k` - The index of the wanted term, starting from 0.
+/// * `p` - A prime modulus below 2^32.
+///
+/// # Panics
+///
+/// Panics if `init` has fewer than `rec.len()` terms.
+pub fn linear_recurrence_kth(rec: &[u64], init: &[u64], k: u64, p: u64) -> u64 {
+    let d = rec.len();
+    if init.len() < d {
+        panic!("init must contain at least as many terms as the recurrence!");
+    }
+    if k < init.len() as u64 {
+        return init[k as usize] % p;
+    }
+    if d == 0 {
+        return 0;
+    }
+    let mut q: Vec<u64> = std::iter::once(1).chain(rec.iter().map(|&c| (p - c % p) % p)).collect();
+    let a: Vec<u64> = init[..d].iter().map(|&x| x % p).collect();
+    let mut num = poly::multiply(&a, &q, p);
+    num.truncate(d);
+    let mut k = k;
+    while k > 0 {
+        let q_neg: Vec<u64> = q.iter().enumerate().map(|(i, &c)| if i % 2 == 1 { (p - c) % p } else { c }).collect();
+        let u = poly::multiply(&num, &q_neg, p);
+        let v = poly::multiply(&q, &q_neg, p);
+        num = u.into_iter().skip((k % 2) as usize).step_by(2).collect();
+        q = v.into_iter().step_by(2).collect();
+        num.resize(d, 0);
+        q.resize(d + 1, 0);
+        k /= 2;
+    }
+    num[0]
+}
+
+/// Computes the `k`-th term of a sequence from its first terms by finding the shortest linear
+/// recurrence with Berlekamp–Massey and evaluating it with Bostan–Mori.
+///
+/// A recurrence of order `d` is only trusted when the sequence has more than `2d` terms, so
+/// that at least one term beyond the `2d` that determine it confirms it.
+///
+/// # Returns
+///
+/// The `k`-th term, or `None` if the shortest recurrence has order at least half the number
+/// of terms, so that `seq` is too short to pin it down.
+pub fn extrapolate(seq: &[u64], k: u64, p: u64) -> Option<u64> {
+    let rec = berlekamp_massey(seq, p);
+    if 2 * rec.len() >= seq.len() {
+        return None;
+    }
+    Some(linear_recurrence_kth(&rec, seq, k, p))
+}
+
+fn add_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 + b as u128) % m as u128) as u64
+}
+
+/// Computes the Fibonacci number `F(n)` modulo `m` by fast doubling, using
+/// `F(2k) = F(k)·(2F(k+1) − F(k))` and `F(2k+1) = F(k)² + F(k+1)²`.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+pub fn fibonacci_mod(n: u64, m: u64) -> u64 {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    // (a, b) = (F(k), F(k+1)) for the prefix k of the bits of n read so far.
+    let (mut a, mut b) = (0, 1 % m);
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let c = modmul(a, ((2 * b as u128 + m as u128 - a as u128) % m as u128) as u64, m);
+        let d = add_mod(modmul(a, a, m), modmul(b, b, m), m);
+        (a, b) = if n >> bit & 1 == 1 { (d, add_mod(c, d, m)) } else { (c, d) };
+    }
+    a
+}
+
+// The Fibonacci numbers F(2), F(3), …, F(93) = 1, 2, 3, 5, …, the distinct ones that fit in
+// a u64.
+const FIBONACCI: [u64; 92] = {
+    let mut f = [1; 92];
+    f[1] = 2;
+    let mut i = 2;
+    while i < f.len() {
+        f[i] = f[i - 1] + f[i - 2];
+        i += 1;
+    }
+    f
+};
+
+/// Writes `n` as its Zeckendorf representation: the unique sum of non-consecutive
+/// Fibonacci numbers, found greedily by taking the largest one that fits each time.
+///
+/// # Returns
+///
+/// The Fibonacci numbers of the sum in decreasing order (empty for 0).
+pub fn zeckendorf(n: u64) -> Vec<u64> {
+    let mut rest = n;
+    let mut terms = vec![];
+    for &f in FIBONACCI.iter().rev() {
+        if f <= rest {
+            terms.push(f);
+            rest -= f;
+        }
+    }
+    terms
+}
+
+/// Encodes `n` with the Fibonacci code: bit `i` tells whether `F(i + 2)` is in the
+/// Zeckendorf representation of `n`, and a final 1 follows the highest bit. A codeword thus
+/// ends with the only two consecutive 1s it contains, so codewords can be concatenated.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn fibonacci_encode(n: u64) -> Vec<bool> {
+    if n == 0 {
+        panic!("n cannot be zero!");
+    }
+    let mut rest = n;
+    let mut bits = vec![false; FIBONACCI.partition_point(|&f| f <= n)];
+    for i in (0..bits.len()).rev() {
+        if FIBONACCI[i] <= rest {
+            bits[i] = true;
+            rest -= FIBONACCI[i];
+        }
+    }
+    bits.push(true);
+    bits
+}
+
+/// Decodes a stream of concatenated Fibonacci codewords, as written by `fibonacci_encode`.
+///
+/// # Panics
+///
+/// Panics if the stream ends inside a codeword, or if a codeword is too large for a `u64`.
+pub fn fibonacci_decode(bits: &[bool]) -> Vec<u64> {
+    let mut values = vec![];
+    // The value and length of the codeword read so far, and whether its last bit is a 1.
+    let (mut value, mut len, mut prev) = (0u64, 0, false);
+    for &bit in bits {
+        if bit && prev {
+            values.push(value);
+            (value, len, prev) = (0, 0, false);
+            continue;
+        }
+        if bit {
+            value = match FIBONACCI.get(len).and_then(|&f| value.checked_add(f)) {
+                Some(value) => value,
+                None => panic!("codeword does not fit in a u64!"),
+            };
+        }
+        (len, prev) = (len + 1, bit);
+    }
+    if len > 0 {
+        panic!("bits end inside a codeword!");
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOD: u64 = 998244353;
+
+    // Checks that rec generates every term of seq from the ones before it.
+    fn generates(rec: &[u64], seq: &[u64], p: u64) -> bool {
+        (rec.len()..seq.len()).all(|n| rec.iter().enumerate().fold(0, |acc, (i, &c)| (acc + c * seq[n - 1 - i]) % p) == seq[n] % p)
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        assert_eq!(berlekamp_massey(&[], MOD), vec![]);
+        assert_eq!(berlekamp_massey(&[0, 0, 0], MOD), vec![]);
+        assert_eq!(berlekamp_massey(&[1, 1, 2, 3, 5, 8, 13], MOD), vec![1, 1]);
+        assert_eq!(berlekamp_massey(&[3, 6, 12, 24], MOD), vec![2]);
+        assert_eq!(berlekamp_massey(&[0, 0, 1], MOD).len(), 3);
+        // a[n] = 2a[n-1] - a[n-3] + 5a[n-4]
+        let mut seq = vec![1, 4, 1, 5];
+        for n in 4..40 {
+            seq.push((2 * seq[n - 1] + MOD - seq[n - 3] + 5 * seq[n - 4]) % MOD);
+        }
+        assert_eq!(berlekamp_massey(&seq, MOD), vec![2, 0, MOD - 1, 5]);
+        // Squares satisfy a recurrence of order 3 modulo a small prime too.
+        let squares: Vec<u64> = (0..20).map(|i| i * i % 101).collect();
+        let rec = berlekamp_massey(&squares, 101);
+        assert_eq!(rec.len(), 3);
+        assert!(generates(&rec, &squares, 101));
+        // Without structure the shortest recurrence has about half the length of the input.
+        let noise: Vec<u64> = (0..30u64).map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) >> 40).collect();
+        let rec = berlekamp_massey(&noise, MOD);
+        assert_eq!(rec.len(), 15);
+        assert!(generates(&rec, &noise, MOD));
+    }
+
+    #[test]
+    fn test_linear_recurrence_kth() {
+        let fib = [1, 1];
+        assert_eq!(linear_recurrence_kth(&fib, &[0, 1], 0, MOD), 0);
+        assert_eq!(linear_recurrence_kth(&fib, &[0, 1], 10, MOD), 55);
+        assert_eq!(linear_recurrence_kth(&fib, &[0, 1], 90, 1_000_000_007), 2880067194370816120 % 1_000_000_007);
+        assert_eq!(linear_recurrence_kth(&[2], &[3], 20, MOD), 3 << 20);
+        assert_eq!(linear_recurrence_kth(&[], &[4, 5], 1, MOD), 5);
+        assert_eq!(linear_recurrence_kth(&[], &[4, 5], 9, MOD), 0);
+        // Extra initial terms are returned as given.
+        assert_eq!(linear_recurrence_kth(&[1], &[1, 7, 9], 2, MOD), 9);
+        assert_eq!(linear_recurrence_kth(&[1], &[1, 7, 9], 3, MOD), 1);
+        // a[n] = 2a[n-1] - a[n-3] + 5a[n-4], checked against direct iteration.
+        let rec = [2, 0, MOD - 1, 5];
+        let mut seq = vec![1, 4, 1, 5];
+        for n in 4..300 {
+            seq.push((2 * seq[n - 1] + MOD - seq[n - 3] + 5 * seq[n - 4]) % MOD);
+        }
+        for k in [4, 5, 77, 128, 299] {
+            assert_eq!(linear_recurrence_kth(&rec, &seq[..4], k, MOD), seq[k as usize]);
+        }
+        // A long recurrence from Berlekamp–Massey, evaluated at a huge index and shifted back.
+        let noise: Vec<u64> = (0..100u64).map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) >> 40).collect();
+        let rec = berlekamp_massey(&noise, MOD);
+        let k = 1_000_000_000_000_000_000;
+        let window: Vec<u64> = (0..rec.len() as u64).map(|i| linear_recurrence_kth(&rec, &noise, k + i, MOD)).collect();
+        let next = rec.iter().enumerate().fold(0, |acc, (i, &c)| (acc + c * window[rec.len() - 1 - i]) % MOD);
+        assert_eq!(linear_recurrence_kth(&rec, &noise, k + rec.len() as u64, MOD), next);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(extrapolate(&[0, 1, 1, 2, 3], 90, 1_000_000_007), Some(2880067194370816120 % 1_000_000_007));
+        assert_eq!(extrapolate(&[0, 1, 1, 2, 3], 3, MOD), Some(2));
+        assert_eq!(extrapolate(&[0, 0, 0], 5, MOD), Some(0));
+        // Cubes satisfy a recurrence of order 4, so nine terms suffice.
+        let cubes: Vec<u64> = (0..9).map(|i| i * i * i).collect();
+        assert_eq!(extrapolate(&cubes, 1_000_000, MOD), Some(1_000_000u64.pow(3) % MOD));
+        // The number of walks of length n on a path with 5 vertices starting at one end.
+        let step = |c: [u64; 5]| [c[1], (c[0] + c[2]) % MOD, (c[1] + c[3]) % MOD, (c[2] + c[4]) % MOD, c[3]];
+        let mut counts = [1, 0, 0, 0, 0];
+        let mut walks = vec![];
+        for _ in 0..12 {
+            walks.push(counts.iter().sum::<u64>() % MOD);
+            counts = step(counts);
+        }
+        let expected = (12..100).fold(counts, |c, _| step(c));
+        assert_eq!(extrapolate(&walks, 100, MOD), Some(expected.iter().sum::<u64>() % MOD));
+    }
+
+    #[test]
+    fn test_extrapolate_no_recurrence() {
+        let noise: Vec<u64> = (0..30u64).map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) >> 40).collect();
+        assert_eq!(extrapolate(&noise, 100, MOD), None);
+        assert_eq!(extrapolate(&[], 0, MOD), None);
+    }
+
+    #[test]
+    fn test_fibonacci_mod() {
+        let mut fib = vec![0u128, 1];
+        for n in 2..=186 {
+            fib.push(fib[n - 1] + fib[n - 2]);
+        }
+        for m in [1, 2, 10, 1_000_000_007, u64::MAX, (1 << 63) + 5] {
+            for n in 0..=186 {
+                assert_eq!(fibonacci_mod(n, m) as u128, fib[n as usize] % m as u128, "F({}) mod {}", n, m);
+            }
+        }
+        // The Pisano period of 10 is 60.
+        assert_eq!(fibonacci_mod(u64::MAX, 10), fibonacci_mod(u64::MAX % 60, 10));
+        let n = 1_000_000_000_000_000_000;
+        assert_eq!(fibonacci_mod(n, MOD), linear_recurrence_kth(&[1, 1], &[0, 1], n, MOD));
+    }
+
+    #[test]
+    fn test_zeckendorf() {
+        assert_eq!(zeckendorf(0), vec![]);
+        assert_eq!(zeckendorf(1), vec![1]);
+        assert_eq!(zeckendorf(100), vec![89, 8, 3]);
+        for n in (1..5000).chain([u64::MAX - 1, u64::MAX]) {
+            let terms = zeckendorf(n);
+            assert_eq!(terms.iter().sum::<u64>(), n);
+            // No two of the terms are consecutive Fibonacci numbers.
+            let indices: Vec<usize> = terms.iter().map(|t| FIBONACCI.iter().position(|f| f == t).unwrap()).collect();
+            assert!(indices.windows(2).all(|w| w[0] >= w[1] + 2));
+        }
+    }
+
+    #[test]
+    fn test_fibonacci_code() {
+        let bits = |s: &str| s.chars().map(|c| c == '1').collect::<Vec<bool>>();
+        assert_eq!(fibonacci_encode(1), bits("11"));
+        assert_eq!(fibonacci_encode(4), bits("1011"));
+        assert_eq!(fibonacci_encode(11), bits("001011"));
+        assert_eq!(fibonacci_encode(u64::MAX).len(), 93);
+        let numbers: Vec<u64> = (1..3000).chain([u64::MAX - 1, u64::MAX, 12200160415121876738]).collect();
+        let stream: Vec<bool> = numbers.iter().flat_map(|&n| fibonacci_encode(n)).collect();
+        assert_eq!(fibonacci_decode(&stream), numbers);
+        assert_eq!(fibonacci_decode(&[]), vec![]);
+        assert_eq!(fibonacci_decode(&bits("0011")), vec![3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bits end inside a codeword!")]
+    fn test_fibonacci_decode_truncated() {
+        fibonacci_decode(&[true, true, false, true]);
+    }
+
+    #[test]
+    #[should_panic(expected = "codeword does not fit in a u64!")]
+    fn test_fibonacci_decode_overflow() {
+        let mut bits = vec![false; 92];
+        bits.extend([true, true]);
+        fibonacci_decode(&bits);
+    }
+
+    #[test]
+    #[should_panic(expected = "init must contain at least as many terms as the recurrence!")]
+    fn test_linear_recurrence_short_init() {
+        linear_recurrence_kth(&[1, 1], &[1], 5, MOD);
+    }
+}
@@ -0,0 +1,34 @@
+//! Linear recurrences, Fibonacci numbers and codes, and combinatorial sequences modulo a
+//! prime (Bernoulli numbers, partition counts and Stirling numbers) from formal power
+//! series.
+//!
+//! It holds the functions of the former `Recurrence` and `Series` facades, callable as
+//! `sequence::berlekamp_massey(&seq, p)`.
+
+pub use crate::recurrence::{
+    berlekamp_massey, extrapolate, fibonacci_decode, fibonacci_encode, fibonacci_mod, linear_recurrence_kth,
+    zeckendorf,
+};
+pub use crate::series::{
+    bernoulli_mod, partitions, partitions_distinct, partitions_exact_parts, partitions_max_part, partitions_odd,
+    stirling1_row, stirling2_row,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_paths() {
+        let p = 998244353;
+        let fib = [0, 1, 1, 2, 3, 5, 8, 13];
+        assert_eq!(berlekamp_massey(&fib, p), vec![1, 1]);
+        assert_eq!(linear_recurrence_kth(&[1, 1], &[0, 1], 10, p), 55);
+        assert_eq!(fibonacci_mod(10, 1000), 55);
+        assert_eq!(zeckendorf(100), vec![89, 8, 3]);
+        assert_eq!(fibonacci_decode(&fibonacci_encode(4)), vec![4]);
+        assert_eq!(partitions(5, p), vec![1, 1, 2, 3, 5, 7]);
+        assert_eq!(stirling2_row(3, p), vec![0, 1, 3, 1]);
+        assert_eq!(bernoulli_mod(1, p)[0], 1);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::poly::{self, factorials, Poly};
+
+fn check_length(n: usize, p: u64) {
+    if n as u64 >= p {
+        panic!("n must be less than the modulus!");
+    }
+}
+
+/// Computes the Bernoulli numbers `B_0, …, B_n_max` modulo the prime `p` from the
+/// exponential generating function `x / (e^x − 1)`, with the convention `B_1 = −1/2`.
+///
+/// # Panics
+///
+/// Panics if `n_max + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+pub fn bernoulli_mod(n_max: usize, p: u64) -> Vec<u64> {
+    check_length(n_max + 1, p);
+    let (fact, inv_fact) = factorials(n_max + 1, p);
+    // (e^x − 1) / x = Σ x^k / (k + 1)!
+    let series = Poly::new(inv_fact[1..].to_vec(), p);
+    let inv = series.inv(n_max + 1);
+    (0..=n_max).map(|k| inv.coeff(k) * fact[k] % p).collect()
+}
+
+// Computes Π 1/(1 − x^i) (or Π (1 + x^i) if `distinct`) over the parts i ≤ n allowed by
+// `allowed`, up to x^n, as the exponential of its logarithm
+// Σ_i Σ_j ±x^(ij)/j, which a harmonic loop fills in O(n log n).
+fn euler_product(n: usize, p: u64, distinct: bool, allowed: impl Fn(usize) -> bool) -> Vec<u64> {
+    check_length(n + 1, p);
+    let inv = poly::inverses(n, p);
+    let mut log = vec![0; n + 1];
+    for i in (1..=n).filter(|&i| allowed(i)) {
+        for j in 1..=n / i {
+            let term = if distinct && j % 2 == 0 { p - inv[j] } else { inv[j] };
+            log[i * j] = (log[i * j] + term) % p;
+        }
+    }
+    let series = Poly::new(log, p).exp(n + 1);
+    (0..=n).map(|k| series.coeff(k)).collect()
+}
+
+/// Counts the partitions of every number up to `n` modulo the prime `p`.
+///
+/// # Panics
+///
+/// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+pub fn partitions(n: usize, p: u64) -> Vec<u64> {
+    euler_product(n, p, false, |_| true)
+}
+
+/// Counts the partitions of every number up to `n` into distinct parts modulo `p`.
+pub fn partitions_distinct(n: usize, p: u64) -> Vec<u64> {
+    euler_product(n, p, true, |_| true)
+}
+
+/// Counts the partitions of every number up to `n` into odd parts modulo `p`.
+pub fn partitions_odd(n: usize, p: u64) -> Vec<u64> {
+    euler_product(n, p, false, |i| i % 2 == 1)
+}
+
+/// Counts the partitions of every number up to `n` into parts of size at most `k` modulo `p`.
+pub fn partitions_max_part(n: usize, k: usize, p: u64) -> Vec<u64> {
+    euler_product(n, p, false, |i| i <= k)
+}
+
+/// Counts the partitions of every number up to `n` into exactly `k` parts modulo `p`.
+///
+/// Taking one from each part maps these bijectively onto the partitions of `m − k` into at most
+/// `k` parts, which conjugation turns into parts of size at most `k`.
+pub fn partitions_exact_parts(n: usize, k: usize, p: u64) -> Vec<u64> {
+    let mut counts = vec![0; n + 1];
+    if k <= n {
+        let shifted = partitions_max_part(n - k, k, p);
+        counts[k..].copy_from_slice(&shifted);
+    } else {
+        check_length(n + 1, p);
+    }
+    counts
+}
+
+/// Computes the Stirling numbers of the second kind `S(n, 0), …, S(n, n)` modulo the prime
+/// `p` from `S(n, k) = Σ_i (−1)^(k−i)·i^n / (i!·(k−i)!)`, a single convolution.
+///
+/// # Panics
+///
+/// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+pub fn stirling2_row(n: usize, p: u64) -> Vec<u64> {
+    check_length(n + 1, p);
+    let (_, inv_fact) = factorials(n, p);
+    let a: Vec<u64> = (0..=n as u64).map(|i| crate::modexp::mod_exp(i, n as u64, p) * inv_fact[i as usize] % p).collect();
+    let b: Vec<u64> = (0..=n).map(|j| if j % 2 == 0 { inv_fact[j] } else { (p - inv_fact[j]) % p }).collect();
+    let product = &Poly::new(a, p) * &Poly::new(b, p);
+    (0..=n).map(|k| product.coeff(k)).collect()
+}
+
+/// Computes the unsigned Stirling numbers of the first kind `[n, 0], …, [n, n]` modulo the
+/// prime `p` as the coefficients of the rising factorial `x(x + 1)…(x + n − 1)`, doubling
+/// with `R_2m(x) = R_m(x)·R_m(x + m)`.
+///
+/// # Panics
+///
+/// Panics if `n + 1` is not less than `p`, or if `p` is not a prime below 2^32.
+pub fn stirling1_row(n: usize, p: u64) -> Vec<u64> {
+    check_length(n + 1, p);
+    let mut rising = Poly::new(vec![1], p);
+    let mut m = 0;
+    for bit in (0..usize::BITS - n.leading_zeros()).rev() {
+        rising = &rising * &rising.taylor_shift(m as u64);
+        m *= 2;
+        if n >> bit & 1 == 1 {
+            rising = &rising * &Poly::new(vec![m as u64, 1], p);
+            m += 1;
+        }
+    }
+    (0..=n).map(|k| rising.coeff(k)).collect()
+}
+
+// Multiplies out Π (x + i) for i in lo..hi by splitting the range in halves.
+fn rising_product(lo: u64, hi: u64, p: u64) -> Poly {
+    if hi - lo == 1 {
+        return Poly::new(vec![lo, 1], p);
+    }
+    let mid = lo + (hi - lo) / 2;
+    &rising_product(lo, mid, p) * &rising_product(mid, hi, p)
+}
+
+/// Computes `n!` modulo the prime `p` without a table of all smaller factorials.
+///
+/// With `v = ⌊√n⌋` and `g(x) = (x + 1)(x + 2)…(x + v)`, the product `g(0)·g(v)·…·g((v−1)v)`
+/// covers `1..=v²`, so `n!` takes one multipoint evaluation plus at most `2v` more factors.
+///
+/// # Panics
+///
+/// Panics if `p` is not a prime below 2^32.
+pub fn factorial_mod_large(n: u64, p: u64) -> u64 {
+    if p >= 1 << 32 {
+        panic!("Modulus must be below 2^32!");
+    }
+    if n >= p {
+        return 0;
+    }
+    let v = n.isqrt();
+    let mut result = 1;
+    if v > 0 {
+        let g = rising_product(1, v + 1, p);
+        let points: Vec<u64> = (0..v).map(|i| i * v).collect();
+        result = g.multi_eval(&points).iter().fold(1, |acc, &x| acc * x % p);
+    }
+    (v * v + 1..=n).fold(result, |acc, i| acc * i % p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modexp::mod_inv;
+
+    const MOD: u64 = 998244353;
+
+    // The fraction a / b modulo MOD, for a possibly negative numerator.
+    fn frac(a: i64, b: u64) -> u64 {
+        (a.rem_euclid(MOD as i64) as u64) * mod_inv(b, MOD) % MOD
+    }
+
+    #[test]
+    fn test_bernoulli_mod() {
+        let expected = [
+            frac(1, 1),
+            frac(-1, 2),
+            frac(1, 6),
+            0,
+            frac(-1, 30),
+            0,
+            frac(1, 42),
+            0,
+            frac(-1, 30),
+            0,
+            frac(5, 66),
+            0,
+            frac(-691, 2730),
+            0,
+            frac(7, 6),
+        ];
+        assert_eq!(bernoulli_mod(14, MOD), expected);
+        assert_eq!(bernoulli_mod(0, MOD), vec![1]);
+        assert_eq!(bernoulli_mod(2, 5), vec![1, 2, 1]);
+        // Faulhaber: Σ_{i<n} i^k = 1/(k+1) Σ_j C(k+1, j) B_j n^(k+1-j).
+        let b = bernoulli_mod(3000, MOD);
+        let (fact, inv_fact) = factorials(3001, MOD);
+        let (n, k) = (1000u64, 2999usize);
+        let direct = (0..n).fold(0, |acc, i| (acc + crate::modexp::mod_exp(i, k as u64, MOD)) % MOD);
+        let faulhaber = (0..=k).fold(0, |acc, j| {
+            let binom = fact[k + 1] * inv_fact[j] % MOD * inv_fact[k + 1 - j] % MOD;
+            (acc + binom * b[j] % MOD * crate::modexp::mod_exp(n, (k + 1 - j) as u64, MOD)) % MOD
+        }) * mod_inv(k as u64 + 1, MOD) % MOD;
+        assert_eq!(faulhaber, direct);
+    }
+
+    // Counts partitions of m into parts from `parts`, each used at most `max_uses` times.
+    fn count_naive(n: usize, parts: &[usize], max_uses: usize) -> Vec<u64> {
+        let mut counts = vec![0u64; n + 1];
+        counts[0] = 1;
+        for &part in parts {
+            for m in (0..=n).rev() {
+                for uses in 1..=max_uses.min(m / part) {
+                    counts[m] += counts[m - uses * part];
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_partitions() {
+        assert_eq!(partitions(10, MOD), vec![1, 1, 2, 3, 5, 7, 11, 15, 22, 30, 42]);
+        assert_eq!(partitions(0, MOD), vec![1]);
+        assert_eq!(partitions(100, MOD)[100], 190569292);
+        let p1000 = 24061467864032622473692149727991u128;
+        assert_eq!(partitions(1000, MOD)[1000], (p1000 % MOD as u128) as u64);
+        let all: Vec<usize> = (1..=60).collect();
+        let odd: Vec<usize> = (1..=60).step_by(2).collect();
+        assert_eq!(partitions_distinct(60, MOD), count_naive(60, &all, 1));
+        assert_eq!(partitions_odd(60, MOD), count_naive(60, &odd, 60));
+        assert_eq!(partitions_distinct(500, MOD), partitions_odd(500, MOD));
+        for k in [0, 1, 3, 7, 60, 100] {
+            let parts: Vec<usize> = (1..=k.min(60)).collect();
+            assert_eq!(partitions_max_part(60, k, MOD), count_naive(60, &parts, 60), "k = {}", k);
+        }
+        assert_eq!(partitions_exact_parts(10, 3, MOD), vec![0, 0, 0, 1, 1, 2, 3, 4, 5, 7, 8]);
+        assert_eq!(partitions_exact_parts(5, 0, MOD), vec![1, 0, 0, 0, 0, 0]);
+        assert_eq!(partitions_exact_parts(5, 9, MOD), vec![0; 6]);
+        // Summing over the number of parts gives all partitions.
+        let total = (0..=40).fold(vec![0; 41], |acc, k| {
+            acc.iter().zip(partitions_exact_parts(40, k, MOD)).map(|(a, b)| (a + b) % MOD).collect()
+        });
+        assert_eq!(total, partitions(40, MOD));
+        assert_eq!(partitions(5, 7), vec![1, 1, 2, 3, 5, 0]);
+    }
+
+    #[test]
+    fn test_stirling_rows() {
+        assert_eq!(stirling2_row(0, MOD), vec![1]);
+        assert_eq!(stirling2_row(5, MOD), vec![0, 1, 15, 25, 10, 1]);
+        assert_eq!(stirling1_row(0, MOD), vec![1]);
+        assert_eq!(stirling1_row(1, MOD), vec![0, 1]);
+        assert_eq!(stirling1_row(5, MOD), vec![0, 24, 50, 35, 10, 1]);
+        // Compare with the recurrences for a few hundred rows.
+        let n = 300;
+        let (mut s1, mut s2) = (vec![1u64], vec![1u64]);
+        for m in 1..=n {
+            let mut next1 = vec![0; m + 1];
+            let mut next2 = vec![0; m + 1];
+            for k in 1..=m {
+                let (a1, b1) = (s1[k - 1], s1.get(k).copied().unwrap_or(0));
+                let (a2, b2) = (s2[k - 1], s2.get(k).copied().unwrap_or(0));
+                next1[k] = (a1 + (m as u64 - 1) * b1) % MOD;
+                next2[k] = (a2 + k as u64 * b2) % MOD;
+            }
+            (s1, s2) = (next1, next2);
+            if [2, 3, 64, 127, 128, 255, 300].contains(&m) {
+                assert_eq!(stirling1_row(m, MOD), s1, "first kind, n = {}", m);
+                assert_eq!(stirling2_row(m, MOD), s2, "second kind, n = {}", m);
+            }
+        }
+        // Row sums: Σ_k [n, k] = n! and Σ_k S(n, k) = Bell(n).
+        assert_eq!(stirling1_row(6, 1_000_000_007).iter().sum::<u64>(), 720);
+        assert_eq!(stirling2_row(10, 1_000_000_007).iter().sum::<u64>(), 115975);
+    }
+
+    #[test]
+    fn test_factorial_mod_large() {
+        let (fact, _) = factorials(5000, MOD);
+        for n in [0, 1, 2, 3, 4, 15, 16, 17, 99, 1000, 4999] {
+            assert_eq!(factorial_mod_large(n, MOD), fact[n as usize], "n = {}", n);
+        }
+        assert_eq!(factorial_mod_large(MOD, MOD), 0);
+        // Wilson's theorem: (p − 1)! ≡ −1 and (p − 2)! ≡ 1 modulo p.
+        let q = 1_000_003;
+        assert_eq!(factorial_mod_large(q - 1, q), q - 1);
+        assert_eq!(factorial_mod_large(q - 2, q), 1);
+        assert_eq!(factorial_mod_large(6, 7), 6);
+        assert_eq!(factorial_mod_large(3, 2), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be less than the modulus!")]
+    fn test_bernoulli_mod_too_long() {
+        bernoulli_mod(5, 5);
+    }
+}
@@ -0,0 +1,1947 @@
+use std::io;
+use std::ops::Mul;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::error::MathCombError;
+use crate::table::{Table, TableReader, TableWriter};
+use crate::ProgressHook;
+
+/// Lists all primes up to and including `n` using the sieve of Eratosthenes.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive upper bound.
+///
+/// # Returns
+///
+/// The primes `<= n` in increasing order.
+pub fn primes_up_to(n: usize) -> Vec<u64> {
+    if n < 2 {
+        return vec![];
+    }
+    let mut composite = vec![false; n + 1];
+    let mut primes = Vec::new();
+    for i in 2..=n {
+        if !composite[i] {
+            primes.push(i as u64);
+            for j in (i * i..=n).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// Sieves the inclusive window `[l, r]` with the given base primes.
+///
+/// # Arguments
+///
+/// * `l` - The lower end of the window.
+/// * `r` - The upper end of the window.
+/// * `base` - All primes up to at least √`r`, in increasing order.
+///
+/// # Returns
+///
+/// A vector whose `i`-th entry tells whether `l + i` is prime.
+pub fn sieve_segment(l: u64, r: u64, base: &[u64]) -> Vec<bool> {
+    if l > r {
+        return vec![];
+    }
+    let mut is_prime = vec![true; (r - l + 1) as usize];
+    for &p in base {
+        if p * p > r {
+            break;
+        }
+        let start = (p * p).max(l.div_ceil(p) * p);
+        for j in (start..=r).step_by(p as usize) {
+            is_prime[(j - l) as usize] = false;
+        }
+    }
+    for i in l..=r.min(1) {
+        is_prime[(i - l) as usize] = false;
+    }
+    is_prime
+}
+
+/// The number of odd entries per segment when an `Spf` table is built in parallel, and
+/// between two progress reports when it is built sequentially.
+const SEGMENT_ODDS: usize = 1 << 16;
+
+/// The number of 64-bit words per segment of a segmented `PrimeSieve`, 32 KiB so a
+/// segment stays in the L1 cache.
+const SEGMENT_WORDS: usize = 1 << 12;
+
+// The least odd multiple of the odd prime `p` that is at least `max(p², from)`.
+fn first_odd_multiple(p: u64, from: u64) -> u64 {
+    let mut m = (p * p).max(from.div_ceil(p) * p);
+    if m.is_multiple_of(2) {
+        m += p;
+    }
+    m
+}
+
+/// The number of values per segment when a multiplicative function is sieved in parallel.
+#[cfg(feature = "rayon")]
+const SEGMENT_VALUES: usize = 1 << 16;
+
+// Evaluates a multiplicative function at every `x` in `0..=n` by segments in parallel. Each
+// segment divides its numbers by the primes up to √`n`, folding every prime power `p^k` into
+// the value with `step(value, p, k, p^k)`, and the cofactor left over is 1 or a single prime
+// above √`n`. The value at 0 is `zero`.
+#[cfg(feature = "rayon")]
+fn par_multiplicative<T, F>(n: u64, zero: T, one: T, step: F) -> Vec<T>
+where
+    T: Copy + Send,
+    F: Fn(T, u64, u32, u64) -> T + Sync,
+{
+    use rayon::prelude::*;
+    let base = primes_up_to(n.isqrt() as usize);
+    let mut values = vec![one; n as usize + 1];
+    values.par_chunks_mut(SEGMENT_VALUES).enumerate().for_each(|(c, chunk)| {
+        let lo = (c * SEGMENT_VALUES) as u64;
+        let hi = lo + chunk.len() as u64;
+        let mut rest: Vec<u64> = (lo..hi).collect();
+        for &p in &base {
+            for x in ((lo.div_ceil(p) * p).max(p)..hi).step_by(p as usize) {
+                let i = (x - lo) as usize;
+                let (mut k, mut pk) = (0, 1);
+                while rest[i].is_multiple_of(p) {
+                    rest[i] /= p;
+                    k += 1;
+                    pk *= p;
+                }
+                chunk[i] = step(chunk[i], p, k, pk);
+            }
+        }
+        for (value, &q) in chunk.iter_mut().zip(&rest) {
+            if q > 1 {
+                *value = step(*value, q, 1, q);
+            }
+        }
+    });
+    values[0] = zero;
+    values
+}
+
+/// Builds the smallest-prime-factor table of the odd numbers up to `n`, indexed by `x / 2`
+/// with primes stored as 0, together with the list of primes up to `n`, split into those
+/// that fit in a `u32` and the rest.
+///
+/// Without the `rayon` feature a linear (Euler) sieve writes every composite exactly once.
+/// With it, the table is split into segments that are sieved in parallel by the odd primes
+/// up to √`n`, in increasing order so the first prime to reach an entry is its smallest.
+///
+/// # Arguments
+///
+/// * `n` - The inclusive upper bound.
+/// * `hook` - Receives `(done, n)` with the count of numbers sieved after every segment
+///   of `2 * SEGMENT_ODDS` numbers, and may cancel the sieve.
+///
+/// # Returns
+///
+/// The table and the primes, or `None` if `hook` cancelled first.
+pub fn odd_spf_table(n: usize, hook: &dyn ProgressHook) -> Option<(Vec<u32>, Vec<u32>, Vec<u64>)> {
+    let mut odd_spf = vec![0u32; n / 2 + 1];
+    let mut primes = Vec::new();
+    let mut large_primes = Vec::new();
+    if n >= 2 {
+        primes.push(2);
+    }
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        let base = primes_up_to(n.isqrt());
+        let sieved = AtomicU64::new(0);
+        let segments: Option<Vec<Vec<u64>>> = odd_spf
+            .par_chunks_mut(SEGMENT_ODDS)
+            .enumerate()
+            .map(|(c, chunk)| {
+                if hook.is_cancelled() {
+                    return None;
+                }
+                let k0 = c * SEGMENT_ODDS;
+                let hi = n.min(2 * (k0 + chunk.len()) - 1) as u64;
+                for &p in base.iter().skip(1) {
+                    for m in (first_odd_multiple(p, 2 * k0 as u64 + 1)..=hi).step_by(2 * p as usize) {
+                        let k = m as usize / 2 - k0;
+                        if chunk[k] == 0 {
+                            chunk[k] = p as u32;
+                        }
+                    }
+                }
+                let odds = chunk.len() as u64;
+                let done = sieved.fetch_add(odds, Ordering::Relaxed) + odds;
+                hook.report((2 * done).min(n as u64), n as u64);
+                Some(
+                    (0..chunk.len())
+                        .map(|k| 2 * (k0 + k) as u64 + 1)
+                        .filter(|&x| x >= 3 && x <= hi && chunk[x as usize / 2 - k0] == 0)
+                        .collect(),
+                )
+            })
+            .collect();
+        for p in segments?.into_iter().flatten() {
+            match u32::try_from(p) {
+                Ok(p) => primes.push(p),
+                Err(_) => large_primes.push(p),
+            }
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    for i in (3..=n).step_by(2) {
+        if i / 2 % SEGMENT_ODDS == 1 {
+            if hook.is_cancelled() {
+                return None;
+            }
+            hook.report(i as u64 - 1, n as u64);
+        }
+        let limit = match odd_spf[i / 2] {
+            0 => {
+                match u32::try_from(i) {
+                    Ok(p) => primes.push(p),
+                    Err(_) => large_primes.push(i as u64),
+                }
+                i as u64
+            }
+            p => p as u64,
+        };
+        for &p in &primes[1..] {
+            if p as u64 > limit || i > n / p as usize {
+                break;
+            }
+            odd_spf[i * p as usize / 2] = p;
+        }
+    }
+    hook.report(n as u64, n as u64);
+    Some((odd_spf, primes, large_primes))
+}
+
+/// The algorithm used to fill a `PrimeSieve`.
+///
+/// Both backends produce the same bit layout. Measured single-threaded by
+/// `cargo bench --bench backends -- --large`, the two are on par up to `n` = 10^7
+/// (about 10 ms), but once the bit array outgrows the cache the segmented sieve pulls
+/// ahead: 110 ms against 170 ms for `n` = 10^8 and 1.2 s against 2.0 s for `n` = 10^9.
+/// `Segmented` is therefore the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SieveBackend {
+    /// The classic sieve crossing out the multiples of each prime over the whole array.
+    Eratosthenes,
+    /// Sieves 32 KiB segments one after another (in parallel with the `rayon` feature) by
+    /// the primes up to √n, so each segment stays in the L1 cache.
+    #[default]
+    Segmented,
+}
+
+/// The magic bytes opening a `PrimeSieve` file.
+const PRIME_SIEVE_MAGIC: &[u8; 8] = b"MCPSIEV\x01";
+
+/// A bit-packed sieve of Eratosthenes answering primality queries up to a fixed limit.
+///
+/// Only odd numbers are stored, one bit each, so the sieve takes n / 16 bytes: 64 times
+/// less than an `Spf` table when only primality is needed.
+pub struct PrimeSieve {
+    n: u64,
+    // Bit k of the packed words is set when 2k + 1 is prime.
+    bits: Table<u64>,
+}
+
+impl PrimeSieve {
+    /// Sieves all numbers up to and including `n` with the default `Segmented` backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log log n) time and n / 16 bytes of memory.
+    pub fn new(n: u64) -> PrimeSieve {
+        PrimeSieve::with_backend(n, SieveBackend::default())
+    }
+
+    /// Sieves all numbers up to and including `n` with the given backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    /// * `backend` - The algorithm filling the bit array; see `SieveBackend` for how to
+    ///   choose.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log log n) time and n / 16 bytes of memory.
+    pub fn with_backend(n: u64, backend: SieveBackend) -> PrimeSieve {
+        // `()` never cancels.
+        PrimeSieve::build(n, backend, &()).unwrap()
+    }
+
+    /// Sieves all numbers up to and including `n` like `new`, reporting progress to `hook`
+    /// and giving up when it cancels.
+    ///
+    /// Progress is counted in numbers: `hook.report(done, n)` is called after each segment
+    /// with `done` the count of numbers sieved so far. With the `rayon` feature segments
+    /// finish in parallel and reports arrive from several threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    /// * `hook` - Receives progress reports and may cancel the sieve.
+    ///
+    /// # Returns
+    ///
+    /// The sieve, or `MathCombError::Cancelled` if `hook` cancelled first.
+    pub fn with_progress(n: u64, hook: &impl ProgressHook) -> Result<PrimeSieve, MathCombError> {
+        PrimeSieve::build(n, SieveBackend::Segmented, hook).ok_or(MathCombError::Cancelled)
+    }
+
+    // Sieves with `backend`, returning `None` if `hook` cancels a segmented sieve.
+    fn build(n: u64, backend: SieveBackend, hook: &dyn ProgressHook) -> Option<PrimeSieve> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("prime_sieve", n, ?backend).entered();
+        let odds = n.div_ceil(2);
+        let mut bits = vec![u64::MAX; odds.div_ceil(64) as usize];
+        if !odds.is_multiple_of(64) {
+            *bits.last_mut().unwrap() = (1 << (odds % 64)) - 1;
+        }
+        if odds > 0 {
+            // 1 is not prime.
+            bits[0] &= !1;
+        }
+        match backend {
+            SieveBackend::Eratosthenes => {
+                let mut i = 3;
+                while i <= n / i {
+                    let k = i / 2;
+                    if bits[(k / 64) as usize] >> (k % 64) & 1 == 1 {
+                        let mut j = i * i;
+                        while j <= n {
+                            let k = j / 2;
+                            bits[(k / 64) as usize] &= !(1 << (k % 64));
+                            j += 2 * i;
+                        }
+                    }
+                    i += 2;
+                }
+            }
+            SieveBackend::Segmented => {
+                let base = primes_up_to(n.isqrt() as usize);
+                let sieved = AtomicU64::new(0);
+                let skipped = AtomicBool::new(false);
+                let sieve_chunk = |(c, chunk): (usize, &mut [u64])| {
+                    if hook.is_cancelled() {
+                        skipped.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                    let k0 = c * SEGMENT_WORDS * 64;
+                    let end = 64 * chunk.len();
+                    for &p in base.iter().skip(1) {
+                        // Bit k stands for 2k + 1, so odd multiples of p are p bits apart.
+                        let mut k = (first_odd_multiple(p, 2 * k0 as u64 + 1) / 2) as usize - k0;
+                        let p = p as usize;
+                        while k < end {
+                            chunk[k / 64] &= !(1 << (k % 64));
+                            k += p;
+                        }
+                    }
+                    // Each word holds 64 odd numbers, standing for 128 numbers.
+                    let words = chunk.len() as u64;
+                    let done = sieved.fetch_add(words, Ordering::Relaxed) + words;
+                    hook.report((128 * done).min(n), n);
+                };
+                #[cfg(feature = "rayon")]
+                {
+                    use rayon::prelude::*;
+                    bits.par_chunks_mut(SEGMENT_WORDS).enumerate().for_each(sieve_chunk);
+                }
+                #[cfg(not(feature = "rayon"))]
+                bits.chunks_mut(SEGMENT_WORDS).enumerate().for_each(sieve_chunk);
+                #[cfg(feature = "tracing")]
+                tracing::debug!(base_primes = base.len(), segments = bits.len().div_ceil(SEGMENT_WORDS), "segments sieved");
+                if skipped.into_inner() {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("prime sieve cancelled");
+                    return None;
+                }
+            }
+        }
+        Some(PrimeSieve { n, bits: bits.into() })
+    }
+
+    /// Writes the sieve to a compact binary file at `path`, to be read back with `load`
+    /// or, with the `mmap` feature, `load_mmap`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised while writing the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = TableWriter::create(path, PRIME_SIEVE_MAGIC, &[self.n])?;
+        writer.section::<u64>(&self.bits)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Reads a sieve written by `save` into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be read, or one of kind `InvalidData` if it
+    /// is not a complete `PrimeSieve` file or its size does not match its limit.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PrimeSieve> {
+        let mut reader = TableReader::open(path, PRIME_SIEVE_MAGIC)?;
+        let (n, words) = PrimeSieve::read_limit(&mut reader)?;
+        let sieve = PrimeSieve { n, bits: reader.section(words)? };
+        sieve.validate()?;
+        Ok(sieve)
+    }
+
+    // Reads the limit from the header of a sieve file, with the number of words it needs.
+    fn read_limit<R: io::Read>(reader: &mut TableReader<R>) -> io::Result<(u64, usize)> {
+        let n = reader.value()?;
+        let words = usize::try_from(n.div_ceil(2).div_ceil(64))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "sieve limit does not fit in memory"))?;
+        Ok((n, words))
+    }
+
+    // Checks that a loaded sieve has one bit per odd number up to its limit, and no bits
+    // set for 1 or past the limit.
+    fn validate(&self) -> io::Result<()> {
+        let odds = self.n.div_ceil(2);
+        let tail_clear = odds.is_multiple_of(64) || self.bits.last().is_some_and(|&w| w >> (odds % 64) == 0);
+        if self.bits.len() as u64 != odds.div_ceil(64) || !tail_clear || self.bits.first().is_some_and(|&w| w & 1 == 1) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt PrimeSieve file"));
+        }
+        Ok(())
+    }
+
+    /// Memory-maps a sieve written by `save` instead of reading it, so the operating system
+    /// pages it in on demand and shares it between processes mapping the same file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file cannot be mapped, or one of kind `InvalidData` if
+    /// it is not a complete `PrimeSieve` file, its size does not match its limit or the
+    /// platform is big-endian.
+    #[cfg(feature = "mmap")]
+    pub fn load_mmap<P: AsRef<Path>>(path: P) -> io::Result<PrimeSieve> {
+        let map = crate::table::map_file(path)?;
+        let mut reader = TableReader::new(&map[..], PRIME_SIEVE_MAGIC)?;
+        let (n, words) = PrimeSieve::read_limit(&mut reader)?;
+        let sieve = PrimeSieve { n, bits: reader.mapped_section(&map, words)? };
+        sieve.validate()?;
+        Ok(sieve)
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Checks whether `x` is prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn is_prime(&self, x: u64) -> bool {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        if x.is_multiple_of(2) {
+            return x == 2;
+        }
+        let k = x / 2;
+        self.bits[(k / 64) as usize] >> (k % 64) & 1 == 1
+    }
+
+    /// Counts the primes up to the limit of the sieve.
+    pub fn count(&self) -> u64 {
+        let odd_primes: u64 = self.bits.iter().map(|w| w.count_ones() as u64).sum();
+        odd_primes + (self.n >= 2) as u64
+    }
+
+    /// Iterates over the primes up to the limit of the sieve in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let two = (self.n >= 2).then_some(2);
+        let odd = self.bits.iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let b = w.trailing_zeros() as u64;
+                w &= w - 1;
+                Some(2 * (64 * i as u64 + b) + 1)
+            })
+        });
+        two.into_iter().chain(odd)
+    }
+}
+
+/// The residues modulo 30 that are coprime to 30, one per bit of a `WheelSieve` byte.
+const WHEEL_RESIDUES: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// The bit of each residue modulo 30 in a `WheelSieve` byte, or 8 if it shares a factor
+/// with 30.
+const WHEEL_BIT: [u8; 30] = [
+    8, 0, 8, 8, 8, 8, 8, 1, 8, 8, 8, 2, 8, 3, 8, 8, 8, 4, 8, 5, 8, 8, 8, 6, 8, 8, 8, 8, 8, 7,
+];
+
+/// A sieve of Eratosthenes on a 2·3·5 wheel, with the same interface as `PrimeSieve`.
+///
+/// Only the 8 residues modulo 30 coprime to 30 are stored, one byte per 30 numbers, so the
+/// sieve takes n / 30 bytes (about half of `PrimeSieve`) and crosses out only multiples
+/// that lie on the wheel.
+pub struct WheelSieve {
+    n: u64,
+    // Bit r of byte b is set when 30b + WHEEL_RESIDUES[r] is prime.
+    bytes: Vec<u8>,
+}
+
+impl WheelSieve {
+    /// Sieves all numbers up to and including `n`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log log n) time and n / 30 bytes of memory.
+    pub fn new(n: u64) -> WheelSieve {
+        let mut bytes = vec![u8::MAX; (n / 30 + 1) as usize];
+        // 1 is not prime.
+        bytes[0] &= !1;
+        // Clear the residues of the last byte that lie beyond n.
+        let last = bytes.len() - 1;
+        for (r, &res) in WHEEL_RESIDUES.iter().enumerate() {
+            if 30 * last as u64 + res > n {
+                bytes[last] &= !(1 << r);
+            }
+        }
+        let mut pb = 0;
+        let mut pr = 1;
+        loop {
+            let p = 30 * pb + WHEEL_RESIDUES[pr];
+            if p > n / p {
+                break;
+            }
+            if bytes[pb as usize] >> pr & 1 == 1 {
+                // Cross out p·q for every q >= p on the wheel.
+                let (mut qb, mut qr) = (pb, pr);
+                loop {
+                    let m = p * (30 * qb + WHEEL_RESIDUES[qr]);
+                    if m > n {
+                        break;
+                    }
+                    bytes[(m / 30) as usize] &= !(1 << WHEEL_BIT[(m % 30) as usize]);
+                    qr += 1;
+                    if qr == 8 {
+                        qr = 0;
+                        qb += 1;
+                    }
+                }
+            }
+            pr += 1;
+            if pr == 8 {
+                pr = 0;
+                pb += 1;
+            }
+        }
+        WheelSieve { n, bytes }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Checks whether `x` is prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn is_prime(&self, x: u64) -> bool {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        match WHEEL_BIT[(x % 30) as usize] {
+            8 => x == 2 || x == 3 || x == 5,
+            r => self.bytes[(x / 30) as usize] >> r & 1 == 1,
+        }
+    }
+
+    /// Counts the primes up to the limit of the sieve.
+    pub fn count(&self) -> u64 {
+        let wheel_primes: u64 = self.bytes.iter().map(|b| b.count_ones() as u64).sum();
+        wheel_primes + [2, 3, 5].iter().filter(|&&p| p <= self.n).count() as u64
+    }
+
+    /// Iterates over the primes up to the limit of the sieve in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let small = [2, 3, 5].into_iter().filter(|&p| p <= self.n);
+        let wheel = self.bytes.iter().enumerate().flat_map(|(b, &byte)| {
+            WHEEL_RESIDUES
+                .iter()
+                .enumerate()
+                .filter(move |&(r, _)| byte >> r & 1 == 1)
+                .map(move |(_, &res)| 30 * b as u64 + res)
+        });
+        small.chain(wheel)
+    }
+}
+
+/// Euler's totient φ(x) for every `x` up to a fixed limit, with prefix sums.
+///
+/// Only the prefix sums Φ(x) = φ(1) + ... + φ(x) are kept; single values are recovered as
+/// Φ(x) - Φ(x - 1), so the table costs 8 bytes per number.
+pub struct TotientSieve {
+    n: u64,
+    prefix: Vec<u64>,
+}
+
+impl TotientSieve {
+    /// Computes φ(1..=n) with a linear sieve, or with the `rayon` feature by segments in
+    /// parallel, dividing each segment by the primes up to √n.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> TotientSieve {
+        let len = n as usize + 1;
+        #[cfg(feature = "rayon")]
+        let mut phi = par_multiplicative(n, 0, 1, |value, p, _, pk| value * (pk - pk / p));
+        #[cfg(not(feature = "rayon"))]
+        let mut phi = {
+            let mut phi = vec![0u64; len];
+            let mut primes: Vec<u64> = Vec::new();
+            if n >= 1 {
+                phi[1] = 1;
+            }
+            for i in 2..len {
+                if phi[i] == 0 {
+                    phi[i] = i as u64 - 1;
+                    primes.push(i as u64);
+                }
+                for &p in &primes {
+                    let j = i * p as usize;
+                    if j >= len {
+                        break;
+                    }
+                    if i.is_multiple_of(p as usize) {
+                        phi[j] = phi[i] * p;
+                        break;
+                    }
+                    phi[j] = phi[i] * (p - 1);
+                }
+            }
+            phi
+        };
+        for i in 1..len {
+            phi[i] += phi[i - 1];
+        }
+        TotientSieve { n, prefix: phi }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns φ(x), with φ(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn phi(&self, x: u64) -> u64 {
+        if x == 0 {
+            return self.prefix_sum(0);
+        }
+        self.prefix_sum(x) - self.prefix[x as usize - 1]
+    }
+
+    /// Returns Φ(x) = φ(1) + φ(2) + ... + φ(x).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn prefix_sum(&self, x: u64) -> u64 {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.prefix[x as usize]
+    }
+
+    /// Counts, for every `x` up to the limit, how many applications of φ take `x` down to 1.
+    ///
+    /// Since φ(x) < x for `x > 1`, the lengths follow in increasing order from
+    /// `L(x) = L(φ(x)) + 1`, each reusing one already computed.
+    ///
+    /// # Returns
+    ///
+    /// The lengths indexed by `x`, with 0 for `x` = 0 and `x` = 1.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time.
+    pub fn phi_iteration_lengths(&self) -> Vec<u32> {
+        let len = self.n as usize + 1;
+        let mut lengths = vec![0u32; len];
+        for x in 2..len {
+            lengths[x] = lengths[self.phi(x as u64) as usize] + 1;
+        }
+        lengths
+    }
+}
+
+/// The Möbius function μ(x) for every `x` up to a fixed limit, with prefix sums.
+///
+/// Only the prefix sums M(x) = μ(1) + ... + μ(x) (the Mertens function) are kept, as
+/// `i32`; single values are recovered as M(x) - M(x - 1).
+pub struct MobiusSieve {
+    n: u64,
+    mertens: Vec<i32>,
+}
+
+impl MobiusSieve {
+    /// Computes μ(1..=n) with a linear sieve, or with the `rayon` feature by segments in
+    /// parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> MobiusSieve {
+        let len = n as usize + 1;
+        #[cfg(feature = "rayon")]
+        let mut mu = par_multiplicative(n, 0, 1, |value: i32, _, k, _| if k == 1 { -value } else { 0 });
+        #[cfg(not(feature = "rayon"))]
+        let mut mu = {
+            let mut mu = vec![0i32; len];
+            let mut composite = vec![false; len];
+            let mut primes: Vec<usize> = Vec::new();
+            if n >= 1 {
+                mu[1] = 1;
+            }
+            for i in 2..len {
+                if !composite[i] {
+                    mu[i] = -1;
+                    primes.push(i);
+                }
+                for &p in &primes {
+                    let j = i * p;
+                    if j >= len {
+                        break;
+                    }
+                    composite[j] = true;
+                    if i.is_multiple_of(p) {
+                        break;
+                    }
+                    mu[j] = -mu[i];
+                }
+            }
+            mu
+        };
+        for i in 1..len {
+            mu[i] += mu[i - 1];
+        }
+        MobiusSieve { n, mertens: mu }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns μ(x), with μ(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn mu(&self, x: u64) -> i32 {
+        if x == 0 {
+            return self.prefix_sum(0);
+        }
+        self.prefix_sum(x) - self.mertens[x as usize - 1]
+    }
+
+    /// Returns the Mertens function M(x) = μ(1) + μ(2) + ... + μ(x).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn prefix_sum(&self, x: u64) -> i32 {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.mertens[x as usize]
+    }
+}
+
+/// The Liouville function λ(x) = (−1)^Ω(x) for every `x` up to a fixed limit, with prefix
+/// sums.
+///
+/// As for `MobiusSieve`, only the prefix sums L(x) = λ(1) + ... + λ(x) are kept, as `i32`.
+pub struct LiouvilleSieve {
+    n: u64,
+    prefix: Vec<i32>,
+}
+
+impl LiouvilleSieve {
+    /// Computes λ(1..=n) with a linear sieve, using λ(i·p) = −λ(i) for every prime `p`, or
+    /// with the `rayon` feature by segments in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> LiouvilleSieve {
+        let len = n as usize + 1;
+        #[cfg(feature = "rayon")]
+        let mut lambda = par_multiplicative(n, 0, 1, |value: i32, _, k, _| if k % 2 == 1 { -value } else { value });
+        #[cfg(not(feature = "rayon"))]
+        let mut lambda = {
+            let mut lambda = vec![0i32; len];
+            let mut composite = vec![false; len];
+            let mut primes: Vec<usize> = Vec::new();
+            if n >= 1 {
+                lambda[1] = 1;
+            }
+            for i in 2..len {
+                if !composite[i] {
+                    lambda[i] = -1;
+                    primes.push(i);
+                }
+                for &p in &primes {
+                    let j = i * p;
+                    if j >= len {
+                        break;
+                    }
+                    composite[j] = true;
+                    lambda[j] = -lambda[i];
+                    if i.is_multiple_of(p) {
+                        break;
+                    }
+                }
+            }
+            lambda
+        };
+        for i in 1..len {
+            lambda[i] += lambda[i - 1];
+        }
+        LiouvilleSieve { n, prefix: lambda }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns λ(x), with λ(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn lambda(&self, x: u64) -> i32 {
+        if x == 0 {
+            return self.prefix_sum(0);
+        }
+        self.prefix_sum(x) - self.prefix[x as usize - 1]
+    }
+
+    /// Returns the summatory Liouville function L(x) = λ(1) + λ(2) + ... + λ(x).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn prefix_sum(&self, x: u64) -> i32 {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.prefix[x as usize]
+    }
+}
+
+/// The divisor count d(x) and divisor sum σ(x) for every `x` up to a fixed limit.
+pub struct DivisorSieve {
+    n: u64,
+    count: Vec<u32>,
+    sum: Vec<u64>,
+}
+
+impl DivisorSieve {
+    /// Computes d(1..=n) and σ(1..=n) with a linear sieve.
+    ///
+    /// Alongside both functions the sieve tracks, for every `i`, the exponent `e` of its
+    /// smallest prime `p` and the sum `1 + p + ... + p^e`, so that multiplying `i` by `p`
+    /// again only updates that one factor of d and σ. With the `rayon` feature both are
+    /// instead sieved by segments in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> DivisorSieve {
+        #[cfg(feature = "rayon")]
+        {
+            // σ(p^k) = 1 + p·(1 + ... + p^(k−1)), without forming p^(k+1).
+            let values = par_multiplicative(n, (0u32, 0u64), (1, 1), |(count, sum), p, k, pk| {
+                (count * (k + 1), sum * ((pk - 1) / (p - 1) * p + 1))
+            });
+            let (count, sum) = values.into_iter().unzip();
+            DivisorSieve { n, count, sum }
+        }
+        #[cfg(not(feature = "rayon"))]
+        Self::linear(n)
+    }
+
+    // The sequential linear sieve behind `new`.
+    #[cfg(not(feature = "rayon"))]
+    fn linear(n: u64) -> DivisorSieve {
+        let len = n as usize + 1;
+        let mut count = vec![0u32; len];
+        let mut sum = vec![0u64; len];
+        let mut exp = vec![0u32; len];
+        let mut power_sum = vec![0u64; len];
+        let mut primes: Vec<usize> = Vec::new();
+        if n >= 1 {
+            count[1] = 1;
+            sum[1] = 1;
+        }
+        for i in 2..len {
+            if count[i] == 0 {
+                primes.push(i);
+                count[i] = 2;
+                sum[i] = i as u64 + 1;
+                exp[i] = 1;
+                power_sum[i] = i as u64 + 1;
+            }
+            for &p in &primes {
+                let j = i * p;
+                if j >= len {
+                    break;
+                }
+                if i.is_multiple_of(p) {
+                    exp[j] = exp[i] + 1;
+                    count[j] = count[i] / (exp[i] + 1) * (exp[j] + 1);
+                    power_sum[j] = power_sum[i] * p as u64 + 1;
+                    sum[j] = sum[i] / power_sum[i] * power_sum[j];
+                    break;
+                }
+                exp[j] = 1;
+                count[j] = count[i] * 2;
+                power_sum[j] = p as u64 + 1;
+                sum[j] = sum[i] * (p as u64 + 1);
+            }
+        }
+        DivisorSieve { n, count, sum }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns the number of divisors d(x), with d(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn divisor_count(&self, x: u64) -> u32 {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.count[x as usize]
+    }
+
+    /// Returns the sum of divisors σ(x), with σ(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn divisor_sum(&self, x: u64) -> u64 {
+        if x > self.n {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.sum[x as usize]
+    }
+}
+
+/// Pillai's gcd-sum function P(x) = Σ_{i≤x} gcd(i, x) for every `x` up to a fixed limit.
+///
+/// P is multiplicative with P(p^k) = (k + 1)·p^k − k·p^(k−1), since P = id * φ as a
+/// Dirichlet convolution.
+pub struct GcdSumSieve {
+    values: MultiplicativeSieve<u64>,
+}
+
+impl GcdSumSieve {
+    /// Computes P(1..=n) with a linear sieve, or with the `rayon` feature by segments in
+    /// parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> GcdSumSieve {
+        let f = |p: u64, k: u32, pk: u64| (k as u64 + 1) * pk - k as u64 * (pk / p);
+        #[cfg(feature = "rayon")]
+        let values = MultiplicativeSieve { values: par_multiplicative(n, 0, 1, |value, p, k, pk| value * f(p, k, pk)) };
+        #[cfg(not(feature = "rayon"))]
+        let values = MultiplicativeSieve::new(n, f);
+        GcdSumSieve { values }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.values.limit()
+    }
+
+    /// Returns P(x), with P(0) = 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn gcd_sum(&self, x: u64) -> u64 {
+        self.values.get(x)
+    }
+}
+
+/// The values of an arbitrary multiplicative function at every `x` up to a fixed limit.
+///
+/// The function is described by its values on prime powers, `f(p, k, p^k)`; every other
+/// value follows from `f(a·b) = f(a)·f(b)` for coprime `a` and `b`. This covers φ, μ, d, σ,
+/// the Liouville function and many more with a single sieve.
+pub struct MultiplicativeSieve<T> {
+    values: Vec<T>,
+}
+
+impl<T: Copy + Mul<Output = T> + From<u8>> MultiplicativeSieve<T> {
+    /// Computes `f(1..=n)` with a linear sieve, setting `f(1) = 1` and `f(0) = 0`.
+    ///
+    /// The sieve stays sequential with the `rayon` feature: `f` is an `FnMut` that may keep
+    /// state between calls, so it cannot be shared across threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    /// * `f` - The function on prime powers, called once per prime power `p^k <= n` with
+    ///   the prime `p`, the exponent `k` and the power `p^k`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time plus one call of `f` per prime power up to `n`.
+    pub fn new<F: FnMut(u64, u32, u64) -> T>(n: u64, mut f: F) -> MultiplicativeSieve<T> {
+        let len = n as usize + 1;
+        let mut values = vec![T::from(0); len];
+        // The smallest prime of each index, its exponent and the full power p^k.
+        let mut spf = vec![0usize; len];
+        let mut exp = vec![0u32; len];
+        let mut power = vec![0usize; len];
+        let mut primes: Vec<usize> = Vec::new();
+        if n >= 1 {
+            values[1] = T::from(1);
+        }
+        for i in 2..len {
+            if spf[i] == 0 {
+                primes.push(i);
+                spf[i] = i;
+                exp[i] = 1;
+                power[i] = i;
+            }
+            values[i] = if power[i] == i {
+                f(spf[i] as u64, exp[i], i as u64)
+            } else {
+                values[i / power[i]] * values[power[i]]
+            };
+            for &p in &primes {
+                let j = i * p;
+                if p > spf[i] || j >= len {
+                    break;
+                }
+                spf[j] = p;
+                if p == spf[i] {
+                    exp[j] = exp[i] + 1;
+                    power[j] = power[i] * p;
+                } else {
+                    exp[j] = 1;
+                    power[j] = p;
+                }
+            }
+        }
+        MultiplicativeSieve { values }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.values.len() as u64 - 1
+    }
+
+    /// Returns `f(x)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn get(&self, x: u64) -> T {
+        if x > self.limit() {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.values[x as usize]
+    }
+
+    /// Returns the values `f(0), f(1), ..., f(n)`.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+}
+
+/// Squarefree indicators for every `x` up to a fixed limit.
+pub struct SquarefreeSieve {
+    squarefree: Vec<bool>,
+}
+
+impl SquarefreeSieve {
+    /// Marks the squarefree numbers up to and including `n` by crossing out the multiples
+    /// of `p²` for every prime `p <= √n`. With the `rayon` feature the numbers are instead
+    /// factored by segments in parallel, and a number is squarefree when no prime divides
+    /// it twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n) time, or O(n log log n) work spread across the cores with `rayon`.
+    pub fn new(n: u64) -> SquarefreeSieve {
+        #[cfg(feature = "rayon")]
+        let squarefree = par_multiplicative(n, false, true, |value, _, k, _| value && k == 1);
+        #[cfg(not(feature = "rayon"))]
+        let squarefree = {
+            let len = n as usize + 1;
+            let mut squarefree = vec![true; len];
+            squarefree[0] = false;
+            for p in primes_up_to(n.isqrt() as usize) {
+                let q = (p * p) as usize;
+                for j in (q..len).step_by(q) {
+                    squarefree[j] = false;
+                }
+            }
+            squarefree
+        };
+        SquarefreeSieve { squarefree }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.squarefree.len() as u64 - 1
+    }
+
+    /// Checks whether `x` is squarefree (0 is not, 1 is).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn is_squarefree(&self, x: u64) -> bool {
+        if x > self.limit() {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.squarefree[x as usize]
+    }
+
+    /// Counts the squarefree numbers up to the limit of the sieve.
+    pub fn count(&self) -> u64 {
+        self.squarefree.iter().filter(|&&b| b).count() as u64
+    }
+
+    /// Iterates over the squarefree numbers up to the limit of the sieve in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.squarefree.len()).filter(|&x| self.squarefree[x]).map(|x| x as u64)
+    }
+}
+
+/// The number of prime factors of every `x` up to a fixed limit, both distinct (ω) and
+/// with multiplicity (Ω), for picking out the k-almost-primes.
+pub struct OmegaSieve {
+    omega: Vec<u8>,
+    big_omega: Vec<u8>,
+}
+
+impl OmegaSieve {
+    /// Counts the prime factors of every number up to and including `n`: every prime `p`
+    /// adds one to ω of its multiples, and every prime power `p^e` adds one to Ω of its
+    /// multiples. With the `rayon` feature the numbers are instead factored by segments in
+    /// parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log log n) time, spread across the cores with `rayon`.
+    pub fn new(n: u64) -> OmegaSieve {
+        #[cfg(feature = "rayon")]
+        {
+            let counts = par_multiplicative(n, (0u8, 0u8), (0, 0), |(w, big_w), _, k, _| (w + 1, big_w + k as u8));
+            let (omega, big_omega) = counts.into_iter().unzip();
+            OmegaSieve { omega, big_omega }
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            OmegaSieve::linear(n)
+        }
+    }
+
+    // Adds every prime to ω of its multiples and every prime power to Ω of its multiples.
+    #[cfg(not(feature = "rayon"))]
+    fn linear(n: u64) -> OmegaSieve {
+        let len = n as usize + 1;
+        let mut omega = vec![0u8; len];
+        let mut big_omega = vec![0u8; len];
+        for p in 2..len {
+            if omega[p] != 0 {
+                continue;
+            }
+            for j in (p..len).step_by(p) {
+                omega[j] += 1;
+            }
+            let mut q = p;
+            while q < len {
+                for j in (q..len).step_by(q) {
+                    big_omega[j] += 1;
+                }
+                q = match q.checked_mul(p) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+        }
+        OmegaSieve { omega, big_omega }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.omega.len() as u64 - 1
+    }
+
+    /// Returns ω(x), the number of distinct prime factors (0 for 0 and 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn omega(&self, x: u64) -> u32 {
+        if x > self.limit() {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.omega[x as usize] as u32
+    }
+
+    /// Returns Ω(x), the number of prime factors counted with multiplicity (0 for 0 and 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn big_omega(&self, x: u64) -> u32 {
+        if x > self.limit() {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.big_omega[x as usize] as u32
+    }
+
+    /// Iterates in increasing order over the k-almost-primes up to the limit, the numbers
+    /// with exactly `k` prime factors counted with multiplicity (1 for `k = 0`).
+    pub fn iter_big_omega(&self, k: u32) -> impl Iterator<Item = u64> + '_ {
+        (1..self.big_omega.len()).filter(move |&x| self.big_omega[x] as u32 == k).map(|x| x as u64)
+    }
+
+    /// Iterates in increasing order over the numbers up to the limit with exactly `k`
+    /// distinct prime factors (1 for `k = 0`).
+    pub fn iter_omega(&self, k: u32) -> impl Iterator<Item = u64> + '_ {
+        (1..self.omega.len()).filter(move |&x| self.omega[x] as u32 == k).map(|x| x as u64)
+    }
+}
+
+/// The largest prime factor of every `x` up to a fixed limit.
+pub struct LpfSieve {
+    lpf: Vec<u32>,
+}
+
+impl LpfSieve {
+    /// Computes the largest prime factor of every number up to and including `n`.
+    ///
+    /// Each prime overwrites the entries of all its multiples, and primes are visited in
+    /// increasing order, so every entry ends up holding its largest prime factor. With the
+    /// `rayon` feature the numbers are instead factored by segments in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The inclusive upper bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` does not fit in a `u32`.
+    ///
+    /// # Complexity
+    ///
+    /// O(n log log n) time, spread across the cores with `rayon`, and 4 bytes per number.
+    pub fn new(n: u64) -> LpfSieve {
+        if n > u32::MAX as u64 {
+            panic!("n must fit in a u32!");
+        }
+        #[cfg(feature = "rayon")]
+        let lpf = par_multiplicative(n, 0, 0, |value: u32, p, _, _| value.max(p as u32));
+        #[cfg(not(feature = "rayon"))]
+        let lpf = {
+            let len = n as usize + 1;
+            let mut lpf = vec![0u32; len];
+            for i in 2..len {
+                if lpf[i] == 0 {
+                    for j in (i..len).step_by(i) {
+                        lpf[j] = i as u32;
+                    }
+                }
+            }
+            lpf
+        };
+        LpfSieve { lpf }
+    }
+
+    /// Returns the inclusive upper bound the sieve was built for.
+    pub fn limit(&self) -> u64 {
+        self.lpf.len() as u64 - 1
+    }
+
+    /// Returns the largest prime factor of `x`, or 0 if `x` < 2.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is greater than the limit of the sieve.
+    pub fn get_lpf(&self, x: u64) -> u64 {
+        if x > self.limit() {
+            panic!("x cannot be greater than the sieve limit!");
+        }
+        self.lpf[x as usize] as u64
+    }
+}
+
+/// Prime factor tables for a window `[l, r]` of large numbers.
+///
+/// Every prime `p <= √r` is recorded against each of its multiples in the window, so a
+/// number's small prime factors are a slice lookup away and whatever remains after dividing
+/// them out is a single prime. This allows factorizing every number of the window with `r`
+/// up to around 10^12 and `r - l` up to around 10^7.
+pub struct RangeSpf {
+    l: u64,
+    r: u64,
+    // The distinct primes <= √r dividing l + i are small_primes[offsets[i]..offsets[i + 1]],
+    // in increasing order.
+    offsets: Vec<usize>,
+    small_primes: Vec<u32>,
+}
+
+impl RangeSpf {
+    /// Sieves the prime factors of every number in `[l, r]`, sequentially even with the
+    /// `rayon` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `l` - The lower end of the window.
+    /// * `r` - The upper end of the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`.
+    ///
+    /// # Complexity
+    ///
+    /// O(√r + (r - l) log log r) time and memory.
+    pub fn new(l: u64, r: u64) -> RangeSpf {
+        if l > r {
+            panic!("l cannot be greater than r!");
+        }
+        let len = (r - l + 1) as usize;
+        let base = primes_up_to(r.isqrt() as usize);
+        let multiples = |p: u64| (l.div_ceil(p).max(1) * p..=r).step_by(p as usize);
+        let mut offsets = vec![0usize; len + 1];
+        for &p in &base {
+            for x in multiples(p) {
+                offsets[(x - l) as usize + 1] += 1;
+            }
+        }
+        for i in 0..len {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut small_primes = vec![0u32; offsets[len]];
+        let mut next = offsets.clone();
+        for &p in &base {
+            for x in multiples(p) {
+                let i = (x - l) as usize;
+                small_primes[next[i]] = p as u32;
+                next[i] += 1;
+            }
+        }
+        RangeSpf { l, r, offsets, small_primes }
+    }
+
+    /// Returns the window `(l, r)` the tables were built for.
+    pub fn range(&self) -> (u64, u64) {
+        (self.l, self.r)
+    }
+
+    // The distinct primes <= √r dividing `x`, after checking that `x` lies in the window.
+    fn small_factors(&self, x: u64) -> &[u32] {
+        if x < self.l || x > self.r {
+            panic!("x must lie in [l, r]!");
+        }
+        let i = (x - self.l) as usize;
+        &self.small_primes[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// Returns the smallest prime factor of `x`, or 0 if `x` < 2.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` lies outside the window.
+    pub fn get_spf(&self, x: u64) -> u64 {
+        let small = self.small_factors(x);
+        if x < 2 {
+            return 0;
+        }
+        small.first().map_or(x, |&p| p as u64)
+    }
+
+    /// Checks whether `x` is prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` lies outside the window.
+    pub fn is_prime(&self, x: u64) -> bool {
+        x >= 2 && self.get_spf(x) == x
+    }
+
+    /// Factorizes `x` into distinct primes paired with their multiplicities.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(prime, exponent)` pairs sorted by prime (empty for `x` = 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or lies outside the window.
+    ///
+    /// # Complexity
+    ///
+    /// O(log x) time.
+    pub fn factorize_map(&self, x: u64) -> Vec<(u64, u32)> {
+        let small = self.small_factors(x);
+        if x == 0 {
+            panic!("x cannot be zero!");
+        }
+        let mut map: Vec<(u64, u32)> = Vec::new();
+        let mut y = x;
+        for &p in small {
+            let p = p as u64;
+            let mut e = 0;
+            while y.is_multiple_of(p) {
+                y /= p;
+                e += 1;
+            }
+            map.push((p, e));
+        }
+        // At most one prime factor exceeds √r.
+        if y > 1 {
+            map.push((y, 1));
+        }
+        map
+    }
+
+    /// Factorizes `x` into its prime factors in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is 0 or lies outside the window.
+    pub fn factorize(&self, x: u64) -> Vec<u64> {
+        let mut factors = Vec::new();
+        for (p, e) in self.factorize_map(x) {
+            factors.extend(std::iter::repeat_n(p, e as usize));
+        }
+        factors
+    }
+}
+
+/// The number of integers sieved at once by `SegmentedSieve`.
+const SEGMENT_SIZE: u64 = 1 << 18;
+
+/// A segmented sieve of Eratosthenes for primes in an arbitrary range `[l, r]`.
+///
+/// Only the primes up to √`r` are sieved in full; the range itself is processed in
+/// cache-sized blocks (in parallel with the `rayon` feature), so `r` can go up to around
+/// 10^12 with `r - l` up to around 10^7.
+pub struct SegmentedSieve {}
+
+impl SegmentedSieve {
+    /// Lists the primes in the inclusive range `[l, r]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `l` - The lower end of the range.
+    /// * `r` - The upper end of the range.
+    ///
+    /// # Returns
+    ///
+    /// The primes `p` with `l <= p <= r` in increasing order (empty if `l > r`).
+    ///
+    /// # Complexity
+    ///
+    /// O(√r + (r - l) log log r) time and O(√r / log r + segment) memory besides the output.
+    pub fn primes_in(l: u64, r: u64) -> Vec<u64> {
+        Self::map_segments(l, r, |lo, window| {
+            window.iter().enumerate().filter(|&(_, &p)| p).map(|(i, _)| lo + i as u64).collect::<Vec<u64>>()
+        })
+        .concat()
+    }
+
+    /// Counts the primes in the inclusive range `[l, r]` without storing them.
+    ///
+    /// # Arguments
+    ///
+    /// * `l` - The lower end of the range.
+    /// * `r` - The upper end of the range.
+    ///
+    /// # Returns
+    ///
+    /// The number of primes `p` with `l <= p <= r` (0 if `l > r`).
+    pub fn count_in(l: u64, r: u64) -> u64 {
+        Self::map_segments(l, r, |_, window| window.iter().filter(|&&p| p).count() as u64).into_iter().sum()
+    }
+
+    // Sieves `[l, r]` block by block (in parallel with the `rayon` feature), passing each
+    // block's start and primality flags to `f`, and returns the results in block order.
+    fn map_segments<T: Send, F: Fn(u64, &[bool]) -> T + Sync>(l: u64, r: u64, f: F) -> Vec<T> {
+        if l > r {
+            return Vec::new();
+        }
+        let base = primes_up_to(r.isqrt() as usize);
+        let segment = |s: u64| {
+            let lo = l + s * SEGMENT_SIZE;
+            f(lo, &sieve_segment(lo, r.min(lo.saturating_add(SEGMENT_SIZE - 1)), &base))
+        };
+        let segments = (r - l) / SEGMENT_SIZE + 1;
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            (0..segments).into_par_iter().map(segment).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        (0..segments).map(segment).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primes_up_to() {
+        assert_eq!(primes_up_to(0), vec![]);
+        assert_eq!(primes_up_to(2), vec![2]);
+        assert_eq!(primes_up_to(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes_up_to(1000000).len(), 78498);
+    }
+
+    #[test]
+    fn test_sieve_segment() {
+        let base = primes_up_to(1000);
+        let window = sieve_segment(0, 30, &base);
+        let found: Vec<u64> = (0..=30).filter(|&i| window[i as usize]).collect();
+        assert_eq!(found, primes_up_to(30));
+        let window = sieve_segment(1000000, 1000100, &base);
+        let found: Vec<u64> = (0..=100).filter(|&i| window[i as usize]).map(|i| 1000000 + i).collect();
+        assert_eq!(found, vec![1000003, 1000033, 1000037, 1000039, 1000081, 1000099]);
+        assert!(sieve_segment(5, 4, &base).is_empty());
+    }
+
+    #[test]
+    fn test_prime_sieve() {
+        for n in [0, 1, 2, 3, 4, 127, 128, 129, 130, 1000] {
+            let sieve = PrimeSieve::new(n);
+            let expected = primes_up_to(n as usize);
+            assert_eq!(sieve.iter().collect::<Vec<u64>>(), expected, "n = {}", n);
+            assert_eq!(sieve.count(), expected.len() as u64);
+            for x in 0..=n {
+                assert_eq!(sieve.is_prime(x), expected.binary_search(&x).is_ok());
+            }
+        }
+        let sieve = PrimeSieve::new(10000000);
+        assert_eq!(sieve.count(), 664579);
+        assert_eq!(sieve.iter().last(), Some(9999991));
+        assert!(sieve.is_prime(1000003));
+        assert!(!sieve.is_prime(10000000));
+    }
+
+    #[test]
+    fn test_prime_sieve_with_progress() {
+        use crate::CancelToken;
+        use std::sync::Mutex;
+
+        let n = 10000000;
+        let reports = Mutex::new(Vec::new());
+        let hook = |done, total| reports.lock().unwrap().push((done, total));
+        let sieve = PrimeSieve::with_progress(n, &hook).unwrap();
+        assert_eq!(sieve.iter().count(), 664579);
+        let reports = reports.into_inner().unwrap();
+        // One report per segment of 2^18 numbers, ending with all of them.
+        assert_eq!(reports.len() as u64, n.div_ceil(128 * SEGMENT_WORDS as u64));
+        assert_eq!(reports.iter().max(), Some(&(n, n)));
+
+        let token = CancelToken::new();
+        token.cancel();
+        assert!(matches!(PrimeSieve::with_progress(n, &token), Err(MathCombError::Cancelled)));
+        assert_eq!(PrimeSieve::with_progress(0, &token).unwrap().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_prime_sieve_backends() {
+        for n in [0, 1, 2, 3, 127, 128, 129, 1 << 19, (1 << 19) + 1, 1000003] {
+            let classic = PrimeSieve::with_backend(n, SieveBackend::Eratosthenes);
+            let segmented = PrimeSieve::with_backend(n, SieveBackend::Segmented);
+            assert_eq!(*classic.bits, *segmented.bits, "n = {}", n);
+            assert_eq!(classic.count(), primes_up_to(n as usize).len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_prime_sieve_save_load() {
+        let path = std::env::temp_dir().join(format!("math-comb-sieve-{}.bin", std::process::id()));
+        let sieve = PrimeSieve::new(1000003);
+        sieve.save(&path).unwrap();
+        let loaded = PrimeSieve::load(&path).unwrap();
+        assert_eq!(loaded.limit(), 1000003);
+        assert!(loaded.iter().eq(sieve.iter()));
+        #[cfg(feature = "mmap")]
+        {
+            let mapped = PrimeSieve::load_mmap(&path).unwrap();
+            assert_eq!(mapped.count(), 78499);
+            assert!(mapped.is_prime(1000003));
+        }
+        assert!(crate::Spf::load(&path).is_err());
+        // A limit that disagrees with the size of the bit array.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[8] ^= 0x80;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(PrimeSieve::load(&path).is_err_and(|e| e.kind() == io::ErrorKind::InvalidData));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be greater than the sieve limit!")]
+    fn test_prime_sieve_above_limit() {
+        PrimeSieve::new(100).is_prime(101);
+    }
+
+    #[test]
+    fn test_wheel_sieve() {
+        for n in [0, 1, 2, 3, 4, 5, 6, 7, 29, 30, 31, 48, 49, 120, 1000, 100000] {
+            let sieve = WheelSieve::new(n);
+            let expected = primes_up_to(n as usize);
+            assert_eq!(sieve.iter().collect::<Vec<u64>>(), expected, "n = {}", n);
+            assert_eq!(sieve.count(), expected.len() as u64);
+            for x in 0..=n {
+                assert_eq!(sieve.is_prime(x), expected.binary_search(&x).is_ok());
+            }
+        }
+        let sieve = WheelSieve::new(10000000);
+        assert_eq!(sieve.count(), 664579);
+        assert_eq!(sieve.iter().last(), Some(9999991));
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be greater than the sieve limit!")]
+    fn test_wheel_sieve_above_limit() {
+        WheelSieve::new(100).is_prime(101);
+    }
+
+    #[test]
+    fn test_totient_sieve() {
+        let sieve = TotientSieve::new(1000);
+        assert_eq!(sieve.phi(0), 0);
+        assert_eq!(sieve.phi(1), 1);
+        assert_eq!(sieve.phi(36), 12);
+        assert_eq!(sieve.phi(997), 996);
+        let mut sum = 0;
+        for x in 1..=1000u64 {
+            let expected = (1..=x).filter(|&k| crate::modexp::gcd(k, x) == 1).count() as u64;
+            assert_eq!(sieve.phi(x), expected);
+            sum += expected;
+            assert_eq!(sieve.prefix_sum(x), sum);
+        }
+        assert_eq!(TotientSieve::new(10000000).prefix_sum(10000000), 30396356427242);
+        assert_eq!(TotientSieve::new(0).prefix_sum(0), 0);
+        assert_eq!(TotientSieve::new(0).phi_iteration_lengths(), vec![0]);
+        let lengths = sieve.phi_iteration_lengths();
+        assert_eq!(lengths[..11], [0, 0, 1, 2, 2, 3, 2, 3, 3, 3, 3]);
+        // The first number with a chain of 10 steps.
+        assert_eq!(lengths.iter().position(|&l| l == 10), Some(641));
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be greater than the sieve limit!")]
+    fn test_totient_sieve_above_limit() {
+        TotientSieve::new(10).phi(11);
+    }
+
+    #[test]
+    fn test_mobius_sieve() {
+        let sieve = MobiusSieve::new(1000);
+        let mu: Vec<i32> = (0..=12).map(|x| sieve.mu(x)).collect();
+        assert_eq!(mu, vec![0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0]);
+        for x in 1..=1000u64 {
+            let map = crate::pollard::factor_map(x);
+            let expected = if map.iter().any(|&(_, e)| e > 1) {
+                0
+            } else if map.len().is_multiple_of(2) {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(sieve.mu(x), expected, "x = {}", x);
+        }
+        assert_eq!(sieve.prefix_sum(1000), 2);
+        // M(10^6) = 212 and M(10^7) = 1037.
+        let sieve = MobiusSieve::new(10000000);
+        assert_eq!(sieve.prefix_sum(1000000), 212);
+        assert_eq!(sieve.prefix_sum(10000000), 1037);
+        assert_eq!(MobiusSieve::new(0).prefix_sum(0), 0);
+    }
+
+    #[test]
+    fn test_divisor_sieve() {
+        let sieve = DivisorSieve::new(2000);
+        assert_eq!(sieve.divisor_count(0), 0);
+        assert_eq!(sieve.divisor_sum(0), 0);
+        assert_eq!(sieve.divisor_count(1), 1);
+        assert_eq!(sieve.divisor_count(1680), 40);
+        assert_eq!(sieve.divisor_sum(12), 28);
+        for x in 1..=2000u64 {
+            let divisors: Vec<u64> = (1..=x).filter(|d| x % d == 0).collect();
+            assert_eq!(sieve.divisor_count(x), divisors.len() as u32, "x = {}", x);
+            assert_eq!(sieve.divisor_sum(x), divisors.iter().sum::<u64>(), "x = {}", x);
+        }
+        let sieve = DivisorSieve::new(1 << 20);
+        assert_eq!(sieve.divisor_count(1 << 20), 21);
+        assert_eq!(sieve.divisor_sum(1 << 20), (1 << 21) - 1);
+        assert_eq!(sieve.divisor_sum(720720) as u128, crate::arith::sigma(720720));
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be greater than the sieve limit!")]
+    fn test_divisor_sieve_above_limit() {
+        DivisorSieve::new(10).divisor_sum(11);
+    }
+
+    #[test]
+    fn test_liouville_sieve() {
+        let sieve = LiouvilleSieve::new(5000);
+        assert_eq!(sieve.limit(), 5000);
+        assert_eq!(sieve.lambda(0), 0);
+        for x in 1..=5000u64 {
+            assert_eq!(sieve.lambda(x), crate::arith::liouville(x), "x = {}", x);
+        }
+        // L(x) <= 0 for 2 <= x < 906150257 (Pólya), with L(10^6) = -530.
+        let sieve = LiouvilleSieve::new(1000000);
+        assert!((2..=1000000).all(|x| sieve.prefix_sum(x) <= 0));
+        assert_eq!(sieve.prefix_sum(1000000), -530);
+        assert_eq!(LiouvilleSieve::new(0).prefix_sum(0), 0);
+    }
+
+    #[test]
+    fn test_gcd_sum_sieve() {
+        let sieve = GcdSumSieve::new(3000);
+        assert_eq!(sieve.limit(), 3000);
+        assert_eq!(sieve.gcd_sum(0), 0);
+        assert_eq!(sieve.gcd_sum(1), 1);
+        assert_eq!(sieve.gcd_sum(6), 15);
+        for x in 1..=3000u64 {
+            let brute: u64 = (1..=x).map(|i| crate::modexp::gcd(i, x)).sum();
+            assert_eq!(sieve.gcd_sum(x), brute, "x = {}", x);
+            assert_eq!(sieve.gcd_sum(x) as u128, crate::arith::gcd_sum(x));
+        }
+    }
+
+    #[test]
+    fn test_multiplicative_sieve() {
+        let n = 5000;
+        let phi = MultiplicativeSieve::new(n, |p, _, pk| pk - pk / p);
+        let mu = MultiplicativeSieve::new(n, |_, k, _| if k == 1 { -1i64 } else { 0 });
+        let liouville = MultiplicativeSieve::new(n, |_, k, _| if k % 2 == 1 { -1i64 } else { 1 });
+        let d = MultiplicativeSieve::new(n, |_, k, _| k as u64 + 1);
+        let sigma = MultiplicativeSieve::new(n, |p, _, pk| (pk * p - 1) / (p - 1));
+        let totients = TotientSieve::new(n);
+        let mobius = MobiusSieve::new(n);
+        let divisors = DivisorSieve::new(n);
+        for x in 0..=n {
+            assert_eq!(phi.get(x), totients.phi(x));
+            assert_eq!(mu.get(x), mobius.mu(x) as i64);
+            assert_eq!(d.get(x), divisors.divisor_count(x) as u64);
+            assert_eq!(sigma.get(x), divisors.divisor_sum(x));
+        }
+        assert_eq!(liouville.values()[..11], [0, 1, -1, -1, 1, -1, 1, -1, -1, 1, 1]);
+        assert_eq!(phi.limit(), n);
+        let empty = MultiplicativeSieve::new(0, |_, _, pk| pk);
+        assert_eq!(empty.values(), &[0]);
+
+        // Several segments of the parallel sieves with the `rayon` feature.
+        let n = 200003;
+        let phi = MultiplicativeSieve::new(n, |p, _, pk| pk - pk / p);
+        let mu = MultiplicativeSieve::new(n, |_, k, _| if k == 1 { -1i64 } else { 0 });
+        let liouville = MultiplicativeSieve::new(n, |_, k, _| if k % 2 == 1 { -1i64 } else { 1 });
+        let sigma = MultiplicativeSieve::new(n, |p, _, pk| (pk * p - 1) / (p - 1));
+        let totients = TotientSieve::new(n);
+        let mobius = MobiusSieve::new(n);
+        let liouvilles = LiouvilleSieve::new(n);
+        let divisors = DivisorSieve::new(n);
+        // 2^ω and 2^Ω are multiplicative.
+        let two_omega = MultiplicativeSieve::new(n, |_, _, _| 2u64);
+        let two_big_omega = MultiplicativeSieve::new(n, |_, k, _| 1u64 << k);
+        let pillai = MultiplicativeSieve::new(n, |p, k, pk| (k as u64 + 1) * pk - k as u64 * (pk / p));
+        let omegas = OmegaSieve::new(n);
+        let squarefree = SquarefreeSieve::new(n);
+        let lpf = LpfSieve::new(n);
+        let gcd_sums = GcdSumSieve::new(n);
+        for x in 0..=n {
+            assert_eq!(phi.get(x), totients.phi(x), "x = {}", x);
+            assert_eq!(mu.get(x), mobius.mu(x) as i64, "x = {}", x);
+            assert_eq!(liouville.get(x), liouvilles.lambda(x) as i64, "x = {}", x);
+            assert_eq!(sigma.get(x), divisors.divisor_sum(x), "x = {}", x);
+            assert_eq!(squarefree.is_squarefree(x), mu.get(x) != 0, "x = {}", x);
+            assert_eq!(gcd_sums.gcd_sum(x), pillai.get(x), "x = {}", x);
+            if x > 0 {
+                assert_eq!(omegas.omega(x), two_omega.get(x).trailing_zeros(), "x = {}", x);
+                assert_eq!(omegas.big_omega(x), two_big_omega.get(x).trailing_zeros(), "x = {}", x);
+            }
+        }
+        for x in (2..=n).step_by(7) {
+            assert_eq!(lpf.get_lpf(x), *crate::pollard::factor(x).last().unwrap(), "x = {}", x);
+        }
+        assert_eq!((omegas.omega(0), omegas.big_omega(0), lpf.get_lpf(0), lpf.get_lpf(1)), (0, 0, 0, 0));
+        assert_eq!(divisors.divisor_count(196608), 34);
+    }
+
+    #[test]
+    fn test_squarefree_sieve() {
+        let sieve = SquarefreeSieve::new(10000);
+        assert!(!sieve.is_squarefree(0));
+        assert!(sieve.is_squarefree(1));
+        assert!(!sieve.is_squarefree(9800));
+        assert_eq!(sieve.iter().take(8).collect::<Vec<u64>>(), vec![1, 2, 3, 5, 6, 7, 10, 11]);
+        for x in 1..=10000u64 {
+            assert_eq!(sieve.is_squarefree(x), crate::arith::is_squarefree(x));
+        }
+        assert_eq!(sieve.count(), 6083);
+        assert_eq!(SquarefreeSieve::new(0).count(), 0);
+    }
+
+    #[test]
+    fn test_omega_sieve() {
+        let sieve = OmegaSieve::new(20000);
+        assert_eq!(sieve.limit(), 20000);
+        assert_eq!((sieve.omega(0), sieve.big_omega(0)), (0, 0));
+        assert_eq!((sieve.omega(1), sieve.big_omega(1)), (0, 0));
+        assert_eq!((sieve.omega(16384), sieve.big_omega(16384)), (1, 14));
+        assert_eq!((sieve.omega(720), sieve.big_omega(720)), (3, 7));
+        for x in 1..=20000u64 {
+            assert_eq!(sieve.omega(x), crate::arith::omega(x));
+            assert_eq!(sieve.big_omega(x), crate::arith::big_omega(x));
+        }
+        assert_eq!(sieve.iter_big_omega(2).take(8).collect::<Vec<u64>>(), vec![4, 6, 9, 10, 14, 15, 21, 22]);
+        assert_eq!(sieve.iter_omega(2).take(8).collect::<Vec<u64>>(), vec![6, 10, 12, 14, 15, 18, 20, 21]);
+        assert_eq!(sieve.iter_big_omega(0).collect::<Vec<u64>>(), vec![1]);
+        // The prime powers up to 20000, of which 2262 are primes.
+        assert_eq!(sieve.iter_omega(1).count(), 2328);
+        for k in 0..16 {
+            assert_eq!(sieve.iter_big_omega(k).count() as u64, crate::primecount::count_almost_primes(20000, k));
+        }
+        assert_eq!(OmegaSieve::new(0).iter_omega(0).count(), 0);
+    }
+
+    #[test]
+    fn test_lpf_sieve() {
+        let sieve = LpfSieve::new(100000);
+        assert_eq!(sieve.get_lpf(0), 0);
+        assert_eq!(sieve.get_lpf(1), 0);
+        assert_eq!(sieve.get_lpf(2), 2);
+        assert_eq!(sieve.get_lpf(1024), 2);
+        assert_eq!(sieve.get_lpf(99991 - 91), 37);
+        for x in 2..=100000u64 {
+            assert_eq!(sieve.get_lpf(x), crate::pollard::largest_prime_factor(x).unwrap());
+        }
+        assert_eq!(LpfSieve::new(0).limit(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "x cannot be greater than the sieve limit!")]
+    fn test_lpf_sieve_above_limit() {
+        LpfSieve::new(10).get_lpf(11);
+    }
+
+    #[test]
+    fn test_range_spf() {
+        let spf = RangeSpf::new(0, 1000);
+        assert_eq!(spf.get_spf(0), 0);
+        assert_eq!(spf.get_spf(1), 0);
+        assert_eq!(spf.factorize(1), vec![]);
+        for x in 2..=1000 {
+            assert_eq!(spf.factorize(x), crate::pollard::factor(x));
+            assert_eq!(spf.is_prime(x), crate::pollard::is_prime(x));
+        }
+        let (l, r) = (1000000000000 - 100000, 1000000000000 + 100000);
+        let spf = RangeSpf::new(l, r);
+        assert_eq!(spf.range(), (l, r));
+        for x in (l..=r).step_by(97) {
+            assert_eq!(spf.factorize_map(x), crate::pollard::factor_map(x));
+            assert_eq!(spf.get_spf(x), crate::pollard::smallest_prime_factor(x).unwrap());
+        }
+        assert!(spf.is_prime(1000000000039));
+        assert_eq!(spf.factorize(1000000000000), crate::pollard::factor(1000000000000));
+    }
+
+    #[test]
+    #[should_panic(expected = "x must lie in [l, r]!")]
+    fn test_range_spf_outside() {
+        RangeSpf::new(100, 200).get_spf(99);
+    }
+
+    #[test]
+    fn test_segmented_sieve() {
+        assert_eq!(SegmentedSieve::primes_in(0, 30), primes_up_to(30));
+        assert_eq!(SegmentedSieve::primes_in(5, 4), vec![]);
+        assert_eq!(SegmentedSieve::primes_in(1, 1), vec![]);
+        assert_eq!(SegmentedSieve::primes_in(2, 2), vec![2]);
+        let primes = primes_up_to(1000000);
+        let expected: Vec<u64> = primes.iter().copied().filter(|&p| p >= 123456).collect();
+        assert_eq!(SegmentedSieve::primes_in(123456, 1000000), expected);
+        assert_eq!(SegmentedSieve::count_in(0, 1000000), 78498);
+        let (l, r) = (10000000000 - 999999, 10000000000);
+        let pi = |n| crate::primecount::prime_pi(n);
+        assert_eq!(SegmentedSieve::count_in(l, r), pi(r) - pi(l - 1));
+        let expected: Vec<u64> = (l..=l + 10000).filter(|&n| crate::pollard::is_prime(n)).collect();
+        assert_eq!(SegmentedSieve::primes_in(l, l + 10000), expected);
+    }
+}
@@ -0,0 +1,111 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{pollard, sieve};
+
+/// Candidates up to this bound are removed by trial division in `is_smooth`; larger prime
+/// factors are checked through the largest prime factor of the cofactor.
+const TRIAL_BOUND: u64 = 1000;
+
+/// Checks whether every prime factor of `n` is at most `b`.
+///
+/// # Arguments
+///
+/// * `n` - The number to check.
+/// * `b` - The smoothness bound.
+///
+/// # Returns
+///
+/// `true` if `n` is `b`-smooth (`1` is smooth for every `b`, `0` never is).
+pub fn is_smooth(n: u64, b: u64) -> bool {
+    if n == 0 {
+        return false;
+    }
+    let mut m = n;
+    for p in sieve::primes_up_to(b.min(TRIAL_BOUND) as usize) {
+        while m.is_multiple_of(p) {
+            m /= p;
+        }
+        if m == 1 {
+            return true;
+        }
+    }
+    m == 1 || (b > TRIAL_BOUND && pollard::largest_prime_factor(m).is_some_and(|p| p <= b))
+}
+
+/// An iterator over all `b`-smooth numbers up to a limit, in increasing order.
+///
+/// Numbers are generated from a min-heap: every popped `x` spawns `x * p` for the primes
+/// `p` no smaller than the largest prime factor of `x`, so each smooth number is produced
+/// exactly once. The heap hands out values strictly in order, so unlike the sieves the
+/// iterator does not run in parallel with the `rayon` feature.
+pub struct SmoothNumbers {
+    primes: Vec<u64>,
+    limit: u64,
+    // (value, index of its largest prime factor in `primes`)
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+}
+
+impl SmoothNumbers {
+    /// Creates an iterator over the `b`-smooth numbers in `[1, limit]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The smoothness bound.
+    /// * `limit` - The inclusive upper bound of the generated numbers.
+    pub fn new(b: u64, limit: u64) -> SmoothNumbers {
+        let primes = sieve::primes_up_to(b.min(limit) as usize);
+        let mut heap = BinaryHeap::new();
+        if limit >= 1 {
+            heap.push(Reverse((1, 0)));
+        }
+        SmoothNumbers { primes, limit, heap }
+    }
+}
+
+impl Iterator for SmoothNumbers {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let Reverse((x, idx)) = self.heap.pop()?;
+        for j in idx..self.primes.len() {
+            match x.checked_mul(self.primes[j]) {
+                Some(y) if y <= self.limit => self.heap.push(Reverse((y, j))),
+                _ => break,
+            }
+        }
+        Some(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_smooth() {
+        assert!(!is_smooth(0, 10));
+        assert!(is_smooth(1, 0));
+        assert!(is_smooth(1024, 2));
+        assert!(!is_smooth(1024 * 3, 2));
+        assert!(is_smooth(2 * 3 * 5 * 7 * 7, 7));
+        assert!(!is_smooth(2 * 3 * 5 * 7 * 11, 7));
+        assert!(is_smooth(1000003 * 1009, 1000003));
+        assert!(!is_smooth(1000003 * 1009, 1000002));
+        assert!(is_smooth(1000000007, 1000000007));
+        for n in 1..2000 {
+            let lpf = pollard::largest_prime_factor(n).unwrap_or(1);
+            assert_eq!(is_smooth(n, 13), lpf <= 13);
+        }
+    }
+
+    #[test]
+    fn test_smooth_numbers() {
+        let hamming: Vec<u64> = SmoothNumbers::new(5, 30).collect();
+        assert_eq!(hamming, vec![1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 15, 16, 18, 20, 24, 25, 27, 30]);
+        let expected: Vec<u64> = (1..=5000).filter(|&n| is_smooth(n, 17)).collect();
+        assert_eq!(SmoothNumbers::new(17, 5000).collect::<Vec<u64>>(), expected);
+        assert_eq!(SmoothNumbers::new(2, u64::MAX).count(), 64);
+        assert_eq!(SmoothNumbers::new(100, 0).count(), 0);
+    }
+}
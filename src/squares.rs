@@ -0,0 +1,217 @@
+use crate::random::{RandomSource, SplitMix64};
+use crate::{modexp, pollard};
+
+// Multiplies the Gaussian integers a and b.
+fn gauss_mul(a: (i128, i128), b: (i128, i128)) -> (i128, i128) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+// Writes a prime p ≡ 1 (mod 4) as x² + y² using a square root of -1 and the Euclidean
+// algorithm (Hermite–Serret / Cornacchia).
+fn prime_two_squares(p: u64) -> (u64, u64) {
+    let mut a = p;
+    let mut b = modexp::sqrt_mod(p - 1, p).unwrap();
+    let limit = p.isqrt();
+    while b > limit {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    (b, (p - b * b).isqrt())
+}
+
+/// Writes `n` as a sum of two squares, if possible.
+///
+/// A representation exists iff every prime `≡ 3 (mod 4)` divides `n` to an even power. It is
+/// assembled by multiplying the Gaussian-integer representations of the prime factors.
+///
+/// # Arguments
+///
+/// * `n` - The number to decompose.
+///
+/// # Returns
+///
+/// `Some((a, b))` with `a² + b² = n` and `a >= b`, or `None` if no representation exists.
+pub fn two_squares(n: u64) -> Option<(u64, u64)> {
+    if n == 0 {
+        return Some((0, 0));
+    }
+    let mut z: (i128, i128) = (1, 0);
+    let mut scale: u64 = 1;
+    for (p, e) in pollard::factor_map(n) {
+        if p % 4 == 3 {
+            if e % 2 == 1 {
+                return None;
+            }
+            scale *= p.pow(e / 2);
+            continue;
+        }
+        let w = if p == 2 {
+            (1, 1)
+        } else {
+            let (x, y) = prime_two_squares(p);
+            (x as i128, y as i128)
+        };
+        for _ in 0..e {
+            z = gauss_mul(z, w);
+        }
+    }
+    let a = z.0.unsigned_abs() as u64 * scale;
+    let b = z.1.unsigned_abs() as u64 * scale;
+    Some((a.max(b), a.min(b)))
+}
+
+/// Writes `n` as a sum of four squares (Lagrange's four-square theorem) like
+/// `four_squares_with_rng`, drawing from a fixed deterministic sequence, so the same `n`
+/// always gives the same decomposition.
+///
+/// # Arguments
+///
+/// * `n` - The number to decompose.
+///
+/// # Returns
+///
+/// `(a, b, c, d)` with `a² + b² + c² + d² = n` and `a >= b >= c >= d`.
+pub fn four_squares(n: u64) -> (u64, u64, u64, u64) {
+    four_squares_with_rng(n, &mut SplitMix64::default())
+}
+
+/// Writes `n` as a sum of four squares with the randomized reduction of Rabin and Shallit,
+/// drawing from `rng`.
+///
+/// Factors of 4 are removed first, leaving `m ≡ 1, 2, 3 (mod 4)`. Random `x` and `y` of the
+/// parities that make `p = m − x² − y² ≡ 1 (mod 4)` are drawn until `p` is 1 or a prime,
+/// which then is a sum of two squares by Cornacchia's algorithm, so nothing is factorized.
+/// Since primes are dense, O(log m) draws are expected. Small `m`, and the unlikely run of
+/// draws that all fail, fall back to scanning `x` downwards with `two_squares`.
+///
+/// # Arguments
+///
+/// * `n` - The number to decompose.
+/// * `rng` - The source of the random draws.
+///
+/// # Returns
+///
+/// `(a, b, c, d)` with `a² + b² + c² + d² = n` and `a >= b >= c >= d`.
+///
+/// # Complexity
+///
+/// O(log n) expected primality tests and one modular square root.
+pub fn four_squares_with_rng(n: u64, rng: &mut impl RandomSource) -> (u64, u64, u64, u64) {
+    if n == 0 {
+        return (0, 0, 0, 0);
+    }
+    let k = n.trailing_zeros() / 2;
+    let m = n >> (2 * k);
+    let parts = random_four_squares(m, rng).unwrap_or_else(|| scan_four_squares(m));
+    let mut parts = parts.map(|x| x << k);
+    parts.sort_unstable_by(|x, y| y.cmp(x));
+    (parts[0], parts[1], parts[2], parts[3])
+}
+
+// Draws `x` and `y` for the `m ≡ 1, 2, 3 (mod 4)` until `m − x² − y²` is 1 or a prime, and
+// returns `None` for small `m` or after too many draws.
+fn random_four_squares(m: u64, rng: &mut impl RandomSource) -> Option<[u64; 4]> {
+    const MIN_RANDOM: u64 = 1 << 10;
+    const MAX_DRAWS: u32 = 4096;
+    if m < MIN_RANDOM {
+        return None;
+    }
+    // x odd for m ≡ 2, 3 and y odd for m ≡ 3 (mod 4), so m − x² − y² ≡ 1 (mod 4).
+    let (px, py) = match m % 4 {
+        1 => (0, 0),
+        2 => (1, 0),
+        _ => (1, 1),
+    };
+    // A random number of the given parity in [0, bound], if there is one.
+    let mut draw = |bound: u64, parity: u64| (bound >= parity).then(|| 2 * rng.below((bound - parity) / 2 + 1) + parity);
+    for _ in 0..MAX_DRAWS {
+        let Some(x) = draw(m.isqrt(), px) else { continue };
+        let Some(y) = draw((m - x * x).isqrt(), py) else { continue };
+        let p = m - x * x - y * y;
+        let (z, w) = match p {
+            1 => (1, 0),
+            _ if pollard::is_prime(p) => prime_two_squares(p),
+            _ => continue,
+        };
+        return Some([x, y, z, w]);
+    }
+    None
+}
+
+// Finds a decomposition of `m` deterministically: an `m ≡ 7 (mod 8)` gives up one unit
+// square, after which the rest is a sum of three squares by Legendre's theorem, found by
+// scanning `x` downwards from √`m` until `m − x²` is a sum of two squares.
+fn scan_four_squares(m: u64) -> [u64; 4] {
+    let (d, m) = if m % 8 == 7 { (1, m - 1) } else { (0, m) };
+    let mut x = m.isqrt();
+    loop {
+        if let Some((a, b)) = two_squares(m - x * x) {
+            return [x, a, b, d];
+        }
+        x -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_squares() {
+        assert_eq!(two_squares(0), Some((0, 0)));
+        assert_eq!(two_squares(1), Some((1, 0)));
+        assert_eq!(two_squares(2), Some((1, 1)));
+        assert_eq!(two_squares(3), None);
+        assert_eq!(two_squares(9), Some((3, 0)));
+        assert_eq!(two_squares(21), None);
+        for n in 0..5000u64 {
+            let brute = (0..=n.isqrt()).any(|a| {
+                let r = n - a * a;
+                r.isqrt() * r.isqrt() == r
+            });
+            match two_squares(n) {
+                Some((a, b)) => {
+                    assert_eq!(a * a + b * b, n);
+                    assert!(a >= b);
+                }
+                None => assert!(!brute, "n = {}", n),
+            }
+        }
+        let n = 1000000009 * 998244353;
+        let (a, b) = two_squares(n).unwrap();
+        assert_eq!(a as u128 * a as u128 + b as u128 * b as u128, n as u128);
+    }
+
+    #[test]
+    fn test_four_squares() {
+        assert_eq!(four_squares(0), (0, 0, 0, 0));
+        assert_eq!(four_squares(7), (2, 1, 1, 1));
+        assert_eq!(four_squares(16), (4, 0, 0, 0));
+        for n in (0..5000).chain([1 << 62, (1 << 62) - 1, u64::MAX, 1000000007 * 7]) {
+            let (a, b, c, d) = four_squares(n);
+            assert!(a >= b && b >= c && c >= d);
+            let sum = [a, b, c, d].iter().map(|&x| x as u128 * x as u128).sum::<u128>();
+            assert_eq!(sum, n as u128, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_four_squares_with_rng() {
+        let mut rng = SplitMix64::new(7);
+        for m in [1025, 1026, 1027, 999999999999999989, 1 << 61, u64::MAX, u64::MAX - 1, u64::MAX - 2] {
+            for n in [m, m / 4 * 4] {
+                let (a, b, c, d) = four_squares_with_rng(n, &mut rng);
+                assert!(a >= b && b >= c && c >= d);
+                let sum = [a, b, c, d].iter().map(|&x| x as u128 * x as u128).sum::<u128>();
+                assert_eq!(sum, n as u128, "n = {}", n);
+            }
+        }
+        // Each residue class modulo 4 is found by a draw rather than the scan.
+        for m in [1000000001, 1000000002, 1000000003] {
+            let [x, y, z, w] = random_four_squares(m, &mut rng).unwrap();
+            assert_eq!(x * x + y * y + z * z + w * w, m);
+        }
+        assert_eq!(four_squares(1000000007 * 7), four_squares(1000000007 * 7));
+    }
+}
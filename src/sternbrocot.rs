@@ -0,0 +1,391 @@
+use crate::modexp;
+
+/// A step down the Stern–Brocot tree, towards the smaller or the larger child.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+/// A fraction `(num, den)`.
+pub type Fraction = (u64, u64);
+
+// Reduces a positive fraction to lowest terms.
+fn reduce(num: u64, den: u64) -> (u64, u64) {
+    if num == 0 || den == 0 {
+        panic!("Fraction must be positive!");
+    }
+    let g = modexp::gcd(num, den);
+    (num / g, den / g)
+}
+
+/// Finds the path from the root 1/1 of the Stern–Brocot tree to `num/den`.
+///
+/// The run lengths are the terms of the continued fraction of `num/den`, with the last one
+/// decreased by one, so the path stays short even for huge fractions.
+///
+/// # Arguments
+///
+/// * `num` - The numerator.
+/// * `den` - The denominator.
+///
+/// # Returns
+///
+/// The path as runs `(direction, length)` of equal steps, alternating in direction and
+/// empty for 1/1.
+///
+/// # Panics
+///
+/// Panics if `num` or `den` is zero.
+pub fn path(num: u64, den: u64) -> Vec<(Direction, u64)> {
+    let (mut a, mut b) = reduce(num, den);
+    let mut runs = Vec::new();
+    let mut dir = Direction::Right;
+    loop {
+        let (q, r) = (a / b, a % b);
+        let q = if r == 0 { q - 1 } else { q };
+        if q > 0 {
+            runs.push((dir, q));
+        }
+        if r == 0 {
+            return runs;
+        }
+        (a, b) = (b, r);
+        dir = if dir == Direction::Left { Direction::Right } else { Direction::Left };
+    }
+}
+
+// Follows the runs from the root, returning the bounds `left < x < right` whose mediant is
+// the node `x` reached.
+fn bounds(runs: &[(Direction, u64)]) -> ((u64, u64), (u64, u64)) {
+    let (mut left, mut right) = ((0u64, 1u64), (1u64, 0u64));
+    let step = |x: (u64, u64), y: (u64, u64), k: u64| {
+        let num = y.0.checked_mul(k).and_then(|t| t.checked_add(x.0));
+        let den = y.1.checked_mul(k).and_then(|t| t.checked_add(x.1));
+        match (num, den) {
+            (Some(num), Some(den)) => (num, den),
+            _ => panic!("Fraction does not fit in a u64!"),
+        }
+    };
+    for &(dir, k) in runs {
+        match dir {
+            Direction::Right => left = step(left, right, k),
+            Direction::Left => right = step(right, left, k),
+        }
+    }
+    (left, right)
+}
+
+/// Finds the fraction reached by following `runs` from the root 1/1 of the Stern–Brocot
+/// tree, inverting `path`.
+///
+/// # Arguments
+///
+/// * `runs` - The path as runs `(direction, length)`; consecutive runs may share a direction.
+///
+/// # Returns
+///
+/// The fraction `(num, den)` in lowest terms.
+///
+/// # Panics
+///
+/// Panics if the fraction does not fit in a `u64`.
+pub fn from_path(runs: &[(Direction, u64)]) -> (u64, u64) {
+    let (left, right) = bounds(runs);
+    match (left.0.checked_add(right.0), left.1.checked_add(right.1)) {
+        (Some(num), Some(den)) => (num, den),
+        _ => panic!("Fraction does not fit in a u64!"),
+    }
+}
+
+/// Finds the two fractions whose mediant is `num/den` in the Stern–Brocot tree: its nearest
+/// ancestors on either side, which for a fraction below 1 are also its neighbours in the
+/// Farey sequence of order `den`.
+///
+/// # Arguments
+///
+/// * `num` - The numerator.
+/// * `den` - The denominator.
+///
+/// # Returns
+///
+/// `(left, right)` with `left < num/den < right`, where `left` may be 0/1 and `right` may
+/// be 1/0 for infinity.
+///
+/// # Panics
+///
+/// Panics if `num` or `den` is zero.
+pub fn neighbors(num: u64, den: u64) -> ((u64, u64), (u64, u64)) {
+    bounds(&path(num, den))
+}
+
+/// Finds the neighbours of `p/q` in the Farey sequence F_n, the fractions in [0, 1] with
+/// denominators of at most `n`.
+///
+/// The neighbours in F_q are the Stern–Brocot bounds `a/b` and `c/d` of `p/q`; each later
+/// order adds the mediants `(a + k·p)/(b + k·q)` next to it, so the neighbours in F_n take
+/// the largest `k` with a denominator of at most `n`.
+///
+/// # Arguments
+///
+/// * `p` - The numerator.
+/// * `q` - The denominator.
+/// * `n` - The order of the sequence.
+///
+/// # Returns
+///
+/// The fractions just before and after `p/q` in F_n, `None` past either end.
+///
+/// # Panics
+///
+/// Panics if `n` or `q` is zero, or if `p/q` is not in F_n.
+pub fn farey_neighbors(p: u64, q: u64, n: u64) -> (Option<Fraction>, Option<Fraction>) {
+    if n == 0 {
+        panic!("n must be at least 1!");
+    }
+    if q == 0 {
+        panic!("Denominator cannot be zero!");
+    }
+    if p == 0 {
+        return (None, Some((1, n)));
+    }
+    let (p, q) = reduce(p, q);
+    if p > q || q > n {
+        panic!("Fraction is not in the Farey sequence!");
+    }
+    let ((a, b), (c, d)) = neighbors(p, q);
+    let left = (a + (n - b) / q * p, b + (n - b) / q * q);
+    let right = if d == 0 { None } else { Some((c + (n - d) / q * p, d + (n - d) / q * q)) };
+    (Some(left), right)
+}
+
+/// An iterator over the Farey sequence F_n, the fractions `p/q` in [0, 1] in lowest terms
+/// with `q <= n`, in increasing order.
+///
+/// Each term follows from the two before it: after `a/b` and `c/d` comes
+/// `(k·c − a)/(k·d − b)` with `k = ⌊(n + b)/d⌋`.
+pub struct FareySequence {
+    n: u64,
+    // The next two terms, the second absent once the first is 1/1.
+    cur: Option<(u64, u64)>,
+    next: Option<(u64, u64)>,
+}
+
+impl FareySequence {
+    /// Creates the iterator over F_n, from 0/1 to 1/1.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The order of the sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn new(n: u64) -> FareySequence {
+        if n == 0 {
+            panic!("n must be at least 1!");
+        }
+        FareySequence { n, cur: Some((0, 1)), next: Some((1, n)) }
+    }
+}
+
+impl Iterator for FareySequence {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        let (a, b) = self.cur?;
+        self.cur = self.next;
+        self.next = self.next.filter(|&(c, d)| c != d).map(|(c, d)| {
+            let k = (self.n as u128 + b as u128) / d as u128;
+            ((k * c as u128 - a as u128) as u64, (k * d as u128 - b as u128) as u64)
+        });
+        Some((a, b))
+    }
+}
+
+// Multiplies a u128 by a u64 into a 192-bit value (high, low).
+fn mul_wide(a: u128, b: u64) -> (u128, u128) {
+    let low = (a as u64 as u128) * b as u128;
+    let high = (a >> 64) * b as u128;
+    let (sum, carry) = low.overflowing_add(high << 64);
+    ((high >> 64) + carry as u128, sum)
+}
+
+/// Finds the fraction closest to `x_num/x_den` among those with a denominator of at most
+/// `max_den`.
+///
+/// The answer is either the last convergent of the continued fraction within the bound or
+/// the largest semiconvergent after it, so the two are compared exactly.
+///
+/// # Arguments
+///
+/// * `x_num` - The numerator of the target.
+/// * `x_den` - The denominator of the target.
+/// * `max_den` - The largest allowed denominator.
+///
+/// # Returns
+///
+/// The closest fraction `(num, den)` in lowest terms, preferring the smaller denominator on
+/// ties.
+///
+/// # Panics
+///
+/// Panics if `x_den` or `max_den` is zero.
+///
+/// # Complexity
+///
+/// O(log x_den).
+pub fn best_approx(x_num: u64, x_den: u64, max_den: u64) -> (u64, u64) {
+    if x_den == 0 || max_den == 0 {
+        panic!("Denominator cannot be zero!");
+    }
+    // (p0, q0) and (p1, q1) are the last two convergents, starting from 0/1 and 1/0.
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let (mut a, mut b) = (x_num, x_den);
+    while b != 0 {
+        let t = a / b;
+        let q2 = t as u128 * q1 as u128 + q0 as u128;
+        if q2 > max_den as u128 {
+            let k = (max_den - q0) / q1;
+            let (p, q) = (p0 + k * p1, q0 + k * q1);
+            // Compares |x − p/q| with |x − p1/q1| as |x_num·q − p·x_den| / q.
+            let err = |p: u64, q: u64| (x_num as u128 * q as u128).abs_diff(p as u128 * x_den as u128);
+            let (semi, conv) = (mul_wide(err(p, q), q1), mul_wide(err(p1, q1), q));
+            return if semi < conv || (semi == conv && q < q1) { (p, q) } else { (p1, q1) };
+        }
+        (p0, q0, p1, q1) = (p1, q1, t * p1 + p0, q2 as u64);
+        (a, b) = (b, a - t * b);
+    }
+    (p1, q1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::{Left, Right};
+
+    #[test]
+    fn test_path() {
+        assert_eq!(path(1, 1), vec![]);
+        assert_eq!(path(2, 1), vec![(Right, 1)]);
+        assert_eq!(path(1, 2), vec![(Left, 1)]);
+        assert_eq!(path(3, 2), vec![(Right, 1), (Left, 1)]);
+        assert_eq!(path(6, 4), vec![(Right, 1), (Left, 1)]);
+        assert_eq!(path(5, 1), vec![(Right, 4)]);
+        assert_eq!(path(u64::MAX, 1), vec![(Right, u64::MAX - 1)]);
+        for num in 1..60 {
+            for den in 1..60 {
+                let g = modexp::gcd(num, den);
+                assert_eq!(from_path(&path(num, den)), (num / g, den / g));
+            }
+        }
+        assert_eq!(from_path(&[(Left, 2), (Left, 1)]), (1, 4));
+        let (num, den) = (u64::MAX / 2, u64::MAX / 3);
+        let g = modexp::gcd(num, den);
+        assert_eq!(from_path(&path(num, den)), (num / g, den / g));
+    }
+
+    #[test]
+    #[should_panic(expected = "Fraction does not fit in a u64!")]
+    fn test_from_path_overflow() {
+        from_path(&[(Right, u64::MAX)]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(neighbors(1, 1), ((0, 1), (1, 0)));
+        assert_eq!(neighbors(3, 5), ((1, 2), (2, 3)));
+        assert_eq!(neighbors(7, 1), ((6, 1), (1, 0)));
+        for num in 1..40u64 {
+            for den in 1..40u64 {
+                let ((a, b), (c, d)) = neighbors(num, den);
+                let g = modexp::gcd(num, den);
+                assert_eq!((a + c, b + d), (num / g, den / g));
+                // Farey neighbours satisfy bc − ad = 1.
+                assert_eq!(b * c - a * d, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_farey_sequence() {
+        let f5: Vec<(u64, u64)> = FareySequence::new(5).collect();
+        assert_eq!(
+            f5,
+            vec![(0, 1), (1, 5), (1, 4), (1, 3), (2, 5), (1, 2), (3, 5), (2, 3), (3, 4), (4, 5), (1, 1)]
+        );
+        assert_eq!(FareySequence::new(1).collect::<Vec<_>>(), vec![(0, 1), (1, 1)]);
+        for n in 1..60 {
+            let terms: Vec<(u64, u64)> = FareySequence::new(n).collect();
+            assert_eq!(terms.len() as u128, crate::arith::farey_length(n));
+            for w in terms.windows(2) {
+                let ((a, b), (c, d)) = (w[0], w[1]);
+                assert_eq!(b * c - a * d, 1);
+            }
+            for (i, &(a, b)) in terms.iter().enumerate() {
+                let before = i.checked_sub(1).map(|j| terms[j]);
+                assert_eq!(farey_neighbors(a, b, n), (before, terms.get(i + 1).copied()));
+            }
+        }
+        assert_eq!(farey_neighbors(2, 4, 8), (Some((3, 7)), Some((4, 7))));
+        let n = u64::MAX;
+        assert_eq!(farey_neighbors(1, 1, n), (Some((n - 1, n)), None));
+        assert_eq!(farey_neighbors(1, 2, n), (Some((n / 2, n)), Some((n / 2 + 1, n))));
+        let head: Vec<(u64, u64)> = FareySequence::new(n).take(3).collect();
+        assert_eq!(head, vec![(0, 1), (1, n), (1, n - 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fraction is not in the Farey sequence!")]
+    fn test_farey_neighbors_outside() {
+        farey_neighbors(3, 7, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "n must be at least 1!")]
+    fn test_farey_neighbors_order_zero() {
+        farey_neighbors(0, 1, 0);
+    }
+
+    // Finds the closest fraction by trying every denominator.
+    fn best_approx_naive(x_num: u64, x_den: u64, max_den: u64) -> (u64, u64) {
+        let mut best = (0, 1);
+        let mut best_err = (u128::MAX, 1u128);
+        for q in 1..=max_den {
+            let p = (x_num as u128 * q as u128 + x_den as u128 / 2) / x_den as u128;
+            for p in p.saturating_sub(1)..=p + 1 {
+                let err = (x_num as u128 * q as u128).abs_diff(p * x_den as u128);
+                if err * best_err.1 < best_err.0.saturating_mul(q as u128) {
+                    best = (p as u64, q);
+                    best_err = (err, q as u128);
+                }
+            }
+        }
+        let g = modexp::gcd(best.0, best.1);
+        (best.0 / g, best.1 / g)
+    }
+
+    #[test]
+    fn test_best_approx() {
+        assert_eq!(best_approx(314159265, 100000000, 7), (22, 7));
+        assert_eq!(best_approx(314159265, 100000000, 113), (355, 113));
+        assert_eq!(best_approx(314159265, 100000000, 56), (22, 7));
+        assert_eq!(best_approx(0, 5, 3), (0, 1));
+        assert_eq!(best_approx(1, 2, 1), (0, 1));
+        assert_eq!(best_approx(6, 4, 100), (3, 2));
+        for x_den in 1..40 {
+            for x_num in 0..80 {
+                for max_den in 1..45 {
+                    assert_eq!(best_approx(x_num, x_den, max_den), best_approx_naive(x_num, x_den, max_den), "{}/{} {}", x_num, x_den, max_den);
+                }
+            }
+        }
+        assert_eq!(best_approx(u64::MAX, u64::MAX - 1, u64::MAX - 2), (u64::MAX - 1, u64::MAX - 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Fraction must be positive!")]
+    fn test_path_zero() {
+        path(0, 3);
+    }
+}
@@ -0,0 +1,129 @@
+use std::vec;
+
+use crate::{pollard, Factors, Spf};
+
+/// A lazy iterator factorizing the numbers of an input iterator one by one, created by
+/// `prime::factor_stream` and `Spf::factorize_stream`.
+///
+/// Each item is the `Factors` of the corresponding input, so no vector is allocated per
+/// number. After `chunked`, inputs are instead pulled in chunks and every chunk is
+/// factorized at once (in parallel with the `rayon` feature), still yielding results in
+/// input order.
+pub struct FactorStream<'a, I> {
+    numbers: I,
+    // The table to factorize with, or `None` for `prime::factor`.
+    spf: Option<&'a Spf>,
+    chunk_size: usize,
+    chunk: vec::IntoIter<Factors>,
+}
+
+impl<'a, I: Iterator<Item = u64>> FactorStream<'a, I> {
+    pub(crate) fn new(numbers: I, spf: Option<&'a Spf>) -> FactorStream<'a, I> {
+        FactorStream { numbers, spf, chunk_size: 1, chunk: Vec::new().into_iter() }
+    }
+
+    /// Pulls the inputs in chunks of `chunk_size` numbers and factorizes each chunk at
+    /// once, in parallel with the `rayon` feature. At most one chunk of results is held in
+    /// memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_size` - The number of inputs factorized together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn chunked(mut self, chunk_size: usize) -> FactorStream<'a, I> {
+        if chunk_size == 0 {
+            panic!("chunk_size cannot be zero!");
+        }
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    // Factorizes the next chunk of inputs, returning `false` once the inputs are exhausted.
+    fn refill(&mut self) -> bool {
+        let numbers: Vec<u64> = self.numbers.by_ref().take(self.chunk_size).collect();
+        if numbers.is_empty() {
+            return false;
+        }
+        let spf = self.spf;
+        #[cfg(feature = "rayon")]
+        let factors: Vec<Factors> = {
+            use rayon::prelude::*;
+            numbers.par_iter().map(|&n| factorize(spf, n)).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let factors: Vec<Factors> = numbers.iter().map(|&n| factorize(spf, n)).collect();
+        self.chunk = factors.into_iter();
+        true
+    }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for FactorStream<'_, I> {
+    type Item = Factors;
+
+    fn next(&mut self) -> Option<Factors> {
+        if self.chunk_size == 1 {
+            return self.numbers.next().map(|n| factorize(self.spf, n));
+        }
+        if self.chunk.len() == 0 && !self.refill() {
+            return None;
+        }
+        self.chunk.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.numbers.size_hint();
+        let held = self.chunk.len();
+        (lo.saturating_add(held), hi.and_then(|hi| hi.checked_add(held)))
+    }
+}
+
+// Factorizes `n` with the table `spf`, or with `prime::factor` if there is none.
+fn factorize(spf: Option<&Spf>, n: u64) -> Factors {
+    match spf {
+        Some(spf) => spf.factorize_inline(n),
+        None => pollard::factor_inline(n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime;
+
+    #[test]
+    fn test_factor_stream() {
+        let mut stream = prime::factor_stream(1..=6);
+        assert_eq!(stream.size_hint(), (6, Some(6)));
+        assert_eq!(*stream.next().unwrap(), []);
+        assert_eq!(*stream.nth(3).unwrap(), [5]);
+        let expected: Vec<Vec<u64>> = (1..2000).map(prime::factor).collect();
+        for chunk_size in [1, 7, 1000, 5000] {
+            let stream = prime::factor_stream(1..2000).chunked(chunk_size);
+            let streamed: Vec<Vec<u64>> = stream.map(|f| f.to_vec()).collect();
+            assert_eq!(streamed, expected);
+        }
+        // Inputs are only pulled one chunk at a time.
+        let mut chunked = prime::factor_stream(1..).chunked(100);
+        assert_eq!(*chunked.nth(359).unwrap(), [2, 2, 2, 3, 3, 5]);
+        assert_eq!(chunked.size_hint().0, usize::MAX);
+    }
+
+    #[test]
+    fn test_factorize_stream() {
+        let spf = Spf::new(100000);
+        let inputs = [1, 24, 99991, 65536, 100000];
+        let streamed: Vec<Vec<u64>> = spf.factorize_stream(inputs).chunked(2).map(|f| f.to_vec()).collect();
+        let expected: Vec<Vec<u64>> = inputs.iter().map(|&x| spf.factorize(x)).collect();
+        assert_eq!(streamed, expected);
+        assert_eq!(spf.factorize_stream(inputs).count(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk_size cannot be zero!")]
+    fn test_factor_stream_zero_chunk() {
+        let _ = prime::factor_stream(1..10).chunked(0);
+    }
+}
@@ -0,0 +1,294 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Deref;
+use std::path::Path;
+#[cfg(feature = "mmap")]
+use std::sync::Arc;
+
+/// Integer types that can be written to and read back from a table file.
+pub trait Pod: Copy + Default {
+    /// The size of one value in bytes.
+    const SIZE: usize;
+
+    /// Writes the little-endian bytes of `self` to `out`, which holds `SIZE` bytes.
+    fn write_le(self, out: &mut [u8]);
+
+    /// Decodes a value from its `SIZE` little-endian bytes.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            impl Pod for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_le(self, out: &mut [u8]) {
+                    out.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_pod!(u8, u32, u64);
+
+/// The number of values encoded or decoded at a time, so a section never needs a second
+/// copy of itself in memory.
+const CHUNK: usize = 8192;
+
+/// The backing storage of a sieve table: either an owned vector or, with the `mmap`
+/// feature, a slice of a memory-mapped table file shared with the other tables of the
+/// same file.
+pub enum Table<T> {
+    Owned(Vec<T>),
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: Arc<memmap2::Mmap>,
+        offset: usize,
+        len: usize,
+    },
+}
+
+impl<T: Pod> Table<T> {
+    /// Returns the values as a mutable vector, copying a mapped table into memory first.
+    pub fn to_mut(&mut self) -> &mut Vec<T> {
+        #[cfg(feature = "mmap")]
+        if let Table::Mapped { .. } = self {
+            *self = Table::Owned(self.to_vec());
+        }
+        match self {
+            Table::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            Table::Mapped { .. } => unreachable!(),
+        }
+    }
+}
+
+impl<T: Pod> Deref for Table<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Table::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            // SAFETY: `read_mapped` checked that the section lies inside the map and is
+            // aligned for `T`, and the map lives as long as `self`.
+            Table::Mapped { map, offset, len } => unsafe {
+                std::slice::from_raw_parts(map.as_ptr().add(*offset) as *const T, *len)
+            },
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Table<T> {
+    fn from(values: Vec<T>) -> Self {
+        Table::Owned(values)
+    }
+}
+
+/// Streams a table file: an 8-byte magic, a list of `u64` header values, and then the
+/// sections, each a `u64` length followed by the little-endian values, padded to 8 bytes.
+pub struct TableWriter<W: Write> {
+    out: W,
+    pos: usize,
+}
+
+impl TableWriter<BufWriter<File>> {
+    /// Creates the file at `path` and writes the magic and header values.
+    pub fn create<P: AsRef<Path>>(path: P, magic: &[u8; 8], header: &[u64]) -> io::Result<Self> {
+        TableWriter::new(BufWriter::new(File::create(path)?), magic, header)
+    }
+}
+
+impl<W: Write> TableWriter<W> {
+    /// Starts a file in `out` with the given magic and header values.
+    pub fn new(out: W, magic: &[u8; 8], header: &[u64]) -> io::Result<TableWriter<W>> {
+        let mut writer = TableWriter { out, pos: 0 };
+        writer.write(magic)?;
+        for &value in header {
+            writer.write(&value.to_le_bytes())?;
+        }
+        Ok(writer)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+        self.pos += bytes.len();
+        Ok(())
+    }
+
+    /// Appends a section holding `values`.
+    pub fn section<T: Pod>(&mut self, values: &[T]) -> io::Result<()> {
+        self.write(&(values.len() as u64).to_le_bytes())?;
+        let mut buf = vec![0; CHUNK.min(values.len()) * T::SIZE];
+        for chunk in values.chunks(CHUNK) {
+            let bytes = &mut buf[..chunk.len() * T::SIZE];
+            for (&value, out) in chunk.iter().zip(bytes.chunks_exact_mut(T::SIZE)) {
+                value.write_le(out);
+            }
+            self.write(bytes)?;
+        }
+        let padding = self.pos.next_multiple_of(8) - self.pos;
+        self.write(&[0; 8][..padding])
+    }
+
+    /// Flushes the file and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Streams through a table file written by `TableWriter`. Section lengths are checked
+/// against a bound from the header before anything is allocated, and a truncated file is
+/// reported as `InvalidData`.
+pub struct TableReader<R: Read> {
+    inner: R,
+    pos: usize,
+}
+
+impl TableReader<BufReader<File>> {
+    /// Opens the file at `path` and checks its magic.
+    pub fn open<P: AsRef<Path>>(path: P, magic: &[u8; 8]) -> io::Result<Self> {
+        TableReader::new(BufReader::new(File::open(path)?), magic)
+    }
+}
+
+impl<R: Read> TableReader<R> {
+    /// Checks the magic of the file and returns a reader positioned at its header.
+    pub fn new(inner: R, magic: &[u8; 8]) -> io::Result<TableReader<R>> {
+        let mut reader = TableReader { inner, pos: 0 };
+        let mut start = [0; 8];
+        if reader.read(&mut start).is_err() || &start != magic {
+            return Err(invalid("unexpected table file magic"));
+        }
+        Ok(reader)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => invalid("truncated table file"),
+            _ => e,
+        })?;
+        self.pos += buf.len();
+        Ok(())
+    }
+
+    /// Reads the next `u64` header value.
+    pub fn value(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        self.read(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    // Reads the length of the next section, which may not exceed `max_len`.
+    fn section_len(&mut self, max_len: usize) -> io::Result<usize> {
+        usize::try_from(self.value()?)
+            .ok()
+            .filter(|&len| len <= max_len)
+            .ok_or_else(|| invalid("table section longer than its header allows"))
+    }
+
+    fn skip_padding(&mut self) -> io::Result<()> {
+        let padding = self.pos.next_multiple_of(8) - self.pos;
+        self.read(&mut [0; 8][..padding])
+    }
+
+    /// Reads the next section, of at most `max_len` values, into an owned table.
+    pub fn section<T: Pod>(&mut self, max_len: usize) -> io::Result<Table<T>> {
+        let len = self.section_len(max_len)?;
+        // Grown as the data arrives, so a bogus length in a short file allocates little.
+        let mut values = Vec::with_capacity(len.min(CHUNK));
+        let mut buf = vec![0; CHUNK.min(len) * T::SIZE];
+        let mut left = len;
+        while left > 0 {
+            let bytes = &mut buf[..left.min(CHUNK) * T::SIZE];
+            self.read(bytes)?;
+            values.extend(bytes.chunks_exact(T::SIZE).map(T::read_le));
+            left -= left.min(CHUNK);
+        }
+        self.skip_padding()?;
+        Ok(Table::Owned(values))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl TableReader<&[u8]> {
+    /// Refers to the next section, of at most `max_len` values, inside `map` without
+    /// copying it. The reader must have been created on the whole of `map`.
+    pub fn mapped_section<T: Pod>(&mut self, map: &Arc<memmap2::Mmap>, max_len: usize) -> io::Result<Table<T>> {
+        let len = self.section_len(max_len)?;
+        let offset = self.pos;
+        let size = len
+            .checked_mul(T::SIZE)
+            .filter(|&size| size <= self.inner.len())
+            .ok_or_else(|| invalid("truncated table file"))?;
+        if cfg!(target_endian = "big") || !(map.as_ptr() as usize + offset).is_multiple_of(T::SIZE) {
+            return Err(invalid("table file cannot be mapped on this platform"));
+        }
+        self.inner = &self.inner[size..];
+        self.pos += size;
+        self.skip_padding()?;
+        Ok(Table::Mapped { map: Arc::clone(map), offset, len })
+    }
+}
+
+/// Memory-maps the file at `path` read-only.
+#[cfg(feature = "mmap")]
+pub fn map_file<P: AsRef<Path>>(path: P) -> io::Result<Arc<memmap2::Mmap>> {
+    let file = File::open(path)?;
+    // SAFETY: the map is read-only; as with any memory map, the file must not be modified
+    // by another process while it is mapped.
+    Ok(Arc::new(unsafe { memmap2::Mmap::map(&file)? }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_round_trip() {
+        let mut writer = TableWriter::new(Vec::new(), b"TESTTBL1", &[7, 42]).unwrap();
+        writer.section(&[1u8, 2, 3]).unwrap();
+        writer.section(&[u32::MAX, 5]).unwrap();
+        writer.section::<u64>(&[]).unwrap();
+        let long: Vec<u32> = (0..3 * CHUNK as u32 + 5).collect();
+        writer.section(&long).unwrap();
+        writer.section(&[u64::MAX]).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert!(bytes.len().is_multiple_of(8));
+        let mut reader = TableReader::new(&bytes[..], b"TESTTBL1").unwrap();
+        assert_eq!(reader.value().unwrap(), 7);
+        assert_eq!(reader.value().unwrap(), 42);
+        assert_eq!(&*reader.section::<u8>(3).unwrap(), &[1, 2, 3]);
+        assert_eq!(&*reader.section::<u32>(2).unwrap(), &[u32::MAX, 5]);
+        assert!(reader.section::<u64>(0).unwrap().is_empty());
+        assert_eq!(&*reader.section::<u32>(long.len()).unwrap(), &long[..]);
+        let mut last = reader.section::<u64>(1).unwrap();
+        assert_eq!(&*last, &[u64::MAX]);
+        last.to_mut().push(1);
+        assert_eq!(&*last, &[u64::MAX, 1]);
+        assert!(reader.section::<u8>(10).is_err());
+        assert!(TableReader::new(&bytes[..], b"OTHERTBL").is_err());
+        assert!(TableReader::new(&bytes[..4], b"TESTTBL1").is_err());
+        // A section longer than allowed, and one cut short.
+        let invalid_data = |e: io::Error| e.kind() == io::ErrorKind::InvalidData;
+        let mut reader = TableReader::new(&bytes[..], b"TESTTBL1").unwrap();
+        reader.value().unwrap();
+        reader.value().unwrap();
+        assert!(reader.section::<u8>(2).is_err_and(invalid_data));
+        let mut reader = TableReader::new(&bytes[..26], b"TESTTBL1").unwrap();
+        reader.value().unwrap();
+        reader.value().unwrap();
+        assert!(reader.section::<u8>(3).is_err_and(invalid_data));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::modexp;
+use crate::pollard::modmul;
+
+fn add_mod(a: u64, b: u64, p: u64) -> u64 {
+    if a >= p - b { a - (p - b) } else { a + b }
+}
+
+fn sub_mod(a: u64, b: u64, p: u64) -> u64 {
+    if a >= b { a - b } else { a + (p - b) }
+}
+
+// Reduces a modulo p and checks that its length is a power of two.
+fn prepare(a: &mut [u64], p: u64) {
+    if p == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if !a.len().is_power_of_two() {
+        panic!("Length must be a power of two!");
+    }
+    a.iter_mut().for_each(|x| *x %= p);
+}
+
+// Applies `op(low, high)` to every pair of masks differing only in one bit.
+fn butterflies(a: &mut [u64], mut op: impl FnMut(&mut u64, &mut u64)) {
+    let mut half = 1;
+    while half < a.len() {
+        for block in a.chunks_exact_mut(2 * half) {
+            let (low, high) = block.split_at_mut(half);
+            low.iter_mut().zip(high.iter_mut()).for_each(|(x, y)| op(x, y));
+        }
+        half *= 2;
+    }
+}
+
+/// Replaces `a[mask]` with the sum of `a[sub]` over the submasks `sub` of `mask`, modulo `p`
+/// (the zeta transform over subsets).
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or `p` is zero.
+pub fn subset_sum(a: &mut [u64], p: u64) {
+    prepare(a, p);
+    butterflies(a, |low, high| *high = add_mod(*high, *low, p));
+}
+
+/// Inverts `subset_sum` (the Möbius transform over subsets).
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or `p` is zero.
+pub fn subset_sum_inverse(a: &mut [u64], p: u64) {
+    prepare(a, p);
+    butterflies(a, |low, high| *high = sub_mod(*high, *low, p));
+}
+
+/// Replaces `a[mask]` with the sum of `a[sup]` over the supermasks `sup` of `mask`, modulo
+/// `p` (the zeta transform over supersets).
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or `p` is zero.
+pub fn superset_sum(a: &mut [u64], p: u64) {
+    prepare(a, p);
+    butterflies(a, |low, high| *low = add_mod(*low, *high, p));
+}
+
+/// Inverts `superset_sum` (the Möbius transform over supersets).
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or `p` is zero.
+pub fn superset_sum_inverse(a: &mut [u64], p: u64) {
+    prepare(a, p);
+    butterflies(a, |low, high| *low = sub_mod(*low, *high, p));
+}
+
+/// Applies the Walsh–Hadamard transform modulo `p` in place, mapping every pair
+/// `(x, y)` of masks differing in one bit to `(x + y, x − y)`.
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or `p` is zero.
+pub fn walsh_hadamard(a: &mut [u64], p: u64) {
+    prepare(a, p);
+    butterflies(a, |low, high| (*low, *high) = (add_mod(*low, *high, p), sub_mod(*low, *high, p)));
+}
+
+/// Inverts `walsh_hadamard`, which is the same transform followed by a division by the length.
+///
+/// # Panics
+///
+/// Panics if the length of `a` is not a power of two or is not invertible modulo `p`.
+pub fn walsh_hadamard_inverse(a: &mut [u64], p: u64) {
+    walsh_hadamard(a, p);
+    let inv = modexp::mod_inv(a.len() as u64 % p, p);
+    a.iter_mut().for_each(|x| *x = modmul(*x, inv, p));
+}
+
+// Transforms copies of a and b, multiplies them pointwise and transforms back.
+fn convolve(a: &[u64], b: &[u64], p: u64, forward: fn(&mut [u64], u64), inverse: fn(&mut [u64], u64)) -> Vec<u64> {
+    if a.len() != b.len() {
+        panic!("Arrays must have the same length!");
+    }
+    let (mut fa, mut fb) = (a.to_vec(), b.to_vec());
+    forward(&mut fa, p);
+    forward(&mut fb, p);
+    fa.iter_mut().zip(&fb).for_each(|(x, &y)| *x = modmul(*x, y, p));
+    inverse(&mut fa, p);
+    fa
+}
+
+/// Computes `c[k] = Σ a[i]·b[j]` over `i ^ j = k`, modulo the prime `p`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` differ in length, the length is not a power of two, or it is not
+/// invertible modulo `p`.
+pub fn xor_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    convolve(a, b, p, walsh_hadamard, walsh_hadamard_inverse)
+}
+
+/// Computes `c[k] = Σ a[i]·b[j]` over `i & j = k`, modulo `p`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` differ in length, the length is not a power of two, or `p` is zero.
+pub fn and_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    convolve(a, b, p, superset_sum, superset_sum_inverse)
+}
+
+/// Computes `c[k] = Σ a[i]·b[j]` over `i | j = k`, modulo `p`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` differ in length, the length is not a power of two, or `p` is zero.
+pub fn or_convolution(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    convolve(a, b, p, subset_sum, subset_sum_inverse)
+}
+
+/// Computes the Dirichlet convolution `h(n) = Σ_{d | n} f(d)·g(n / d)` modulo `m` for every
+/// `n` below the table length, with index 0 unused.
+///
+/// # Panics
+///
+/// Panics if `f` and `g` differ in length or `m` is zero.
+pub fn dirichlet_convolve(f: &[u64], g: &[u64], m: u64) -> Vec<u64> {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    if f.len() != g.len() {
+        panic!("Arrays must have the same length!");
+    }
+    let mut h = vec![0; f.len()];
+    for i in 1..f.len() {
+        let fi = f[i] % m;
+        for (j, &gj) in g.iter().enumerate().take((f.len() - 1) / i + 1).skip(1) {
+            h[i * j] = add_mod(h[i * j], modmul(fi, gj % m, m), m);
+        }
+    }
+    h
+}
+
+/// Computes the Dirichlet inverse `g` of `f` modulo the prime `m`, the table with
+/// `f * g = ε` where `ε(1) = 1` and `ε(n) = 0` otherwise, with index 0 unused.
+///
+/// # Panics
+///
+/// Panics if `m` is zero or `f(1)` is not invertible modulo `m`.
+pub fn dirichlet_inverse(f: &[u64], m: u64) -> Vec<u64> {
+    if m == 0 {
+        panic!("Modulus cannot be zero.");
+    }
+    let n = f.len();
+    if n < 2 {
+        return vec![0; n];
+    }
+    if f[1].is_multiple_of(m) {
+        panic!("f(1) must be invertible!");
+    }
+    let inv = modexp::mod_inv(f[1] % m, m);
+    // acc[i] = Σ f(d)·g(i / d) over the divisors d > 1 of i, filled in as g grows.
+    let mut acc = vec![0; n];
+    let mut g = vec![0; n];
+    for i in 1..n {
+        g[i] = if i == 1 { inv } else { modmul(sub_mod(0, acc[i], m), inv, m) };
+        for d in 2..=(n - 1) / i {
+            acc[i * d] = add_mod(acc[i * d], modmul(f[d] % m, g[i], m), m);
+        }
+    }
+    g
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOD: u64 = 998244353;
+
+    #[test]
+    fn test_subset_transforms() {
+        let original: Vec<u64> = (0..32).map(|i| i * i * 7 + 3).collect();
+        let mut a = original.clone();
+        subset_sum(&mut a, MOD);
+        for (mask, &sum) in a.iter().enumerate() {
+            assert_eq!(sum, (0..32).filter(|&s| s & mask == s).map(|s| original[s]).sum());
+        }
+        subset_sum_inverse(&mut a, MOD);
+        assert_eq!(a, original);
+        superset_sum(&mut a, MOD);
+        for (mask, &sum) in a.iter().enumerate() {
+            assert_eq!(sum, (0..32).filter(|&s| s & mask == mask).map(|s| original[s]).sum());
+        }
+        superset_sum_inverse(&mut a, MOD);
+        assert_eq!(a, original);
+        // Reduction and moduli near 2^64.
+        let mut b = vec![u64::MAX, u64::MAX - 1];
+        subset_sum(&mut b, u64::MAX);
+        assert_eq!(b, vec![0, u64::MAX - 1]);
+        let mut single = vec![5];
+        superset_sum_inverse(&mut single, 3);
+        assert_eq!(single, vec![2]);
+    }
+
+    // Computes the convolution of a and b under the index operation op directly.
+    fn convolve_naive(a: &[u64], b: &[u64], p: u64, op: fn(usize, usize) -> usize) -> Vec<u64> {
+        let mut c = vec![0; a.len()];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                c[op(i, j)] = (c[op(i, j)] + modmul(x, y, p)) % p;
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn test_bitwise_convolutions() {
+        for p in [MOD, 1_000_000_007, (1 << 61) - 1] {
+            let a: Vec<u64> = (0..64).map(|i| (i * i * 31 + 7) % p).collect();
+            let b: Vec<u64> = (0..64).map(|i| (i * 1009 + p - 5) % p).collect();
+            assert_eq!(xor_convolution(&a, &b, p), convolve_naive(&a, &b, p, |i, j| i ^ j));
+            assert_eq!(and_convolution(&a, &b, p), convolve_naive(&a, &b, p, |i, j| i & j));
+            assert_eq!(or_convolution(&a, &b, p), convolve_naive(&a, &b, p, |i, j| i | j));
+        }
+        assert_eq!(xor_convolution(&[3], &[4], 7), vec![5]);
+        assert_eq!(xor_convolution(&[1, 2], &[3, 4], MOD), vec![11, 10]);
+        let mut a = vec![1, 2, 3, 4];
+        walsh_hadamard(&mut a, MOD);
+        assert_eq!(a, vec![10, MOD - 2, MOD - 4, 0]);
+        walsh_hadamard_inverse(&mut a, MOD);
+        assert_eq!(a, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Arrays must have the same length!")]
+    fn test_convolution_length_mismatch() {
+        or_convolution(&[1, 2], &[1, 2, 3, 4], MOD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Length must be a power of two!")]
+    fn test_subset_sum_bad_length() {
+        subset_sum(&mut [1, 2, 3], MOD);
+    }
+
+    // Tabulates f(0..=n) for f given on all positive integers, with f(0) = 0.
+    fn table(n: u64, f: impl Fn(u64) -> u64) -> Vec<u64> {
+        (0..=n).map(|i| if i == 0 { 0 } else { f(i) }).collect()
+    }
+
+    #[test]
+    fn test_dirichlet_convolve() {
+        let n = 500;
+        let one = table(n, |_| 1);
+        let id = table(n, |i| i);
+        let divisors = |i: u64| (1..=i).filter(move |d| i.is_multiple_of(*d));
+        assert_eq!(dirichlet_convolve(&one, &one, MOD), table(n, |i| divisors(i).count() as u64));
+        assert_eq!(dirichlet_convolve(&id, &one, MOD), table(n, |i| divisors(i).sum()));
+        let phi = table(n, |i| (1..=i).filter(|&j| crate::modexp::gcd(i, j) == 1).count() as u64);
+        assert_eq!(dirichlet_convolve(&phi, &one, MOD), id);
+        assert_eq!(dirichlet_convolve(&[], &[], 7), Vec::<u64>::new());
+        assert_eq!(dirichlet_convolve(&[9, 3, 4], &[9, 5, 6], 7), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_dirichlet_inverse() {
+        let n = 1000;
+        let one = table(n, |_| 1);
+        let mu = dirichlet_inverse(&one, MOD);
+        let sieve = crate::sieve::MobiusSieve::new(n);
+        assert_eq!(mu, table(n, |i| (sieve.mu(i) as i64).rem_euclid(MOD as i64) as u64));
+        let f = table(n, |i| (i * i + 3 * i + 5) % 13);
+        for p in [13, MOD] {
+            let g = dirichlet_inverse(&f, p);
+            assert_eq!(dirichlet_convolve(&f, &g, p), table(n, |i| (i == 1) as u64));
+        }
+        assert_eq!(dirichlet_inverse(&[5], 7), vec![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "f(1) must be invertible!")]
+    fn test_dirichlet_inverse_singular() {
+        dirichlet_inverse(&[0, 7, 1], 7);
+    }
+}
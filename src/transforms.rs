@@ -0,0 +1,30 @@
+//! Number-theoretic transform convolutions, subset and Walsh–Hadamard transforms over
+//! bitmasks, and Dirichlet convolutions of tabulated arithmetic functions.
+//!
+//! It holds the functions of the former `Ntt` and `Transform` facades, callable as
+//! `transforms::convolution(&a, &b, p)`. Primitive roots live in the
+//! [`modular`](crate::modular) module.
+
+pub use crate::ntt::{convolution, convolution_mod};
+pub use crate::transform::{
+    and_convolution, dirichlet_convolve, dirichlet_inverse, or_convolution, subset_sum, subset_sum_inverse,
+    superset_sum, superset_sum_inverse, walsh_hadamard, walsh_hadamard_inverse, xor_convolution,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_paths() {
+        assert_eq!(convolution(&[1, 2], &[3, 4], 998244353), vec![3, 10, 8]);
+        assert_eq!(convolution_mod(&[1000000006], &[1000000006], 1000000007), vec![1]);
+        let mut a = vec![1, 2, 3, 4];
+        subset_sum(&mut a, 1000000007);
+        assert_eq!(a, vec![1, 3, 4, 10]);
+        subset_sum_inverse(&mut a, 1000000007);
+        assert_eq!(a, vec![1, 2, 3, 4]);
+        assert_eq!(xor_convolution(&[1, 1], &[1, 1], 998244353), vec![2, 2]);
+        assert_eq!(dirichlet_convolve(&[0, 1, 1, 1, 1], &[0, 1, 1, 1, 1], 1000000007), vec![0, 1, 2, 2, 3]);
+    }
+}
@@ -0,0 +1,518 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::{Add, Div, Mul, Rem, Shr, Sub};
+
+use crate::random::RandomSource;
+use crate::{pollard, primality, MulMod};
+
+/// Primes below 100, stripped by trial division before Pollard's rho.
+const SMALL_PRIMES: [u8; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// An unsigned integer width that the generic entry points (the `int` module and `Comb<T>`)
+/// run on natively: `u32`, `u64` or `u128`.
+///
+/// Each width supplies its own modular multiplication, primality test and factorization:
+/// `u32` multiplies through `u64`, `u64` uses the tuned routines of the `prime` module, and
+/// `u128` hands values that fit in a `u64` to those and works with 128-bit arithmetic
+/// otherwise.
+pub trait UInt:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Hash
+    + Default
+    + From<u8>
+    + TryFrom<u64>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Returns the number of trailing zero bits.
+    fn trailing_zeros(self) -> u32;
+
+    /// Computes `(self * b) % m` without overflow.
+    fn mul_mod(self, b: Self, m: Self) -> Self;
+
+    /// A modulus prepared by `prepare_modulus` for repeated multiplications.
+    type Modulus: Copy + Debug + Send + Sync;
+
+    /// Prepares the modulus `m`, picking the multiplication strategy for its magnitude once.
+    fn prepare_modulus(m: Self) -> Self::Modulus;
+
+    /// Computes `(self * b) % m` like `mul_mod` for `self` and `b` below the prepared
+    /// modulus `m`.
+    fn mul_prepared(self, b: Self, m: &Self::Modulus) -> Self;
+
+    /// Checks whether `self` is prime.
+    fn is_prime(self) -> bool;
+
+    /// Factorizes `self` into its prime factors in sorted order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is 0.
+    fn factor(self) -> Vec<Self>;
+}
+
+impl UInt for u32 {
+    const ZERO: u32 = 0;
+    const ONE: u32 = 1;
+
+    fn trailing_zeros(self) -> u32 {
+        u32::trailing_zeros(self)
+    }
+
+    fn mul_mod(self, b: u32, m: u32) -> u32 {
+        (self as u64 * b as u64 % m as u64) as u32
+    }
+
+    type Modulus = MulMod;
+
+    fn prepare_modulus(m: u32) -> MulMod {
+        MulMod::new(m as u64)
+    }
+
+    #[inline]
+    fn mul_prepared(self, b: u32, m: &MulMod) -> u32 {
+        m.mul(self as u64, b as u64) as u32
+    }
+
+    fn is_prime(self) -> bool {
+        // Two rounds decide every u32: base 2 and the hashed Forišek–Jančina base.
+        let n = self;
+        if let Some(&p) = SMALL_PRIMES.iter().find(|&&p| n.is_multiple_of(p as u32)) {
+            return n == p as u32;
+        }
+        if n < 97 * 97 {
+            return n > 1;
+        }
+        strong_probable_prime(n, 2) && strong_probable_prime(n, primality::FJ32_BASES[primality::fj_hash(n)] as u32)
+    }
+
+    fn factor(self) -> Vec<u32> {
+        factor(self)
+    }
+}
+
+impl UInt for u64 {
+    const ZERO: u64 = 0;
+    const ONE: u64 = 1;
+
+    fn trailing_zeros(self) -> u32 {
+        u64::trailing_zeros(self)
+    }
+
+    fn mul_mod(self, b: u64, m: u64) -> u64 {
+        pollard::modmul(self, b, m)
+    }
+
+    type Modulus = MulMod;
+
+    fn prepare_modulus(m: u64) -> MulMod {
+        MulMod::new(m)
+    }
+
+    #[inline]
+    fn mul_prepared(self, b: u64, m: &MulMod) -> u64 {
+        m.mul(self, b)
+    }
+
+    fn is_prime(self) -> bool {
+        pollard::is_prime(self)
+    }
+
+    fn factor(self) -> Vec<u64> {
+        pollard::factor(self)
+    }
+}
+
+impl UInt for u128 {
+    const ZERO: u128 = 0;
+    const ONE: u128 = 1;
+
+    fn trailing_zeros(self) -> u32 {
+        u128::trailing_zeros(self)
+    }
+
+    fn mul_mod(self, b: u128, m: u128) -> u128 {
+        let (mut a, mut b) = (self % m, b % m);
+        if m >> 64 == 0 {
+            return a * b % m;
+        }
+        // Double and add, since the product needs 256 bits.
+        let mut retval = 0;
+        while b != 0 {
+            if b & 1 == 1 {
+                retval = add_mod(retval, a, m);
+            }
+            a = add_mod(a, a, m);
+            b >>= 1;
+        }
+        retval
+    }
+
+    type Modulus = u128;
+
+    fn prepare_modulus(m: u128) -> u128 {
+        m
+    }
+
+    fn mul_prepared(self, b: u128, m: &u128) -> u128 {
+        self.mul_mod(b, *m)
+    }
+
+    fn is_prime(self) -> bool {
+        if let Ok(n) = u64::try_from(self) {
+            return pollard::is_prime(n);
+        }
+        if SMALL_PRIMES.iter().any(|&p| self.is_multiple_of(p as u128)) {
+            return false;
+        }
+        // Baillie–PSW, since no fixed set of Miller-Rabin bases is proven for all of u128.
+        strong_probable_prime(self, 2) && strong_lucas(self)
+    }
+
+    fn factor(self) -> Vec<u128> {
+        match u64::try_from(self) {
+            Ok(n) => pollard::factor(n).into_iter().map(|p| p as u128).collect(),
+            Err(_) => factor(self),
+        }
+    }
+}
+
+// Adds `a + b` modulo `m`, for `a, b < m`.
+fn add_mod<T: UInt>(a: T, b: T, m: T) -> T {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// Computes the greatest common divisor of `a` and `b` with the Euclidean algorithm.
+pub fn gcd<T: UInt>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Computes `base^exponent` modulo `modulus` by repeated squaring.
+///
+/// # Panics
+///
+/// Panics if `modulus` is 0.
+pub fn mod_exp<T: UInt>(base: T, exponent: T, modulus: T) -> T {
+    if modulus == T::ZERO {
+        panic!("Modulus cannot be zero.");
+    }
+    let two = T::from(2);
+    let m = T::prepare_modulus(modulus);
+    let (mut b, mut e) = (base % modulus, exponent);
+    let mut retval = T::ONE % modulus;
+    while e != T::ZERO {
+        if e % two == T::ONE {
+            retval = retval.mul_prepared(b, &m);
+        }
+        b = b.mul_prepared(b, &m);
+        e = e >> 1;
+    }
+    retval
+}
+
+/// Checks whether `n` is prime with `rounds` rounds of Miller-Rabin to random bases drawn
+/// from `rng`, for control over the bases of the probabilistic `u128` test. Values up to
+/// `u64::MAX` are decided exactly by `is_prime` and draw nothing.
+pub fn is_probable_prime<T: UInt>(n: T, rounds: u32, rng: &mut dyn RandomSource) -> bool {
+    match T::try_from(u64::MAX) {
+        Ok(max) if n > max => {}
+        _ => return n.is_prime(),
+    }
+    if SMALL_PRIMES.iter().any(|&p| n % T::from(p) == T::ZERO) {
+        return false;
+    }
+    // Every base in [2, 2^64) lies below n − 1, and T holds it since n does not fit in a u64.
+    (0..rounds).all(|_| strong_probable_prime(n, T::try_from(rng.next_u64().max(2)).ok().unwrap()))
+}
+
+// One round of Miller-Rabin: whether the odd `n > a` is a strong probable prime to base `a`.
+fn strong_probable_prime<T: UInt>(n: T, a: T) -> bool {
+    let s = (n - T::ONE).trailing_zeros();
+    let mut p = mod_exp(a, (n - T::ONE) >> s, n);
+    if p == T::ONE || p == n - T::ONE {
+        return true;
+    }
+    for _ in 1..s {
+        p = p.mul_mod(p, n);
+        if p == n - T::ONE {
+            return true;
+        }
+    }
+    false
+}
+
+// The strong Lucas test of `primality::strong_lucas` over 128-bit arithmetic, with Selfridge's
+// parameters, for an odd `n > u64::MAX` without prime factors below 100.
+fn strong_lucas(n: u128) -> bool {
+    let r = n.isqrt();
+    if r * r == n {
+        // No D with (D / n) = -1 exists for perfect squares.
+        return false;
+    }
+    let mut d: i64 = 5;
+    loop {
+        let dm = if d > 0 { d as u128 } else { n - d.unsigned_abs() as u128 };
+        match jacobi(dm, n) {
+            -1 => break,
+            // n exceeds every |D| tried, so a shared factor proves it composite.
+            0 => return false,
+            _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+        }
+    }
+    let to_residue = |x: i64| if x >= 0 { x as u128 } else { n - x.unsigned_abs() as u128 };
+    let dm = to_residue(d);
+    let q = to_residue((1 - d) / 4);
+    // (x + n) / 2 for odd x, without overflowing.
+    let half = |x: u128| if x.is_multiple_of(2) { x / 2 } else { x / 2 + n / 2 + 1 };
+    let sub = |x: u128, y: u128| if x >= y { x - y } else { x + (n - y) };
+
+    // n is not u128::MAX, which is divisible by 3, so n + 1 does not overflow.
+    let k = n + 1;
+    let s = k.trailing_zeros();
+    let k = k >> s;
+    // U_1 = 1, V_1 = P = 1, Q^1 = Q
+    let mut u: u128 = 1;
+    let mut v: u128 = 1;
+    let mut qk = q;
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = u.mul_mod(v, n);
+        v = sub(v.mul_mod(v, n), add_mod(qk, qk, n));
+        qk = qk.mul_mod(qk, n);
+        if (k >> bit) & 1 == 1 {
+            let nu = half(add_mod(u, v, n));
+            let nv = half(add_mod(dm.mul_mod(u, n), v, n));
+            u = nu;
+            v = nv;
+            qk = qk.mul_mod(q, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub(v.mul_mod(v, n), add_mod(qk, qk, n));
+        qk = qk.mul_mod(qk, n);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+// The Jacobi symbol (`a` / `n`) for odd `n`, the 128-bit counterpart of `primality::jacobi`.
+fn jacobi(a: u128, n: u128) -> i32 {
+    let mut a = a % n;
+    let mut n = n;
+    let mut retval = 1;
+    while a != 0 {
+        let tz = a.trailing_zeros();
+        a >>= tz;
+        if tz % 2 == 1 && (n % 8 == 3 || n % 8 == 5) {
+            retval = -retval;
+        }
+        if a % 4 == 3 && n % 4 == 3 {
+            retval = -retval;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= n;
+    }
+    if n == 1 {
+        retval
+    } else {
+        0
+    }
+}
+
+// Finds a non-trivial factor of the odd composite `n` with Brent's variant of Pollard's rho,
+// batching the gcds over 128 steps and moving on to the next constant if a cycle closes
+// without splitting `n`.
+fn rho<T: UInt>(n: T) -> T {
+    const BATCH: u64 = 128;
+    let diff = |x: T, y: T| if x > y { x - y } else { y - x };
+    let mut c = T::ONE;
+    loop {
+        let f = |x: T| add_mod(x.mul_mod(x, n), c, n);
+        let (mut x, mut y, mut ys) = (T::from(2), T::from(2), T::from(2));
+        let (mut q, mut g) = (T::ONE, T::ONE);
+        let mut r = 1;
+        while g == T::ONE {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == T::ONE {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = q.mul_mod(diff(x, y), n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // The batch overshot: retrace it one step at a time.
+            loop {
+                ys = f(ys);
+                g = gcd(diff(x, ys), n);
+                if g != T::ONE {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+        c = c + T::ONE;
+    }
+}
+
+// Factorizes `n` by trial division by the primes below 100, then splits the cofactor with
+// Pollard's rho, factorizing both parts through their width's own `factor`.
+fn factor<T: UInt>(n: T) -> Vec<T> {
+    if n == T::ZERO {
+        panic!("n cannot be zero!");
+    }
+    let mut factors = Vec::new();
+    let mut m = n;
+    for p in SMALL_PRIMES {
+        let p = T::from(p);
+        while m % p == T::ZERO {
+            factors.push(p);
+            m = m / p;
+        }
+    }
+    if m != T::ONE {
+        if m.is_prime() {
+            factors.push(m);
+        } else {
+            let d = rho(m);
+            factors.extend(d.factor());
+            factors.extend((m / d).factor());
+        }
+    }
+    factors.sort_unstable();
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_prime() {
+        for n in 0..100000u32 {
+            assert_eq!(n.is_prime(), pollard::is_prime(n as u64), "n = {}", n);
+            assert_eq!((n as u128).is_prime(), pollard::is_prime(n as u64));
+        }
+        // Base-2 strong pseudoprimes and primes near 2^32.
+        for n in [2047u32, 3277, 4033, 4681, 8321, 3215031751, 4294967291, 4294967295] {
+            assert_eq!(n.is_prime(), pollard::is_prime(n as u64), "n = {}", n);
+        }
+        // Mersenne primes 2^89 − 1 and 2^127 − 1, and composites beyond u64.
+        assert!(((1u128 << 89) - 1).is_prime());
+        assert!(((1u128 << 127) - 1).is_prime());
+        assert!(!((1u128 << 67) - 1).is_prime());
+        assert!(!((1u128 << 64) + 1).is_prime());
+        // The square of a prime, and a product of two 40-bit primes.
+        let p: u128 = 1000000000039;
+        assert!(!(p * p).is_prime());
+        assert!(!(p * 1099511627791).is_prime());
+        // The smallest prime above 2^64.
+        assert!(((1u128 << 64) + 13).is_prime());
+        // ψ13, the smallest strong pseudoprime to the first 13 prime bases.
+        assert!(!3317044064679887385961981u128.is_prime());
+        assert!(((1u128 << 107) - 1).is_prime());
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        use crate::random::{RandomSource, SplitMix64};
+
+        // Counts the words drawn.
+        struct Counting(SplitMix64, u32);
+        impl RandomSource for Counting {
+            fn next_u64(&mut self) -> u64 {
+                self.1 += 1;
+                self.0.next_u64()
+            }
+        }
+        let mut rng = Counting(SplitMix64::default(), 0);
+        assert!(is_probable_prime((1u128 << 127) - 1, 20, &mut rng));
+        assert_eq!(rng.1, 20);
+        assert!(!is_probable_prime(1000000000039u128 * 1099511627791, 20, &mut rng));
+        assert!(!is_probable_prime((1u128 << 64) + 1, 20, &mut rng));
+        let drawn = rng.1;
+        for n in [0u64, 1, 2, 2047, 1000000007, u64::MAX] {
+            assert_eq!(is_probable_prime(n, 5, &mut rng), pollard::is_prime(n));
+            assert_eq!(is_probable_prime(n as u32, 5, &mut rng), (n as u32).is_prime());
+        }
+        assert_eq!(rng.1, drawn);
+    }
+
+    #[test]
+    fn test_factor() {
+        for n in 1..5000u32 {
+            let expected = pollard::factor(n as u64);
+            assert_eq!(n.factor().iter().map(|&p| p as u64).collect::<Vec<u64>>(), expected);
+            assert_eq!((n as u128).factor().iter().map(|&p| p as u64).collect::<Vec<u64>>(), expected);
+        }
+        assert_eq!(4294967291u32.factor(), vec![4294967291]);
+        assert_eq!(4294967295u32.factor(), vec![3, 5, 17, 257, 65537]);
+        assert_eq!((65521u32 * 65519).factor(), vec![65519, 65521]);
+        assert_eq!(((1u128 << 64) + 1).factor(), vec![274177, 67280421310721]);
+        assert_eq!(((1u128 << 67) - 1).factor(), vec![193707721, 761838257287]);
+        assert_eq!(3317044064679887385961981u128.factor(), vec![1287836182261, 2575672364521]);
+        // A prime cube beyond u64.
+        let p: u128 = 3000017;
+        assert_eq!((p * p * p * 6).factor(), vec![2, 3, p, p, p]);
+        assert_eq!(u128::MAX.factor(), vec![3, 5, 17, 257, 641, 65537, 274177, 6700417, 67280421310721]);
+    }
+
+    #[test]
+    #[should_panic(expected = "n cannot be zero!")]
+    fn test_factor_zero() {
+        0u32.factor();
+    }
+
+    #[test]
+    fn test_mod_exp_and_gcd() {
+        assert_eq!(mod_exp(2u32, 10, 1000), 24);
+        assert_eq!(mod_exp(3u32, 4294967290, 4294967291), 1);
+        assert_eq!(mod_exp(5u128, 0, 1), 0);
+        let p = (1u128 << 127) - 1;
+        assert_eq!(mod_exp(3u128, p - 1, p), 1);
+        assert_eq!(mod_exp(2u128, 127, p), 1);
+        assert_eq!(u128::MAX.mul_mod(u128::MAX - 2, p), p - 1);
+        assert_eq!((p - 2).mul_mod(p - 3, p), 6);
+        for a in 0..200u64 {
+            for b in 0..50u64 {
+                assert_eq!(gcd(a as u32, b as u32) as u64, crate::modexp::gcd(a, b));
+                assert_eq!(gcd(a as u128, b as u128) as u64, crate::modexp::gcd(a, b));
+                assert_eq!(mod_exp(a as u32, b as u32, 97) as u64, crate::modexp::mod_exp(a, b, 97));
+            }
+        }
+    }
+}